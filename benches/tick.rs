@@ -0,0 +1,32 @@
+//! Times `GameOfLife::tick`, and the convolution step beside it, on a large random soup:
+//! `cargo bench --bench tick`.
+use std::time::Instant;
+use game_of_life::{convolution, BoxRule, GameOfLife};
+
+const SIZES: [(usize, usize); 3] = [(256, 256), (1024, 1024), (256, 4096)];
+const GENERATIONS: u32 = 20;
+
+fn main() {
+    for (rows, columns) in SIZES {
+        let mut game = GameOfLife::builder().size(rows, columns).density(0.3).seed(1).build().unwrap();
+        game.tick();
+
+        let start = Instant::now();
+        for _ in 0..GENERATIONS {
+            game.tick();
+        }
+        let per_tick = start.elapsed() / GENERATIONS;
+        let per_cell = per_tick.as_nanos() as f64 / (rows * columns) as f64;
+        println!("{:>5}x{:<5} {:>10.3?} per tick, {:.2}ns per cell", rows, columns, per_tick, per_cell);
+
+        let (rule, boundary) = (BoxRule::from(game.rule()), game.boundary());
+        let mut matrix = game.current().clone();
+        let start = Instant::now();
+        for _ in 0..GENERATIONS {
+            matrix = convolution::step(&matrix, &rule, boundary);
+        }
+        let per_step = start.elapsed() / GENERATIONS;
+        let per_cell = per_step.as_nanos() as f64 / (rows * columns) as f64;
+        println!("{:>11} {:>10.3?} per step, {:.2}ns per cell (convolution)", "", per_step, per_cell);
+    }
+}
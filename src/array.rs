@@ -0,0 +1,99 @@
+//! Boards as ndarray arrays of `u8`, one byte per cell holding the `CellState` discriminant: 0 for
+//! dead, 1 alive, 2 a wall and 3 immortal. A `GameMatrix` stores its cells that way already, so
+//! the conversions reuse its bytes rather than copying them, and an `Array2<u8>` can go on to
+//! any numeric crate, or to numpy through the `numpy` crate's `PyArray2::from_owned_array`.
+use std::mem::ManuallyDrop;
+use ndarray::{Array2, ArrayView2};
+use crate::engine::{CellState, GameMatrix};
+use crate::error::{Error, Result};
+use crate::grid::Grid;
+
+impl GameMatrix {
+    /// The cells as a borrowed array, rows by columns, without copying them.
+    pub fn as_array(&self) -> ArrayView2<'_, u8> {
+        let states = self.get_internal().as_slice();
+        // SAFETY: `CellState` is `repr(u8)`, so every state is a valid byte with the same size and
+        // alignment, and the bytes are only read for as long as `self` is borrowed.
+        let bytes = unsafe { std::slice::from_raw_parts(states.as_ptr() as *const u8, states.len()) };
+        ArrayView2::from_shape(self.shape(), bytes).expect("a grid holds rows times columns cells")
+    }
+}
+
+/// Hands the matrix's cells over to an array without copying them.
+impl From<GameMatrix> for Array2<u8> {
+    fn from(matrix: GameMatrix) -> Array2<u8> {
+        let shape = matrix.shape();
+        let mut states = ManuallyDrop::new(matrix.into_grid().into_vec());
+        // SAFETY: `CellState` is `repr(u8)`, so the allocation holds valid bytes laid out and
+        // aligned as a `Vec<u8>` of the same length and capacity would be, and `ManuallyDrop`
+        // keeps it from being freed twice.
+        let bytes = unsafe { Vec::from_raw_parts(states.as_mut_ptr() as *mut u8, states.len(), states.capacity()) };
+        Array2::from_shape_vec(shape, bytes).expect("a grid holds rows times columns cells")
+    }
+}
+
+/// Takes an array's bytes over as cells, copying them only if the array is not laid out row by
+/// row from the start of its buffer, as slices and transposes may not be. Fails on any byte that
+/// is not a `CellState`.
+impl TryFrom<Array2<u8>> for GameMatrix {
+    type Error = Error;
+
+    fn try_from(array: Array2<u8>) -> Result<GameMatrix> {
+        if let Some(((row, column), value)) = array.indexed_iter().find(|(_, value)| **value > CellState::Immortal as u8) {
+            return Err(Error::Parse(format!("{} at ({}, {}) is not a cell state", value, row, column)));
+        }
+        let (rows, columns) = array.dim();
+        let array = if array.is_standard_layout() { array } else { array.as_standard_layout().into_owned() };
+        let bytes = match array.into_raw_vec_and_offset() {
+            (bytes, Some(0)) if bytes.len() == rows * columns => bytes,
+            (bytes, Some(offset)) => bytes[offset..offset + rows * columns].to_vec(),
+            (_, None) => vec![],
+        };
+        let mut bytes = ManuallyDrop::new(bytes);
+        // SAFETY: every byte was checked above to be the discriminant of a `CellState`, which is
+        // `repr(u8)`, so the allocation can be taken over as one of states.
+        let states = unsafe { Vec::from_raw_parts(bytes.as_mut_ptr() as *mut CellState, bytes.len(), bytes.capacity()) };
+        let grid = Grid::from_vec(rows, columns, states).expect("a standard layout array holds rows times columns values");
+        Ok(GameMatrix::from_grid(grid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{array, s};
+
+    #[test]
+    fn test_round_trip() {
+        let mut matrix = GameMatrix::from_ascii("
+            O..
+            .O.
+        ").unwrap();
+        matrix.set_state((1, 2), CellState::Wall);
+        assert_eq!(matrix.as_array(), array![[1, 0, 0], [0, 1, 2]]);
+
+        let array = Array2::from(matrix.clone());
+        let address = array.as_ptr();
+        assert_eq!(array, array![[1, 0, 0], [0, 1, 2]]);
+        let back = GameMatrix::try_from(array).unwrap();
+        assert_eq!(back, matrix);
+        // The same bytes all the way round.
+        assert_eq!(back.get_internal().as_slice().as_ptr() as *const u8, address);
+    }
+
+    #[test]
+    fn test_layouts() {
+        let transposed = array![[1u8, 0], [0, 0], [3, 0]].reversed_axes();
+        assert_eq!(GameMatrix::try_from(transposed).unwrap().to_string(), "O.O\n...");
+        let mut sliced = array![[0u8, 0, 0], [0, 1, 0], [0, 0, 1]];
+        sliced.slice_collapse(s![1.., 1..]);
+        assert_eq!(GameMatrix::try_from(sliced).unwrap().to_string(), "O.\n.O");
+        assert_eq!(GameMatrix::try_from(Array2::<u8>::zeros((0, 4))).unwrap().shape(), (0, 4));
+    }
+
+    #[test]
+    fn test_invalid_state() {
+        let error = GameMatrix::try_from(array![[0u8, 4]]).unwrap_err();
+        assert_eq!(error.to_string(), "parse error: 4 at (0, 1) is not a cell state");
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+use game_of_life::{io, pattern, protocol, Cell, CellState, Pattern};
+use crate::http::{Request, Response};
+use crate::serve::Headless;
+
+/// Answers a control API request against a headless game:
+///
+/// - `GET /grid`: a snapshot, as streamed to clients
+/// - `GET /status`: generation, population, whether it is running and the interval
+/// - `GET /metrics`: the same and timings for Prometheus
+/// - `POST /cells?alive=ROW,COLUMN;...&dead=ROW,COLUMN;...`: sets cells
+/// - `POST /stamp?pattern=NAME&row=ROW&column=COLUMN`: stamps a built in pattern, or the pattern
+///   in the body when no name is given (`format=rle` for RLE, plaintext otherwise)
+/// - `POST /pause`, `POST /resume`, `POST /step`
+/// - `POST /speed?interval=MILLISECONDS`
+///
+/// Edits are applied between generations, so they may not show in a `GET` sent right after.
+pub fn handle(request: &Request, headless: &mut Headless) -> Response {
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/grid") => Ok(protocol::snapshot(&headless.game, headless.generation)),
+        ("GET", "/status") => Ok(format!(
+            "{{\"generation\":{},\"population\":{},\"running\":{},\"interval\":{}}}",
+            headless.generation, headless.game.current().population(), headless.running, headless.interval.as_millis()
+        )),
+        ("GET", "/metrics") => return Response::prometheus(metrics(headless)),
+        ("POST", "/cells") => set_cells(request, headless),
+        ("POST", "/stamp") => stamp(request, headless),
+        ("POST", "/pause") => set_running(headless, false),
+        ("POST", "/resume") => set_running(headless, true),
+        ("POST", "/step") => {
+            headless.simulation.step();
+            Ok(ok())
+        }
+        ("POST", "/speed") => set_speed(request, headless),
+        (_, "/grid" | "/status" | "/metrics" | "/cells" | "/stamp" | "/pause" | "/resume" | "/step" | "/speed") => {
+            return Response::error(405, &format!("{} is not allowed on {}", request.method, request.path));
+        }
+        _ => return Response::error(404, &format!("no such endpoint {}", request.path)),
+    };
+    match result {
+        Ok(body) => Response::ok(body),
+        Err(message) => Response::error(400, &message),
+    }
+}
+
+fn ok() -> String {
+    "{\"ok\":true}".to_string()
+}
+
+fn metrics(headless: &Headless) -> String {
+    let metrics = [
+        ("generation", "counter", "Generations computed since the server started.", headless.generation as f64),
+        ("population", "gauge", "Living cells in the current generation.", headless.game.current().population() as f64),
+        ("running", "gauge", "1 while generations are advancing, 0 while paused.", headless.running as u8 as f64),
+        ("interval_seconds", "gauge", "Target time between generations.", headless.interval.as_secs_f64()),
+        ("tick_seconds", "gauge", "Time the most recent generation took to compute.", headless.simulation.tick_duration().as_secs_f64()),
+        ("render_seconds", "gauge", "Time the most recent update took to encode and send.", headless.render_duration.as_secs_f64()),
+        ("clients", "gauge", "Connected streaming clients.", headless.clients as f64),
+    ];
+    metrics
+        .iter()
+        .map(|(name, kind, help, value)| {
+            format!("# HELP gol_{0} {1}\n# TYPE gol_{0} {2}\ngol_{0} {3}\n", name, help, kind, value)
+        })
+        .collect()
+}
+
+fn set_running(headless: &mut Headless, running: bool) -> Result<String, String> {
+    headless.running = running;
+    headless.simulation.set_running(running);
+    Ok(ok())
+}
+
+fn set_speed(request: &Request, headless: &mut Headless) -> Result<String, String> {
+    let interval = number(request, "interval")?;
+    if interval == 0 {
+        return Err("'interval' must be at least 1".to_string());
+    }
+    headless.interval = Duration::from_millis(interval);
+    headless.simulation.set_interval(headless.interval);
+    Ok(ok())
+}
+
+fn set_cells(request: &Request, headless: &mut Headless) -> Result<String, String> {
+    let mut changes = vec![];
+    for (name, state) in [("alive", CellState::Alive), ("dead", CellState::Dead)] {
+        for cell in cells(request.query(name).unwrap_or_default())? {
+            if !headless.game.current().contains(cell) {
+                return Err(format!("cell {:?} is outside of the grid", cell));
+            }
+            changes.push((cell, state));
+        }
+    }
+    headless.simulation.edit(move |game| {
+        // Checked against the grid above, whose shape edits do not change.
+        let _ = game.current_mut().set_states(&changes);
+    });
+    Ok(ok())
+}
+
+fn stamp(request: &Request, headless: &mut Headless) -> Result<String, String> {
+    let pattern = match request.query("pattern") {
+        Some(name) => pattern::builtin()
+            .into_iter()
+            .find(|pattern| pattern.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown pattern '{}'", name))?,
+        // A pattern with more living cells than the grid has cells can't fit, so there is no
+        // need to read any more of it than that.
+        None if request.query("format") == Some("rle") => {
+            let (rows, columns) = headless.game.shape();
+            io::parse_rle_within("", &request.body, rows * columns).map_err(|error| error.to_string())?
+        }
+        None => io::parse_plaintext("", &request.body),
+    };
+    let origin = (number(request, "row")? as usize, number(request, "column")? as usize);
+    if !fits(&pattern, origin, headless.game.shape()) {
+        return Err(format!("{} does not fit at {:?}", pattern.name(), origin));
+    }
+    headless.simulation.edit(move |game| pattern.stamp(game.current_mut(), origin));
+    Ok(ok())
+}
+
+/// Whether the pattern lies within the grid at `origin`; a corner past `usize::MAX` never does.
+fn fits(pattern: &Pattern, (row, column): Cell, (rows, columns): (usize, usize)) -> bool {
+    let (pattern_rows, pattern_columns) = pattern.shape();
+    row.checked_add(pattern_rows).is_some_and(|bottom| bottom <= rows)
+        && column.checked_add(pattern_columns).is_some_and(|right| right <= columns)
+}
+
+/// A numeric query parameter, zero when it is missing.
+fn number(request: &Request, name: &str) -> Result<u64, String> {
+    match request.query(name) {
+        Some(value) => value.parse().map_err(|_| format!("'{}' must be a number", name)),
+        None => Ok(0),
+    }
+}
+
+/// Parses `ROW,COLUMN;ROW,COLUMN;...`.
+fn cells(text: &str) -> Result<Vec<Cell>, String> {
+    text.split(';')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            pair.split_once(',')
+                .and_then(|(row, column)| Some((row.trim().parse().ok()?, column.trim().parse().ok()?)))
+                .ok_or_else(|| format!("invalid cell '{}'", pair))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::{CellType, GameOfLife};
+
+    fn request(text: &str) -> Request {
+        Request::read(text.as_bytes()).unwrap()
+    }
+
+    fn get(headless: &mut Headless, path: &str) -> Response {
+        handle(&request(&format!("GET {} HTTP/1.1\r\n\r\n", path)), headless)
+    }
+
+    fn post(headless: &mut Headless, path: &str, body: &str) -> Response {
+        let text = format!("POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body);
+        handle(&request(&text), headless)
+    }
+
+    /// Waits for the edits and steps the requests asked for to reach the replica.
+    fn sync(headless: &mut Headless, updates: usize) {
+        for _ in 0..updates {
+            headless.simulation.recv().unwrap().apply(&mut headless.game);
+        }
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut headless = Headless::new(GameOfLife::new(8, 8), Duration::from_secs(60));
+        assert_eq!(post(&mut headless, "/pause", "").status, 200);
+        assert_eq!(get(&mut headless, "/status").body, "{\"generation\":0,\"population\":0,\"running\":false,\"interval\":60000}");
+
+        assert_eq!(post(&mut headless, "/cells?alive=1,1;2,2", "").status, 200);
+        assert_eq!(post(&mut headless, "/stamp?pattern=blinker&row=5&column=0", "").status, 200);
+        assert_eq!(post(&mut headless, "/stamp?row=0&column=5", "O\nO").status, 200);
+        assert_eq!(post(&mut headless, "/cells?dead=1,1", "").status, 200);
+        sync(&mut headless, 4);
+        assert_eq!(headless.game.current().population(), 6);
+        assert!(headless.game.current()[(5, 1)].is_alive());
+        assert!(headless.game.current()[(1, 5)].is_alive());
+
+        assert_eq!(post(&mut headless, "/speed?interval=20", "").status, 200);
+        assert!(get(&mut headless, "/status").body.ends_with("\"interval\":20}"));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut headless = Headless::new(GameOfLife::new(8, 8), Duration::from_secs(60));
+        post(&mut headless, "/step", "");
+        sync(&mut headless, 1);
+        headless.generation = 1;
+
+        let response = get(&mut headless, "/metrics");
+        assert_eq!(response.content_type, "text/plain; version=0.0.4");
+        assert!(response.body.contains("# TYPE gol_generation counter\ngol_generation 1\n"));
+        assert!(response.body.contains("gol_population 0\n"));
+        assert!(response.body.contains("gol_interval_seconds 60\n"));
+        assert!(response.body.contains("gol_tick_seconds "));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut headless = Headless::new(GameOfLife::new(8, 8), Duration::from_secs(60));
+        assert_eq!(get(&mut headless, "/nowhere").status, 404);
+        assert_eq!(get(&mut headless, "/pause").status, 405);
+        assert_eq!(post(&mut headless, "/cells?alive=9,9", "").status, 400);
+        assert_eq!(post(&mut headless, "/cells?alive=1", "").status, 400);
+        assert_eq!(post(&mut headless, "/stamp?pattern=glider&row=7", "").status, 400);
+        assert_eq!(post(&mut headless, "/stamp?pattern=nothing", "").status, 400);
+        assert_eq!(post(&mut headless, "/stamp?pattern=glider&row=18446744073709551615", "").status, 400);
+        assert_eq!(post(&mut headless, "/stamp?pattern=glider&column=18446744073709551615", "").status, 400);
+        // Far more cells than the grid holds, each run within the limits of a pattern file.
+        let huge = format!("x = 1048576, y = 1048576\n{}!", "1048576o$".repeat(1024));
+        assert_eq!(post(&mut headless, "/stamp?format=rle", &huge).status, 400);
+        assert_eq!(post(&mut headless, "/speed?interval=0", "").status, 400);
+    }
+}
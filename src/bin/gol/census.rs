@@ -0,0 +1,17 @@
+use std::io::Result;
+use game_of_life::stats::lifespan;
+use game_of_life::GameOfLife;
+
+/// Runs the game until it settles, or for `limit` generations if it doesn't, then prints what
+/// it settled into, one kind of object per line.
+pub fn run(mut game: GameOfLife, limit: u64) -> Result<()> {
+    let lifespan = lifespan(&mut game, limit);
+    let census = game.current().census();
+    let settled = if lifespan.settled { "settled" } else { "still unsettled" };
+    println!("{} after {} generations, population {}", settled, lifespan.generations, lifespan.final_population);
+    for (kind, count) in census.counts() {
+        println!("{:>8} {}", count, kind.plural());
+    }
+    println!("{:>8} recognized objects", census.total());
+    Ok(())
+}
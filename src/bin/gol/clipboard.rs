@@ -0,0 +1,54 @@
+use std::io::{Error, Result};
+
+#[cfg(feature = "clipboard")]
+pub use self::system::Clipboard;
+
+/// Stands in for the system clipboard when the `clipboard` feature is off, refusing to open it.
+#[cfg(not(feature = "clipboard"))]
+pub enum Clipboard {}
+
+#[cfg(not(feature = "clipboard"))]
+impl Clipboard {
+    pub fn open() -> Result<Clipboard> {
+        Err(Error::new(std::io::ErrorKind::Unsupported, "copying needs the clipboard feature"))
+    }
+
+    pub fn copy(&mut self, _: String) -> Result<()> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "clipboard")]
+mod system {
+    use super::*;
+
+    /// The system clipboard. On X11 and Wayland the copying program serves the clipboard itself,
+    /// so what `gol` copies outlives it only if a clipboard manager is running to take it over.
+    pub struct Clipboard(arboard::Clipboard);
+
+    impl Clipboard {
+        pub fn open() -> Result<Clipboard> {
+            arboard::Clipboard::new().map(Clipboard).map_err(unavailable)
+        }
+
+        pub fn copy(&mut self, text: String) -> Result<()> {
+            self.0.set_text(text).map_err(unavailable)
+        }
+    }
+
+    fn unavailable(error: arboard::Error) -> Error {
+        Error::other(format!("clipboard: {}", error))
+    }
+}
+
+#[cfg(all(test, not(feature = "clipboard")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_feature() {
+        let error = Clipboard::open().err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        assert!(error.to_string().contains("clipboard feature"));
+    }
+}
@@ -0,0 +1,291 @@
+use crossterm::style::Color;
+
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Guesses what the terminal supports from `COLORTERM` and `TERM`, the same variables most
+    /// terminal applications consult.
+    pub fn detect() -> ColorDepth {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<ColorDepth> {
+        match name {
+            "auto" => Some(ColorDepth::detect()),
+            "truecolor" | "24bit" => Some(ColorDepth::TrueColor),
+            "256" => Some(ColorDepth::Ansi256),
+            "16" => Some(ColorDepth::Ansi16),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self, (r, g, b): Rgb) -> Color {
+        match self {
+            ColorDepth::TrueColor => Color::Rgb { r, g, b },
+            ColorDepth::Ansi256 => {
+                let level = |value: u8| (value as u16 * 5 / 255) as u8;
+                Color::AnsiValue(16 + 36 * level(r) + 6 * level(g) + level(b))
+            }
+            ColorDepth::Ansi16 => nearest_ansi16((r, g, b)),
+        }
+    }
+
+    /// `color` as the terminal can show it: RGB colors brought down to its depth, named and
+    /// numbered ones left to its palette.
+    pub fn fit(&self, color: Color) -> Color {
+        match color {
+            Color::Rgb { r, g, b } => self.color((r, g, b)),
+            color => color,
+        }
+    }
+}
+
+/// Whether the terminal draws on a dark background or a light one. The default colors are for a
+/// dark one; on a light one `adapt` swaps them for darker ones that still show.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Background {
+    Dark,
+    Light,
+}
+
+impl Background {
+    pub fn parse(name: &str) -> Option<Background> {
+        match name {
+            "dark" => Some(Background::Dark),
+            "light" => Some(Background::Light),
+            _ => None,
+        }
+    }
+
+    /// Light when `color` is brighter than mid grey, weighing green most as the eye does.
+    pub fn of((r, g, b): Rgb) -> Background {
+        match 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32 > 127.5 {
+            true => Background::Light,
+            false => Background::Dark,
+        }
+    }
+
+    /// `color` as it should be on this background: on a light one black and white trade places
+    /// and the bright named colors, which wash out against it, become their dark versions.
+    /// Anything else is left alone.
+    pub fn adapt(&self, color: Color) -> Color {
+        match (self, color) {
+            (Background::Dark, color) => color,
+            (Background::Light, Color::White) => Color::Black,
+            (Background::Light, Color::Black) => Color::White,
+            (Background::Light, Color::Yellow) => Color::DarkYellow,
+            (Background::Light, Color::Green) => Color::DarkGreen,
+            (Background::Light, Color::Cyan) => Color::DarkCyan,
+            (Background::Light, Color::Grey) => Color::DarkGrey,
+            (Background::Light, color) => color,
+        }
+    }
+}
+
+const ANSI16: [(Color, Rgb); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// What a terminal color looks like, going by the usual palette for the named and 256 colors;
+/// `None` for the terminal's own default.
+pub fn rgb(color: Color) -> Option<Rgb> {
+    let level = |value: u8| if value == 0 { 0 } else { 55 + 40 * value };
+    match color {
+        Color::Reset => None,
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::AnsiValue(value @ 0..=15) => Some(ANSI16[value as usize].1),
+        Color::AnsiValue(value @ 16..=231) => {
+            let value = value - 16;
+            Some((level(value / 36), level(value / 6 % 6), level(value % 6)))
+        }
+        Color::AnsiValue(value) => {
+            let grey = 8 + 10 * (value - 232);
+            Some((grey, grey, grey))
+        }
+        color => ANSI16.iter().find(|(named, _)| *named == color).map(|(_, rgb)| *rgb),
+    }
+}
+
+fn nearest_ansi16((r, g, b): Rgb) -> Color {
+    let distance = |(other_r, other_g, other_b): Rgb| {
+        let dr = r as i32 - other_r as i32;
+        let dg = g as i32 - other_g as i32;
+        let db = b as i32 - other_b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(color, _)| *color)
+        .unwrap()
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    stops: Vec<Rgb>,
+}
+
+impl Gradient {
+    pub fn preset(name: &str) -> Option<Gradient> {
+        let stops = match name {
+            "fire" => vec![(255, 255, 160), (255, 200, 0), (230, 80, 0), (140, 0, 0)],
+            "ocean" => vec![(200, 255, 255), (0, 200, 220), (0, 90, 200), (10, 20, 120)],
+            "forest" => vec![(210, 255, 120), (60, 200, 60), (20, 110, 40), (10, 60, 30)],
+            "rainbow" => vec![(255, 0, 0), (255, 200, 0), (0, 220, 0), (0, 120, 255), (160, 0, 255)],
+            // Yellow to blue, and red to white, hold up under red-green and blue-yellow color
+            // blindness respectively; grey holds up under anything.
+            "cividis" => vec![(255, 234, 70), (180, 170, 110), (110, 110, 115), (40, 70, 110), (0, 34, 78)],
+            "rose" => vec![(255, 225, 225), (240, 120, 120), (200, 40, 60), (110, 0, 30)],
+            "grey" => vec![(255, 255, 255), (170, 170, 170), (90, 90, 90)],
+            _ => return None,
+        };
+        Some(Gradient { stops })
+    }
+
+    /// Linearly interpolates between the stops, `t` running from 0.0 to 1.0.
+    pub fn at(&self, t: f32) -> Rgb {
+        let t = t.clamp(0.0, 1.0) * (self.stops.len() - 1) as f32;
+        let index = (t as usize).min(self.stops.len() - 2);
+        let fraction = t - index as f32;
+        let (from, to) = (self.stops[index], self.stops[index + 1]);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * fraction).round() as u8;
+        (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+}
+
+/// A bright color `t` of the way round the color wheel, from red at 0.0 through green and blue
+/// back to red at 1.0.
+pub fn hue(t: f32) -> Rgb {
+    let t = t.rem_euclid(1.0) * 6.0;
+    let fraction = t - t.floor();
+    let (rising, falling) = ((fraction * 255.0).round() as u8, ((1.0 - fraction) * 255.0).round() as u8);
+    match t as u32 {
+        0 => (255, rising, 0),
+        1 => (falling, 255, 0),
+        2 => (0, 255, rising),
+        3 => (0, falling, 255),
+        4 => (rising, 0, 255),
+        _ => (255, 0, falling),
+    }
+}
+
+/// The color of a continuous `state` from 0.0 to 1.0, from black at none to white at full, or along
+/// `gradient` from its last stop to its first, so full takes the brightest end of the presets.
+pub fn shade(state: f32, gradient: Option<&Gradient>) -> Rgb {
+    match gradient {
+        Some(gradient) => gradient.at(1.0 - state),
+        None => {
+            let level = (state.clamp(0.0, 1.0) * 255.0).round() as u8;
+            (level, level, level)
+        }
+    }
+}
+
+/// Paints each color over the ones before it, starting from black, letting `alpha` of it through:
+/// at 1.0 the last color hides the rest, and below that colors on top of each other mix.
+pub fn blend(colors: &[Rgb], alpha: f32) -> Rgb {
+    let mix = |under: u8, over: u8| (under as f32 + (over as f32 - under as f32) * alpha.clamp(0.0, 1.0)).round() as u8;
+    colors.iter().fold((0, 0, 0), |under, over| (mix(under.0, over.0), mix(under.1, over.1), mix(under.2, over.2)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient { stops: vec![(0, 0, 0), (200, 100, 0)] };
+        assert_eq!(gradient.at(0.0), (0, 0, 0));
+        assert_eq!(gradient.at(0.5), (100, 50, 0));
+        assert_eq!(gradient.at(1.0), (200, 100, 0));
+        assert_eq!(gradient.at(7.0), (200, 100, 0));
+    }
+
+    #[test]
+    fn test_hue() {
+        assert_eq!(hue(0.0), (255, 0, 0));
+        assert_eq!(hue(1.0 / 12.0), (255, 128, 0));
+        assert_eq!(hue(1.0 / 3.0), (0, 255, 0));
+        assert_eq!(hue(2.0 / 3.0), (0, 0, 255));
+        assert_eq!(hue(1.25), hue(0.25));
+    }
+
+    #[test]
+    fn test_shade() {
+        assert_eq!(shade(0.0, None), (0, 0, 0));
+        assert_eq!(shade(0.5, None), (128, 128, 128));
+        assert_eq!(shade(1.0, None), (255, 255, 255));
+        let fire = Gradient::preset("fire").unwrap();
+        assert_eq!(shade(1.0, Some(&fire)), fire.at(0.0));
+        assert_eq!(shade(0.0, Some(&fire)), fire.at(1.0));
+    }
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(blend(&[], 0.5), (0, 0, 0));
+        assert_eq!(blend(&[(200, 100, 0)], 1.0), (200, 100, 0));
+        assert_eq!(blend(&[(200, 100, 0)], 0.5), (100, 50, 0));
+        assert_eq!(blend(&[(200, 0, 0), (0, 0, 200)], 1.0), (0, 0, 200));
+        assert_eq!(blend(&[(200, 0, 0), (0, 0, 200)], 0.5), (50, 0, 100));
+    }
+
+    #[test]
+    fn test_rgb() {
+        assert_eq!(rgb(Color::Reset), None);
+        assert_eq!(rgb(Color::DarkRed), Some((128, 0, 0)));
+        assert_eq!(rgb(Color::Rgb { r: 1, g: 2, b: 3 }), Some((1, 2, 3)));
+        assert_eq!(rgb(Color::AnsiValue(196)), Some((255, 0, 0)));
+        assert_eq!(rgb(Color::AnsiValue(232)), Some((8, 8, 8)));
+        // The 256 color cube agrees with what `ColorDepth::Ansi256` picks for its corners.
+        assert_eq!(rgb(ColorDepth::Ansi256.color((255, 255, 255))), Some((255, 255, 255)));
+    }
+
+    #[test]
+    fn test_background() {
+        assert_eq!(Background::of((0, 0, 0)), Background::Dark);
+        assert_eq!(Background::of((255, 255, 255)), Background::Light);
+        assert_eq!(Background::of((253, 246, 227)), Background::Light);
+        assert_eq!(Background::of((0, 43, 54)), Background::Dark);
+        assert_eq!(Background::Light.adapt(Color::White), Color::Black);
+        assert_eq!(Background::Light.adapt(Color::DarkRed), Color::DarkRed);
+        assert_eq!(Background::Dark.adapt(Color::White), Color::White);
+    }
+
+    #[test]
+    fn test_color_depth_fallback() {
+        assert_eq!(ColorDepth::TrueColor.color((1, 2, 3)), Color::Rgb { r: 1, g: 2, b: 3 });
+        assert_eq!(ColorDepth::Ansi256.color((255, 0, 0)), Color::AnsiValue(196));
+        assert_eq!(ColorDepth::Ansi16.color((250, 10, 10)), Color::Red);
+        assert_eq!(ColorDepth::Ansi16.color((20, 20, 20)), Color::Black);
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use game_of_life::{Cell, GameMatrix, Grid};
+use crate::color::{hue, Rgb};
+
+/// Gives each group of touching living cells a label that it keeps from one generation to the
+/// next, so it can be drawn in its own color as it moves and changes shape.
+pub struct Tracker {
+    /// The label of every living cell in the last generation seen, zero for dead cells.
+    labels: Grid<u32>,
+    next_label: u32,
+}
+
+impl Tracker {
+    pub fn new(matrix: &GameMatrix) -> Tracker {
+        let (rows, columns) = matrix.shape();
+        let mut tracker = Tracker { labels: Grid::from_element(rows, columns, 0), next_label: 1 };
+        tracker.update(matrix);
+        tracker
+    }
+
+    /// Labels the groups of `matrix`, the next generation after the last one seen. A group takes
+    /// over the label most of its cells or their neighbors had, unless a bigger group already has,
+    /// so when groups merge the biggest part keeps its label and when one splits its biggest piece
+    /// does.
+    pub fn update(&mut self, matrix: &GameMatrix) {
+        let (rows, columns) = matrix.shape();
+        let mut components = matrix.components();
+        components.sort_by_key(|cells| std::cmp::Reverse(cells.len()));
+
+        let mut labels = Grid::from_element(rows, columns, 0);
+        let mut taken = vec![];
+        for cells in components {
+            let mut votes: HashMap<u32, usize> = HashMap::new();
+            for &(row, column) in &cells {
+                for neighbor_row in row.saturating_sub(1)..=(row + 1).min(rows - 1) {
+                    for neighbor_column in column.saturating_sub(1)..=(column + 1).min(columns - 1) {
+                        let label = self.labels[(neighbor_row, neighbor_column)];
+                        if label != 0 && !taken.contains(&label) {
+                            *votes.entry(label).or_default() += 1;
+                        }
+                    }
+                }
+            }
+            let label = votes
+                .into_iter()
+                .max_by_key(|(label, votes)| (*votes, std::cmp::Reverse(*label)))
+                .map(|(label, _)| label)
+                .unwrap_or_else(|| {
+                    self.next_label += 1;
+                    self.next_label - 1
+                });
+            taken.push(label);
+            for cell in cells {
+                labels[cell] = label;
+            }
+        }
+        self.labels = labels;
+    }
+
+    /// The color of the group the cell belongs to, if it is alive.
+    pub fn color(&self, cell: Cell) -> Option<Rgb> {
+        // Stepping round the color wheel by the golden ratio keeps consecutive labels far apart.
+        let label = self.labels[cell];
+        (label != 0).then(|| hue(label as f32 * 0.618_034))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::GameOfLife;
+
+    #[test]
+    fn test_labels_follow_moving_groups() {
+        let mut game = GameOfLife::new(10, 10);
+        *game.current_mut() = "
+            .O........
+            ..O.......
+            OOO.......
+            ..........
+            ..........
+            ..........
+            ..........
+            ......OO..
+            ......OO..
+            ..........
+        ".parse().unwrap();
+        let mut tracker = Tracker::new(game.current());
+        let (glider, block) = (tracker.color((0, 1)).unwrap(), tracker.color((7, 6)).unwrap());
+        assert_ne!(glider, block);
+        for _ in 0..4 {
+            game.tick();
+            tracker.update(game.current());
+        }
+        assert_eq!(tracker.color((1, 2)), Some(glider));
+        assert_eq!(tracker.color((7, 6)), Some(block));
+        assert_eq!(tracker.color((0, 0)), None);
+    }
+}
@@ -0,0 +1,1029 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::Duration;
+use crossterm::{style::Color, Result};
+use game_of_life::io::Format;
+use game_of_life::{Capacity, Font, Lenia, Rule, Rule3d, RulePreset, SmoothLife, Symmetry};
+use tracing::Level;
+use crate::color::{Background, ColorDepth, Gradient};
+use crate::keys::KeyMap;
+use crate::graphics::Graphics;
+use crate::render::{Charset, Glyph};
+use crate::snapshot::RenderFormat;
+use crate::theme::Theme;
+
+pub struct Config {
+    pub glyphs: Glyphs,
+    pub color_depth: ColorDepth,
+    /// Whether the terminal can show more than ASCII. With only ASCII the glyphs that need more
+    /// are swapped for `#` and `.`, and quadrants for a character per cell.
+    pub charset: Charset,
+    /// Whether the terminal's background is dark or light when set, otherwise asked of the
+    /// terminal on starting; see `for_background`.
+    pub background: Option<Background>,
+    /// Draws the grid as an image when the terminal supports it, rather than a character per
+    /// cell: through the kitty graphics protocol, `kitty_scale` cells across each character, or
+    /// as sixels, each cell `sixel_scale` pixels on a side. Quadrant block characters, four cells
+    /// to a character, are only ever asked for.
+    pub graphics: Graphics,
+    pub kitty_scale: usize,
+    pub sixel_scale: usize,
+    /// The colors of the cursor, overlays and heatmap: `default`, `deuteranopia`, `protanopia`,
+    /// `tritanopia` or `high-contrast`; see `Theme`.
+    pub theme: Theme,
+    /// Symbols and colors for living cells of given ages, set in an `[ages]` section as
+    /// `2-5 = "▪ green"`, a single age, a range or `6+` for an age and up, then the symbol and
+    /// optionally a color, named or `#rrggbb`. Where ranges overlap the last one set wins; cells
+    /// of other ages keep the `alive` glyph.
+    pub ages: Vec<AgeStep>,
+    /// Colors living cells by age when set, reaching the end of the gradient at `gradient_span`.
+    /// `theme` names the theme's gradient, whichever theme is picked.
+    pub gradient: Option<Gradient>,
+    /// Whether `gradient` is the theme's and follows it.
+    pub themed_gradient: bool,
+    pub gradient_span: u32,
+    pub keys: KeyMap,
+    /// A `.rle` or `.cells` file to start from instead of a random soup.
+    pub pattern: Option<PathBuf>,
+    /// Text to write on the grid in `font` and start from instead of a random soup, when set.
+    /// `\n` starts a new line.
+    pub text: Option<String>,
+    pub font: Font,
+    /// The rule in `B3/S23` notation; the name of a `RulePreset` is turned into its notation.
+    pub rule: String,
+    /// A second rule for the right half of the grid, to compare it with `rule` side by side.
+    pub split_rule: Option<Rule>,
+    /// The fraction of cells alive in random soups when set; see `density`.
+    pub density: Option<f64>,
+    /// The symmetry of random soups, `C1`, `C2`, `C4`, `D2`, `D4` or `D8`; see `Symmetry`.
+    pub symmetry: Symmetry,
+    pub seed: Option<u64>,
+    /// Milliseconds between generations.
+    pub interval: u64,
+    /// Generations per second, overriding `interval` for rates it can't express in whole
+    /// milliseconds, when set; see `tick_interval`.
+    pub tps: Option<f64>,
+    /// Starts in explore mode, switching to a random rule and reseeding this many seconds apart,
+    /// when set; see `explore_interval`.
+    pub explore: Option<f64>,
+    /// How many times a second the terminal is redrawn, whatever the simulation's pace; it always
+    /// shows the latest generation.
+    pub fps: u32,
+    /// How many generations to advance between frames in the terminal, for watching long runs
+    /// as a time-lapse.
+    pub render_every: u64,
+    /// How many of the latest generations to keep for playing backward, or how much memory they
+    /// may take up, written with a unit like `100MB`; none when zero.
+    pub history: Capacity,
+    /// Records the run in the terminal to this replay file, for `gol replay`, when set.
+    pub record: Option<PathBuf>,
+    /// Logs events at this level and above to `log_file`, when set; see `logging`.
+    pub log: Option<Level>,
+    pub log_file: PathBuf,
+    /// Saves the board to this pattern file on quitting, in the format its extension names, when
+    /// set; `--pattern` picks it up again.
+    pub autosave: Option<PathBuf>,
+    /// How many times faster than `tick_interval` `gol replay` plays.
+    pub speed: f64,
+    /// Runs headless, streaming the game to clients connecting to this address, when set.
+    pub serve: Option<String>,
+    /// Rows and columns of the grid when running headless; the terminal UI fills the terminal. Pipe
+    /// mode fits the grid to the pattern when unset.
+    pub size: Option<(usize, usize)>,
+    /// Plays a two player game instead, each player placing this many cells, when set.
+    pub versus: Option<usize>,
+    /// How many generations a two player game runs before it is scored.
+    pub versus_length: u64,
+    /// The kernel radius of `gol lenia`, in cells.
+    pub lenia_radius: usize,
+    /// The potential `gol lenia` cells grow best at, and how far from it they still grow.
+    pub lenia_mu: f64,
+    pub lenia_sigma: f64,
+    /// How far `gol lenia` moves towards the next state each generation.
+    pub lenia_dt: f64,
+    /// The radii of the inner disk and the outer ring `gol smoothlife` averages over, in cells.
+    pub smoothlife_inner_radius: usize,
+    pub smoothlife_outer_radius: usize,
+    /// The outer fillings `gol smoothlife` cells are born and survive between, written `LOW-HIGH`.
+    pub smoothlife_birth: (f64, f64),
+    pub smoothlife_survival: (f64, f64),
+    /// How far `gol smoothlife` moves towards the next state each generation, 1 being the
+    /// discrete rule.
+    pub smoothlife_dt: f64,
+    /// Runs the game under `rule` and each of these rules at once from the same start, drawn over
+    /// each other, when set; written as rules or preset names separated by commas.
+    pub layers: Vec<Rule>,
+    /// How much of each layer's color shows over the layers under it, from 0 to 1.
+    pub layer_alpha: f64,
+    /// The rule `gol 3d` runs, in Bays' notation like `5766` or as `B6/S5-7`.
+    pub life3d_rule: Rule3d,
+    /// How many layers deep the grid of `gol 3d` is.
+    pub life3d_depth: usize,
+    /// Reads the starting pattern from stdin in this format, when set; see `pipe`.
+    pub stdin_format: Option<Format>,
+    /// Writes the result to stdout in this format instead of running interactively, when set.
+    pub stdout_format: Option<Format>,
+    /// Puts the result on the system clipboard in this format instead of running interactively,
+    /// when set.
+    pub clipboard_format: Option<Format>,
+    /// How many generations to advance before writing to stdout, or to record with `csv`.
+    pub generations: u64,
+    /// How `gol render` prints the board.
+    pub format: RenderFormat,
+    /// Runs headless for `generations` generations and writes the population and `Metrics` of
+    /// each to this file as CSV, when set.
+    pub csv: Option<PathBuf>,
+    /// Runs headless for `generations` generations and prints a fingerprint of the grid every
+    /// this many, so runs can be compared across machines, when set.
+    pub verify_hash_every: Option<u64>,
+    /// A Rhai script seeding the grid, defining the rule or editing every generation; see
+    /// `script`.
+    pub script: Option<PathBuf>,
+    /// Runs the soup headless until it settles, for at most this many generations, and prints a
+    /// census of the objects left, when set.
+    pub census: Option<u64>,
+    /// Runs the pattern headless until it settles, for at most this many generations, and
+    /// prints how long that took and its final and peak populations, when set.
+    pub lifespan: Option<u64>,
+    /// How many soups `gol soup` runs.
+    pub count: u64,
+    /// The most generations a soup in `gol soup` runs for before it is given up on as unsettled.
+    pub limit: u64,
+    /// The most generations `gol period` runs a pattern for, waiting for it to repeat.
+    pub max: u64,
+    /// A WebAssembly plugin supplying the rule or the colors of living cells; see `plugin`.
+    pub plugin: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Glyphs {
+    pub alive: Glyph,
+    pub dead: Glyph,
+    pub wall: Glyph,
+    pub immortal: Glyph,
+}
+
+/// The look of living cells with an age in `ages`; see `Config::ages`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AgeStep {
+    pub ages: RangeInclusive<u32>,
+    pub symbol: char,
+    /// Takes the `alive` glyph's color, or the gradient's, when not set.
+    pub color: Option<Color>,
+}
+
+impl AgeStep {
+    fn parse(ages: &str, value: &str) -> Option<AgeStep> {
+        let ages = match (ages.strip_suffix('+'), ages.split_once(['-', '_'])) {
+            (Some(from), _) => from.parse().ok()?..=u32::MAX,
+            (None, Some((from, to))) => from.parse().ok()?..=to.parse().ok()?,
+            (None, None) => {
+                let age = ages.parse().ok()?;
+                age..=age
+            }
+        };
+        let mut parts = value.split_whitespace();
+        let mut symbol = parts.next()?.chars();
+        let (symbol, None) = (symbol.next()?, symbol.next()) else { return None };
+        let color = match parts.next() {
+            Some(name) => Some(parse_color(name)?),
+            None => None,
+        };
+        match (ages.is_empty(), parts.next()) {
+            (false, None) => Some(AgeStep { ages, symbol, color }),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            glyphs: Glyphs::preset("block").unwrap(),
+            color_depth: ColorDepth::detect(),
+            charset: Charset::detect(),
+            background: None,
+            graphics: Graphics::detect(2, 4),
+            kitty_scale: 2,
+            sixel_scale: 4,
+            theme: Theme::default(),
+            ages: vec![],
+            gradient: None,
+            themed_gradient: false,
+            gradient_span: 50,
+            keys: KeyMap::default(),
+            pattern: None,
+            text: None,
+            font: Font::Standard,
+            rule: "B3/S23".to_string(),
+            split_rule: None,
+            density: None,
+            symmetry: Symmetry::C1,
+            seed: None,
+            interval: 50,
+            tps: None,
+            explore: None,
+            fps: 60,
+            render_every: 1,
+            history: Capacity::Generations(0),
+            record: None,
+            autosave: None,
+            log: None,
+            log_file: PathBuf::from("gol.log"),
+            speed: 1.0,
+            serve: None,
+            size: None,
+            versus: None,
+            versus_length: 300,
+            lenia_radius: 13,
+            lenia_mu: 0.15,
+            lenia_sigma: 0.015,
+            lenia_dt: 0.1,
+            smoothlife_inner_radius: 4,
+            smoothlife_outer_radius: 12,
+            smoothlife_birth: (0.257, 0.336),
+            smoothlife_survival: (0.365, 0.549),
+            smoothlife_dt: 0.5,
+            layers: vec![],
+            layer_alpha: 0.6,
+            life3d_rule: Rule3d::life(),
+            life3d_depth: 16,
+            stdin_format: None,
+            stdout_format: None,
+            clipboard_format: None,
+            generations: 0,
+            format: RenderFormat::Ascii,
+            csv: None,
+            verify_hash_every: None,
+            script: None,
+            plugin: None,
+            census: None,
+            lifespan: None,
+            count: 1000,
+            limit: 10_000,
+            max: 1000,
+        }
+    }
+}
+
+impl Glyphs {
+    fn preset(name: &str) -> Option<Glyphs> {
+        let (alive, dead, wall) = match name {
+            "block" => (Glyph::new('█', Color::White), Glyph::new('█', Color::Black), Glyph::new('█', Color::DarkRed)),
+            "dot" => (Glyph::new('●', Color::White), Glyph::new(' ', Color::Reset), Glyph::new('■', Color::DarkRed)),
+            "shade" => (Glyph::new('▓', Color::White), Glyph::new(' ', Color::Reset), Glyph::new('▓', Color::DarkRed)),
+            "at" => (Glyph::new('@', Color::White), Glyph::new(' ', Color::Reset), Glyph::new('#', Color::DarkRed)),
+            "background" => (Glyph::filled(Color::White), Glyph::filled(Color::Black), Glyph::filled(Color::DarkRed)),
+            _ => return None,
+        };
+        // Immortal cells look like live ones in gold.
+        let immortal = alive.colored(Color::Yellow);
+        Some(Glyphs { alive, dead, wall, immortal })
+    }
+
+    /// The glyphs with any symbols outside ASCII swapped: living and immortal cells for `#`,
+    /// walls for `%` and dead cells for a grey `.`, or a blank when they are filled.
+    pub fn ascii(self) -> Glyphs {
+        let swap = |glyph: Glyph, symbol| match glyph.symbol.is_ascii() {
+            true => glyph,
+            false => Glyph { symbol, ..glyph },
+        };
+        let dead = match (self.dead.symbol.is_ascii(), self.dead.background) {
+            (false, Color::Reset) => Glyph::new('.', Color::DarkGrey),
+            _ => swap(self.dead, ' '),
+        };
+        Glyphs { alive: swap(self.alive, '#'), dead, wall: swap(self.wall, '%'), immortal: swap(self.immortal, '#') }
+    }
+}
+
+impl Config {
+    /// Builds the configuration from the config file, then applies command line flags on top.
+    /// Every setting can be given either as `key = value` in the file or as `--key value` on the
+    /// command line; `--config path` picks a file other than the default one.
+    pub fn load<I>(mut args: I) -> Result<Config>
+        where I: Iterator<Item=String>
+    {
+        let mut path = default_path();
+        let mut explicit_path = false;
+        let mut flags = vec![];
+
+        while let Some(arg) = args.next() {
+            let key = arg
+                .strip_prefix("--")
+                .ok_or_else(|| invalid(format!("unexpected argument '{}'", arg)))?
+                .replace('-', "_");
+            let value = args
+                .next()
+                .ok_or_else(|| invalid(format!("missing value for '{}'", arg)))?;
+            if key == "config" {
+                path = Some(PathBuf::from(value));
+                explicit_path = true;
+            } else {
+                flags.push((key, value));
+            }
+        }
+
+        let mut config = Config::default();
+        if let Some(path) = path {
+            match fs::read_to_string(&path) {
+                Ok(text) => {
+                    for (key, value) in parse(&text)? {
+                        config.set(&key, &value)?;
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound && !explicit_path => {}
+                Err(error) => return Err(invalid(format!("{}: {}", path.display(), error))),
+            }
+        }
+        for (key, value) in flags {
+            config.set(&key, &value)?;
+        }
+        config.keys.check_conflicts()?;
+        if config.charset == Charset::Ascii {
+            config.glyphs = config.glyphs.ascii();
+            if config.graphics == Graphics::Quadrants {
+                config.graphics = Graphics::Text;
+            }
+        }
+        if config.smoothlife_inner_radius >= config.smoothlife_outer_radius {
+            return Err(invalid("'smoothlife_inner_radius' must be less than 'smoothlife_outer_radius'".to_string()));
+        }
+
+        Ok(config)
+    }
+
+    /// The configuration with its glyphs and theme recolored to show on `background`.
+    pub fn for_background(mut self, background: Background) -> Config {
+        let glyphs = &mut self.glyphs;
+        for glyph in [&mut glyphs.alive, &mut glyphs.dead, &mut glyphs.wall, &mut glyphs.immortal] {
+            glyph.foreground = background.adapt(glyph.foreground);
+            glyph.background = background.adapt(glyph.background);
+        }
+        let theme = &mut self.theme;
+        for step in &mut self.ages {
+            step.color = step.color.map(|color| background.adapt(color));
+        }
+        for color in [
+            &mut theme.cursor, &mut theme.ghost, &mut theme.frozen, &mut theme.label,
+            &mut theme.lives, &mut theme.dies, &mut theme.stays_dead,
+        ] {
+            *color = background.adapt(*color);
+        }
+        self
+    }
+
+    /// How a living cell of `age` looks by `ages`, if any of them take it in.
+    pub fn age_step(&self, age: u32) -> Option<&AgeStep> {
+        self.ages.iter().rev().find(|step| step.ages.contains(&age))
+    }
+
+    /// The fraction of cells alive in random soups: `density` if set, otherwise the one that suits
+    /// the rule if it is a `RulePreset`, otherwise one in seven.
+    pub fn density(&self) -> f64 {
+        self.density
+            .or_else(|| self.rule.parse().ok().and_then(RulePreset::of).map(|preset| preset.density))
+            .unwrap_or(1.0 / 7.0)
+    }
+
+    /// The time explore mode spends on each random rule: `explore` if set, otherwise ten seconds.
+    pub fn explore_interval(&self) -> Duration {
+        Duration::from_secs_f64(self.explore.unwrap_or(10.0))
+    }
+
+    /// The automaton `gol lenia` runs, from the `lenia_` settings.
+    pub fn lenia(&self) -> Lenia {
+        Lenia::new(self.lenia_radius, self.lenia_mu as f32, self.lenia_sigma as f32, self.lenia_dt as f32)
+    }
+
+    /// The automaton `gol smoothlife` runs, from the `smoothlife_` settings.
+    pub fn smooth_life(&self) -> SmoothLife {
+        let interval = |(low, high): (f64, f64)| (low as f32, high as f32);
+        SmoothLife::new(
+            self.smoothlife_inner_radius, self.smoothlife_outer_radius,
+            interval(self.smoothlife_birth), interval(self.smoothlife_survival), self.smoothlife_dt as f32,
+        )
+    }
+
+    /// The time between generations, from `tps` if set and `interval` otherwise.
+    pub fn tick_interval(&self) -> Duration {
+        match self.tps {
+            Some(tps) => Duration::from_secs_f64(1.0 / tps),
+            None => Duration::from_millis(self.interval),
+        }
+    }
+
+    /// The time between redraws of the terminal.
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / f64::from(self.fps))
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "glyphs" => {
+                self.glyphs = Glyphs::preset(value)
+                    .ok_or_else(|| invalid(format!("unknown glyph preset '{}'", value)))?;
+            }
+            "alive_char" => self.glyphs.alive = Glyph::new(parse_char(key, value)?, self.glyphs.alive.foreground),
+            "dead_char" => self.glyphs.dead = Glyph::new(parse_char(key, value)?, self.glyphs.dead.foreground),
+            "wall_char" => self.glyphs.wall = Glyph::new(parse_char(key, value)?, self.glyphs.wall.foreground),
+            "immortal_char" => self.glyphs.immortal = Glyph::new(parse_char(key, value)?, self.glyphs.immortal.foreground),
+            "colors" => {
+                self.color_depth = ColorDepth::parse(value)
+                    .ok_or_else(|| invalid(format!("unknown color depth '{}'", value)))?;
+            }
+            "charset" => {
+                self.charset = Charset::parse(value)
+                    .ok_or_else(|| invalid(format!("'{}' must be auto, unicode or ascii", key)))?;
+            }
+            "background" => {
+                self.background = match value {
+                    "auto" => None,
+                    _ => Some(Background::parse(value).ok_or_else(|| invalid(format!("'{}' must be auto, dark or light", key)))?),
+                };
+            }
+            "graphics" => {
+                self.graphics = Graphics::parse(value, self.kitty_scale, self.sixel_scale)
+                    .ok_or_else(|| invalid(format!("'{}' must be auto, text, quadrants, kitty or sixel", key)))?;
+            }
+            "kitty_scale" => {
+                self.kitty_scale = match parse_number(key, value)? {
+                    0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                    scale => scale,
+                };
+                if let Graphics::Kitty { scale } = &mut self.graphics {
+                    *scale = self.kitty_scale;
+                }
+            }
+            "sixel_scale" => {
+                self.sixel_scale = match parse_number(key, value)? {
+                    0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                    scale => scale,
+                };
+                if let Graphics::Sixel { scale } = &mut self.graphics {
+                    *scale = self.sixel_scale;
+                }
+            }
+            "theme" => {
+                self.theme = Theme::preset(value).ok_or_else(|| invalid(format!("unknown theme '{}'", value)))?;
+                if self.themed_gradient {
+                    self.gradient = Some(self.theme.gradient.clone());
+                }
+            }
+            "gradient" => {
+                self.themed_gradient = value == "theme";
+                self.gradient = match value {
+                    "none" => None,
+                    "theme" => Some(self.theme.gradient.clone()),
+                    _ => Some(Gradient::preset(value)
+                        .ok_or_else(|| invalid(format!("unknown gradient '{}'", value)))?),
+                };
+            }
+            "gradient_span" => self.gradient_span = parse_number(key, value)?,
+            "pattern" => self.pattern = Some(PathBuf::from(value)),
+            "text" => self.text = Some(value.replace("\\n", "\n")),
+            "font" => self.font = Font::from_name(value).ok_or_else(|| invalid(format!("'{}' must be 5x7 or 3x5", key)))?,
+            "script" => self.script = Some(PathBuf::from(value)),
+            "plugin" => self.plugin = Some(PathBuf::from(value)),
+            "census" => self.census = Some(parse_number(key, value)?),
+            "lifespan" => self.lifespan = Some(parse_number(key, value)?),
+            "count" => self.count = parse_number(key, value)?,
+            "limit" => self.limit = parse_number(key, value)?,
+            "max" => self.max = parse_number(key, value)?,
+            "rule" => self.rule = RulePreset::find(value).map_or(value.to_string(), |preset| preset.notation.to_string()),
+            "split_rule" => self.split_rule = Some(parse_rule(key, value)?),
+            "density" => self.density = Some(parse_number(key, value)?),
+            "symmetry" => {
+                self.symmetry = Symmetry::from_name(value)
+                    .ok_or_else(|| invalid(format!("'{}' must be C1, C2, C4, D2, D4 or D8", key)))?
+            }
+            "seed" => self.seed = Some(parse_number(key, value)?),
+            "interval" => self.interval = parse_number(key, value)?,
+            "tps" => self.tps = Some(parse_positive(key, value)?),
+            "explore" => self.explore = Some(parse_positive(key, value)?),
+            "fps" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                fps => self.fps = fps,
+            },
+            "history" => self.history = parse_capacity(key, value)?,
+            "record" => self.record = Some(PathBuf::from(value)),
+            "autosave" => self.autosave = Some(PathBuf::from(value)),
+            "log" => {
+                self.log = Some(value
+                    .parse()
+                    .map_err(|_| invalid(format!("'{}' must be error, warn, info, debug or trace", key)))?);
+            }
+            "log_file" => self.log_file = PathBuf::from(value),
+            // Written as a multiple, like `2x` or `0.5x`, or as a plain number.
+            "speed" => self.speed = parse_positive(key, value.strip_suffix('x').unwrap_or(value))?,
+            "render_every" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                every => self.render_every = every,
+            },
+            "versus" => self.versus = Some(parse_number(key, value)?),
+            "versus_length" => self.versus_length = parse_number(key, value)?,
+            "lenia_radius" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                radius => self.lenia_radius = radius,
+            },
+            "lenia_mu" => self.lenia_mu = parse_positive(key, value)?,
+            "lenia_sigma" => self.lenia_sigma = parse_positive(key, value)?,
+            "lenia_dt" => self.lenia_dt = parse_positive(key, value)?,
+            "smoothlife_inner_radius" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                radius => self.smoothlife_inner_radius = radius,
+            },
+            "smoothlife_outer_radius" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                radius => self.smoothlife_outer_radius = radius,
+            },
+            "smoothlife_birth" => self.smoothlife_birth = parse_interval(key, value)?,
+            "smoothlife_survival" => self.smoothlife_survival = parse_interval(key, value)?,
+            "layers" => {
+                self.layers = value
+                    .split(',')
+                    .map(|rule| parse_rule(key, rule.trim()))
+                    .collect::<Result<_>>()?;
+            }
+            "layer_alpha" => match parse_positive(key, value)? {
+                alpha if alpha > 1.0 => return Err(invalid(format!("'{}' must be at most 1", key))),
+                alpha => self.layer_alpha = alpha,
+            },
+            "life3d_rule" => {
+                self.life3d_rule = value
+                    .parse()
+                    .map_err(|error| invalid(format!("'{}': {}", key, error)))?;
+            }
+            "life3d_depth" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                depth => self.life3d_depth = depth,
+            },
+            "smoothlife_dt" => match parse_positive(key, value)? {
+                dt if dt > 1.0 => return Err(invalid(format!("'{}' must be at most 1", key))),
+                dt => self.smoothlife_dt = dt,
+            },
+            "stdin_format" => self.stdin_format = Some(parse_format(key, value)?),
+            "stdout_format" => self.stdout_format = Some(parse_format(key, value)?),
+            "clipboard_format" => self.clipboard_format = Some(parse_format(key, value)?),
+            "generations" => self.generations = parse_number(key, value)?,
+            "format" => {
+                self.format = RenderFormat::parse(value).ok_or_else(|| invalid(format!("'{}' must be ascii or ansi", key)))?;
+            }
+            "csv" => self.csv = Some(PathBuf::from(value)),
+            "verify_hash_every" => match parse_number(key, value)? {
+                0 => return Err(invalid(format!("'{}' must be at least 1", key))),
+                every => self.verify_hash_every = Some(every),
+            },
+            "serve" => self.serve = Some(value.to_string()),
+            "size" => {
+                self.size = Some(value
+                    .split_once('x')
+                    .and_then(|(rows, columns)| Some((rows.trim().parse().ok()?, columns.trim().parse().ok()?)))
+                    .filter(|(rows, columns)| *rows > 0 && *columns > 0)
+                    .ok_or_else(|| invalid(format!("'{}' must be ROWSxCOLUMNS", key)))?);
+            }
+            _ => match (key.strip_prefix("keys."), key.strip_prefix("ages.")) {
+                (Some(action), _) => self.keys.bind(action, value)?,
+                (_, Some(ages)) => self.ages.push(AgeStep::parse(ages, value).ok_or_else(|| {
+                    invalid(format!("'{}' must be an age, a range like 2-5 or 6+, then a symbol and a color", key))
+                })?),
+                _ => return Err(invalid(format!("unknown setting '{}'", key))),
+            },
+        }
+        Ok(())
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("game-of-life").join("config.toml"))
+}
+
+/// Parses a small subset of TOML: `key = value` pairs, optionally quoted, `# comments` and
+/// `[section]` headers, which prefix the keys that follow them with `section.`.
+fn parse(text: &str) -> Result<Vec<(String, String)>> {
+    let mut section = String::new();
+    let mut settings = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = format!("{}.", name.trim());
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("line {}: expected 'key = value'", number + 1)))?;
+        let value = value.trim();
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => quoted
+                .split_once('"')
+                .map(|(value, _)| value)
+                .ok_or_else(|| invalid(format!("line {}: unterminated string", number + 1)))?,
+            None => value.split('#').next().unwrap_or_default().trim(),
+        };
+        settings.push((format!("{}{}", section, key.trim().replace('-', "_")), value.to_string()));
+    }
+    Ok(settings)
+}
+
+/// A rule in `B3/S23` notation or the name of a `RulePreset`.
+fn parse_rule(key: &str, value: &str) -> Result<Rule> {
+    match RulePreset::find(value) {
+        Some(preset) => Ok(preset.rule()),
+        None => value.parse().map_err(|error| invalid(format!("'{}': {}", key, error))),
+    }
+}
+
+/// A color by its crossterm name, like `dark_green`, or as `#rrggbb`.
+fn parse_color(value: &str) -> Option<Color> {
+    match value.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 => {
+            let component = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+            Some(Color::Rgb { r: component(0)?, g: component(2)?, b: component(4)? })
+        }
+        Some(_) => None,
+        None => Color::try_from(value).ok(),
+    }
+}
+
+fn parse_char(key: &str, value: &str) -> Result<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(invalid(format!("'{}' must be a single character", key))),
+    }
+}
+
+fn parse_format(key: &str, value: &str) -> Result<Format> {
+    Format::from_name(value).ok_or_else(|| invalid(format!("'{}' must be rle or cells", key)))
+}
+
+fn parse_number<T>(key: &str, value: &str) -> Result<T>
+    where T: std::str::FromStr
+{
+    value.parse().map_err(|_| invalid(format!("'{}' must be a number", key)))
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<f64> {
+    value
+        .parse()
+        .ok()
+        .filter(|number: &f64| *number > 0.0 && number.is_finite())
+        .ok_or_else(|| invalid(format!("'{}' must be a positive number", key)))
+}
+
+/// Parses a number of generations, like `500`, or an amount of memory in bytes, like `100MB`,
+/// the units `B`, `KB`, `MB` and `GB` going up by 1024 at a time.
+fn parse_capacity(key: &str, value: &str) -> Result<Capacity> {
+    const UNITS: [(&str, f64); 4] = [("GB", 1073741824.0), ("MB", 1048576.0), ("KB", 1024.0), ("B", 1.0)];
+    let upper = value.trim().to_ascii_uppercase();
+    let unit = UNITS.iter().find(|(unit, _)| upper.ends_with(unit));
+    match unit {
+        None => Ok(Capacity::Generations(parse_number(key, value)?)),
+        Some((unit, bytes)) => upper[..upper.len() - unit.len()]
+            .trim()
+            .parse()
+            .ok()
+            .filter(|number: &f64| *number >= 0.0 && number.is_finite())
+            .map(|number| Capacity::Bytes((number * bytes) as usize))
+            .ok_or_else(|| invalid(format!("'{}' must be a number of generations or of bytes, like 100MB", key))),
+    }
+}
+
+/// Parses `LOW-HIGH`, two numbers from 0 to 1 with the first no greater.
+fn parse_interval(key: &str, value: &str) -> Result<(f64, f64)> {
+    value
+        .split_once('-')
+        .and_then(|(low, high)| Some((low.trim().parse().ok()?, high.trim().parse().ok()?)))
+        .filter(|(low, high): &(f64, f64)| 0.0 <= *low && low <= high && *high <= 1.0)
+        .ok_or_else(|| invalid(format!("'{}' must be LOW-HIGH, from 0 to 1", key)))
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::Continuous;
+
+    fn load(args: &[&str]) -> Result<Config> {
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        if !args.contains(&"--config".to_string()) {
+            args.extend(["--config".to_string(), "/dev/null".to_string()]);
+        }
+        Config::load(args.into_iter())
+    }
+
+    #[test]
+    fn test_parse() {
+        let settings = parse("# comment\nglyphs = \"dot\"\nalive-char = @ # trailing\n\n[keys]\nquit = \"#\"").unwrap();
+        assert_eq!(settings, vec![
+            ("glyphs".to_string(), "dot".to_string()),
+            ("alive_char".to_string(), "@".to_string()),
+            ("keys.quit".to_string(), "#".to_string()),
+        ]);
+        assert!(parse("glyphs").is_err());
+    }
+
+    #[test]
+    fn test_missing_explicit_config_is_an_error() {
+        assert!(load(&["--config", "/nonexistent/config.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_glyph_flags() {
+        let config = load(&["--glyphs", "at", "--dead-char", "."]).unwrap();
+        assert_eq!(config.glyphs.alive.symbol, '@');
+        assert_eq!(config.glyphs.dead.symbol, '.');
+        assert_eq!(config.glyphs.immortal.symbol, '@');
+        assert_eq!(config.glyphs.immortal.foreground, Color::Yellow);
+        assert!(load(&["--alive-char", "ab"]).is_err());
+        assert!(load(&["--glyphs"]).is_err());
+    }
+
+    #[test]
+    fn test_key_bindings() {
+        assert!(load(&["--keys.pause", "x"]).is_ok());
+        assert!(load(&["--keys.pause", "q"]).is_err());
+        assert!(load(&["--keys.jump", "j"]).is_err());
+    }
+
+    #[test]
+    fn test_color_flags() {
+        let config = load(&["--colors", "256", "--gradient", "fire", "--gradient-span", "20"]).unwrap();
+        assert_eq!(config.color_depth, ColorDepth::Ansi256);
+        assert_eq!(config.gradient, Gradient::preset("fire"));
+        assert_eq!(config.gradient_span, 20);
+        assert!(load(&["--gradient-span", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_ages() {
+        let settings = parse("[ages]\n1 = \"·\"\n2-5 = \"▪ green\"\n6+ = \"█ #ff8000\"").unwrap();
+        let mut config = Config::default();
+        for (key, value) in settings {
+            config.set(&key, &value).unwrap();
+        }
+        assert_eq!(config.age_step(0), None);
+        assert_eq!(config.age_step(1), Some(&AgeStep { ages: 1..=1, symbol: '·', color: None }));
+        assert_eq!(config.age_step(5).map(|step| (step.symbol, step.color)), Some(('▪', Some(Color::Green))));
+        assert_eq!(config.age_step(600).and_then(|step| step.color), Some(Color::Rgb { r: 255, g: 128, b: 0 }));
+        // Later ranges win where they overlap.
+        let config = load(&["--ages.1+", "o", "--ages.3", "O"]).unwrap();
+        assert_eq!((config.age_step(2).unwrap().symbol, config.age_step(3).unwrap().symbol), ('o', 'O'));
+        assert!(load(&["--ages.5-2", "o"]).is_err());
+        assert!(load(&["--ages.old", "o"]).is_err());
+        assert!(load(&["--ages.1", "oo"]).is_err());
+        assert!(load(&["--ages.1", "o mauve"]).is_err());
+    }
+
+    #[test]
+    fn test_charset() {
+        let config = load(&["--charset", "ascii"]).unwrap();
+        assert_eq!((config.glyphs.alive.symbol, config.glyphs.dead.symbol, config.glyphs.wall.symbol), ('#', '.', '%'));
+        assert_eq!(config.glyphs.immortal.foreground, Color::Yellow);
+        // ASCII glyphs and filled ones are kept.
+        let config = load(&["--charset", "ascii", "--glyphs", "at"]).unwrap();
+        assert_eq!((config.glyphs.alive.symbol, config.glyphs.wall.symbol), ('@', '#'));
+        let config = load(&["--charset", "ascii", "--glyphs", "background"]).unwrap();
+        assert_eq!(config.glyphs.dead, Glyph::filled(Color::Black));
+        let config = load(&["--graphics", "quadrants", "--charset", "ascii"]).unwrap();
+        assert_eq!(config.graphics, Graphics::Text);
+        let config = load(&["--charset", "unicode"]).unwrap();
+        assert_eq!(config.glyphs.alive.symbol, '█');
+        assert!(load(&["--charset", "latin1"]).is_err());
+    }
+
+    #[test]
+    fn test_background() {
+        assert_eq!(load(&[]).unwrap().background, None);
+        let config = load(&["--background", "light", "--charset", "unicode"]).unwrap();
+        assert_eq!(config.background, Some(Background::Light));
+        let config = config.for_background(Background::Light);
+        assert_eq!(config.glyphs.alive.foreground, Color::Black);
+        assert_eq!(config.glyphs.dead.foreground, Color::White);
+        assert_eq!(config.theme.cursor, Color::DarkYellow);
+        let config = load(&["--glyphs", "background"]).unwrap().for_background(Background::Light);
+        assert_eq!(config.glyphs.alive.background, Color::Black);
+        assert!(load(&["--background", "grey"]).is_err());
+    }
+
+    #[test]
+    fn test_theme_flag() {
+        let config = load(&["--theme", "tritanopia"]).unwrap();
+        assert_eq!(config.theme, Theme::preset("tritanopia").unwrap());
+        assert_eq!(config.gradient, None);
+        // The theme's gradient follows the theme whichever flag comes first.
+        let config = load(&["--gradient", "theme", "--theme", "deuteranopia"]).unwrap();
+        assert_eq!(config.gradient, Gradient::preset("cividis"));
+        let config = load(&["--theme", "high-contrast", "--gradient", "theme"]).unwrap();
+        assert_eq!(config.gradient, Gradient::preset("grey"));
+        let config = load(&["--gradient", "theme", "--gradient", "ocean", "--theme", "protanopia"]).unwrap();
+        assert_eq!(config.gradient, Gradient::preset("ocean"));
+        assert!(load(&["--theme", "sepia"]).is_err());
+    }
+
+    #[test]
+    fn test_graphics_flags() {
+        assert_eq!(load(&["--graphics", "text"]).unwrap().graphics, Graphics::Text);
+        // The scale applies whichever flag comes first.
+        assert_eq!(load(&["--graphics", "kitty", "--kitty-scale", "3"]).unwrap().graphics, Graphics::Kitty { scale: 3 });
+        assert_eq!(load(&["--kitty-scale", "3", "--graphics", "kitty"]).unwrap().graphics, Graphics::Kitty { scale: 3 });
+        assert_eq!(load(&["--sixel-scale", "6", "--graphics", "sixel"]).unwrap().graphics, Graphics::Sixel { scale: 6 });
+        assert_eq!(load(&["--graphics", "quadrants", "--charset", "unicode"]).unwrap().graphics, Graphics::Quadrants);
+        assert!(load(&["--graphics", "ascii"]).is_err());
+        assert!(load(&["--kitty-scale", "0"]).is_err());
+        assert!(load(&["--sixel-scale", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_versus_flags() {
+        let config = load(&["--versus", "12", "--versus-length", "100"]).unwrap();
+        assert_eq!(config.versus, Some(12));
+        assert_eq!(config.versus_length, 100);
+        assert!(load(&["--versus", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_pipe_flags() {
+        let config = load(&["--stdin-format", "rle", "--stdout-format", "cells", "--generations", "4"]).unwrap();
+        assert_eq!(config.stdin_format, Some(Format::Rle));
+        assert_eq!(config.stdout_format, Some(Format::Plaintext));
+        assert_eq!(config.generations, 4);
+        assert!(load(&["--stdout-format", "gif"]).is_err());
+        assert_eq!(load(&["--clipboard-format", "rle"]).unwrap().clipboard_format, Some(Format::Rle));
+    }
+
+    #[test]
+    fn test_csv_flag() {
+        let config = load(&["--csv", "metrics.csv", "--generations", "500"]).unwrap();
+        assert_eq!(config.csv, Some(PathBuf::from("metrics.csv")));
+        assert_eq!(config.generations, 500);
+    }
+
+    #[test]
+    fn test_render_every_flag() {
+        assert_eq!(Config::default().render_every, 1);
+        assert_eq!(load(&["--render-every", "10"]).unwrap().render_every, 10);
+        assert!(load(&["--render-every", "0"]).is_err());
+        assert_eq!(load(&["--history", "500"]).unwrap().history, Capacity::Generations(500));
+    }
+
+    #[test]
+    fn test_history_flag() {
+        assert_eq!(Config::default().history, Capacity::Generations(0));
+        assert_eq!(load(&["--history", "100MB"]).unwrap().history, Capacity::Bytes(100 << 20));
+        assert_eq!(load(&["--history", "1.5kb"]).unwrap().history, Capacity::Bytes(1536));
+        assert_eq!(load(&["--history", "64 B"]).unwrap().history, Capacity::Bytes(64));
+        assert!(load(&["--history", "lots"]).is_err());
+        assert!(load(&["--history", "MB"]).is_err());
+        assert!(load(&["--history", "-1GB"]).is_err());
+    }
+
+    #[test]
+    fn test_log_flags() {
+        let config = load(&["--log", "debug", "--log-file", "/tmp/gol.log"]).unwrap();
+        assert_eq!(config.log, Some(Level::DEBUG));
+        assert_eq!(config.log_file, PathBuf::from("/tmp/gol.log"));
+        assert_eq!(Config::default().log, None);
+        assert!(load(&["--log", "loud"]).is_err());
+    }
+
+    #[test]
+    fn test_rate_flags() {
+        let config = load(&["--tps", "200", "--fps", "30"]).unwrap();
+        assert_eq!(config.tick_interval(), Duration::from_millis(5));
+        assert_eq!(config.frame_time(), Duration::from_secs_f64(1.0 / 30.0));
+        assert_eq!(load(&["--interval", "20"]).unwrap().tick_interval(), Duration::from_millis(20));
+        assert!(load(&["--tps", "0"]).is_err());
+        assert!(load(&["--fps", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_text_flags() {
+        let config = load(&["--text", "HELLO\\nWORLD", "--font", "3x5"]).unwrap();
+        assert_eq!(config.text.as_deref(), Some("HELLO\nWORLD"));
+        assert_eq!(config.font, Font::Small);
+        assert_eq!(Config::default().font, Font::Standard);
+        assert!(load(&["--font", "8x8"]).is_err());
+    }
+
+    #[test]
+    fn test_replay_flags() {
+        let config = load(&["--record", "run.golrec", "--speed", "2x"]).unwrap();
+        assert_eq!(config.record, Some(PathBuf::from("run.golrec")));
+        assert_eq!(load(&["--autosave", "last.rle"]).unwrap().autosave, Some(PathBuf::from("last.rle")));
+        assert_eq!(config.speed, 2.0);
+        assert_eq!(load(&["--speed", "0.25"]).unwrap().speed, 0.25);
+        assert!(load(&["--speed", "0x"]).is_err());
+        assert!(load(&["--speed", "fast"]).is_err());
+    }
+
+    #[test]
+    fn test_verify_hash_every_flag() {
+        assert_eq!(load(&["--verify-hash-every", "100"]).unwrap().verify_hash_every, Some(100));
+        assert!(load(&["--verify-hash-every", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_soup_flags() {
+        let config = load(&["--count", "100000", "--limit", "500"]).unwrap();
+        assert_eq!((config.count, config.limit), (100000, 500));
+        assert_eq!(load(&["--symmetry", "d4"]).unwrap().symmetry, Symmetry::D4);
+        assert!(load(&["--symmetry", "D3"]).is_err());
+        assert_eq!(load(&["--split-rule", "B2/S"]).unwrap().split_rule, Some("B2/S".parse().unwrap()));
+        assert!(load(&["--split-rule", "nope"]).is_err());
+        assert_eq!(load(&["--split-rule", "seeds"]).unwrap().split_rule, Some("B2/S".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_rule_presets() {
+        let config = load(&["--rule", "highlife"]).unwrap();
+        assert_eq!(config.rule, "B36/S23");
+        assert_eq!(config.density(), 0.15);
+        assert_eq!(load(&["--rule", "seeds", "--density", "0.3"]).unwrap().density(), 0.3);
+        assert_eq!(load(&["--rule", "B2/S"]).unwrap().density(), 0.02);
+        assert_eq!(load(&["--rule", "B1/S"]).unwrap().density(), 1.0 / 7.0);
+        assert_eq!(Config::default().density(), 1.0 / 7.0);
+    }
+
+    #[test]
+    fn test_explore_flags() {
+        assert_eq!(Config::default().explore_interval(), Duration::from_secs(10));
+        let config = load(&["--explore", "2.5"]).unwrap();
+        assert_eq!((config.explore, config.explore_interval()), (Some(2.5), Duration::from_millis(2500)));
+        assert!(load(&["--explore", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_lenia_flags() {
+        assert_eq!(Config::default().lenia(), Lenia::default());
+        let config = load(&["--lenia-radius", "8", "--lenia-mu", "0.2", "--lenia-dt", "0.05"]).unwrap();
+        let lenia = config.lenia();
+        assert_eq!((lenia.radius(), lenia.mu(), lenia.sigma(), lenia.dt()), (8, 0.2, 0.015, 0.05));
+        assert!(load(&["--lenia-radius", "0"]).is_err());
+        assert!(load(&["--lenia-sigma", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_smoothlife_flags() {
+        assert_eq!(Config::default().smooth_life(), SmoothLife::default());
+        let config = load(&["--smoothlife-outer-radius", "9", "--smoothlife-birth", "0.278-0.365", "--smoothlife-dt", "1"]).unwrap();
+        let rule = config.smooth_life();
+        assert_eq!((rule.inner_radius(), rule.outer_radius(), rule.birth(), rule.dt()), (4, 9, (0.278, 0.365), 1.0));
+        assert!(load(&["--smoothlife-survival", "0.5-0.4"]).is_err());
+        assert!(load(&["--smoothlife-survival", "0.5"]).is_err());
+        assert!(load(&["--smoothlife-dt", "2"]).is_err());
+        assert!(load(&["--smoothlife-inner-radius", "12"]).is_err());
+    }
+
+    #[test]
+    fn test_layers_flags() {
+        assert!(Config::default().layers.is_empty());
+        let config = load(&["--layers", "HighLife, B2/S", "--layer-alpha", "0.5"]).unwrap();
+        assert_eq!(config.layers, vec![RulePreset::find("HighLife").unwrap().rule(), "B2/S".parse().unwrap()]);
+        assert_eq!(config.layer_alpha, 0.5);
+        assert!(load(&["--layers", "B3/S23,nonsense"]).is_err());
+        assert!(load(&["--layer-alpha", "1.5"]).is_err());
+    }
+
+    #[test]
+    fn test_life3d_flags() {
+        assert_eq!((Config::default().life3d_rule, Config::default().life3d_depth), (Rule3d::life(), 16));
+        let config = load(&["--life3d-rule", "4555", "--life3d-depth", "8"]).unwrap();
+        assert_eq!((config.life3d_rule.to_string().as_str(), config.life3d_depth), ("4555", 8));
+        assert!(load(&["--life3d-rule", "B3/S23/C2"]).is_err());
+        assert!(load(&["--life3d-depth", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_period_flags() {
+        assert_eq!(Config::default().max, 1000);
+        assert_eq!(load(&["--max", "50"]).unwrap().max, 50);
+    }
+
+    #[test]
+    fn test_census_flag() {
+        assert_eq!(load(&["--census", "5000"]).unwrap().census, Some(5000));
+        assert!(load(&["--census", "soon"]).is_err());
+        assert_eq!(load(&["--lifespan", "5000"]).unwrap().lifespan, Some(5000));
+    }
+
+    #[test]
+    fn test_serve_flags() {
+        let config = load(&["--serve", "127.0.0.1:7878", "--size", "40x120"]).unwrap();
+        assert_eq!(config.serve.as_deref(), Some("127.0.0.1:7878"));
+        assert_eq!(config.size, Some((40, 120)));
+        assert!(load(&["--size", "40"]).is_err());
+        assert!(load(&["--size", "0x10"]).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use game_of_life::{divergence, io, Pattern, Rule};
+use crate::config::Config;
+
+/// How many differing cells are listed before the rest are only counted.
+const LISTED: usize = 20;
+
+/// Loads the patterns at `first` and `second`, runs both for `config.generations` generations,
+/// each under the rule its file names or else the configured one, and prints the first
+/// generation their evolutions differ in and where, or that they never do.
+pub fn run(config: &Config, first: &Path, second: &Path) -> Result<()> {
+    let rule: Rule = config.rule
+        .parse()
+        .map_err(|error: game_of_life::Error| Error::new(ErrorKind::InvalidInput, error.to_string()))?;
+    let (first, second) = (load(first)?, load(second)?);
+    let rules = (first.metadata().rule.unwrap_or(rule), second.metadata().rule.unwrap_or(rule));
+    match divergence(&first, &second, rules, config.generations) {
+        None => println!("identical for {} generations", config.generations),
+        Some(divergence) => {
+            println!(
+                "diverge at generation {}: {} {} differ, populations {} and {}",
+                divergence.generation, divergence.cells.len(), if divergence.cells.len() == 1 { "cell" } else { "cells" },
+                divergence.populations.0, divergence.populations.1
+            );
+            for (row, column) in divergence.cells.iter().take(LISTED) {
+                println!("  ({}, {})", row, column);
+            }
+            if divergence.cells.len() > LISTED {
+                println!("  and {} more", divergence.cells.len() - LISTED);
+            }
+            match divergence.rejoined {
+                Some(generation) => println!("identical again from generation {}", generation),
+                None => println!("still different at generation {}", config.generations),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Pattern> {
+    io::load(path).map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", path.display(), error)))
+}
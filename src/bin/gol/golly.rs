@@ -0,0 +1,67 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use game_of_life::{Boundary, GameMatrix, GollyRule, GollyState};
+use crate::config::Config;
+
+/// Loads the Golly `.rule` file at `path`, runs a random soup of `size` under it for
+/// `config.generations` generations, each living cell in any of its states alike, and prints the
+/// grid and how many cells ended up in each state.
+pub fn run(config: &Config, path: &Path, size: (usize, usize)) -> Result<()> {
+    let rule = GollyRule::load(path)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", path.display(), error)))?;
+    let mut rng = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+    let mut matrix = GameMatrix::new(size.0, size.1);
+    for row in 0..size.0 {
+        for column in 0..size.1 {
+            if rng.gen_bool(config.density().clamp(0.0, 1.0)) {
+                matrix.set_state((row, column), GollyState(rng.gen_range(1..rule.states()) as u8));
+            }
+        }
+    }
+    for _ in 0..config.generations {
+        matrix = rule.step(&matrix, Boundary::Wrap);
+    }
+
+    print!("{}", picture(&matrix));
+    let mut counts = vec![0; rule.states()];
+    for row in 0..size.0 {
+        for column in 0..size.1 {
+            counts[usize::from(matrix.get_state((row, column)).0)] += 1;
+        }
+    }
+    let counts: Vec<String> = counts.iter().enumerate().skip(1).map(|(state, count)| format!("{}: {}", state, count)).collect();
+    println!("{} after {} generations | {}", rule.name(), config.generations, counts.join(" "));
+    Ok(())
+}
+
+/// The grid a line per row, `.` for the background and a digit or letter for every other state.
+fn picture(matrix: &GameMatrix<GollyState>) -> String {
+    let (rows, columns) = matrix.shape();
+    let mut picture = String::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            picture.push(match matrix.get_state((row, column)).0 {
+                0 => '.',
+                state => char::from_digit(u32::from(state), 36).unwrap_or('?').to_ascii_uppercase(),
+            });
+        }
+        picture.push('\n');
+    }
+    picture
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_picture() {
+        let mut matrix = GameMatrix::new(2, 3);
+        matrix.set_state((0, 1), GollyState(2));
+        matrix.set_state((1, 2), GollyState(11));
+        matrix.set_state((1, 0), GollyState(200));
+        assert_eq!(picture(&matrix), ".2.\n?.B\n");
+    }
+}
@@ -0,0 +1,102 @@
+use crossterm::style::Color;
+use crate::color::{rgb, Rgb};
+use crate::render::Glyph;
+use crate::terminal;
+
+/// The size of a character in pixels, rows then columns, when the terminal does not say.
+const CHARACTER: (usize, usize) = (16, 8);
+
+/// How the terminal loops put the grid on screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Graphics {
+    /// A character per cell.
+    Text,
+    /// Four cells to a character, a two by two block drawn with the quadrant block characters,
+    /// which leaves the cells about square.
+    Quadrants,
+    /// An image through the kitty graphics protocol, `scale` cells across each character and
+    /// twice that down it, since characters are about twice as tall as they are wide.
+    Kitty { scale: usize },
+    /// A sixel image, each cell a square `scale` pixels on a side, as many as fit in the
+    /// terminal's window.
+    Sixel { scale: usize },
+}
+
+impl Graphics {
+    /// Guesses what the terminal can show from the variables it sets: kitty, WezTerm and Ghostty
+    /// speak the kitty graphics protocol, and mlterm, foot and mintty sixels. xterm draws sixels
+    /// only when started as a VT340, which it does not advertise, so it has to be asked for.
+    pub fn detect(kitty_scale: usize, sixel_scale: usize) -> Graphics {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || program == "WezTerm"
+        {
+            Graphics::Kitty { scale: kitty_scale }
+        } else if term.starts_with("mlterm") || term.starts_with("foot") || program == "mintty" {
+            Graphics::Sixel { scale: sixel_scale }
+        } else {
+            Graphics::Text
+        }
+    }
+
+    pub fn parse(name: &str, kitty_scale: usize, sixel_scale: usize) -> Option<Graphics> {
+        match name {
+            "auto" => Some(Graphics::detect(kitty_scale, sixel_scale)),
+            "text" => Some(Graphics::Text),
+            "quadrants" => Some(Graphics::Quadrants),
+            "kitty" => Some(Graphics::Kitty { scale: kitty_scale }),
+            "sixel" => Some(Graphics::Sixel { scale: sixel_scale }),
+            _ => None,
+        }
+    }
+
+    /// How many cells of the grid fit on a screen of `rows` by `columns` characters.
+    pub fn grid_shape(&self, (rows, columns): (usize, usize)) -> (usize, usize) {
+        match self {
+            Graphics::Text => (rows, columns),
+            Graphics::Quadrants => (rows * 2, columns * 2),
+            Graphics::Kitty { scale } => (rows * 2 * scale, columns * scale),
+            Graphics::Sixel { scale } => {
+                let (height, width) = terminal::character_size().unwrap_or(CHARACTER);
+                ((rows * height / scale).max(1), (columns * width / scale).max(1))
+            }
+        }
+    }
+}
+
+/// The color a glyph shows as a pixel: its background if it has one, or else its symbol's
+/// color, with blank symbols and the terminal's own colors taken as black and white.
+pub fn pixel(glyph: &Glyph) -> Rgb {
+    match (glyph.background, glyph.symbol, glyph.foreground) {
+        (Color::Reset, ' ', _) => (0, 0, 0),
+        (Color::Reset, _, foreground) => rgb(foreground).unwrap_or((255, 255, 255)),
+        (background, _, _) => rgb(background).unwrap_or((0, 0, 0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_shape() {
+        assert_eq!(Graphics::Text.grid_shape((30, 100)), (30, 100));
+        assert_eq!(Graphics::Quadrants.grid_shape((30, 100)), (60, 200));
+        assert_eq!(Graphics::Kitty { scale: 2 }.grid_shape((30, 100)), (120, 200));
+        assert_eq!(Graphics::parse("kitty", 3, 4), Some(Graphics::Kitty { scale: 3 }));
+        assert_eq!(Graphics::parse("sixel", 3, 4), Some(Graphics::Sixel { scale: 4 }));
+        assert_eq!(Graphics::parse("ascii", 3, 4), None);
+    }
+
+    #[test]
+    fn test_pixel() {
+        assert_eq!(pixel(&Glyph::new('█', Color::White)), (255, 255, 255));
+        assert_eq!(pixel(&Glyph::new('█', Color::Black)), (0, 0, 0));
+        assert_eq!(pixel(&Glyph::new(' ', Color::Reset)), (0, 0, 0));
+        assert_eq!(pixel(&Glyph::new('@', Color::Reset)), (255, 255, 255));
+        assert_eq!(pixel(&Glyph::filled(Color::Rgb { r: 1, g: 2, b: 3 })), (1, 2, 3));
+    }
+}
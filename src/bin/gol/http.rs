@@ -0,0 +1,186 @@
+// Without the control API only the WebSocket handshake reads requests.
+#![cfg_attr(not(feature = "server"), allow(dead_code))]
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
+
+/// Request bodies larger than this are refused; the API only takes coordinates and patterns.
+const MAX_BODY: usize = 1 << 20;
+const MAX_HEADERS: usize = 100;
+
+/// Just enough of an HTTP/1.1 request for the WebSocket handshake and the control API.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Request {
+    pub fn read<R>(read: R) -> Result<Request>
+        where R: Read
+    {
+        let mut reader = BufReader::new(read);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => (method.to_string(), target),
+            _ => return Err(malformed("request line")),
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (decode(name), decode(value))
+            })
+            .collect();
+        let path = decode(path);
+
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "connection closed in the headers"));
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if headers.len() == MAX_HEADERS {
+                return Err(malformed("headers"));
+            }
+            let (name, value) = line.split_once(':').ok_or_else(|| malformed("header"))?;
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+
+        let mut request = Request { method, path, query, headers, body: String::new() };
+        let length: usize = match request.header("content-length") {
+            Some(length) => length.parse().map_err(|_| malformed("content length"))?,
+            None => 0,
+        };
+        if length > MAX_BODY {
+            return Err(malformed("body size"));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        request.body = String::from_utf8(body).map_err(|_| malformed("body"))?;
+        Ok(request)
+    }
+
+    /// The value of a header, matching its name case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers.iter().find(|(header, _)| *header == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+impl Response {
+    pub fn ok(body: String) -> Response {
+        Response { status: 200, content_type: "application/json", body }
+    }
+
+    /// Plain text in the Prometheus exposition format.
+    pub fn prometheus(body: String) -> Response {
+        Response { status: 200, content_type: "text/plain; version=0.0.4", body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Response {
+        let message = message.replace('\\', "\\\\").replace('"', "\\\"");
+        Response { status, content_type: "application/json", body: format!("{{\"error\":\"{}\"}}", message) }
+    }
+
+    pub fn write_to<W>(&self, write: &mut W) -> Result<()>
+        where W: Write
+    {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Error",
+        };
+        write!(
+            write,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status, reason, self.content_type, self.body.len(), self.body
+        )?;
+        write.flush()
+    }
+}
+
+fn malformed(part: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("malformed {}", part))
+}
+
+/// Undoes percent encoding, and `+` for spaces, in a path or query component.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => match text.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    index += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let text = "POST /stamp?pattern=Gosper+glider%20gun&row=3 HTTP/1.1\r\nHost: x\r\nContent-Length: 5\r\n\r\nhello";
+        let request = Request::read(text.as_bytes()).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/stamp");
+        assert_eq!(request.query("pattern"), Some("Gosper glider gun"));
+        assert_eq!(request.query("row"), Some("3"));
+        assert_eq!(request.header("content-length"), Some("5"));
+        assert_eq!(request.header("HOST"), Some("x"));
+        assert_eq!(request.body, "hello");
+
+        assert!(Request::read("GET\r\n\r\n".as_bytes()).is_err());
+        assert!(Request::read("GET / HTTP/1.1\r\nHost: x\r\n".as_bytes()).is_err());
+        assert!(Request::read("GET / HTTP/1.1\r\nContent-Length: 99999999\r\n\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("a%2Cb+c"), "a,b c");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_response() {
+        let mut written = vec![];
+        Response::error(404, "no \"such\" page").write_to(&mut written).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        assert!(written.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(written.ends_with("\r\n\r\n{\"error\":\"no \\\"such\\\" page\"}"));
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use game_of_life::{io, Behavior, Pattern, Rule};
+use crate::config::Config;
+
+/// Loads the pattern at `path` and prints what there is to know about it before loading it: its
+/// size, population, rule, bounding box, what kind of pattern it is and the comments it came with.
+/// It is classified under the rule it names, or the configured one when it names none, looking at
+/// most `config.max` generations ahead.
+pub fn run(config: &Config, path: &Path) -> Result<()> {
+    let pattern = io::load(path)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", path.display(), error)))?;
+    let rule: Rule = match pattern.metadata().rule {
+        Some(rule) => rule,
+        None => config.rule
+            .parse()
+            .map_err(|error: game_of_life::Error| Error::new(ErrorKind::InvalidInput, error.to_string()))?,
+    };
+    print!("{}", describe(&pattern, rule, config.max));
+    Ok(())
+}
+
+/// A `key: value` line for each fact about the pattern, then its comments, indented.
+fn describe(pattern: &Pattern, rule: Rule, max: u64) -> String {
+    let metadata = pattern.metadata();
+    let (rows, columns) = pattern.shape();
+    let mut lines = vec![];
+    if !pattern.name().is_empty() {
+        lines.push(format!("name: {}", pattern.name()));
+    }
+    if let Some(author) = &metadata.author {
+        lines.push(format!("author: {}", author));
+    }
+    if let Some(discovered) = &metadata.discovered {
+        lines.push(format!("discovered: {}", discovered));
+    }
+    lines.push(format!("size: {}x{}", rows, columns));
+    lines.push(format!("population: {}", pattern.cells().len()));
+    let source = if metadata.rule.is_some() { "" } else { " (configured)" };
+    lines.push(format!("rule: {}{}", rule, source));
+    // Cells are kept relative to the top left corner, so the box is found among them and then
+    // moved to where the pattern sits on the plane.
+    let cells = pattern.cells();
+    if let (Some(top), Some(bottom)) = (cells.first().map(|cell| cell.0), cells.last().map(|cell| cell.0)) {
+        let left = cells.iter().map(|(_, column)| *column).min().unwrap_or(0);
+        let right = cells.iter().map(|(_, column)| *column).max().unwrap_or(0);
+        let (row, column) = pattern.origin();
+        lines.push(format!(
+            "bounding box: ({}, {}) to ({}, {})",
+            row + top as i64, column + left as i64, row + bottom as i64, column + right as i64
+        ));
+    }
+    // Only what the pattern is as it stands counts; one that turns into something else is unknown.
+    let kind = match pattern.periodicity(rule, max) {
+        Some(periodicity) if periodicity.start == 0 && periodicity.behavior != Behavior::DiesOut => {
+            periodicity.behavior.to_string()
+        }
+        _ => "unknown".to_string(),
+    };
+    lines.push(format!("type: {}", kind));
+    if !metadata.comments.is_empty() {
+        lines.push("comments:".to_string());
+        lines.extend(metadata.comments.iter().map(|comment| format!("  {}", comment)));
+    }
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let text = "!Name: Glider\n!Author: Richard K. Guy\n!The smallest spaceship.\n.O.\n..O\nOOO\n";
+        let glider = io::parse_plaintext("glider", text);
+        assert_eq!(describe(&glider, Rule::life(), 100), "\
+name: Glider
+author: Richard K. Guy
+size: 3x3
+population: 5
+rule: B3/S23 (configured)
+bounding box: (0, 0) to (2, 2)
+type: spaceship with period 4 and displacement (1, 1)
+comments:
+  The smallest spaceship.
+");
+    }
+
+    #[test]
+    fn test_describe_type() {
+        let kind = |text| {
+            let description = describe(&Pattern::from_plaintext("", text), Rule::life(), 100);
+            description.lines().find_map(|line| line.strip_prefix("type: ")).unwrap().to_string()
+        };
+        assert_eq!(kind("OO\nOO"), "still life");
+        assert_eq!(kind("OOO"), "oscillator with period 2");
+        // Four in a row becomes a beehive, but isn't one yet.
+        assert_eq!(kind("OOOO"), "unknown");
+        assert_eq!(kind("O"), "unknown");
+    }
+}
@@ -0,0 +1,363 @@
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::Result;
+use game_of_life::Player;
+use crate::terminal;
+use crate::{Command, Mode};
+
+const ANY: &[Mode] = &[Mode::Run, Mode::Edit, Mode::Place, Mode::Rules, Mode::RuleEditor, Mode::Versus, Mode::Tutorial, Mode::Replay, Mode::Lenia, Mode::Life3d, Mode::Layers];
+const RUN: &[Mode] = &[Mode::Run];
+const EDIT: &[Mode] = &[Mode::Edit];
+const PLACE: &[Mode] = &[Mode::Place];
+const SEARCH: &[Mode] = &[Mode::Search];
+const RULES: &[Mode] = &[Mode::Rules];
+const RULE_EDITOR: &[Mode] = &[Mode::RuleEditor];
+const PICKER: &[Mode] = &[Mode::Place, Mode::Search];
+const RUN_EDIT: &[Mode] = &[Mode::Run, Mode::Edit];
+const CURSOR: &[Mode] = &[Mode::Edit, Mode::Place, Mode::RuleEditor];
+const BACK: &[Mode] = &[Mode::Edit, Mode::Place, Mode::Rules, Mode::RuleEditor];
+const VERSUS: &[Mode] = &[Mode::Versus];
+const TUTORIAL: &[Mode] = &[Mode::Tutorial];
+const REPLAY: &[Mode] = &[Mode::Replay];
+const LENIA: &[Mode] = &[Mode::Lenia];
+const LIFE3D: &[Mode] = &[Mode::Life3d];
+const LAYERS: &[Mode] = &[Mode::Layers];
+const RESTART: &[Mode] = &[Mode::Tutorial, Mode::Replay, Mode::Lenia, Mode::Life3d, Mode::Layers];
+
+struct Binding {
+    action: &'static str,
+    modes: &'static [Mode],
+    command: Command,
+    keys: Vec<KeyEvent>,
+}
+
+/// Maps keys to commands for each mode. Every action can be rebound from the `[keys]` section of
+/// the config file, e.g. `up = "k Up"`, with `shift-` in front of a key name for Shift and that
+/// key.
+pub struct KeyMap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for KeyMap {
+    fn default() -> KeyMap {
+        use KeyCode::*;
+
+        let binding = |action, modes, command, keys: &[KeyCode]| Binding {
+            action,
+            modes,
+            command,
+            keys: keys.iter().map(|key| KeyEvent::from(*key)).collect(),
+        };
+        let shifted = |action, modes, command, keys: &[KeyCode]| Binding {
+            action,
+            modes,
+            command,
+            keys: keys.iter().map(|key| KeyEvent::new(*key, KeyModifiers::SHIFT)).collect(),
+        };
+        KeyMap {
+            bindings: vec![
+                binding("quit", ANY, Command::Quit, &[Char('q')]),
+                binding("pause", RUN, Command::Paused, &[Char(' ')]),
+                binding("step", RUN, Command::Step, &[Char('s')]),
+                binding("reverse", RUN, Command::Reverse, &[Char('b')]),
+                binding("reseed", RUN_EDIT, Command::Reseed, &[Char('r')]),
+                binding("edit", RUN_EDIT, Command::Edit, &[Char('e')]),
+                binding("place", RUN_EDIT, Command::Place, &[Char('p')]),
+                binding("wrap", RUN_EDIT, Command::ToggleBoundary, &[Char('w')]),
+                binding("graph", RUN_EDIT, Command::ToggleGraph, &[Char('g')]),
+                binding("objects", RUN_EDIT, Command::ToggleObjects, &[Char('o')]),
+                binding("census", RUN, Command::ToggleCensus, &[Char('c')]),
+                binding("heatmap", RUN_EDIT, Command::ToggleHeatmap, &[Char('h')]),
+                binding("components", RUN_EDIT, Command::ToggleComponents, &[Char('m')]),
+                binding("center", RUN_EDIT, Command::ToggleCenter, &[Char('z')]),
+                binding("ruler", RUN_EDIT, Command::ToggleRuler, &[Char('t')]),
+                binding("copy", RUN_EDIT, Command::Copy, &[Char('y')]),
+                shifted("shift_up", RUN_EDIT, Command::Shift(-1, 0), &[Up]),
+                shifted("shift_down", RUN_EDIT, Command::Shift(1, 0), &[Down]),
+                shifted("shift_left", RUN_EDIT, Command::Shift(0, -1), &[Left]),
+                shifted("shift_right", RUN_EDIT, Command::Shift(0, 1), &[Right]),
+                binding("back", BACK, Command::Back, &[Esc]),
+                binding("up", CURSOR, Command::Move(-1, 0), &[Up]),
+                binding("down", CURSOR, Command::Move(1, 0), &[Down]),
+                binding("left", CURSOR, Command::Move(0, -1), &[Left]),
+                binding("right", CURSOR, Command::Move(0, 1), &[Right]),
+                binding("toggle", EDIT, Command::Toggle, &[Enter]),
+                binding("wall", EDIT, Command::Wall, &[Char('x')]),
+                binding("immortal", EDIT, Command::Immortal, &[Char('i')]),
+                binding("clear", EDIT, Command::Clear, &[Char('c')]),
+                binding("neighbors", EDIT, Command::ToggleNeighbors, &[Char('n')]),
+                binding("freeze", EDIT, Command::Freeze, &[Char('f')]),
+                binding("rectangle", EDIT, Command::Rectangle, &[Char('b')]),
+                binding("line", EDIT, Command::Line, &[Char('l')]),
+                binding("soup", EDIT, Command::Soup, &[Char('s')]),
+                binding("next_pattern", PICKER, Command::NextPattern, &[Tab]),
+                binding("search", PLACE, Command::Search, &[Char('/')]),
+                binding("search_erase", SEARCH, Command::Erase, &[Backspace]),
+                binding("search_done", SEARCH, Command::Back, &[Enter, Esc]),
+                binding("rules", RUN, Command::Rules, &[Char('u')]),
+                binding("rule_editor", RUN, Command::EditRule, &[Char('R')]),
+                binding("rule_toggle", RULE_EDITOR, Command::Toggle, &[Char(' '), Enter]),
+                binding("explore", RUN, Command::Explore, &[Char('n')]),
+                binding("pin", RUN, Command::Pin, &[Char('l')]),
+                binding("rule_previous", RULES, Command::NextRule(-1), &[Up]),
+                binding("rule_next", RULES, Command::NextRule(1), &[Down, Tab]),
+                binding("rule_apply", RULES, Command::ApplyRule, &[Enter]),
+                binding("rotate_clockwise", PLACE, Command::RotateClockwise, &[Char(']')]),
+                binding("rotate_counter_clockwise", PLACE, Command::RotateCounterClockwise, &[Char('[')]),
+                binding("flip", PLACE, Command::Flip, &[Char('f')]),
+                binding("stamp", PLACE, Command::Stamp, &[Enter]),
+                binding("one_up", VERSUS, Command::Aim(Player::One, -1, 0), &[Char('w')]),
+                binding("one_down", VERSUS, Command::Aim(Player::One, 1, 0), &[Char('s')]),
+                binding("one_left", VERSUS, Command::Aim(Player::One, 0, -1), &[Char('a')]),
+                binding("one_right", VERSUS, Command::Aim(Player::One, 0, 1), &[Char('d')]),
+                binding("one_claim", VERSUS, Command::Claim(Player::One), &[Char(' ')]),
+                binding("two_up", VERSUS, Command::Aim(Player::Two, -1, 0), &[Up]),
+                binding("two_down", VERSUS, Command::Aim(Player::Two, 1, 0), &[Down]),
+                binding("two_left", VERSUS, Command::Aim(Player::Two, 0, -1), &[Left]),
+                binding("two_right", VERSUS, Command::Aim(Player::Two, 0, 1), &[Right]),
+                binding("two_claim", VERSUS, Command::Claim(Player::Two), &[Enter]),
+                binding("tutorial_pause", TUTORIAL, Command::Paused, &[Char(' ')]),
+                binding("tutorial_step", TUTORIAL, Command::Step, &[Char('s')]),
+                binding("restart", RESTART, Command::Reseed, &[Char('r')]),
+                binding("next_stage", TUTORIAL, Command::NextStage, &[Right, Char('n')]),
+                binding("previous_stage", TUTORIAL, Command::PreviousStage, &[Left, Char('b')]),
+                binding("replay_pause", REPLAY, Command::Paused, &[Char(' ')]),
+                binding("replay_back", REPLAY, Command::Seek(-1), &[Left]),
+                binding("replay_forward", REPLAY, Command::Seek(1), &[Right]),
+                binding("jump_back", REPLAY, Command::Jump(-1), &[Char('['), PageUp]),
+                binding("jump_forward", REPLAY, Command::Jump(1), &[Char(']'), PageDown]),
+                binding("lenia_pause", LENIA, Command::Paused, &[Char(' ')]),
+                binding("lenia_step", LENIA, Command::Step, &[Char('s')]),
+                binding("life3d_pause", LIFE3D, Command::Paused, &[Char(' ')]),
+                binding("life3d_step", LIFE3D, Command::Step, &[Char('s')]),
+                binding("slice_up", LIFE3D, Command::Slice(-1), &[Up, Char('[')]),
+                binding("slice_down", LIFE3D, Command::Slice(1), &[Down, Char(']')]),
+                binding("projection", LIFE3D, Command::ToggleProjection, &[Char('v')]),
+                binding("layers_pause", LAYERS, Command::Paused, &[Char(' ')]),
+                binding("layers_step", LAYERS, Command::Step, &[Char('s')]),
+            ],
+        }
+    }
+}
+
+impl KeyMap {
+    /// Replaces the keys bound to `action` with the whitespace separated key names in `keys`.
+    pub fn bind(&mut self, action: &str, keys: &str) -> Result<()> {
+        let keys = keys
+            .split_whitespace()
+            .map(|name| parse_key(name).ok_or_else(|| invalid(format!("unknown key '{}' for '{}'", name, action))))
+            .collect::<Result<Vec<_>>>()?;
+        let binding = self.bindings
+            .iter_mut()
+            .find(|binding| binding.action == action)
+            .ok_or_else(|| invalid(format!("unknown action '{}'", action)))?;
+        binding.keys = keys;
+        Ok(())
+    }
+
+    /// Fails if a key is bound to two actions that are available in the same mode.
+    pub fn check_conflicts(&self) -> Result<()> {
+        for (index, first) in self.bindings.iter().enumerate() {
+            for second in &self.bindings[index + 1..] {
+                let shared_mode = first.modes.iter().any(|mode| second.modes.contains(mode));
+                if let Some(key) = first.keys.iter().find(|key| shared_mode && second.keys.contains(key)) {
+                    return Err(invalid(format!(
+                        "key '{}' is bound to both '{}' and '{}'",
+                        key_name(key), first.action, second.action
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The command bound to `key` in `mode`. A key held with modifiers that nothing is bound to
+    /// does what it does alone. In `Mode::Search`, characters that aren't bound to anything are
+    /// typed into the search.
+    pub fn lookup(&self, mode: &Mode, key: KeyEvent) -> Option<Command> {
+        let find = |key: &KeyEvent| self.bindings.iter().find(|binding| binding.modes.contains(mode) && binding.keys.contains(key));
+        find(&key)
+            .or_else(|| find(&KeyEvent::from(key.code)))
+            .map(|binding| binding.command.clone())
+            .or(match (mode, key.code) {
+                (Mode::Search, KeyCode::Char(symbol)) => Some(Command::Type(symbol)),
+                _ => None,
+            })
+    }
+
+    /// Waits up to `timeout` for a key and returns its command in `mode`, if it has one. Ctrl+C
+    /// quits in every mode, whether it arrives as a key, as it does in raw mode, or as a signal.
+    pub fn next_command(&self, mode: &Mode, timeout: Duration) -> Result<Option<Command>> {
+        if terminal::interrupted() {
+            return Ok(Some(Command::Quit));
+        }
+        if !poll(timeout)? {
+            return Ok(None);
+        }
+        match read()? {
+            Event::Key(event) if is_interrupt(&event) => Ok(Some(Command::Quit)),
+            Event::Key(event) => Ok(self.lookup(mode, event)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The name of the first key bound to `action`, for the hints in the status bar.
+    pub fn describe(&self, action: &str) -> String {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .and_then(|binding| binding.keys.first())
+            .map(key_name)
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+fn is_interrupt(event: &KeyEvent) -> bool {
+    event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Shift with a character is the character in upper case, as terminals send it, with or without
+/// the modifier; only the other keys keep Shift as a modifier.
+fn parse_key(name: &str) -> Option<KeyEvent> {
+    if let Some(name) = name.strip_prefix("shift-").or_else(|| name.strip_prefix("Shift-")) {
+        return parse_key(name).map(|key| match key.code {
+            KeyCode::Char(c) => KeyEvent::from(KeyCode::Char(c.to_ascii_uppercase())),
+            code => KeyEvent::new(code, KeyModifiers::SHIFT),
+        });
+    }
+    let key = match name.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+    };
+    Some(KeyEvent::from(key))
+}
+
+fn key_name(key: &KeyEvent) -> String {
+    if let (true, KeyCode::Char(c)) = (key.modifiers.contains(KeyModifiers::SHIFT), key.code) {
+        return c.to_ascii_uppercase().to_string();
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        return format!("shift-{}", key_name(&KeyEvent::from(key.code)));
+    }
+    match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bindings_do_not_conflict() {
+        assert!(KeyMap::default().check_conflicts().is_ok());
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut keys = KeyMap::default();
+        keys.bind("up", "k Up").unwrap();
+        assert_eq!(keys.lookup(&Mode::Edit, KeyCode::Char('k').into()), Some(Command::Move(-1, 0)));
+        assert_eq!(keys.lookup(&Mode::Edit, KeyCode::Up.into()), Some(Command::Move(-1, 0)));
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('k').into()), None);
+        assert_eq!(keys.describe("up"), "k");
+
+        assert!(keys.bind("fly", "x").is_err());
+        assert!(keys.bind("up", "ctrl-k").is_err());
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut keys = KeyMap::default();
+        let shift = |code| KeyEvent::new(code, KeyModifiers::SHIFT);
+        assert_eq!(keys.lookup(&Mode::Edit, shift(KeyCode::Up)), Some(Command::Shift(-1, 0)));
+        assert_eq!(keys.lookup(&Mode::Edit, KeyCode::Up.into()), Some(Command::Move(-1, 0)));
+        // Shift+Up still moves the cursor where nothing else is bound to it.
+        assert_eq!(keys.lookup(&Mode::Place, shift(KeyCode::Up)), Some(Command::Move(-1, 0)));
+        assert_eq!(keys.describe("shift_left"), "shift-left");
+
+        // Shift+k arrives as an upper case K, some terminals saying Shift was held and some not.
+        keys.bind("shift_left", "shift-k").unwrap();
+        assert_eq!(keys.lookup(&Mode::Run, shift(KeyCode::Char('K'))), Some(Command::Shift(0, -1)));
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('K').into()), Some(Command::Shift(0, -1)));
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('k').into()), None);
+        assert_eq!(keys.describe("shift_left"), "K");
+    }
+
+    #[test]
+    fn test_search_typing() {
+        let keys = KeyMap::default();
+        assert_eq!(keys.lookup(&Mode::Place, KeyCode::Char('/').into()), Some(Command::Search));
+        // Even the keys that mean something elsewhere are typed.
+        assert_eq!(keys.lookup(&Mode::Search, KeyCode::Char('q').into()), Some(Command::Type('q')));
+        assert_eq!(keys.lookup(&Mode::Search, KeyCode::Backspace.into()), Some(Command::Erase));
+        assert_eq!(keys.lookup(&Mode::Search, KeyCode::Enter.into()), Some(Command::Back));
+        assert_eq!(keys.lookup(&Mode::Place, KeyCode::Char('x').into()), None);
+    }
+
+    #[test]
+    fn test_rule_menu() {
+        let keys = KeyMap::default();
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('u').into()), Some(Command::Rules));
+        assert_eq!(keys.lookup(&Mode::Rules, KeyCode::Down.into()), Some(Command::NextRule(1)));
+        assert_eq!(keys.lookup(&Mode::Rules, KeyCode::Enter.into()), Some(Command::ApplyRule));
+        assert_eq!(keys.lookup(&Mode::Rules, KeyCode::Esc.into()), Some(Command::Back));
+        assert_eq!(keys.lookup(&Mode::Rules, KeyCode::Char('s').into()), None);
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('R').into()), Some(Command::EditRule));
+        assert_eq!(keys.lookup(&Mode::RuleEditor, KeyCode::Left.into()), Some(Command::Move(0, -1)));
+        assert_eq!(keys.lookup(&Mode::RuleEditor, KeyCode::Char(' ').into()), Some(Command::Toggle));
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('n').into()), Some(Command::Explore));
+        assert_eq!(keys.lookup(&Mode::Edit, KeyCode::Char('n').into()), Some(Command::ToggleNeighbors));
+        assert_eq!(keys.lookup(&Mode::Run, KeyCode::Char('l').into()), Some(Command::Pin));
+    }
+
+    #[test]
+    fn test_interrupt() {
+        assert!(is_interrupt(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!is_interrupt(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut keys = KeyMap::default();
+        keys.bind("pause", "p").unwrap();
+        assert!(keys.check_conflicts().is_err());
+
+        // `f` flips while placing and is free while running, so it can pause as well.
+        keys.bind("pause", "f").unwrap();
+        assert!(keys.check_conflicts().is_ok());
+    }
+}
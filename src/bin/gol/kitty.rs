@@ -0,0 +1,48 @@
+use crate::websocket::base64;
+
+/// The most base64 a single escape sequence may carry; longer images are sent in chunks.
+const CHUNK: usize = 4096;
+
+/// The escape sequences that show `pixels`, rows of RGB triples `width` wide, stretched over
+/// `rows` by `columns` characters from the cursor, in place of the image shown last. The cursor
+/// stays where it is.
+pub fn image(pixels: &[u8], width: usize, rows: usize, columns: usize) -> String {
+    let height = pixels.len() / (3 * width.max(1));
+    let data = base64(pixels);
+    let chunks: Vec<&str> = data
+        .as_bytes()
+        .chunks(CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+    let mut sequences = String::with_capacity(data.len() + chunks.len() * 16 + 64);
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = usize::from(index + 1 < chunks.len());
+        if index == 0 {
+            sequences.push_str(&format!(
+                "\x1b_Ga=T,f=24,s={},v={},i=1,p=1,c={},r={},C=1,q=2,m={};{}\x1b\\",
+                width, height, columns, rows, more, chunk
+            ));
+        } else {
+            sequences.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    sequences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image() {
+        // A 2x1 image fits in one sequence.
+        let small = image(&[255, 0, 0, 0, 255, 0], 2, 1, 1);
+        assert_eq!(small, "\x1b_Ga=T,f=24,s=2,v=1,i=1,p=1,c=1,r=1,C=1,q=2,m=0;/wAAAP8A\x1b\\");
+
+        // A larger one is split, every chunk but the last saying more follows.
+        let large = image(&[7; 3 * 4000], 100, 10, 20);
+        assert!(large.starts_with("\x1b_Ga=T,f=24,s=100,v=40,"));
+        assert_eq!(large.matches("m=1;").count(), 3);
+        assert_eq!(large.matches("m=0;").count(), 1);
+    }
+}
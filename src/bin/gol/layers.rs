@@ -0,0 +1,102 @@
+use std::io::Write;
+use std::time::Instant;
+use crossterm::Result;
+use game_of_life::{GameOfLife, Layers};
+use crate::color::{blend, Rgb};
+use crate::config::Config;
+use crate::render::{Glyph, TerminalRenderer};
+use crate::{Command, Mode};
+
+/// The color of each layer, in order, with its name for the status bar; layers past the end take
+/// the colors again from the start.
+const PALETTE: &[(&str, Rgb)] = &[
+    ("red", (240, 60, 60)),
+    ("green", (60, 220, 90)),
+    ("blue", (70, 120, 255)),
+    ("yellow", (240, 210, 40)),
+    ("magenta", (220, 70, 220)),
+    ("cyan", (50, 210, 220)),
+];
+
+/// Runs `game` under its own rule and under each of `layers` at once, all from its start, and
+/// draws them over each other: each living cell in the color of the layers it is alive in,
+/// blended `layer_alpha` at a time, so where the rules agree the colors mix and where they
+/// part each shows on its own.
+pub fn run<W>(config: &Config, game: &GameOfLife, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let rules: Vec<_> = std::iter::once(game.rule()).chain(config.layers.iter().copied()).collect();
+    let mut layers = Layers::new(game.current(), game.boundary(), &rules);
+    let mut paused = false;
+    let interval = config.tick_interval();
+    let mut next_tick = Instant::now();
+
+    loop {
+        if let Some(command) = config.keys.next_command(&Mode::Layers, config.frame_time())? {
+            match command {
+                Command::Quit => return Ok(()),
+                Command::Paused => paused = !paused,
+                Command::Step => {
+                    paused = true;
+                    layers.tick();
+                }
+                Command::Reseed => layers.reset(game.current()),
+                _ => {}
+            }
+        }
+
+        if !paused && Instant::now() >= next_tick {
+            layers.tick();
+            next_tick = Instant::now() + interval;
+        }
+        draw(&layers, paused, config, renderer)?;
+    }
+}
+
+fn draw<W>(layers: &Layers, paused: bool, config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = renderer.shape();
+    let mut colors = Vec::with_capacity(layers.games().len());
+    for row in 0..rows {
+        for column in 0..columns {
+            colors.clear();
+            colors.extend(layers.alive((row, column)).enumerate().filter(|(_, alive)| *alive).map(|(layer, _)| color(layer)));
+            let glyph = match colors.is_empty() {
+                true => config.glyphs.dead,
+                false => Glyph::filled(config.color_depth.color(blend(&colors, config.layer_alpha as f32))),
+            };
+            renderer.put((row, column), glyph);
+        }
+    }
+
+    let legend: Vec<String> = layers.games()
+        .iter()
+        .enumerate()
+        .map(|(layer, game)| format!("{} {} {}", PALETTE[layer % PALETTE.len()].0, game.rule(), game.current().population()))
+        .collect();
+    let keys = |action| config.keys.describe(action);
+    let status = format!(
+        " {} | gen {} | {} | {} differ | {}: {}  {}: step  {}: restart  {}: quit",
+        if paused { "paused" } else { "running" }, layers.generation(), legend.join("  "), layers.differences(),
+        keys("layers_pause"), if paused { "run" } else { "pause" }, keys("layers_step"), keys("restart"), keys("quit")
+    );
+    renderer.put_status(format!("{:width$}", status, width = columns))?;
+    renderer.flush()
+}
+
+fn color(layer: usize) -> Rgb {
+    PALETTE[layer % PALETTE.len()].1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_repeats() {
+        assert_eq!(color(0), (240, 60, 60));
+        assert_eq!(color(PALETTE.len() + 1), color(1));
+        assert_ne!(color(0), color(1));
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::Write;
+use std::time::Instant;
+use crossterm::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use game_of_life::{Boundary, Continuous, GameMatrix};
+use crate::color::shade;
+use crate::config::Config;
+use crate::render::{Glyph, TerminalRenderer};
+use crate::{Command, Mode};
+
+/// States below this are drawn as dead cells rather than in the darkest color.
+const FAINT: f32 = 0.01;
+
+/// Runs a continuous automaton, such as Lenia or SmoothLife, on the whole terminal from a few
+/// patches of noise, each cell shaded by its state along `gradient`, or in grays without one.
+pub fn run<W>(config: &Config, rule: &dyn Continuous, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let mut rng = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+    let mut matrix = seed(rule, renderer.shape(), &mut rng);
+    let mut generation = 0;
+    let mut paused = false;
+    let interval = config.tick_interval();
+    let mut next_tick = Instant::now();
+
+    loop {
+        if let Some(command) = config.keys.next_command(&Mode::Lenia, config.frame_time())? {
+            match command {
+                Command::Quit => return Ok(()),
+                Command::Paused => paused = !paused,
+                Command::Step => {
+                    paused = true;
+                    matrix = rule.step(&matrix, Boundary::Wrap);
+                    generation += 1;
+                }
+                Command::Reseed => {
+                    matrix = seed(rule, renderer.shape(), &mut rng);
+                    generation = 0;
+                }
+                _ => {}
+            }
+        }
+
+        if !paused && Instant::now() >= next_tick {
+            matrix = rule.step(&matrix, Boundary::Wrap);
+            generation += 1;
+            next_tick = Instant::now() + interval;
+        }
+        draw(&matrix, rule, generation, paused, config, renderer)?;
+    }
+}
+
+/// A grid of `shape` with a patch of noise for about every two kernels' worth of area, every
+/// cell in each given a random state. Sparser soups mostly fade away at once.
+fn seed<R>(rule: &dyn Continuous, (rows, columns): (usize, usize), rng: &mut R) -> GameMatrix<f32>
+    where R: Rng
+{
+    let mut matrix = GameMatrix::new(rows, columns);
+    let side = (rule.radius() * 2).min(rows).min(columns);
+    for _ in 0..(rows * columns / (side * side * 2)).max(1) {
+        let (top, left) = (rng.gen_range(0..rows), rng.gen_range(0..columns));
+        for row in top..top + side {
+            for column in left..left + side {
+                matrix.set_state((row % rows, column % columns), rng.gen());
+            }
+        }
+    }
+    matrix
+}
+
+fn draw<W>(matrix: &GameMatrix<f32>, rule: &dyn Continuous, generation: u64, paused: bool, config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = matrix.shape();
+    let mut mass = 0.0;
+    for row in 0..rows {
+        for column in 0..columns {
+            let state = matrix.get_state((row, column));
+            mass += state;
+            let glyph = match state {
+                state if state < FAINT => config.glyphs.dead,
+                state => Glyph::filled(config.color_depth.color(shade(state, config.gradient.as_ref()))),
+            };
+            renderer.put((row, column), glyph);
+        }
+    }
+
+    let keys = |action| config.keys.describe(action);
+    let status = format!(
+        " {} | {} | gen {} | mass {:.0} | {}: {}  {}: step  {}: reseed  {}: quit",
+        if paused { "paused" } else { "running" }, rule, generation, mass, keys("lenia_pause"), if paused { "run" } else { "pause" }, keys("lenia_step"),
+        keys("restart"), keys("quit")
+    );
+    renderer.put_status(format!("{:width$}", status, width = columns))?;
+    renderer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::Lenia;
+
+    #[test]
+    fn test_seed() {
+        let lenia = Lenia::new(4, 0.15, 0.015, 0.1);
+        let matrix = seed(&lenia, (20, 30), &mut StdRng::seed_from_u64(1));
+        assert_eq!(matrix.shape(), (20, 30));
+        // Four patches of 8 by 8, some perhaps overlapping.
+        let filled = matrix.get_internal().iter().filter(|state| **state > 0.0).count();
+        assert!(filled > 64 && filled <= 256);
+        assert!(matrix.get_internal().iter().all(|state| (0.0..1.0).contains(state)));
+    }
+}
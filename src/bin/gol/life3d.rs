@@ -0,0 +1,143 @@
+use std::io::Write;
+use std::time::Instant;
+use crossterm::Result;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use game_of_life::{Grid, Life3d};
+use crate::color::shade;
+use crate::config::Config;
+use crate::render::{Glyph, TerminalRenderer};
+use crate::{Command, Mode};
+
+/// How much of the grid starts alive without a `density`; sparser soups of the usual rules
+/// mostly die at once.
+const DENSITY: f64 = 0.2;
+
+/// Runs Life in three dimensions, `life3d_depth` layers of the terminal's size, from a random
+/// soup. It shows one layer at a time, which can be moved up and down through the grid, or a
+/// projection down through all of them, each cell shaded by how many layers it is alive in.
+pub fn run<W>(config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = renderer.shape();
+    let mut rng = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+    let mut game = Life3d::new(config.life3d_depth, rows, columns, config.life3d_rule);
+    let density = config.density.unwrap_or(DENSITY);
+    game.randomize(density, &mut rng);
+    let mut view = View { layer: config.life3d_depth / 2, projection: false };
+    let mut generation = 0;
+    let mut paused = false;
+    let interval = config.tick_interval();
+    let mut next_tick = Instant::now();
+
+    loop {
+        if let Some(command) = config.keys.next_command(&Mode::Life3d, config.frame_time())? {
+            match command {
+                Command::Quit => return Ok(()),
+                Command::Paused => paused = !paused,
+                Command::Step => {
+                    paused = true;
+                    game.tick();
+                    generation += 1;
+                }
+                Command::Reseed => {
+                    game.randomize(density, &mut rng);
+                    generation = 0;
+                }
+                Command::Slice(layers) => {
+                    view.layer = (view.layer as isize + layers).rem_euclid(config.life3d_depth as isize) as usize;
+                    view.projection = false;
+                }
+                Command::ToggleProjection => view.projection = !view.projection,
+                _ => {}
+            }
+        }
+
+        if !paused && Instant::now() >= next_tick {
+            game.tick();
+            generation += 1;
+            next_tick = Instant::now() + interval;
+        }
+        draw(&game, &view, generation, paused, config, renderer)?;
+    }
+}
+
+/// What of the grid is on screen.
+struct View {
+    layer: usize,
+    projection: bool,
+}
+
+fn draw<W>(game: &Life3d, view: &View, generation: u64, paused: bool, config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (layers, rows, columns) = game.shape();
+    let glyphs = if view.projection {
+        projection(&game.projection(), layers, config)
+    } else {
+        let mut glyphs = Grid::from_element(rows, columns, config.glyphs.dead);
+        for row in 0..rows {
+            for column in 0..columns {
+                if game.is_alive((view.layer, row, column)) {
+                    glyphs[(row, column)] = config.glyphs.alive;
+                }
+            }
+        }
+        glyphs
+    };
+    for row in 0..rows {
+        for column in 0..columns {
+            renderer.put((row, column), glyphs[(row, column)]);
+        }
+    }
+
+    let keys = |action| config.keys.describe(action);
+    let shown = match view.projection {
+        true => format!("all {} layers", layers),
+        false => format!("layer {}/{}", view.layer + 1, layers),
+    };
+    let status = format!(
+        " {} | 3D {} | {} | gen {} | population {} | {}: {}  {}: step  {}/{}: layer  {}: {}  {}: reseed  {}: quit",
+        if paused { "paused" } else { "running" }, game.rule(), shown, generation, game.population(),
+        keys("life3d_pause"), if paused { "run" } else { "pause" }, keys("life3d_step"), keys("slice_up"),
+        keys("slice_down"), keys("projection"), if view.projection { "layers" } else { "projection" },
+        keys("restart"), keys("quit")
+    );
+    renderer.put_status(format!("{:width$}", status, width = columns))?;
+    renderer.flush()
+}
+
+/// Each cell of a projection through `layers` layers shaded by how many of them it is alive in.
+fn projection(counts: &Grid<usize>, layers: usize, config: &Config) -> Grid<Glyph> {
+    let (rows, columns) = counts.shape();
+    let mut glyphs = Grid::from_element(rows, columns, config.glyphs.dead);
+    for row in 0..rows {
+        for column in 0..columns {
+            let count = counts[(row, column)];
+            if count > 0 {
+                let color = shade(count as f32 / layers as f32, config.gradient.as_ref());
+                glyphs[(row, column)] = Glyph::filled(config.color_depth.color(color));
+            }
+        }
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::color::ColorDepth;
+    use super::*;
+
+    #[test]
+    fn test_projection() {
+        let config = Config { color_depth: ColorDepth::TrueColor, ..Config::default() };
+        let mut counts = Grid::from_element(1, 3, 0);
+        counts[(0, 1)] = 2;
+        counts[(0, 2)] = 4;
+        let glyphs = projection(&counts, 4, &config);
+        assert_eq!(glyphs[(0, 0)], config.glyphs.dead);
+        assert_eq!(glyphs[(0, 1)], Glyph::filled(Color::Rgb { r: 128, g: 128, b: 128 }));
+        assert_eq!(glyphs[(0, 2)], Glyph::filled(Color::Rgb { r: 255, g: 255, b: 255 }));
+    }
+}
@@ -0,0 +1,17 @@
+use std::io::Result;
+use game_of_life::stats::lifespan;
+use game_of_life::GameOfLife;
+
+/// Runs the game until it settles, or for `limit` generations if it doesn't, and prints how long
+/// that took and how big it got on the way, as for a methuselah.
+pub fn run(mut game: GameOfLife, limit: u64) -> Result<()> {
+    let lifespan = lifespan(&mut game, limit);
+    if lifespan.settled {
+        println!("settled after {} generations", lifespan.generations);
+    } else {
+        println!("still unsettled after {} generations", lifespan.generations);
+    }
+    println!("final population {}", lifespan.final_population);
+    println!("peak population {} at generation {}", lifespan.peak_population, lifespan.peak_generation);
+    Ok(())
+}
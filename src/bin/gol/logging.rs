@@ -0,0 +1,18 @@
+use std::fs::OpenOptions;
+use std::io::Result;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::Level;
+
+/// Sends `tracing` events at `level` and above to the end of the file at `path`: tick timings
+/// from the simulation thread, draw timings, commands and file and clipboard operations. The
+/// alternate screen takes over stdout and stderr while `gol` runs, so a file is the only place
+/// they can go.
+pub fn init(level: Level, path: &Path) -> Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_max_level(level)
+        .init();
+    Ok(())
+}
@@ -0,0 +1,1106 @@
+#[cfg(feature = "server")]
+mod api;
+mod census;
+mod clipboard;
+mod color;
+mod components;
+mod config;
+mod diff;
+mod golly;
+mod graphics;
+mod http;
+mod info;
+mod keys;
+mod kitty;
+mod layers;
+mod lenia;
+mod life3d;
+mod lifespan;
+mod logging;
+mod metrics;
+mod pacer;
+mod patterns;
+mod period;
+mod pipe;
+mod plugin;
+mod render;
+mod replay;
+mod rules;
+mod script;
+mod serve;
+mod sixel;
+mod snapshot;
+mod soup;
+mod terminal;
+mod theme;
+mod tutorial;
+mod verify;
+mod versus;
+mod websocket;
+
+use std::fs::File;
+use std::io::{stdout, BufWriter, Error, ErrorKind, Write};
+use crossterm::Result;
+use game_of_life::simulation::Hook;
+use game_of_life::io::Format;
+use game_of_life::rule::PRESETS;
+use game_of_life::{io, pattern, Boundary, BoundingBox, line, Cell, History, CellState, GameOfLife, Metadata, Pattern, Query, Player, Recorder, Replay, Rule, Simulation, Stats, Update};
+use crate::clipboard::Clipboard;
+use crate::components::Tracker;
+use crate::config::Config;
+use crate::graphics::Graphics;
+use crate::pacer::Pacer;
+use crate::plugin::Plugin;
+use crate::render::{render, TerminalRenderer};
+use crate::script::Script;
+use crate::terminal::Terminal;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Rows and columns of the grid when running headless without a `size`.
+const DEFAULT_SIZE: (usize, usize) = (64, 64);
+
+fn main() -> Result<()> {
+    // `gol soup` searches many soups, `gol period PATTERN` classifies one, `gol info PATTERN`
+    // describes a pattern file, `gol diff A B` compares two patterns' evolutions, `gol patterns
+    // list [QUERY]` and `gol patterns search TEXT` search the built-in ones, `gol rules` lists the
+    // rule presets and `gol golly RULE` runs a Golly rule file, all headless, `gol tutorial`
+    // introduces Life, `gol replay FILE` plays a recorded run, `gol lenia` and `gol smoothlife` run
+    // continuous automata, `gol 3d` runs Life in three dimensions and `gol render PATTERN` prints a
+    // pattern's board once; everything else is configured by flags alone.
+    let mut args = std::env::args().skip(1).peekable();
+    let subcommand = args.next_if(|arg| {
+        ["soup", "period", "info", "diff", "patterns", "rules", "golly", "tutorial", "replay", "lenia", "smoothlife", "3d", "render"].contains(&arg.as_str())
+    });
+    // `gol patterns search TEXT` searches fuzzily where `gol patterns list` filters.
+    let search = subcommand.as_deref() == Some("patterns") && args.next_if_eq("search").is_some();
+    let argument = match subcommand.as_deref() {
+        Some("period") => Some(args.next().ok_or_else(|| invalid("usage: gol period PATTERN".to_string()))?),
+        Some("info") => Some(args.next().ok_or_else(|| invalid("usage: gol info PATTERN".to_string()))?),
+        Some("diff") => Some(args.next().ok_or_else(|| invalid("usage: gol diff A B".to_string()))?),
+        Some("replay") => Some(args.next().ok_or_else(|| invalid("usage: gol replay FILE".to_string()))?),
+        Some("golly") => Some(args.next().ok_or_else(|| invalid("usage: gol golly RULE".to_string()))?),
+        Some("render") => Some(args.next().ok_or_else(|| invalid("usage: gol render PATTERN".to_string()))?),
+        Some("patterns") if search => {
+            Some(args.next().ok_or_else(|| invalid("usage: gol patterns search TEXT".to_string()))?)
+        }
+        Some("patterns") => {
+            args.next_if_eq("list")
+                .ok_or_else(|| invalid("usage: gol patterns list [QUERY] | gol patterns search TEXT".to_string()))?;
+            Some(args.next_if(|arg| !arg.starts_with("--")).unwrap_or_default())
+        }
+        _ => None,
+    };
+    // `gol diff` takes a second pattern as well.
+    let other = match subcommand.as_deref() {
+        Some("diff") => Some(args.next().ok_or_else(|| invalid("usage: gol diff A B".to_string()))?),
+        _ => None,
+    };
+    let mut config = Config::load(args)?;
+    if let (Some("render"), Some(path)) = (subcommand.as_deref(), &argument) {
+        config.pattern = Some(path.into());
+    }
+    if let Some(level) = config.log {
+        logging::init(level, &config.log_file)
+            .map_err(|error| invalid(format!("{}: {}", config.log_file.display(), error)))?;
+    }
+    if let (Some("period"), Some(path)) = (subcommand.as_deref(), &argument) {
+        return period::run(&config, path.as_ref());
+    }
+    if let (Some("info"), Some(path)) = (subcommand.as_deref(), &argument) {
+        return info::run(&config, path.as_ref());
+    }
+    if let (Some(first), Some(second)) = (&argument, &other) {
+        return diff::run(&config, first.as_ref(), second.as_ref());
+    }
+    if let (Some("patterns"), Some(query)) = (subcommand.as_deref(), &argument) {
+        return if search { patterns::search(query) } else { patterns::list(query) };
+    }
+    if subcommand.as_deref() == Some("rules") {
+        return rules::list();
+    }
+    if let (Some("golly"), Some(path)) = (subcommand.as_deref(), &argument) {
+        return golly::run(&config, path.as_ref(), config.size.unwrap_or(DEFAULT_SIZE));
+    }
+    let replay = match (subcommand.as_deref(), &argument) {
+        (Some("replay"), Some(path)) => {
+            let replay = Replay::load(path).map_err(|error| invalid(format!("{}: {}", path, error)))?;
+            info!(path, frames = replay.frames().len(), "loaded replay");
+            Some(replay)
+        }
+        _ => None,
+    };
+    if subcommand.as_deref() == Some("soup") {
+        return soup::run(&config, config.size.unwrap_or(DEFAULT_SIZE));
+    }
+    let loaded = match (config.stdin_format, &config.pattern, &config.text) {
+        (Some(format), _, _) => Some(pipe::read(format)?),
+        (None, Some(path), _) => {
+            let pattern = io::load(path).map_err(|error| invalid(format!("{}: {}", path.display(), error)))?;
+            info!(path = %path.display(), cells = pattern.cells().len(), "loaded pattern");
+            Some(pattern)
+        }
+        (None, None, Some(text)) => Some(config.font.render(text)),
+        (None, None, None) => None,
+    };
+    let script = config.script.as_deref().map(Script::load).transpose()?;
+    let plugin = config.plugin.as_deref().map(Plugin::load).transpose()?;
+    if subcommand.as_deref() == Some("render") {
+        let (rows, columns) = match (config.size, &loaded) {
+            (Some(size), _) => size,
+            (None, Some(pattern)) => pipe::fit(pattern, config.generations)?,
+            (None, None) => DEFAULT_SIZE,
+        };
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        return snapshot::run(game, &config, script.and_then(Script::hook));
+    }
+    if config.stdout_format.is_some() || config.clipboard_format.is_some() {
+        let (rows, columns) = match (config.size, &loaded) {
+            (Some(size), _) => size,
+            (None, Some(pattern)) => pipe::fit(pattern, config.generations)?,
+            (None, None) => DEFAULT_SIZE,
+        };
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        let pattern = pipe::run(game, loaded.as_ref(), config.generations, script.and_then(Script::hook));
+        if let Some(format) = config.stdout_format {
+            pipe::write(stdout(), &pattern, format)?;
+        }
+        if let Some(format) = config.clipboard_format {
+            Clipboard::open()?.copy(format.write(&pattern))?;
+        }
+        return Ok(());
+    }
+    if let Some(path) = &config.csv {
+        let (rows, columns) = config.size.unwrap_or(DEFAULT_SIZE);
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        return metrics::run(game, config.generations, path, script.and_then(Script::hook));
+    }
+    if let Some(every) = config.verify_hash_every {
+        let (rows, columns) = config.size.unwrap_or(DEFAULT_SIZE);
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        return verify::run(game, config.generations, every, script.and_then(Script::hook));
+    }
+    if let Some(limit) = config.census {
+        let (rows, columns) = config.size.unwrap_or(DEFAULT_SIZE);
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        return census::run(game, limit);
+    }
+    if let Some(limit) = config.lifespan {
+        let (rows, columns) = config.size.unwrap_or(DEFAULT_SIZE);
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        return lifespan::run(game, limit);
+    }
+    if let Some(address) = &config.serve {
+        let (rows, columns) = config.size.unwrap_or(DEFAULT_SIZE);
+        let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+        return serve::run(game, config.tick_interval(), address, script.and_then(Script::hook));
+    }
+
+    // The default colors are for a dark background, so a light one needs them swapped for
+    // others before anything is drawn.
+    let background = config.background.unwrap_or_else(terminal::background);
+    let config = config.for_background(background);
+
+    // Input is handled and the screen redrawn this often, independently of the tick interval.
+    let frame_time = config.frame_time();
+
+    // The bottom line of the terminal is reserved for the status bar.
+    let (rows, columns) = crossterm::terminal::size()
+        .map(|(width, height)| ((height as usize).saturating_sub(1).max(1), width as usize))?;
+    let mut renderer = TerminalRenderer::new(stdout(), rows, columns, config.glyphs, config.graphics, config.charset);
+    let (rows, columns) = renderer.shape();
+    let game = build_game(&config, loaded.as_ref(), script.as_ref(), plugin.as_ref(), rows, columns)?;
+    let recorder = match &config.record {
+        Some(path) => Some(File::create(path)
+            .map_err(Into::into)
+            .and_then(|file| Recorder::new(BufWriter::new(file), &game))
+            .map_err(|error| invalid(format!("{}: {}", path.display(), error)))?),
+        None => None,
+    };
+    info!(rows, columns, ?background, rule = %game.rule(), recording = recorder.is_some(), "starting in the terminal");
+
+    let _terminal = Terminal::enter()?;
+    if let Some(replay) = replay {
+        replay::run(&config, replay, &mut renderer)?;
+    } else if subcommand.as_deref() == Some("tutorial") {
+        tutorial::run(&config, &mut renderer)?;
+    } else if subcommand.as_deref() == Some("lenia") {
+        lenia::run(&config, &config.lenia(), &mut renderer)?;
+    } else if subcommand.as_deref() == Some("smoothlife") {
+        lenia::run(&config, &config.smooth_life(), &mut renderer)?;
+    } else if subcommand.as_deref() == Some("3d") {
+        life3d::run(&config, &mut renderer)?;
+    } else if !config.layers.is_empty() {
+        layers::run(&config, &game, &mut renderer)?;
+    } else if let Some(budget) = config.versus {
+        versus::run(&config, game.rule(), budget, &mut renderer)?;
+    } else {
+        let hook = script.and_then(Script::hook);
+        run(App::new(game, config, loaded, hook, plugin, recorder), &mut renderer, frame_time)?;
+    }
+    Ok(())
+}
+
+fn run<W>(mut app: App, renderer: &mut TerminalRenderer<W>, frame_time: Duration) -> Result<()>
+    where W: Write
+{
+    let mut pacer = Pacer::new(frame_time);
+    loop {
+        // Waiting on the terminal rather than sleeping, a key is handled and drawn the moment it
+        // arrives, and a settled game is woken up only now and then.
+        let now = Instant::now();
+        let timeout = pacer.wake_at(now, app.is_busy(), app.active_at).saturating_duration_since(now);
+        let command = app.config.keys.next_command(&app.mode, timeout)?;
+        let commanded = command.is_some();
+        match command {
+            Some(Command::Quit) => { break; }
+            Some(command) => {
+                debug!(?command, "command");
+                app.apply(command);
+            }
+            None => {}
+        }
+
+        app.receive_updates()?;
+        app.play_back();
+        app.explore();
+        let now = Instant::now();
+        if commanded || pacer.is_due(now) {
+            render(&app, renderer)?;
+            let took = now.elapsed();
+            debug!(micros = took.as_micros() as u64, "drew frame");
+            pacer.rendered(now, took);
+        }
+    }
+    app.finish()
+}
+
+/// Builds the game from the configured rule, seed and density, or with the loaded pattern centred
+/// on an empty grid, then lets the plugin and the script, if any, change the rule and seed it.
+fn build_game(
+    config: &Config,
+    loaded: Option<&Pattern>,
+    script: Option<&Script>,
+    plugin: Option<&Plugin>,
+    rows: usize,
+    columns: usize,
+) -> Result<GameOfLife> {
+    let mut builder = GameOfLife::builder()
+        .size(rows, columns)
+        .rule(&config.rule)
+        .density(if loaded.is_some() { 0.0 } else { config.density() })
+        .symmetry(config.symmetry);
+    if let Some(seed) = config.seed {
+        builder = builder.seed(seed);
+    }
+    let mut game = builder.build().map_err(|error| invalid(error.to_string()))?;
+    if let Some(rule) = config.split_rule {
+        game.set_region_rule(BoundingBox { top: 0, left: columns / 2, bottom: rows - 1, right: columns - 1 }, rule);
+    }
+    if let Some(pattern) = loaded {
+        let (pattern_rows, pattern_columns) = pattern.shape();
+        let origin = (rows.saturating_sub(pattern_rows) / 2, columns.saturating_sub(pattern_columns) / 2);
+        pattern.try_stamp(game.current_mut(), origin).map_err(|_| invalid(format!(
+            "{} is {}x{} but the grid only fits {}x{}",
+            pattern.name(), pattern_rows, pattern_columns, rows, columns
+        )))?;
+    }
+    if let Some(plugin) = plugin {
+        plugin.prepare(&mut game)?;
+    }
+    if let Some(script) = script {
+        script.prepare(&mut game)?;
+    }
+    Ok(game)
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Mode {
+    Run,
+    Edit,
+    Place,
+    /// Typing into the search box of the pattern picker, from `Place`.
+    Search,
+    /// Choosing one of the rule presets, from `Run`.
+    Rules,
+    /// Ticking the neighbor counts of the rule on and off, from `Run`.
+    RuleEditor,
+    /// A two player game; see `versus`.
+    Versus,
+    /// The guided introduction run by `gol tutorial`; see `tutorial`.
+    Tutorial,
+    /// Playing a recorded run with `gol replay`; see `replay`.
+    Replay,
+    /// The continuous automata run by `gol lenia` and `gol smoothlife`; see `lenia`.
+    Lenia,
+    /// Life in three dimensions, run by `gol 3d`; see `life3d`.
+    Life3d,
+    /// Games under several rules drawn over each other, with `layers`; see `layers`.
+    Layers,
+}
+
+pub struct App {
+    config: Config,
+    /// A replica of the game running on the simulation thread, kept in sync from its updates.
+    game: GameOfLife,
+    simulation: Simulation,
+    running: bool,
+    paused: bool,
+    mode: Mode,
+    cursor: Cell,
+    patterns: Vec<Pattern>,
+    pattern_index: usize,
+    placing: Pattern,
+    /// What has been typed into the picker's search box, as a `Query`.
+    search: String,
+    /// The indexes of the patterns the search matches, which are all the picker cycles through.
+    matches: Vec<usize>,
+    /// The rule preset chosen in `Mode::Rules`.
+    preset_index: usize,
+    /// The rule being changed in `Mode::RuleEditor`, ahead of the replica, which only catches up
+    /// with each change on the simulation's next update.
+    editing_rule: Rule,
+    /// The box under the cursor in `Mode::RuleEditor`: the row, birth or survival, and the count.
+    rule_cursor: (usize, usize),
+    stats: Stats,
+    show_graph: bool,
+    /// Labels the objects `GameMatrix::classify_objects` recognizes, when set.
+    show_objects: bool,
+    /// Shows a census of those objects in the status bar instead of the key hints, when set.
+    show_census: bool,
+    /// Colors each group of touching cells on its own, when set.
+    components: Option<Tracker>,
+    /// Draws how long each cell has been alive in all instead of the cells themselves, when set.
+    show_heatmap: bool,
+    /// Shows how many living neighbors each cell has in edit mode, colored by its fate, when set.
+    show_neighbors: bool,
+    /// Numbers the rows and columns along the left and top of the grid, when set.
+    show_ruler: bool,
+    /// Turns the wrapped grid on screen so the living cells' bounding box sits in the middle,
+    /// when set.
+    center: bool,
+    /// Colors living cells instead of the gradient, when loaded.
+    plugin: Option<Plugin>,
+    /// The latest generations, kept for playing backward when `history` is configured.
+    history: History,
+    /// Plays the history backward, a generation every interval, when set.
+    reversing: bool,
+    /// Set from the first step backward until the simulation thread has been handed the rewound
+    /// game; its updates until then are for a game the replica has left behind.
+    detached: bool,
+    /// How many generations have been played backward, so the generation count can follow.
+    rewound: u64,
+    next_back: Instant,
+    /// When the last command was applied or update received, so the loop knows when things
+    /// have settled.
+    active_at: Instant,
+    /// When explore mode switches to the next random rule, while it is on.
+    next_rule: Option<Instant>,
+    /// Writes every generation to the replay file, when `record` is configured.
+    recorder: Option<Recorder<BufWriter<File>>>,
+    /// Opened the first time the board is copied.
+    clipboard: Option<Clipboard>,
+    /// The outcome of the last command, shown in the status bar instead of the key hints until
+    /// the next.
+    notice: Option<String>,
+    /// The corner a region to freeze or thaw was started from in edit mode, until the cursor is
+    /// moved to the opposite one and it is frozen.
+    freeze_anchor: Option<Cell>,
+    /// The corner a rectangle to fill or clear was started from in edit mode, until the cursor is
+    /// moved to the opposite one.
+    rectangle_anchor: Option<Cell>,
+    /// Where a line was started from in edit mode, until the cursor is moved to its other end.
+    line_anchor: Option<Cell>,
+}
+
+impl App {
+    fn new(
+        game: GameOfLife,
+        config: Config,
+        loaded: Option<Pattern>,
+        hook: Option<Hook>,
+        plugin: Option<Plugin>,
+        recorder: Option<Recorder<BufWriter<File>>>,
+    ) -> App {
+        let mut patterns = pattern::builtin();
+        if let Some(pattern) = loaded {
+            patterns.insert(0, pattern);
+        }
+        let placing = patterns[0].clone();
+        let (rows, columns) = game.shape();
+        let simulation = Simulation::spawn(game.clone(), config.tick_interval());
+        if let Some(hook) = hook {
+            simulation.set_hook(hook);
+        }
+        simulation.set_stride(config.render_every);
+        simulation.set_running(true);
+        let history = History::new(config.history);
+        let next_rule = config.explore.map(|_| Instant::now());
+        App {
+            config,
+            game,
+            simulation,
+            running: true,
+            paused: false,
+            mode: Mode::Run,
+            cursor: (rows / 2, columns / 2),
+            matches: (0..patterns.len()).collect(),
+            patterns,
+            pattern_index: 0,
+            placing,
+            search: String::new(),
+            preset_index: 0,
+            editing_rule: Rule::life(),
+            rule_cursor: (0, 0),
+            stats: Stats::new(300),
+            show_graph: true,
+            show_objects: false,
+            show_census: false,
+            center: false,
+            show_neighbors: false,
+            show_ruler: false,
+            show_heatmap: false,
+            components: None,
+            plugin,
+            history,
+            reversing: false,
+            detached: false,
+            rewound: 0,
+            next_back: Instant::now(),
+            active_at: Instant::now(),
+            next_rule,
+            recorder,
+            clipboard: None,
+            notice: None,
+            freeze_anchor: None,
+            rectangle_anchor: None,
+            line_anchor: None,
+        }
+    }
+
+    /// Brings the replica up to date with every generation published since the last frame. Only
+    /// what has to see each generation happens here; drawing, and the component colors, follow
+    /// the latest one.
+    fn receive_updates(&mut self) -> Result<()> {
+        let mut received = false;
+        while let Some(update) = self.simulation.try_recv() {
+            received = true;
+            if self.detached {
+                // The rewound game coming back is the first snapshot once playing backward stops.
+                if !self.reversing && matches!(update, Update::Snapshot(_)) {
+                    self.detached = false;
+                    self.record()?;
+                }
+                continue;
+            }
+            let generations = update.generations();
+            match update {
+                Update::Generation(changes) => {
+                    self.game.apply_changes(&changes);
+                    self.history.record(&self.game);
+                }
+                update => {
+                    self.history.clear();
+                    update.apply(&mut self.game);
+                }
+            }
+            if generations > 0 {
+                self.stats.skip(generations - 1);
+                self.stats.record(&self.game);
+            }
+            self.record()?;
+        }
+        if received {
+            self.active_at = Instant::now();
+        }
+        if let (true, Some(tracker)) = (received, &mut self.components) {
+            tracker.update(self.game.current());
+        }
+        Ok(())
+    }
+
+    /// Puts the living cells on the clipboard as RLE, cropped to their bounding box, and returns
+    /// the size they were cropped to.
+    fn copy(&mut self) -> Result<(usize, usize)> {
+        let pattern = self.snapshot("");
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self.clipboard.insert(Clipboard::open()?),
+        };
+        clipboard.copy(Format::Rle.write(&pattern))?;
+        info!(cells = pattern.cells().len(), "copied to the clipboard");
+        Ok(pattern.shape())
+    }
+
+    /// The living cells, cropped to their bounding box, with the rule they are running under.
+    fn snapshot(&self, name: &str) -> Pattern {
+        let metadata = Metadata { rule: Some(self.game.rule()), ..Metadata::default() };
+        Pattern::from_game(name, &self.game).with_metadata(metadata)
+    }
+
+    /// Adds the replica's generation to the replay file, when recording.
+    fn record(&mut self) -> Result<()> {
+        let generation = self.stats.generation() - self.rewound;
+        match &mut self.recorder {
+            Some(recorder) => recorder.record(generation, self.game.current()).map_err(|error| invalid(error.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes out what is left of the replay file, when recording, and the board, when
+    /// `autosave` is configured.
+    fn finish(&mut self) -> Result<()> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.flush().map_err(|error| invalid(error.to_string()))?;
+        }
+        if let Some(path) = &self.config.autosave {
+            let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+            let pattern = self.snapshot(&name);
+            std::fs::write(path, Format::from_path(path).write(&pattern))
+                .map_err(|error| invalid(format!("{}: {}", path.display(), error)))?;
+            info!(path = %path.display(), cells = pattern.cells().len(), "saved the board");
+        }
+        Ok(())
+    }
+
+    /// The generations the history can take the game back to, for the status bar, when enabled.
+    fn rewind_range(&self) -> String {
+        if !self.history.is_enabled() {
+            return String::new();
+        }
+        let generation = self.stats.generation() - self.rewound;
+        format!(" | rewind {}-{}", generation.saturating_sub(self.history.len() as u64), generation)
+    }
+
+    /// Whether the game runs, plays backward or explores, so every frame has something new.
+    fn is_busy(&self) -> bool {
+        self.running || self.reversing || self.next_rule.is_some()
+    }
+
+    /// Takes the game back a generation if playing backward and it is time to, stopping at the
+    /// start of the history.
+    fn play_back(&mut self) {
+        if !self.reversing || Instant::now() < self.next_back {
+            return;
+        }
+        self.next_back = Instant::now() + self.config.tick_interval();
+        if self.history.step_back(&mut self.game) {
+            self.detached = true;
+            self.rewound += 1;
+            if let Some(tracker) = &mut self.components {
+                tracker.update(self.game.current());
+            }
+        } else {
+            self.stop_reversing();
+        }
+    }
+
+    /// Switches to the next random rule if exploring and it is time to.
+    fn explore(&mut self) {
+        if self.next_rule.is_some_and(|next| Instant::now() >= next) {
+            self.random_rule();
+        }
+    }
+
+    /// Switches to a random rule and reseeds, then keeps exploring from it.
+    fn random_rule(&mut self) {
+        let rule = Rule::random(&mut rand::thread_rng());
+        let (density, symmetry) = (self.config.density(), self.config.symmetry);
+        self.simulation.edit(move |game| {
+            game.set_rule(rule);
+            game.kill_em_all();
+            game.current_mut().randomize_symmetric(density, symmetry, &mut rand::thread_rng());
+        });
+        self.next_rule = Some(Instant::now() + self.config.explore_interval());
+    }
+
+    /// Stops playing backward and hands the rewound game to the simulation thread, so it goes on
+    /// from there.
+    fn stop_reversing(&mut self) {
+        self.reversing = false;
+        if self.detached {
+            let game = self.game.clone();
+            self.simulation.edit(move |simulated| *simulated = game);
+        }
+    }
+
+    fn apply(&mut self, command: Command) {
+        self.active_at = Instant::now();
+        self.notice = None;
+        if self.reversing && command != Command::Reverse {
+            self.stop_reversing();
+        }
+        self.apply_command(command);
+
+        let running = !self.paused && self.mode == Mode::Run;
+        if running != self.running {
+            self.simulation.set_running(running);
+            self.running = running;
+        }
+    }
+
+    fn apply_command(&mut self, command: Command) {
+        match command {
+            Command::Paused => self.paused = !self.paused,
+            Command::Reverse if self.reversing => self.stop_reversing(),
+            Command::Reverse if self.history.is_enabled() => {
+                self.reversing = true;
+                self.paused = true;
+                self.next_back = Instant::now();
+            }
+            Command::Reverse => {}
+            Command::Copy => {
+                self.notice = Some(match self.copy() {
+                    Ok((rows, columns)) => format!("copied {}x{} as RLE", rows, columns),
+                    Err(error) => error.to_string(),
+                });
+            }
+            Command::Step => self.simulation.step(),
+            Command::Reseed => {
+                let (density, symmetry) = (self.config.density(), self.config.symmetry);
+                self.simulation.edit(move |game| {
+                    game.kill_em_all();
+                    game.current_mut().randomize_symmetric(density, symmetry, &mut rand::thread_rng());
+                });
+            }
+            Command::Edit => {
+                self.mode = match self.mode {
+                    Mode::Run => Mode::Edit,
+                    _ => Mode::Run,
+                }
+            }
+            Command::Place => self.mode = Mode::Place,
+            Command::Search => self.mode = Mode::Search,
+            Command::Explore => self.random_rule(),
+            Command::Pin => {
+                if self.next_rule.take().is_some() {
+                    self.notice = Some(format!("pinned {}; {} explores again", self.game.rule(), self.config.keys.describe("explore")));
+                }
+            }
+            Command::Rules => {
+                let rule = self.game.rule();
+                self.preset_index = PRESETS.iter().position(|preset| preset.rule() == rule).unwrap_or(0);
+                self.mode = Mode::Rules;
+            }
+            Command::NextRule(step) => {
+                self.preset_index = (self.preset_index as isize + step).rem_euclid(PRESETS.len() as isize) as usize;
+            }
+            Command::ApplyRule => {
+                let preset = &PRESETS[self.preset_index];
+                let rule = preset.rule();
+                self.simulation.edit(move |game| game.set_rule(rule));
+                // Reseeding picks the preset's density up from the rule, unless one was given.
+                self.config.rule = preset.notation.to_string();
+                self.notice = Some(format!(
+                    "running {}; {} reseeds at {:.0}%",
+                    preset.name, self.config.keys.describe("reseed"), self.config.density() * 100.0
+                ));
+                self.mode = Mode::Run;
+            }
+            Command::Back => {
+                self.freeze_anchor = None;
+                self.rectangle_anchor = None;
+                self.line_anchor = None;
+                self.mode = match self.mode {
+                    Mode::Place => Mode::Edit,
+                    Mode::Search => Mode::Place,
+                    _ => Mode::Run,
+                }
+            }
+            Command::EditRule => {
+                self.editing_rule = self.game.rule();
+                self.mode = Mode::RuleEditor;
+            }
+            Command::Move(d_row, d_column) if self.mode == Mode::RuleEditor => {
+                let (row, count) = self.rule_cursor;
+                self.rule_cursor = ((row as isize + d_row).rem_euclid(2) as usize, (count as isize + d_column).rem_euclid(9) as usize);
+            }
+            Command::Toggle if self.mode == Mode::RuleEditor => {
+                let rule = &mut self.editing_rule;
+                match self.rule_cursor {
+                    (0, count) => rule.set_birth(count, !rule.is_born(count)),
+                    (_, count) => rule.set_survival(count, !rule.survives(count)),
+                }
+                let rule = *rule;
+                self.simulation.edit(move |game| game.set_rule(rule));
+            }
+            Command::Move(d_row, d_column) => {
+                let (rows, columns) = self.game.shape();
+                let (row, column) = self.cursor;
+                self.cursor = (
+                    (row as isize + d_row).rem_euclid(rows as isize) as usize,
+                    (column as isize + d_column).rem_euclid(columns as isize) as usize,
+                );
+            }
+            Command::Toggle => {
+                let cell = self.cursor;
+                self.simulation.edit(move |game| {
+                    let matrix = game.current_mut();
+                    let state = match matrix.try_get_state(cell) {
+                        Ok(CellState::Alive) => CellState::Dead,
+                        Ok(CellState::Dead) => CellState::Alive,
+                        Ok(CellState::Wall | CellState::Immortal) | Err(_) => return,
+                    };
+                    let _ = matrix.try_set_state(cell, state);
+                });
+            }
+            Command::Clear => self.simulation.edit(|game| game.kill_em_all()),
+            Command::Wall => self.place(CellState::Wall),
+            Command::Immortal => self.place(CellState::Immortal),
+            Command::Freeze => match self.freeze_anchor.take() {
+                None => self.freeze_anchor = Some(self.cursor),
+                Some(anchor) => {
+                    let region = BoundingBox {
+                        top: anchor.0.min(self.cursor.0),
+                        left: anchor.1.min(self.cursor.1),
+                        bottom: anchor.0.max(self.cursor.0),
+                        right: anchor.1.max(self.cursor.1),
+                    };
+                    // A region started on a frozen cell is thawed instead.
+                    let frozen = !self.game.is_frozen(anchor);
+                    self.simulation.edit(move |game| game.freeze(region, frozen));
+                    let (rows, columns) = region.shape();
+                    self.notice = Some(format!("{} {}x{}", if frozen { "froze" } else { "thawed" }, rows, columns));
+                }
+            },
+            Command::Rectangle => match self.rectangle_anchor.take() {
+                None => self.rectangle_anchor = Some(self.cursor),
+                Some(anchor) => {
+                    let top_left = (anchor.0.min(self.cursor.0), anchor.1.min(self.cursor.1));
+                    let bottom_right = (anchor.0.max(self.cursor.0), anchor.1.max(self.cursor.1));
+                    // A rectangle started on a living cell is cleared instead.
+                    let fill = self.game.current().get_state(anchor) != CellState::Alive;
+                    self.simulation.edit(move |game| match fill {
+                        true => game.current_mut().fill_rect(top_left, bottom_right, CellState::Alive),
+                        false => game.current_mut().clear_rect(top_left, bottom_right),
+                    });
+                    self.notice = Some(format!(
+                        "{} {}x{}",
+                        if fill { "filled" } else { "cleared" }, bottom_right.0 - top_left.0 + 1, bottom_right.1 - top_left.1 + 1
+                    ));
+                }
+            },
+            Command::Soup => match self.rectangle_anchor.take() {
+                None => {
+                    self.notice = Some(format!("start a rectangle with {} to fill with soup", self.config.keys.describe("rectangle")));
+                }
+                Some(anchor) => {
+                    let region = BoundingBox {
+                        top: anchor.0.min(self.cursor.0),
+                        left: anchor.1.min(self.cursor.1),
+                        bottom: anchor.0.max(self.cursor.0),
+                        right: anchor.1.max(self.cursor.1),
+                    };
+                    let density = self.config.density();
+                    self.simulation.edit(move |game| game.current_mut().randomize_region(region, density, &mut rand::thread_rng()));
+                    let (rows, columns) = region.shape();
+                    self.notice = Some(format!("soup {}x{} at {:.0}%", rows, columns, density * 100.0));
+                }
+            },
+            Command::Line => match self.line_anchor.take() {
+                None => self.line_anchor = Some(self.cursor),
+                Some(anchor) => {
+                    let (end, state) = (self.cursor, self.line_state(anchor));
+                    self.simulation.edit(move |game| game.current_mut().draw_line(anchor, end, state));
+                    self.notice = Some(format!("drew {} cells from {:?} to {:?}", line(anchor, end).len(), anchor, end));
+                }
+            },
+            Command::Shift(d_row, d_column) => self.simulation.edit(move |game| game.shift(d_row, d_column)),
+            Command::ToggleGraph => self.show_graph = !self.show_graph,
+            Command::ToggleObjects => self.show_objects = !self.show_objects,
+            Command::ToggleCenter => self.center = !self.center,
+            Command::ToggleNeighbors => self.show_neighbors = !self.show_neighbors,
+            Command::ToggleHeatmap => self.show_heatmap = !self.show_heatmap,
+            // Quadrants and images have no characters to spare for numbers.
+            Command::ToggleRuler if self.config.graphics != Graphics::Text => {
+                self.notice = Some("the ruler needs --graphics text".to_string());
+            }
+            Command::ToggleRuler => self.show_ruler = !self.show_ruler,
+            Command::ToggleComponents => {
+                self.components = match self.components {
+                    Some(_) => None,
+                    None => Some(Tracker::new(self.game.current())),
+                }
+            }
+            Command::ToggleCensus => self.show_census = !self.show_census,
+            Command::ToggleBoundary => {
+                self.simulation.edit(|game| {
+                    let boundary = match game.boundary() {
+                        Boundary::Wrap => Boundary::Dead,
+                        Boundary::Dead => Boundary::Wrap,
+                    };
+                    game.set_boundary(boundary);
+                });
+            }
+            Command::NextPattern => {
+                let position = self.matches.iter().position(|index| *index == self.pattern_index);
+                let next = position.map_or(0, |position| position + 1);
+                self.pick(next);
+            }
+            Command::Type(symbol) => {
+                self.search.push(symbol);
+                self.filter();
+            }
+            Command::Erase => {
+                self.search.pop();
+                self.filter();
+            }
+            Command::RotateClockwise => self.placing = self.placing.rotate_clockwise(),
+            Command::RotateCounterClockwise => self.placing = self.placing.rotate_counter_clockwise(),
+            Command::Flip => self.placing = self.placing.flip_horizontal(),
+            Command::Stamp => {
+                let (pattern, cursor) = (self.placing.clone(), self.cursor);
+                self.simulation.edit(move |game| pattern.stamp(game.current_mut(), cursor));
+            }
+            Command::Aim(..) | Command::Claim(_) | Command::NextStage | Command::PreviousStage => {}
+            Command::Seek(_) | Command::Jump(_) | Command::Slice(_) | Command::ToggleProjection | Command::Quit => {}
+        }
+    }
+
+    fn status(&self, width: usize) -> String {
+        let state = match (self.reversing, self.paused) {
+            (true, _) => "reversing",
+            (false, true) => "paused",
+            (false, false) => "running",
+        };
+        let boundary = match self.game.boundary() {
+            Boundary::Wrap => "wrap",
+            Boundary::Dead => "edges",
+        };
+        let status = match self.mode {
+            Mode::Run => format!(
+                " {} {} [{}] gen {} pop {}{} | {}",
+                state, self.rules(), boundary, self.stats.generation() - self.rewound, self.game.current().population(), self.rewind_range(),
+                if let Some(notice) = &self.notice {
+                    notice.clone()
+                } else if self.show_census {
+                    self.game.current().census().to_string()
+                } else if let Some(next) = self.next_rule {
+                    format!(
+                        "exploring, next rule in {}s | {}",
+                        next.saturating_duration_since(Instant::now()).as_secs_f64().ceil(), self.hints(&["explore", "pin", "quit"])
+                    )
+                } else {
+                    self.hints(&["pause", "step", "reverse", "reseed", "rules", "rule_editor", "explore", "wrap", "graph", "objects", "census", "heatmap", "components", "center", "ruler", "copy", "edit", "place", "quit"])
+                }
+            ),
+            Mode::Edit => format!(
+                " edit {:?} [{}] | {}",
+                self.cursor, boundary,
+                if let Some(notice) = &self.notice {
+                    notice.clone()
+                } else if let Some(anchor) = self.freeze_anchor {
+                    format!(
+                        "{} from {:?}: move to the opposite corner and press {}",
+                        if self.game.is_frozen(anchor) { "thawing" } else { "freezing" }, anchor, self.config.keys.describe("freeze")
+                    )
+                } else if let Some(anchor) = self.rectangle_anchor {
+                    format!(
+                        "{} from {:?}: move to the opposite corner and press {}, or {} for a soup",
+                        if self.game.current().get_state(anchor) == CellState::Alive { "clearing" } else { "filling" }, anchor,
+                        self.config.keys.describe("rectangle"), self.config.keys.describe("soup")
+                    )
+                } else if let Some(anchor) = self.line_anchor {
+                    format!(
+                        "{} line from {:?}: move to its other end and press {}",
+                        if self.line_state(anchor) == CellState::Wall { "wall" } else { "living" }, anchor, self.config.keys.describe("line")
+                    )
+                } else if self.show_neighbors {
+                    render::explain(&self.game, self.cursor)
+                } else {
+                    self.hints(&["toggle", "wall", "immortal", "clear", "neighbors", "freeze", "rectangle", "line", "copy", "place", "wrap", "back"])
+                }
+            ),
+            Mode::Place => format!(
+                " place {}{} [{}] | {}",
+                self.placing.name(),
+                self.placing.metadata().author.as_ref().map(|author| format!(" by {}", author)).unwrap_or_default(),
+                boundary,
+                self.hints(&["next_pattern", "search", "rotate_counter_clockwise", "rotate_clockwise", "flip", "stamp", "back"])
+            ),
+            Mode::Search => format!(
+                " search: {}_ | {} | {}",
+                self.search,
+                match self.matches.len() {
+                    0 => "no matches".to_string(),
+                    count => format!("{}/{}: {}", self.matches.iter().position(|index| *index == self.pattern_index).unwrap_or(0) + 1, count, self.placing.name()),
+                },
+                self.hints(&["next_pattern", "search_erase", "search_done"])
+            ),
+            Mode::RuleEditor => format!(
+                " rule {} | {} | {}",
+                self.editing_rule, self.checkboxes(), self.hints(&["rule_toggle", "back"])
+            ),
+            Mode::Rules => {
+                let preset = &PRESETS[self.preset_index];
+                format!(
+                    " rule {}/{}: {} {} | {}, {:.0}% soup | {}",
+                    self.preset_index + 1, PRESETS.len(), preset.name, preset.notation, preset.description, preset.density * 100.0,
+                    self.hints(&["rule_previous", "rule_next", "rule_apply", "back"])
+                )
+            }
+            Mode::Versus | Mode::Tutorial | Mode::Replay | Mode::Lenia | Mode::Life3d | Mode::Layers => String::new(),
+        };
+
+        let graph = if self.show_graph { self.stats.sparkline(width / 4) } else { String::new() };
+        let padding = width.saturating_sub(graph.chars().count());
+        let status: String = status.chars().take(padding).collect();
+        format!("{:padding$}{}", status, graph, padding = padding)
+    }
+
+    /// The birth and survival counts of the rule being edited as boxes, ticked when on, with
+    /// angle brackets around the one under the cursor, e.g. `B [ ]0 [ ]1 [ ]2 <x>3 ...`.
+    fn checkboxes(&self) -> String {
+        let rows = [("B", Rule::is_born as fn(&Rule, usize) -> bool), ("S", Rule::survives)];
+        let rows: Vec<String> = rows.iter().enumerate()
+            .map(|(row, (label, is_on))| {
+                let boxes: Vec<String> = (0..=8)
+                    .map(|count| {
+                        let (open, close) = if self.rule_cursor == (row, count) { ('<', '>') } else { ('[', ']') };
+                        let tick = if is_on(&self.editing_rule, count) { 'x' } else { ' ' };
+                        format!("{}{}{}{}", open, tick, close, count)
+                    })
+                    .collect();
+                format!("{} {}", label, boxes.join(" "))
+            })
+            .collect();
+        rows.join("  ")
+    }
+
+    /// What a line started at `anchor` is drawn with: walls when it starts on one, living cells
+    /// otherwise.
+    fn line_state(&self, anchor: Cell) -> CellState {
+        match self.game.current().get_state(anchor) {
+            CellState::Wall => CellState::Wall,
+            _ => CellState::Alive,
+        }
+    }
+
+    /// Puts a cell of the given kind under the cursor, or clears the cursor if it already holds one.
+    fn place(&mut self, kind: CellState) {
+        let cell = self.cursor;
+        self.simulation.edit(move |game| {
+            let matrix = game.current_mut();
+            let state = match matrix.try_get_state(cell) {
+                Ok(state) if state == kind => CellState::Dead,
+                Ok(_) => kind,
+                Err(_) => return,
+            };
+            let _ = matrix.try_set_state(cell, state);
+        });
+    }
+
+    /// The game's rule, followed by those of any regions running their own, e.g. `B3/S23 | B2/S`.
+    fn rules(&self) -> String {
+        let mut rules = self.game.rule().to_string();
+        for (_, rule) in self.game.region_rules() {
+            rules.push_str(&format!(" | {}", rule));
+        }
+        rules
+    }
+
+    /// Narrows the picker down to the patterns the search matches and picks the first of them.
+    fn filter(&mut self) {
+        let query = Query::parse(&self.search);
+        self.matches = (0..self.patterns.len()).filter(|index| query.matches(&self.patterns[*index])).collect();
+        self.pick(0);
+    }
+
+    /// Picks the match at `position`, wrapping around, if anything matches.
+    fn pick(&mut self, position: usize) {
+        if !self.matches.is_empty() {
+            self.pattern_index = self.matches[position % self.matches.len()];
+            self.placing = self.patterns[self.pattern_index].clone();
+        }
+    }
+
+    fn hints(&self, actions: &[&str]) -> String {
+        actions
+            .iter()
+            .map(|action| format!("{}: {}", self.config.keys.describe(action), action.replace('_', " ")))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Paused,
+    Step,
+    Reseed,
+    Quit,
+    Edit,
+    Place,
+    Back,
+    Move(isize, isize),
+    /// Moves every cell on the grid this many rows and columns.
+    Shift(isize, isize),
+    Toggle,
+    /// Builds a wall at the cursor in edit mode, or takes one down.
+    Wall,
+    /// Places an immortal cell at the cursor in edit mode, or clears one.
+    Immortal,
+    Clear,
+    /// Starts a region to freeze or thaw at the cursor in edit mode, or finishes it there.
+    Freeze,
+    /// Starts a rectangle to fill with living cells or clear at the cursor in edit mode, or
+    /// finishes it there.
+    Rectangle,
+    /// Starts a line at the cursor in edit mode, or draws it from there to the cursor.
+    Line,
+    /// Fills the rectangle started in edit mode with a random soup at the configured density.
+    Soup,
+    ToggleBoundary,
+    ToggleGraph,
+    ToggleObjects,
+    ToggleCenter,
+    ToggleNeighbors,
+    ToggleHeatmap,
+    ToggleRuler,
+    ToggleComponents,
+    Reverse,
+    Copy,
+    NextStage,
+    PreviousStage,
+    /// Moves a replay this many frames.
+    Seek(isize),
+    /// Moves a replay this many tenths of its length.
+    Jump(isize),
+    /// Moves this many layers through a three dimensional grid.
+    Slice(isize),
+    /// Switches between one layer of a three dimensional grid and a view down through all of them.
+    ToggleProjection,
+    ToggleCensus,
+    NextPattern,
+    /// Opens the search box of the pattern picker.
+    Search,
+    /// Adds a character to the search.
+    Type(char),
+    /// Takes the last character off the search.
+    Erase,
+    /// Switches to a random rule and reseeds, and keeps doing so every so often.
+    Explore,
+    /// Stops exploring, keeping the current rule.
+    Pin,
+    /// Opens the list of rule presets.
+    Rules,
+    /// Opens the rule editor.
+    EditRule,
+    /// Moves this many places through the rule presets.
+    NextRule(isize),
+    /// Switches to the chosen rule preset.
+    ApplyRule,
+    RotateClockwise,
+    RotateCounterClockwise,
+    Flip,
+    Stamp,
+    /// Moves a player's cursor in a two player game.
+    Aim(Player, isize, isize),
+    /// Places a player's cell under their cursor in a two player game.
+    Claim(Player),
+}
+
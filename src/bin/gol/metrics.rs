@@ -0,0 +1,21 @@
+use std::fs;
+use std::io::{Error, Result};
+use std::path::Path;
+use game_of_life::simulation::Hook;
+use game_of_life::{GameOfLife, Stats};
+
+/// Advances the game `generations` times and writes the population, density, entropy and
+/// activity of every generation, the first included, to `path` as CSV. `hook` runs after every
+/// tick, just as it would on the simulation thread.
+pub fn run(mut game: GameOfLife, generations: u64, path: &Path, mut hook: Option<Hook>) -> Result<()> {
+    let mut stats = Stats::new(generations as usize + 1);
+    stats.record(&game);
+    for _ in 0..generations {
+        game.tick();
+        if let Some(hook) = &mut hook {
+            hook(&mut game);
+        }
+        stats.record(&game);
+    }
+    fs::write(path, stats.to_csv()).map_err(|error| Error::new(error.kind(), format!("{}: {}", path.display(), error)))
+}
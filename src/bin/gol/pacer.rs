@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+
+/// How long the terminal loop waits for a key between frames once the game has settled, paused
+/// with nothing in flight; only a signal to quit, arriving outside the terminal, waits this long.
+const IDLE: Duration = Duration::from_secs(1);
+
+/// How long the terminal loop keeps drawing every frame after the last command or update, for
+/// whatever the simulation thread still sends back, like the generation a step asked for.
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// Decides which frames get drawn. Normally that is every one, but when drawing takes longer than
+/// half the frame time, as on a slow terminal or over SSH, it backs off so drawing never takes
+/// more than half the time: the generations in between go undrawn while input and the simulation
+/// carry on at full speed.
+pub struct Pacer {
+    frame_time: Duration,
+    /// A moving average of how long drawing takes.
+    render_time: Duration,
+    next_render: Instant,
+}
+
+impl Pacer {
+    pub fn new(frame_time: Duration) -> Pacer {
+        Pacer {
+            frame_time,
+            render_time: Duration::ZERO,
+            next_render: Instant::now(),
+        }
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        now >= self.next_render
+    }
+
+    /// When the loop has something to do next if no key comes first, as of `now`: the next frame
+    /// while the game is `busy` or for `SETTLE` after it was last active, and otherwise not until
+    /// `IDLE` from now, or the next frame if that is later still.
+    pub fn wake_at(&self, now: Instant, busy: bool, active_at: Instant) -> Instant {
+        match busy || now.saturating_duration_since(active_at) < SETTLE {
+            true => self.next_render,
+            false => self.next_render.max(now + IDLE),
+        }
+    }
+
+    /// Notes that drawing started at `start` and took `took`, and schedules the next draw.
+    pub fn rendered(&mut self, start: Instant, took: Duration) {
+        self.render_time = (self.render_time * 3 + took) / 4;
+        self.next_render = start + self.frame_time.max(self.render_time * 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer() {
+        let frame_time = Duration::from_millis(16);
+        let mut pacer = Pacer::new(frame_time);
+        let start = Instant::now();
+        assert!(pacer.is_due(start));
+
+        // Quick draws leave every frame drawn.
+        pacer.rendered(start, Duration::from_millis(2));
+        assert_eq!(pacer.next_render, start + frame_time);
+        assert!(pacer.is_due(start + frame_time));
+
+        // A terminal taking 40ms a draw is soon drawn to no more than every 80ms.
+        for _ in 0..20 {
+            pacer.rendered(start, Duration::from_millis(40));
+        }
+        assert!(!pacer.is_due(start + Duration::from_millis(70)));
+        assert!(pacer.is_due(start + Duration::from_millis(80)));
+
+        // And back to every frame once it speeds up again.
+        for _ in 0..20 {
+            pacer.rendered(start, Duration::from_millis(1));
+        }
+        assert!(pacer.is_due(start + frame_time));
+    }
+
+    #[test]
+    fn test_wake_at() {
+        let frame_time = Duration::from_millis(16);
+        let mut pacer = Pacer::new(frame_time);
+        let start = Instant::now();
+        pacer.rendered(start, Duration::from_millis(1));
+        let next_frame = start + frame_time;
+
+        // Running, or just after a command, the loop wakes for the next frame. An instant a minute
+        // back may not exist so soon after boot, in which case the start has to do.
+        let long_ago = start.checked_sub(Duration::from_secs(60)).unwrap_or(start);
+        assert_eq!(pacer.wake_at(start, true, long_ago), next_frame);
+        assert_eq!(pacer.wake_at(start, false, start), next_frame);
+        assert_eq!(pacer.wake_at(start + SETTLE - frame_time, false, start), next_frame);
+        // Settled, it waits for a key, without spinning on a frame that is already past.
+        assert_eq!(pacer.wake_at(start + SETTLE, false, start), start + SETTLE + IDLE);
+        let later = start + Duration::from_secs(5);
+        assert_eq!(pacer.wake_at(later, false, start), later + IDLE);
+        // A slow terminal's next frame can be later than that.
+        pacer.rendered(start, Duration::from_secs(10));
+        assert!(pacer.wake_at(start + SETTLE, false, start) > start + SETTLE + IDLE);
+    }
+}
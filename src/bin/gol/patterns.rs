@@ -0,0 +1,88 @@
+use std::io::Result;
+use game_of_life::{pattern, Pattern, Query};
+
+/// The most rows and columns of a pattern `search` shows; larger ones are cut short.
+const PREVIEW: (usize, usize) = (10, 40);
+
+/// Prints the built-in patterns that match `query`, as `Query::parse` reads it, one a line with
+/// its category, size and who found it when.
+pub fn list(query: &str) -> Result<()> {
+    let found = pattern::search(&Query::parse(query));
+    let width = found.iter().map(|pattern| pattern.name().len()).max().unwrap_or(0);
+    for pattern in found {
+        println!("{:width$}  {}", pattern.name(), describe(pattern), width = width);
+    }
+    Ok(())
+}
+
+/// Prints the built-in patterns that fuzzily match `text`, best first, each with its category, size
+/// and credit, and a preview of it under that.
+pub fn search(text: &str) -> Result<()> {
+    for pattern in pattern::fuzzy_search(text) {
+        println!("{}  {}", pattern.name(), describe(pattern));
+        print!("{}", preview(pattern));
+    }
+    Ok(())
+}
+
+/// The pattern in plaintext, `O` alive and `.` dead, indented and cut to `PREVIEW`, with `...`
+/// where it goes on.
+fn preview(pattern: &Pattern) -> String {
+    let (rows, columns) = pattern.shape();
+    let (shown_rows, shown_columns) = (rows.min(PREVIEW.0), columns.min(PREVIEW.1));
+    let mut lines = vec![vec!['.'; shown_columns]; shown_rows];
+    for (row, column) in pattern.cells() {
+        if *row < shown_rows && *column < shown_columns {
+            lines[*row][*column] = 'O';
+        }
+    }
+    let mut preview = String::new();
+    for line in lines {
+        preview += "  ";
+        preview.extend(line);
+        if columns > shown_columns {
+            preview += "...";
+        }
+        preview.push('\n');
+    }
+    if rows > shown_rows {
+        preview += "  ...\n";
+    }
+    preview
+}
+
+/// E.g. `spaceship   3x3  Richard K. Guy, 1969`.
+fn describe(pattern: &Pattern) -> String {
+    let metadata = pattern.metadata();
+    let (rows, columns) = pattern.shape();
+    let category = metadata.category.map(|category| category.name()).unwrap_or_default();
+    let credit = match (&metadata.author, &metadata.discovered) {
+        (Some(author), Some(discovered)) => format!("{}, {}", author, discovered),
+        (Some(author), None) => author.clone(),
+        (None, Some(discovered)) => discovered.clone(),
+        (None, None) => String::new(),
+    };
+    format!("{:10}  {:>5}  {}", category, format!("{}x{}", rows, columns), credit).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let glider = pattern::search(&Query::parse("glider spaceship"))[0];
+        assert_eq!(describe(glider), "spaceship     3x3  Richard K. Guy, 1969");
+        assert_eq!(describe(&Pattern::from_plaintext("", "OO")), "              1x2");
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview(&Pattern::from_plaintext("", ".O.\n..O\nOOO")), "  .O.\n  ..O\n  OOO\n");
+        let long = preview(&Pattern::from_plaintext("", &"O".repeat(50)));
+        assert_eq!(long, format!("  {}...\n", "O".repeat(40)));
+        let tall = preview(&Pattern::from_plaintext("", &"O\n".repeat(12)));
+        assert_eq!(tall.lines().count(), 11);
+        assert!(tall.ends_with("  ...\n"));
+    }
+}
@@ -0,0 +1,24 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use game_of_life::{io, Behavior, Rule};
+use crate::config::Config;
+
+/// Loads the pattern at `path` and prints whether it is a still life, an oscillator or a
+/// spaceship under the configured rule, or what it turns into, looking at most `config.max`
+/// generations ahead.
+pub fn run(config: &Config, path: &Path) -> Result<()> {
+    let pattern = io::load(path)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", path.display(), error)))?;
+    let rule: Rule = config.rule
+        .parse()
+        .map_err(|error: game_of_life::Error| Error::new(ErrorKind::InvalidInput, error.to_string()))?;
+    match pattern.periodicity(rule, config.max) {
+        Some(periodicity) if periodicity.start == 0 => println!("{}", periodicity.behavior),
+        Some(periodicity) if periodicity.behavior == Behavior::DiesOut => {
+            println!("dies out after {} generations", periodicity.start)
+        }
+        Some(periodicity) => println!("becomes a {} after {} generations", periodicity.behavior, periodicity.start),
+        None => println!("nothing repeats within {} generations", config.max),
+    }
+    Ok(())
+}
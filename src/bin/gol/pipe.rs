@@ -0,0 +1,85 @@
+use std::io::{stdin, Error, ErrorKind, Read, Result, Write};
+use game_of_life::io::Format;
+use game_of_life::simulation::Hook;
+use game_of_life::{GameOfLife, Metadata, Pattern};
+
+/// Reads the starting pattern from stdin.
+pub fn read(format: Format) -> Result<Pattern> {
+    let mut text = String::new();
+    stdin().read_to_string(&mut text)?;
+    format
+        .parse("", &text)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("stdin: {}", error)))
+}
+
+/// The most cells `fit` makes a grid of, about 64 MB a generation.
+const MAX_CELLS: usize = 1 << 26;
+
+/// A grid big enough that `pattern` can't reach around the wrapped edges to itself within
+/// `generations`: cells spread at most one step per generation, so it gets that much room on
+/// every side. The room needed grows with the square of `generations`, so past `MAX_CELLS` it
+/// fails, asking for a `--size` instead.
+pub fn fit(pattern: &Pattern, generations: u64) -> Result<(usize, usize)> {
+    let (rows, columns) = pattern.shape();
+    let margin = usize::try_from(generations).ok().and_then(|generations| generations.checked_add(1)?.checked_mul(2));
+    margin
+        .and_then(|margin| Some((rows.checked_add(margin)?, columns.checked_add(margin)?)))
+        .filter(|(rows, columns)| rows.checked_mul(*columns).is_some_and(|cells| cells <= MAX_CELLS))
+        .ok_or_else(|| Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} generations need too large a grid to run on without a --size", generations),
+        ))
+}
+
+/// Advances the game `generations` times and returns the living cells, cropped to their bounding
+/// box, so the result can be written to stdout or the clipboard and fed to another `gol` or any
+/// other tool. It keeps the name and metadata of the pattern the game `started` from, if any, with
+/// the rule it ran under. `hook` runs after every tick, just as it would on the simulation thread.
+pub fn run(mut game: GameOfLife, started: Option<&Pattern>, generations: u64, mut hook: Option<Hook>) -> Pattern {
+    for _ in 0..generations {
+        game.tick();
+        if let Some(hook) = &mut hook {
+            hook(&mut game);
+        }
+    }
+    let name = started.map(Pattern::name).unwrap_or_default();
+    let metadata = started.map(|pattern| pattern.metadata().clone()).unwrap_or_default();
+    Pattern::from_game(name, &game).with_metadata(Metadata { rule: Some(game.rule()), ..metadata })
+}
+
+/// Writes the pattern to `out`, usually stdout, in `format`.
+pub fn write<W>(mut out: W, pattern: &Pattern, format: Format) -> Result<()>
+    where W: Write
+{
+    out.write_all(format.write(pattern).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let glider = Pattern::from_plaintext("Glider", ".O.\n..O\nOOO");
+        assert_eq!(fit(&glider, 0).unwrap(), (5, 5));
+        assert_eq!(fit(&glider, 10).unwrap(), (25, 25));
+        assert!(fit(&glider, 100_000).is_err());
+        assert!(fit(&glider, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_write() {
+        let glider = Pattern::from_plaintext("Glider", ".O.\n..O\nOOO");
+        let mut game = GameOfLife::new(9, 9);
+        glider.stamp(game.current_mut(), (2, 2));
+        // Four generations on, the glider is back in shape, a cell further on.
+        let pattern = run(game, Some(&glider), 4, None);
+        let written = |format| {
+            let mut out = vec![];
+            write(&mut out, &pattern, format).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(written(Format::Rle), "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+        assert_eq!(written(Format::Plaintext), "!Name: Glider\n!Rule: B3/S23\n.O.\n..O\nOOO\n");
+    }
+}
@@ -0,0 +1,174 @@
+//! WebAssembly plugins loaded with `--plugin`, so rules and color schemes can be shipped without
+//! being built into `gol`. A plugin is a module with no imports exporting:
+//!
+//! - `gol_abi() -> i32`, returning `ABI_VERSION`.
+//! - `gol_rule(alive: i32, neighbors: i32) -> i32`, optionally: non-zero when the cell is alive in
+//!   the next generation. It is asked once for every combination and replaces the configured
+//!   rule.
+//! - `gol_color(row: i32, column: i32, age: i32) -> i32`, optionally: the `0xRRGGBB` color of a
+//!   living cell, or a negative number to draw it as usual.
+//!
+//! Either `.wasm` binaries or `.wat` text can be loaded.
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use game_of_life::{Cell, GameOfLife};
+use crate::color::Rgb;
+
+/// The version of the exports above; plugins built against another one are refused.
+#[cfg_attr(not(feature = "plugin"), allow(dead_code))]
+pub const ABI_VERSION: i32 = 1;
+
+#[cfg(feature = "plugin")]
+pub use self::wasm::Plugin;
+
+/// Stands in for plugins when the `plugin` feature is off, refusing to load any.
+#[cfg(not(feature = "plugin"))]
+pub enum Plugin {}
+
+#[cfg(not(feature = "plugin"))]
+impl Plugin {
+    pub fn load(_: &Path) -> Result<Plugin> {
+        Err(Error::new(ErrorKind::Unsupported, "--plugin needs the plugin feature"))
+    }
+
+    pub fn prepare(&self, _: &mut GameOfLife) -> Result<()> {
+        match *self {}
+    }
+
+    pub fn color(&self, _: Cell, _: u32) -> Option<Rgb> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "plugin")]
+mod wasm {
+    use std::cell::RefCell;
+    use wasmtime::{Engine, Instance, Module, Store, TypedFunc};
+    use game_of_life::Rule;
+    use super::*;
+
+    pub struct Plugin {
+        name: String,
+        // Calls need the store mutably but rendering only has the plugin by reference.
+        store: RefCell<Store<()>>,
+        rule: Option<TypedFunc<(i32, i32), i32>>,
+        color: Option<TypedFunc<(i32, i32, i32), i32>>,
+    }
+
+    impl Plugin {
+        pub fn load(path: &Path) -> Result<Plugin> {
+            let engine = Engine::default();
+            let module = Module::from_file(&engine, path).map_err(|error| invalid(path, error))?;
+            Plugin::instantiate(&path.display().to_string(), &engine, &module)
+        }
+
+        fn instantiate(name: &str, engine: &Engine, module: &Module) -> Result<Plugin> {
+            let fail = |error: wasmtime::Error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", name, error));
+            let mut store = Store::new(engine, ());
+            let instance = Instance::new(&mut store, module, &[]).map_err(fail)?;
+            let version = instance
+                .get_typed_func::<(), i32>(&mut store, "gol_abi")
+                .and_then(|abi| abi.call(&mut store, ()))
+                .map_err(fail)?;
+            if version != ABI_VERSION {
+                return Err(Error::new(ErrorKind::InvalidInput, format!(
+                    "{}: plugin ABI {} isn't supported, only {}", name, version, ABI_VERSION
+                )));
+            }
+            let rule = optional(instance.get_func(&mut store, "gol_rule"), &store).map_err(fail)?;
+            let color = optional(instance.get_func(&mut store, "gol_color"), &store).map_err(fail)?;
+            Ok(Plugin { name: name.to_string(), store: RefCell::new(store), rule, color })
+        }
+
+        /// Applies the plugin's rule, if it has one, to a freshly built game.
+        pub fn prepare(&self, game: &mut GameOfLife) -> Result<()> {
+            let Some(rule) = &self.rule else {
+                return Ok(());
+            };
+            let mut store = self.store.borrow_mut();
+            let mut next = |alive: i32| -> Result<Vec<usize>> {
+                let mut counts = vec![];
+                for neighbors in 0..=8 {
+                    let result = rule.call(&mut *store, (alive, neighbors as i32)).map_err(|error| {
+                        Error::new(ErrorKind::InvalidInput, format!("{}: {}", self.name, error))
+                    })?;
+                    if result != 0 {
+                        counts.push(neighbors);
+                    }
+                }
+                Ok(counts)
+            };
+            let (birth, survival) = (next(0)?, next(1)?);
+            game.set_rule(Rule::new(&birth, &survival));
+            Ok(())
+        }
+
+        /// The color of a living cell, or `None` to draw it as usual. A plugin that traps is
+        /// treated as having no opinion.
+        pub fn color(&self, (row, column): Cell, age: u32) -> Option<Rgb> {
+            let color = self.color.as_ref()?;
+            let rgb = color.call(&mut *self.store.borrow_mut(), (row as i32, column as i32, age as i32)).ok()?;
+            (rgb >= 0).then_some(((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+        }
+    }
+
+    /// Looks up an optional export, which must have the right signature if it is there at all.
+    fn optional<Params, Results>(
+        func: Option<wasmtime::Func>,
+        store: &Store<()>,
+    ) -> wasmtime::Result<Option<TypedFunc<Params, Results>>>
+        where Params: wasmtime::WasmParams, Results: wasmtime::WasmResults
+    {
+        func.map(|func| func.typed(store)).transpose()
+    }
+
+    fn invalid(path: &Path, error: wasmtime::Error) -> Error {
+        Error::new(ErrorKind::InvalidInput, format!("{}: {}", path.display(), error))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn plugin(wat: &str) -> Result<Plugin> {
+            let engine = Engine::default();
+            Plugin::instantiate("test", &engine, &Module::new(&engine, wat).unwrap())
+        }
+
+        #[test]
+        fn test_rule_and_color() {
+            // HighLife, with cells colored by column.
+            let plugin = plugin(r#"(module
+                (func (export "gol_abi") (result i32) i32.const 1)
+                (func (export "gol_rule") (param $alive i32) (param $neighbors i32) (result i32)
+                    (i32.or
+                        (i32.eq (local.get $neighbors) (i32.const 3))
+                        (select
+                            (i32.eq (local.get $neighbors) (i32.const 2))
+                            (i32.eq (local.get $neighbors) (i32.const 6))
+                            (local.get $alive))))
+                (func (export "gol_color") (param i32 i32 i32) (result i32)
+                    (select (i32.const 0xFF8000) (i32.const -1) (local.get 1))))"#).unwrap();
+            let mut game = GameOfLife::new(3, 3);
+            plugin.prepare(&mut game).unwrap();
+            assert_eq!(game.rule(), "B36/S23".parse().unwrap());
+            assert_eq!(plugin.color((0, 1), 5), Some((255, 128, 0)));
+            assert_eq!(plugin.color((0, 0), 5), None);
+        }
+
+        #[test]
+        fn test_exports_are_checked() {
+            assert!(plugin(r#"(module)"#).is_err());
+            assert!(plugin(r#"(module (func (export "gol_abi") (result i32) i32.const 2))"#).is_err());
+            assert!(plugin(r#"(module
+                (func (export "gol_abi") (result i32) i32.const 1)
+                (func (export "gol_rule") (param i32) (result i32) i32.const 0))"#).is_err());
+
+            let plugin = plugin(r#"(module (func (export "gol_abi") (result i32) i32.const 1))"#).unwrap();
+            let mut game = GameOfLife::builder().rule("B36/S23").build().unwrap();
+            plugin.prepare(&mut game).unwrap();
+            assert_eq!(game.rule(), "B36/S23".parse().unwrap());
+            assert_eq!(plugin.color((0, 0), 1), None);
+        }
+    }
+}
@@ -0,0 +1,680 @@
+use std::io::Write;
+use crossterm::{
+    queue,
+    cursor::MoveTo,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    Result,
+};
+use game_of_life::{Boundary, Cell, CellChange, CellState, GameOfLife, Renderer};
+use crate::config::Glyphs;
+use crate::graphics::{pixel, Graphics};
+use crate::{kitty, sixel};
+use crate::{App, Mode};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Glyph {
+    pub symbol: char,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl Glyph {
+    pub const fn new(symbol: char, foreground: Color) -> Glyph {
+        Glyph { symbol, foreground, background: Color::Reset }
+    }
+
+    pub const fn filled(background: Color) -> Glyph {
+        Glyph { symbol: ' ', foreground: Color::Reset, background }
+    }
+
+    /// Recolors the glyph, painting the background instead for glyphs drawn as filled cells.
+    pub fn colored(self, color: Color) -> Glyph {
+        if self.background == Color::Reset {
+            Glyph { foreground: color, ..self }
+        } else {
+            Glyph { background: color, ..self }
+        }
+    }
+}
+
+/// Which characters the terminal can show: any when its output is UTF-8, otherwise only ASCII,
+/// with everything else written as the nearest ASCII character.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Charset {
+    Unicode,
+    Ascii,
+}
+
+impl Charset {
+    /// Guesses from the locale, as set by the first of `LC_ALL`, `LC_CTYPE` and `LANG` that is,
+    /// the way C programs pick their encoding. Windows terminals all take Unicode.
+    pub fn detect() -> Charset {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+            .to_lowercase();
+        match cfg!(windows) || locale.contains("utf-8") || locale.contains("utf8") {
+            true => Charset::Unicode,
+            false => Charset::Ascii,
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Charset> {
+        match name {
+            "auto" => Some(Charset::detect()),
+            "unicode" | "utf-8" => Some(Charset::Unicode),
+            "ascii" => Some(Charset::Ascii),
+            _ => None,
+        }
+    }
+
+    /// `symbol` as written in this charset: the rising blocks of sparklines become a ramp from `_`
+    /// up, dots `.`, other blocks and shapes `#`, shades `%`, and anything else outside ASCII `?`.
+    pub fn symbol(&self, symbol: char) -> char {
+        match (self, symbol) {
+            (Charset::Unicode, symbol) => symbol,
+            (Charset::Ascii, symbol) if symbol.is_ascii() => symbol,
+            (Charset::Ascii, '▁') => '_',
+            (Charset::Ascii, '▂') => '.',
+            (Charset::Ascii, '▃') => '-',
+            (Charset::Ascii, '▄') => '=',
+            (Charset::Ascii, '▅') => '+',
+            (Charset::Ascii, '▆' | '▇') => '*',
+            (Charset::Ascii, '░' | '▒' | '▓') => '%',
+            (Charset::Ascii, '·' | '•') => '.',
+            (Charset::Ascii, '▀'..='▟' | '■'..='▫' | '●') => '#',
+            (Charset::Ascii, _) => '?',
+        }
+    }
+}
+
+/// The symbol of the cursor and of the pattern being placed, in the theme's colors.
+const MARK: char = '█';
+
+/// The crossterm backend. Cells are put into a frame and written on `flush`, which remembers
+/// what is on screen so only cells whose glyph changed are written to the terminal, row by row,
+/// with a cursor move only where a run of them starts and a color change only where it differs
+/// from the cell before. Over a slow link that traffic is what limits the frame rate. With
+/// quadrants each character is put together from a block of four cells first, and with kitty or
+/// sixel graphics, where the grid is finer still, a changed frame is sent whole as an image.
+pub struct TerminalRenderer<W>
+    where W: Write
+{
+    write: W,
+    rows: usize,
+    columns: usize,
+    graphics: Graphics,
+    /// The rows and columns of characters the grid covers, with the status bar below them.
+    text: (usize, usize),
+    /// What is on the terminal: a glyph for each character, or for each cell of an image.
+    screen: Vec<Option<Glyph>>,
+    /// What the next `flush` puts there.
+    frame: Vec<Option<Glyph>>,
+    status: String,
+    glyphs: Glyphs,
+    charset: Charset,
+}
+
+impl<W> TerminalRenderer<W>
+    where W: Write
+{
+    /// A renderer for a grid covering `rows` by `columns` characters.
+    pub fn new(write: W, rows: usize, columns: usize, glyphs: Glyphs, graphics: Graphics, charset: Charset) -> TerminalRenderer<W> {
+        let text = (rows, columns);
+        let (rows, columns) = graphics.grid_shape(text);
+        let shown = match graphics {
+            Graphics::Kitty { .. } | Graphics::Sixel { .. } => rows * columns,
+            Graphics::Text | Graphics::Quadrants => text.0 * text.1,
+        };
+        TerminalRenderer {
+            write,
+            rows,
+            columns,
+            graphics,
+            text,
+            screen: vec![None; shown],
+            frame: vec![None; rows * columns],
+            status: String::new(),
+            glyphs,
+            charset,
+        }
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    pub fn put(&mut self, (row, column): Cell, glyph: Glyph) {
+        self.frame[row * self.columns + column] = Some(glyph);
+    }
+
+    pub fn put_status(&mut self, mut status: String) -> Result<()> {
+        if self.charset == Charset::Ascii {
+            status = status.chars().map(|symbol| self.charset.symbol(symbol)).collect();
+        }
+        // Callers pad the status to the grid's width, which may be wider than the screen.
+        if let Some((end, _)) = status.char_indices().nth(self.text.1) {
+            status.truncate(end);
+        }
+        if status != self.status {
+            let row = self.text.0 as u16;
+            queue!(self.write, MoveTo(0, row), ResetColor, Print(&status))?;
+            self.status = status;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match self.graphics {
+            Graphics::Text => {
+                let frame = std::mem::take(&mut self.frame);
+                self.write_characters(&frame)?;
+                self.frame = frame;
+            }
+            Graphics::Quadrants => {
+                let characters = quadrants(&self.frame, self.columns);
+                self.write_characters(&characters)?;
+            }
+            Graphics::Kitty { .. } | Graphics::Sixel { .. } => self.write_image()?,
+        }
+        self.write.flush()
+    }
+
+    fn write_characters(&mut self, characters: &[Option<Glyph>]) -> Result<()> {
+        let (rows, columns) = self.text;
+        let (mut foreground, mut background) = (None, None);
+        for row in 0..rows {
+            let mut column = 0;
+            while column < columns {
+                if self.changed(characters, row, column).is_none() {
+                    column += 1;
+                    continue;
+                }
+                queue!(self.write, MoveTo(column as u16, row as u16))?;
+                let mut run = String::new();
+                while let Some(glyph) = self.changed(characters, row, column) {
+                    if foreground != Some(glyph.foreground) || background != Some(glyph.background) {
+                        if !run.is_empty() {
+                            queue!(self.write, Print(std::mem::take(&mut run)))?;
+                        }
+                        if foreground != Some(glyph.foreground) {
+                            queue!(self.write, SetForegroundColor(glyph.foreground))?;
+                        }
+                        if background != Some(glyph.background) {
+                            queue!(self.write, SetBackgroundColor(glyph.background))?;
+                        }
+                        (foreground, background) = (Some(glyph.foreground), Some(glyph.background));
+                    }
+                    run.push(self.charset.symbol(glyph.symbol));
+                    self.screen[row * columns + column] = Some(glyph);
+                    column += 1;
+                }
+                queue!(self.write, Print(run))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_image(&mut self) -> Result<()> {
+        if self.frame != self.screen {
+            let mut pixels = Vec::with_capacity(self.frame.len() * 3);
+            for glyph in &self.frame {
+                let (r, g, b) = glyph.as_ref().map_or((0, 0, 0), pixel);
+                pixels.extend([r, g, b]);
+            }
+            let (rows, columns) = self.text;
+            let image = match self.graphics {
+                Graphics::Sixel { scale } => sixel::image(&pixels, self.columns, scale),
+                _ => kitty::image(&pixels, self.columns, rows, columns),
+            };
+            queue!(self.write, MoveTo(0, 0), Print(image))?;
+            self.screen.clone_from(&self.frame);
+        }
+        Ok(())
+    }
+
+    /// The glyph for the character, if it isn't what is on screen already. `None` past the end
+    /// of the row, so runs stop there.
+    fn changed(&self, characters: &[Option<Glyph>], row: usize, column: usize) -> Option<Glyph> {
+        if column >= self.text.1 {
+            return None;
+        }
+        let index = row * self.text.1 + column;
+        characters[index].filter(|glyph| self.screen[index] != Some(*glyph))
+    }
+}
+
+/// The quadrant block characters, indexed by which quarters are filled: the top left, top
+/// right, bottom left and bottom right adding 1, 2, 4 and 8.
+const QUADRANTS: [char; 16] = [' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█'];
+
+/// The characters showing `frame`, a grid `columns` wide, two by two cells to a character.
+fn quadrants(frame: &[Option<Glyph>], columns: usize) -> Vec<Option<Glyph>> {
+    let rows = frame.len() / columns.max(1);
+    let mut characters = Vec::with_capacity(frame.len() / 4);
+    for row in (0..rows).step_by(2) {
+        for column in (0..columns).step_by(2) {
+            let block = [(row, column), (row, column + 1), (row + 1, column), (row + 1, column + 1)]
+                .map(|(row, column)| frame[row * columns + column]);
+            characters.push(block.iter().any(Option::is_some).then(|| quadrant(block.map(|glyph| glyph.and_then(ink)))));
+        }
+    }
+    characters
+}
+
+/// The color a glyph shows as, `None` for a blank one.
+fn ink(glyph: Glyph) -> Option<Color> {
+    match (glyph.background, glyph.symbol) {
+        (Color::Reset, ' ') => None,
+        (Color::Reset, _) => Some(glyph.foreground),
+        (background, _) => Some(background),
+    }
+}
+
+/// A character showing four cells' inks, a quarter each. It has only two colors, so the blank
+/// ones, or failing that the commonest, go in the background, the commonest of the rest in the
+/// foreground, and any third color is lost to the background.
+fn quadrant(inks: [Option<Color>; 4]) -> Glyph {
+    let count = |ink: &Option<Color>| inks.iter().filter(|other| *other == ink).count();
+    let commonest = |skip: Option<Option<Color>>| {
+        inks.iter().filter(|ink| Some(**ink) != skip).max_by_key(|ink| count(ink)).copied()
+    };
+    let background = if inks.contains(&None) { None } else { commonest(None).flatten() };
+    let foreground = commonest(Some(background)).flatten();
+    let filled = inks
+        .iter()
+        .enumerate()
+        .filter(|(_, ink)| foreground.is_some() && **ink == foreground)
+        .fold(0, |filled, (quarter, _)| filled | 1 << quarter);
+    match (filled, background) {
+        // All four the same color.
+        (0, Some(color)) => Glyph::new('█', color),
+        _ => Glyph {
+            symbol: QUADRANTS[filled],
+            foreground: foreground.unwrap_or(Color::Reset),
+            background: background.unwrap_or(Color::Reset),
+        },
+    }
+}
+
+impl<W> Renderer for TerminalRenderer<W>
+    where W: Write
+{
+    fn draw(&mut self, changes: &[CellChange]) -> game_of_life::Result<()> {
+        for change in changes {
+            let glyph = match change.state {
+                CellState::Alive => self.glyphs.alive,
+                CellState::Dead => self.glyphs.dead,
+                CellState::Wall => self.glyphs.wall,
+                CellState::Immortal => self.glyphs.immortal,
+            };
+            self.put(change.cell, glyph);
+        }
+        Ok(self.flush()?)
+    }
+}
+
+/// Draws the interactive UI: the cells colored as configured, the cursor or placement preview on
+/// top of them, and the status bar.
+pub fn render<W>(app: &App, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let game = &app.game;
+    let (rows, columns) = game.shape();
+    let glyphs = &app.config.glyphs;
+    let theme = &app.config.theme;
+    let paint = |color: Color| app.config.color_depth.fit(color);
+
+    let mut overlay: Vec<(Cell, Glyph)> = vec![];
+    match app.mode {
+        Mode::Run | Mode::Rules | Mode::RuleEditor | Mode::Versus | Mode::Tutorial | Mode::Replay | Mode::Lenia | Mode::Life3d | Mode::Layers => {}
+        Mode::Edit => overlay.extend(app.freeze_anchor.into_iter().chain(app.rectangle_anchor).chain(app.line_anchor).chain([app.cursor]).map(|cell| (cell, Glyph::new(MARK, paint(theme.cursor))))),
+        Mode::Place | Mode::Search => {
+            overlay.extend(app.placing.placed_cells(app.cursor, (rows, columns)).map(|cell| (cell, Glyph::new(MARK, paint(theme.ghost)))))
+        }
+    }
+
+    // Visits are spread out on a log scale, or still lifes that have sat there from the start
+    // would wash out everything else.
+    let scale = (game.max_visits() as f32).ln_1p();
+    let heat = if app.show_heatmap { Some(&theme.gradient) } else { None };
+
+    let mut screen: Vec<Glyph> = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        for column in 0..columns {
+            if let Some(heat) = heat {
+                let visits = game.visits((row, column));
+                screen.push(match visits {
+                    0 => glyphs.dead,
+                    _ => Glyph::filled(app.config.color_depth.color(heat.at(1.0 - (visits as f32).ln_1p() / scale))),
+                });
+                continue;
+            }
+            let mut glyph = match game.current().get_state((row, column)) {
+                CellState::Alive => {
+                    let age = game.age((row, column));
+                    let component = app.components.as_ref().and_then(|tracker| tracker.color((row, column)));
+                    let custom = component.or_else(|| app.plugin.as_ref().and_then(|plugin| plugin.color((row, column), age)));
+                    let step = app.config.age_step(age);
+                    let alive = step.map_or(glyphs.alive, |step| Glyph { symbol: step.symbol, ..glyphs.alive });
+                    match (custom, step.and_then(|step| step.color), &app.config.gradient) {
+                        (Some(rgb), _, _) => alive.colored(app.config.color_depth.color(rgb)),
+                        (None, Some(color), _) => alive.colored(paint(color)),
+                        (None, None, Some(gradient)) => {
+                            let t = age as f32 / app.config.gradient_span.max(1) as f32;
+                            alive.colored(app.config.color_depth.color(gradient.at(t)))
+                        }
+                        (None, None, None) => alive,
+                    }
+                }
+                CellState::Dead => glyphs.dead,
+                CellState::Wall => glyphs.wall,
+                CellState::Immortal => glyphs.immortal,
+            };
+            if game.is_frozen((row, column)) && glyph.background == Color::Reset {
+                glyph.background = paint(theme.frozen);
+            }
+            screen.push(glyph);
+        }
+    }
+    if app.show_objects {
+        // Each label starts at the top-left corner of its object and runs right, cut off at the
+        // edge of the grid.
+        for (kind, (row, column)) in game.current().classify_objects() {
+            for (offset, symbol) in kind.name().chars().enumerate().take(columns.saturating_sub(column)) {
+                screen[row * columns + column + offset] = Glyph::new(symbol, paint(theme.label));
+            }
+        }
+    }
+    if app.show_neighbors && app.mode == Mode::Edit {
+        let colors = (paint(theme.lives), paint(theme.dies), paint(theme.stays_dead));
+        for ((row, column), glyph) in neighbor_overlay(game, colors) {
+            screen[row * columns + column] = glyph;
+        }
+    }
+    for ((row, column), glyph) in overlay {
+        screen[row * columns + column] = glyph;
+    }
+
+    // Only a wrapped grid can be turned like this without its dead edges showing mid-screen.
+    let (shift_rows, shift_columns) = match (app.center, game.boundary(), game.bounding_box()) {
+        (true, Boundary::Wrap, Some(bounds)) => {
+            let (row, column) = bounds.center();
+            ((rows / 2 + rows - row) % rows, (columns / 2 + columns - column) % columns)
+        }
+        _ => (0, 0),
+    };
+    // The rulers take the top row and the left columns, pushing the grid's last ones off screen.
+    let (top, left) = match app.show_ruler {
+        true => (1, ruler_width(rows)),
+        false => (0, 0),
+    };
+    for (index, glyph) in screen.into_iter().enumerate() {
+        let (row, column) = ((index / columns + shift_rows) % rows + top, (index % columns + shift_columns) % columns + left);
+        if row < rows && column < columns {
+            renderer.put((row, column), glyph);
+        }
+    }
+    if app.show_ruler {
+        for (cell, symbol) in rulers((rows, columns), (shift_rows, shift_columns)) {
+            renderer.put(cell, Glyph::new(symbol, paint(theme.label)));
+        }
+    }
+    renderer.put_status(app.status(columns))?;
+    renderer.flush()
+}
+
+/// How many columns the row ruler of a grid `rows` high takes: enough for its last row's number.
+fn ruler_width(rows: usize) -> usize {
+    rows.saturating_sub(1).to_string().len()
+}
+
+/// The characters of the rulers along the top and left of a screen of `rows` by `columns`
+/// cells, the grid on it turned by `shift`: each row's number down the left, and along the top
+/// the number of every tenth column starting above it, with a dot over every fifth.
+fn rulers((rows, columns): (usize, usize), (shift_rows, shift_columns): (usize, usize)) -> Vec<(Cell, char)> {
+    let width = ruler_width(rows);
+    let mut marks: Vec<(Cell, char)> = (0..width.min(columns)).map(|column| ((0, column), ' ')).collect();
+    let mut column = width;
+    while column < columns {
+        let number = (column - width + columns - shift_columns) % columns;
+        let label = match number % 10 {
+            0 => number.to_string(),
+            5 => ".".to_string(),
+            _ => " ".to_string(),
+        };
+        for symbol in label.chars().take(columns - column) {
+            marks.push(((0, column), symbol));
+            column += 1;
+        }
+    }
+    for row in 1..rows {
+        let number = (row - 1 + rows - shift_rows) % rows;
+        let label = format!("{:>width$}", number, width = width);
+        marks.extend(label.chars().take(columns).enumerate().map(|(column, symbol)| ((row, column), symbol)));
+    }
+    marks
+}
+
+/// Every cell's count of living neighbors, in the color of what becomes of it: `lives` for cells
+/// alive next generation, `dies` for those dying and `stays_dead` for those staying dead, frozen
+/// cells staying as they are. Lone dead cells, walls and immortal cells are left out so the counts
+/// that matter stand out.
+fn neighbor_overlay(game: &GameOfLife, (lives, dies, stays_dead): (Color, Color, Color)) -> Vec<(Cell, Glyph)> {
+    let (rows, columns) = game.shape();
+    let boundary = game.boundary();
+    let counts = game.current().neighbor_counts(boundary);
+    let mut overlay = vec![];
+    for row in 0..rows {
+        for column in 0..columns {
+            let cell = (row, column);
+            let neighbors = counts[row * columns + column];
+            let state = game.current().get_state(cell);
+            // Frozen cells stay as they are whatever their neighbors.
+            let next = match game.is_frozen(cell) {
+                true => state,
+                false => game.current().get_next_state(cell, boundary, &game.rule_at(cell)),
+            };
+            let color = match (state, next) {
+                (_, CellState::Alive) => lives,
+                (CellState::Alive, CellState::Dead) => dies,
+                (CellState::Dead, CellState::Dead) if neighbors > 0 => stays_dead,
+                (CellState::Dead, CellState::Dead) | (CellState::Wall | CellState::Immortal, _) | (_, CellState::Wall | CellState::Immortal) => continue,
+            };
+            let digit = char::from_digit(neighbors as u32, 10).unwrap_or('?');
+            overlay.push((cell, Glyph::new(digit, color)));
+        }
+    }
+    overlay
+}
+
+/// Why the cell will be alive or dead next generation, by the clause of its rule that decides,
+/// e.g. `alive with 4 neighbors, not in S23: dies`. Walls, immortal cells and frozen cells have no
+/// say in it.
+pub fn explain(game: &GameOfLife, cell: Cell) -> String {
+    let matrix = game.current();
+    if game.is_frozen(cell) && matches!(matrix.get_state(cell), CellState::Alive | CellState::Dead) {
+        let fate = if matrix.get_state(cell) == CellState::Alive { "stays alive" } else { "stays dead" };
+        return format!("frozen: {}", fate);
+    }
+    let rule = game.rule_at(cell);
+    let neighbors = matrix.alive_neighbor_count(cell, game.boundary());
+    let next = matrix.get_next_state(cell, game.boundary(), &rule);
+    let notation = rule.to_string();
+    let (birth, survival) = notation.split_once('/').unwrap_or((&notation, ""));
+    let (state, clause, fate) = match (matrix.get_state(cell), next) {
+        (CellState::Wall, _) => return "wall: stays a wall".to_string(),
+        (CellState::Immortal, _) => return "immortal: lives forever".to_string(),
+        (CellState::Alive, CellState::Alive) => ("alive", format!("in {}", survival), "survives"),
+        (CellState::Alive, _) => ("alive", format!("not in {}", survival), "dies"),
+        (CellState::Dead, CellState::Alive) => ("dead", format!("in {}", birth), "is born"),
+        (CellState::Dead, _) => ("dead", format!("not in {}", birth), "stays dead"),
+    };
+    let plural = if neighbors == 1 { "" } else { "s" };
+    format!("{} with {} neighbor{}, {}: {}", state, neighbors, plural, clause, fate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::BoundingBox;
+
+    fn renderer() -> TerminalRenderer<Vec<u8>> {
+        TerminalRenderer::new(vec![], 2, 6, glyphs(), Graphics::Text, Charset::Unicode)
+    }
+
+    fn glyphs() -> Glyphs {
+        Glyphs {
+            alive: Glyph::new('O', Color::White),
+            dead: Glyph::new('.', Color::Reset),
+            wall: Glyph::new('#', Color::DarkRed),
+            immortal: Glyph::new('@', Color::Yellow),
+        }
+    }
+
+    #[test]
+    fn test_rulers() {
+        let text = |rows, columns, shift| {
+            let mut lines = vec![vec![' '; columns]; rows];
+            for ((row, column), symbol) in rulers((rows, columns), shift) {
+                lines[row][column] = symbol;
+            }
+            lines.into_iter().map(|line| line.into_iter().collect::<String>()).collect::<Vec<_>>()
+        };
+        assert_eq!(text(12, 26, (0, 0)), [
+            "  0    .    10   .    20 ",
+            " 0", " 1", " 2", " 3", " 4", " 5", " 6", " 7", " 8", " 9", "10",
+        ].map(|line| format!("{:<26}", line)));
+        // Turned, the numbers follow the cells.
+        assert_eq!(text(3, 8, (1, 3)), [" .  0", "2", "0"].map(|line| format!("{:<8}", line)));
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut renderer = TerminalRenderer::new(vec![], 1, 10, glyphs(), Graphics::Text, Charset::Ascii);
+        for (column, symbol) in "█▒@★".chars().enumerate() {
+            renderer.put((0, column), Glyph::new(symbol, Color::Reset));
+        }
+        renderer.flush().unwrap();
+        let text = String::from_utf8(renderer.write).unwrap();
+        assert!(text.contains("#%@?"), "{:?}", text);
+
+        renderer.write = vec![];
+        renderer.put_status("gen 5 ▁▄█".to_string()).unwrap();
+        assert!(String::from_utf8(renderer.write).unwrap().ends_with("gen 5 _=#"));
+    }
+
+    #[test]
+    fn test_flush_batches_runs() {
+        let mut renderer = renderer();
+        for column in 0..6 {
+            renderer.put((0, column), Glyph::new('.', Color::Reset));
+            renderer.put((1, column), Glyph::new('.', Color::Reset));
+        }
+        renderer.flush().unwrap();
+        // One move per row and the colors once.
+        let text = String::from_utf8(std::mem::take(&mut renderer.write)).unwrap();
+        assert_eq!(text.matches('H').count(), 2, "{:?}", text);
+        assert!(text.contains("......"));
+        assert_eq!(text.matches("\x1b[39m").count(), 1, "{:?}", text);
+
+        renderer.put((0, 1), Glyph::new('O', Color::White));
+        renderer.put((0, 2), Glyph::new('O', Color::White));
+        renderer.put((0, 4), Glyph::new('O', Color::White));
+        renderer.flush().unwrap();
+        let text = String::from_utf8(std::mem::take(&mut renderer.write)).unwrap();
+        assert_eq!(text.matches('H').count(), 2, "{:?}", text);
+        assert!(text.contains("OO"));
+
+        // Nothing changed, nothing written.
+        renderer.put((0, 1), Glyph::new('O', Color::White));
+        renderer.flush().unwrap();
+        assert!(renderer.write.is_empty());
+    }
+
+    #[test]
+    fn test_quadrants() {
+        let (alive, dead) = (Glyph::new('█', Color::White), Glyph::new(' ', Color::Reset));
+        let mut renderer = TerminalRenderer::new(vec![], 1, 2, glyphs(), Graphics::Quadrants, Charset::Unicode);
+        assert_eq!(renderer.shape(), (2, 4));
+        for (cell, glyph) in [((0, 0), alive), ((0, 1), dead), ((1, 0), dead), ((1, 1), alive)] {
+            renderer.put(cell, glyph);
+        }
+        for (cell, glyph) in [((0, 2), alive), ((0, 3), alive), ((1, 2), alive), ((1, 3), alive)] {
+            renderer.put(cell, glyph);
+        }
+        renderer.flush().unwrap();
+        let text = String::from_utf8(std::mem::take(&mut renderer.write)).unwrap();
+        assert!(text.contains("▚█"), "{:?}", text);
+
+        let (white, black, red) = (Some(Color::White), Some(Color::Black), ink(Glyph::filled(Color::Red)));
+        assert_eq!(quadrant([white, white, black, black]), Glyph { symbol: '▀', foreground: Color::White, background: Color::Black });
+        // The blank quarters go in the background however few they are.
+        assert_eq!(quadrant([None, red, red, red]), Glyph { symbol: '▟', foreground: Color::Red, background: Color::Reset });
+        // The commonest color goes in the background, the odd one out in the foreground.
+        assert_eq!(quadrant([white, white, white, red]), Glyph { symbol: '▗', foreground: Color::Red, background: Color::White });
+        assert_eq!(quadrant([red; 4]), Glyph::new('█', Color::Red));
+    }
+
+    #[test]
+    fn test_kitty_image() {
+        let mut renderer = TerminalRenderer::new(vec![], 2, 3, glyphs(), Graphics::Kitty { scale: 2 }, Charset::Unicode);
+        assert_eq!(renderer.shape(), (8, 6));
+        renderer.put((7, 5), Glyph::new('O', Color::White));
+        renderer.flush().unwrap();
+        let text = String::from_utf8(std::mem::take(&mut renderer.write)).unwrap();
+        assert!(text.contains("\x1b_Ga=T,f=24,s=6,v=8,i=1,p=1,c=3,r=2,"), "{:?}", text);
+
+        // Nothing changed, nothing sent.
+        renderer.flush().unwrap();
+        assert!(renderer.write.is_empty());
+
+        // The status bar goes below the characters, cut to their width.
+        renderer.put_status("running and more".to_string()).unwrap();
+        let text = String::from_utf8(std::mem::take(&mut renderer.write)).unwrap();
+        assert!(text.contains("\x1b[3;1H") && text.ends_with("run"), "{:?}", text);
+    }
+
+    #[test]
+    fn test_explain() {
+        // A blinker, lying down: its middle survives, its ends die and the cells above and below
+        // its middle are born.
+        let mut game = GameOfLife::new(5, 5);
+        game.current_mut().set_alive(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        assert_eq!(explain(&game, (2, 2)), "alive with 2 neighbors, in S23: survives");
+        assert_eq!(explain(&game, (2, 1)), "alive with 1 neighbor, not in S23: dies");
+        assert_eq!(explain(&game, (1, 2)), "dead with 3 neighbors, in B3: is born");
+        assert_eq!(explain(&game, (0, 0)), "dead with 0 neighbors, not in B3: stays dead");
+        game.current_mut().set_state((0, 0), CellState::Wall);
+        assert_eq!(explain(&game, (0, 0)), "wall: stays a wall");
+
+        // Frozen, the left end stays alive and the cell above the middle stays dead, while the cell
+        // below it is still born.
+        game.freeze(BoundingBox { top: 1, left: 1, bottom: 2, right: 2 }, true);
+        assert_eq!(explain(&game, (2, 1)), "frozen: stays alive");
+        assert_eq!(explain(&game, (1, 2)), "frozen: stays dead");
+        assert_eq!(explain(&game, (3, 2)), "dead with 3 neighbors, in B3: is born");
+    }
+
+    #[test]
+    fn test_neighbor_overlay() {
+        let mut game = GameOfLife::new(5, 5);
+        game.current_mut().set_alive(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        let overlay = neighbor_overlay(&game, (Color::Green, Color::Red, Color::Grey));
+        let at = |cell| overlay.iter().find(|(at, _)| *at == cell).map(|(_, glyph)| *glyph);
+        assert_eq!(at((2, 2)), Some(Glyph::new('2', Color::Green)));
+        assert_eq!(at((2, 1)), Some(Glyph::new('1', Color::Red)));
+        assert_eq!(at((1, 2)), Some(Glyph::new('3', Color::Green)));
+        assert_eq!(at((1, 0)), Some(Glyph::new('1', Color::Grey)));
+        assert_eq!(at((0, 0)), None);
+
+        // Frozen, a dying end is colored as living and a cell to be born as dead.
+        game.freeze(BoundingBox { top: 1, left: 1, bottom: 2, right: 2 }, true);
+        let overlay = neighbor_overlay(&game, (Color::Green, Color::Red, Color::Grey));
+        let at = |cell| overlay.iter().find(|(at, _)| *at == cell).map(|(_, glyph)| *glyph);
+        assert_eq!(at((2, 1)), Some(Glyph::new('1', Color::Green)));
+        assert_eq!(at((1, 2)), Some(Glyph::new('3', Color::Grey)));
+        assert_eq!(at((3, 2)), Some(Glyph::new('3', Color::Green)));
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::Write;
+use std::time::Instant;
+use crossterm::{style::Color, Result};
+use game_of_life::{CellState, Playback, Replay};
+use crate::config::Config;
+use crate::render::{Glyph, TerminalRenderer};
+use crate::{Command, Mode};
+
+/// Plays a run recorded with `record`, a frame every `tick_interval` divided by `speed`. It can be
+/// paused, stepped a frame at a time either way, or skipped through a tenth at a time.
+pub fn run<W>(config: &Config, replay: Replay, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let frame_time = (config.tick_interval().as_secs_f64() / config.speed).max(0.001);
+    let mut playback = Playback::new(replay);
+    let mut paused = false;
+    // When and from which frame playing last started, so a fast replay can play several frames
+    // between redraws.
+    let mut resumed = (Instant::now(), 0);
+
+    loop {
+        if let Some(command) = config.keys.next_command(&Mode::Replay, config.frame_time())? {
+            let tenth = (playback.len() / 10).max(1) as isize;
+            match command {
+                Command::Quit => return Ok(()),
+                // Playing from the end starts over.
+                Command::Paused if paused && playback.position() == playback.len() => {
+                    playback.seek(0);
+                    paused = false;
+                }
+                Command::Paused => paused = !paused,
+                Command::Reseed => playback.seek(0),
+                Command::Seek(frames) => {
+                    paused = true;
+                    playback.seek(playback.position().saturating_add_signed(frames));
+                }
+                Command::Jump(tenths) => playback.seek(playback.position().saturating_add_signed(tenths * tenth)),
+                _ => {}
+            }
+            resumed = (Instant::now(), playback.position());
+        }
+
+        if !paused {
+            let frames = resumed.0.elapsed().as_secs_f64() / frame_time;
+            playback.seek(resumed.1 + frames as usize);
+            paused = playback.position() == playback.len();
+        }
+        draw(&playback, paused, config, renderer)?;
+    }
+}
+
+/// Draws the replayed grid in the middle of the screen, or the middle of the grid when it is
+/// larger than the screen.
+fn draw<W>(playback: &Playback, paused: bool, config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = renderer.shape();
+    let matrix = playback.matrix();
+    let (replay_rows, replay_columns) = matrix.shape();
+    for row in 0..rows {
+        for column in 0..columns {
+            let glyph = match (fit(row, rows, replay_rows), fit(column, columns, replay_columns)) {
+                (Some(row), Some(column)) => match matrix.get_state((row, column)) {
+                    CellState::Alive => config.glyphs.alive,
+                    CellState::Dead => config.glyphs.dead,
+                    CellState::Wall => config.glyphs.wall,
+                    CellState::Immortal => config.glyphs.immortal,
+                },
+                _ => Glyph::new(' ', Color::Reset),
+            };
+            renderer.put((row, column), glyph);
+        }
+    }
+
+    let keys = |action| config.keys.describe(action);
+    let status = format!(
+        " {} | {} | gen {} | frame {}/{} | {}x | {}: {}  {}/{}: step  {}/{}: skip  {}: restart  {}: quit",
+        if paused { "paused" } else { "playing" }, playback.replay().rule(), playback.generation(),
+        playback.position(), playback.len(), config.speed,
+        keys("replay_pause"), if paused { "play" } else { "pause" }, keys("replay_back"), keys("replay_forward"),
+        keys("jump_back"), keys("jump_forward"), keys("restart"), keys("quit")
+    );
+    renderer.put_status(format!("{:width$}", status, width = columns))?;
+    renderer.flush()
+}
+
+/// The row or column of a replayed grid `replayed` long shown at `screen` on a screen `size` long,
+/// if any, with the one centered on the other.
+fn fit(screen: usize, size: usize, replayed: usize) -> Option<usize> {
+    (screen + replayed.saturating_sub(size) / 2)
+        .checked_sub(size.saturating_sub(replayed) / 2)
+        .filter(|index| *index < replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        // A grid of 4 on a screen of 8 sits in columns 2 to 5.
+        assert_eq!((0..8).map(|screen| fit(screen, 8, 4)).collect::<Vec<_>>(), vec![
+            None, None, Some(0), Some(1), Some(2), Some(3), None, None
+        ]);
+        // A grid of 10 on a screen of 4 shows its middle.
+        assert_eq!((0..4).filter_map(|screen| fit(screen, 4, 10)).collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+    }
+}
@@ -0,0 +1,29 @@
+use std::io::Result;
+use game_of_life::rule::PRESETS;
+use game_of_life::RulePreset;
+
+/// Prints the built-in rule presets, one a line with its notation, the density its soups start
+/// at and what it does.
+pub fn list() -> Result<()> {
+    let width = PRESETS.iter().map(|preset| preset.name.len()).max().unwrap_or(0);
+    for preset in PRESETS {
+        println!("{:width$}  {}", preset.name, describe(preset), width = width);
+    }
+    Ok(())
+}
+
+/// E.g. `B36/S23        15%  Life with a self-replicating pattern`.
+fn describe(preset: &RulePreset) -> String {
+    format!("{:13}  {:>3.0}%  {}", preset.notation, preset.density * 100.0, preset.description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let highlife = RulePreset::find("highlife").unwrap();
+        assert_eq!(describe(highlife), "B36/S23         15%  Life with a self-replicating pattern");
+    }
+}
@@ -0,0 +1,247 @@
+//! Experiments written as Rhai scripts and loaded with `--script`. A script can define any of:
+//!
+//! - `rule(alive, neighbors)`, returning whether the cell is alive in the next generation. It is
+//!   asked once for every combination and replaces the configured rule.
+//! - `seed(rows, columns)`, returning the cells to bring to life instead of a random soup.
+//! - `tick(generation, rows, columns)`, called after every generation and returning cells to
+//!   bring to life before it is shown.
+//!
+//! Cells are `[row, column]` arrays and ones off the grid are ignored. `pattern(name, row,
+//! column)` returns the cells of a built in pattern placed at that origin, `line(row, column,
+//! to_row, to_column)` the cells of a straight line between two, and `soup(top, left, bottom,
+//! right, density)` a random scattering of cells over a rectangle, so injecting a glider every 100
+//! generations is
+//!
+//! ```rhai
+//! fn tick(generation, rows, columns) {
+//!     if generation % 100 == 0 { pattern("glider", 1, 1) } else { [] }
+//! }
+//! ```
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use game_of_life::simulation::Hook;
+use game_of_life::GameOfLife;
+
+#[cfg(feature = "script")]
+pub use self::scripting::Script;
+
+/// Stands in for scripts when the `script` feature is off, refusing to load any.
+#[cfg(not(feature = "script"))]
+pub enum Script {}
+
+#[cfg(not(feature = "script"))]
+impl Script {
+    pub fn load(_: &Path) -> Result<Script> {
+        Err(Error::new(ErrorKind::Unsupported, "--script needs the script feature"))
+    }
+
+    pub fn prepare(&self, _: &mut GameOfLife) -> Result<()> {
+        match *self {}
+    }
+
+    pub fn hook(self) -> Option<Hook> {
+        match self {}
+    }
+}
+
+#[cfg(feature = "script")]
+mod scripting {
+    use rand::Rng;
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST, INT};
+    use game_of_life::{line, pattern, Cell, Rule};
+    use super::*;
+
+    pub struct Script {
+        name: String,
+        engine: Engine,
+        ast: AST,
+    }
+
+    impl Script {
+        pub fn load(path: &Path) -> Result<Script> {
+            let text = std::fs::read_to_string(path)
+                .map_err(|error| Error::new(error.kind(), format!("{}: {}", path.display(), error)))?;
+            Script::compile(&path.display().to_string(), &text)
+        }
+
+        fn compile(name: &str, text: &str) -> Result<Script> {
+            let mut engine = Engine::new();
+            engine.register_fn("pattern", place_pattern);
+            engine.register_fn("line", draw_line);
+            engine.register_fn("soup", soup);
+            let ast = engine.compile(text).map_err(|error| invalid(name, error))?;
+            Ok(Script { name: name.to_string(), engine, ast })
+        }
+
+        /// Applies the script's `rule` and `seed` to a freshly built game.
+        pub fn prepare(&self, game: &mut GameOfLife) -> Result<()> {
+            if self.defines("rule", 2) {
+                let next = |alive: bool| -> Result<Vec<usize>> {
+                    let mut counts = vec![];
+                    for neighbors in 0..=8 {
+                        if self.call::<bool>("rule", (alive, neighbors as INT))? {
+                            counts.push(neighbors);
+                        }
+                    }
+                    Ok(counts)
+                };
+                let (birth, survival) = (next(false)?, next(true)?);
+                game.set_rule(Rule::new(&birth, &survival));
+            }
+            if self.defines("seed", 2) {
+                let (rows, columns) = game.shape();
+                let cells = self.call::<Array>("seed", (rows as INT, columns as INT))?;
+                game.kill_em_all();
+                bring_to_life(game, cells).map_err(|error| invalid(&self.name, error))?;
+            }
+            Ok(())
+        }
+
+        /// The script's `tick` as a simulation hook, if it defines one. The hook stops calling
+        /// the script after its first error rather than failing the same way every generation.
+        pub fn hook(self) -> Option<Hook> {
+            if !self.defines("tick", 3) {
+                return None;
+            }
+            let mut generation: INT = 0;
+            let mut failed = false;
+            Some(Box::new(move |game: &mut GameOfLife| {
+                generation += 1;
+                if failed {
+                    return;
+                }
+                let (rows, columns) = game.shape();
+                failed = self
+                    .call::<Array>("tick", (generation, rows as INT, columns as INT))
+                    .and_then(|cells| bring_to_life(game, cells).map_err(|error| invalid(&self.name, error)))
+                    .is_err();
+            }))
+        }
+
+        fn defines(&self, name: &str, arity: usize) -> bool {
+            self.ast.iter_functions().any(|function| function.name == name && function.params.len() == arity)
+        }
+
+        fn call<T>(&self, name: &str, args: impl rhai::FuncArgs) -> Result<T>
+            where T: Clone + Send + Sync + 'static
+        {
+            self.engine
+                .call_fn(&mut Scope::new(), &self.ast, name, args)
+                .map_err(|error| invalid(&self.name, error))
+        }
+    }
+
+    fn place_pattern(name: &str, row: INT, column: INT) -> std::result::Result<Array, Box<EvalAltResult>> {
+        let pattern = pattern::builtin()
+            .into_iter()
+            .find(|pattern| pattern.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown pattern '{}'", name))?;
+        Ok(pattern
+            .cells()
+            .iter()
+            .map(|(r, c)| Dynamic::from_array(vec![(row + *r as INT).into(), (column + *c as INT).into()]))
+            .collect())
+    }
+
+    fn draw_line(row: INT, column: INT, to_row: INT, to_column: INT) -> std::result::Result<Array, Box<EvalAltResult>> {
+        let cell = |row: INT, column: INT| -> std::result::Result<Cell, Box<EvalAltResult>> {
+            match (usize::try_from(row), usize::try_from(column)) {
+                (Ok(row), Ok(column)) => Ok((row, column)),
+                _ => Err(format!("line ends must be on the grid, not [{}, {}]", row, column).into()),
+            }
+        };
+        Ok(line(cell(row, column)?, cell(to_row, to_column)?)
+            .into_iter()
+            .map(|(r, c)| Dynamic::from_array(vec![(r as INT).into(), (c as INT).into()]))
+            .collect())
+    }
+
+    fn soup(top: INT, left: INT, bottom: INT, right: INT, density: rhai::FLOAT) -> Array {
+        let mut rng = rand::thread_rng();
+        (top..=bottom)
+            .flat_map(|row| (left..=right).map(move |column| (row, column)))
+            .filter(|_| rng.gen_bool(density.clamp(0.0, 1.0)))
+            .map(|(row, column)| Dynamic::from_array(vec![row.into(), column.into()]))
+            .collect()
+    }
+
+    fn bring_to_life(game: &mut GameOfLife, cells: Array) -> std::result::Result<(), String> {
+        let mut alive = vec![];
+        for cell in cells {
+            let pair = cell
+                .into_typed_array::<INT>()
+                .ok()
+                .filter(|pair| pair.len() == 2)
+                .ok_or("cells must be [row, column] arrays")?;
+            if let (Ok(row), Ok(column)) = (usize::try_from(pair[0]), usize::try_from(pair[1])) {
+                let cell: Cell = (row, column);
+                if game.current().contains(cell) {
+                    alive.push(cell);
+                }
+            }
+        }
+        game.current_mut().set_alive(&alive).map_err(|error| error.to_string())
+    }
+
+    fn invalid(name: &str, error: impl std::fmt::Display) -> Error {
+        Error::new(ErrorKind::InvalidInput, format!("{}: {}", name, error))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_rule_and_seed() {
+            let script = Script::compile("test", "
+                fn rule(alive, neighbors) { neighbors == 3 || alive && neighbors == 2 || !alive && neighbors == 6 }
+                fn seed(rows, columns) { pattern(\"Blinker\", rows / 2, 0) }
+            ").unwrap();
+            let mut game = GameOfLife::builder().size(3, 3).density(1.0).build().unwrap();
+            script.prepare(&mut game).unwrap();
+            assert_eq!(game.rule(), "B36/S23".parse().unwrap());
+            assert_eq!(game.current().to_string(), "...\nOOO\n...");
+            assert!(script.hook().is_none());
+        }
+
+        #[test]
+        fn test_line() {
+            let script = Script::compile("test", "fn seed(rows, columns) { line(0, 0, rows - 1, columns - 1) }").unwrap();
+            let mut game = GameOfLife::new(3, 3);
+            script.prepare(&mut game).unwrap();
+            assert_eq!(game.current().to_string(), "O..\n.O.\n..O");
+            let script = Script::compile("test", "fn seed(rows, columns) { line(-1, 0, 2, 2) }").unwrap();
+            assert!(script.prepare(&mut game).is_err());
+        }
+
+        #[test]
+        fn test_soup() {
+            let script = Script::compile("test", "fn seed(rows, columns) { soup(1, 1, 1, 5, 1.0) + soup(0, 0, 2, 5, 0.0) }").unwrap();
+            let mut game = GameOfLife::new(3, 4);
+            script.prepare(&mut game).unwrap();
+            assert_eq!(game.current().to_string(), "....\n.OOO\n....");
+        }
+
+        #[test]
+        fn test_tick_hook() {
+            let script = Script::compile("test", "
+                fn tick(generation, rows, columns) { if generation % 2 == 0 { [[0, 0], [-1, 0], [0, 99]] } else { [] } }
+            ").unwrap();
+            let mut hook = script.hook().unwrap();
+            let mut game = GameOfLife::new(3, 3);
+            hook(&mut game);
+            assert_eq!(game.current().population(), 0);
+            hook(&mut game);
+            assert_eq!(game.current().to_string(), "O..\n...\n...");
+        }
+
+        #[test]
+        fn test_errors() {
+            assert!(Script::compile("test", "fn seed(rows, columns) {").is_err());
+            let script = Script::compile("test", "fn seed(rows, columns) { pattern(\"nothing\", 0, 0) }").unwrap();
+            assert!(script.prepare(&mut GameOfLife::new(3, 3)).is_err());
+            let script = Script::compile("test", "fn seed(rows, columns) { [1, 2] }").unwrap();
+            assert!(script.prepare(&mut GameOfLife::new(3, 3)).is_err());
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::{Result, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use game_of_life::simulation::Hook;
+use game_of_life::{protocol, GameOfLife, Simulation, Update};
+use crate::http::{Request, Response};
+use crate::websocket;
+
+/// How long a new connection has to send an HTTP request before it is treated as a plain TCP
+/// client.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(500);
+/// Clients that can't take a message within this long are disconnected rather than allowed to
+/// hold up everyone else.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The state of a headless game, owned by the thread that streams it.
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub struct Headless {
+    pub simulation: Simulation,
+    /// A replica of the game running on the simulation thread.
+    pub game: GameOfLife,
+    pub generation: u64,
+    pub running: bool,
+    pub interval: Duration,
+    pub clients: usize,
+    /// How long encoding and sending the most recent update to the clients took, the headless
+    /// counterpart of drawing a frame.
+    pub render_duration: Duration,
+}
+
+/// Runs the game headless, streaming it to every client that connects to `address`: a snapshot
+/// on connecting and after edits, then one message per generation (see `protocol`). Clients
+/// either read newline separated JSON over plain TCP or connect with a WebSocket. With the
+/// `server` feature, other HTTP requests go to the control API in `api`. `hook` runs after every
+/// tick; see `Simulation::set_hook`.
+pub fn run(game: GameOfLife, interval: Duration, address: &str, hook: Option<Hook>) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("serving on {}", listener.local_addr()?);
+
+    let (connections, new_connections) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let connections = connections.clone();
+            thread::spawn(move || connect(stream, connections));
+        }
+    });
+
+    let mut headless = Headless::new(game, interval);
+    if let Some(hook) = hook {
+        headless.simulation.set_hook(hook);
+    }
+    let mut clients: Vec<Client> = vec![];
+    loop {
+        for connection in new_connections.try_iter() {
+            match connection {
+                Connection::Client(mut client) => {
+                    if client.send(&protocol::snapshot(&headless.game, headless.generation)).is_ok() {
+                        clients.push(client);
+                        headless.clients = clients.len();
+                    }
+                }
+                #[cfg(feature = "server")]
+                Connection::Request(request, reply) => {
+                    let _ = reply.send(crate::api::handle(&request, &mut headless));
+                }
+            }
+        }
+
+        let update = headless.simulation.recv_timeout(Duration::from_millis(10));
+        let start = Instant::now();
+        let message = match update {
+            Some(Update::Generation(changes)) => {
+                headless.game.apply_changes(&changes);
+                headless.generation += 1;
+                protocol::generation(&changes, headless.generation)
+            }
+            Some(update) => {
+                headless.generation += update.generations();
+                update.apply(&mut headless.game);
+                protocol::snapshot(&headless.game, headless.generation)
+            }
+            None => continue,
+        };
+        clients.retain_mut(|client| client.send(&message).is_ok());
+        headless.clients = clients.len();
+        headless.render_duration = start.elapsed();
+    }
+}
+
+impl Headless {
+    pub fn new(game: GameOfLife, interval: Duration) -> Headless {
+        let simulation = Simulation::spawn(game.clone(), interval);
+        simulation.set_running(true);
+        Headless {
+            simulation,
+            game,
+            generation: 0,
+            running: true,
+            interval,
+            clients: 0,
+            render_duration: Duration::ZERO,
+        }
+    }
+}
+
+enum Connection {
+    Client(Client),
+    /// A control API request and where to send its response.
+    #[cfg(feature = "server")]
+    Request(Request, Sender<Response>),
+}
+
+enum Client {
+    Lines(TcpStream),
+    WebSocket(TcpStream),
+}
+
+/// Sorts a new connection out: plain TCP clients send nothing, WebSocket clients send an upgrade
+/// request and anything else is a request for the control API.
+fn connect(mut stream: TcpStream, connections: Sender<Connection>) -> Result<()> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    if stream.peek(&mut [0; 1]).is_err() {
+        let _ = connections.send(Connection::Client(Client::Lines(stream)));
+        return Ok(());
+    }
+
+    let request = Request::read(&stream)?;
+    if request.header("upgrade").is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket")) {
+        websocket::accept(&mut stream, &request)?;
+        let _ = connections.send(Connection::Client(Client::WebSocket(stream)));
+        Ok(())
+    } else {
+        respond(stream, request, connections)
+    }
+}
+
+#[cfg(feature = "server")]
+fn respond(mut stream: TcpStream, request: Request, connections: Sender<Connection>) -> Result<()> {
+    let (reply, response) = mpsc::channel();
+    let _ = connections.send(Connection::Request(request, reply));
+    match response.recv() {
+        Ok(response) => response.write_to(&mut stream),
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "server"))]
+fn respond(mut stream: TcpStream, _: Request, _: Sender<Connection>) -> Result<()> {
+    Response::error(404, "the control API needs the server feature").write_to(&mut stream)
+}
+
+impl Client {
+    fn send(&mut self, message: &str) -> Result<()> {
+        match self {
+            Client::Lines(stream) => writeln!(stream, "{}", message),
+            Client::WebSocket(stream) => websocket::write_text(stream, message),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use crate::color::Rgb;
+
+/// The most colors an image is given; terminals that draw sixels keep at least this many.
+const REGISTERS: usize = 256;
+
+/// The sixel image of `pixels`, rows of RGB triples `width` wide, with each one blown up to a
+/// square `scale` pixels on a side, drawn from the cursor. Sixels are six rows of pixels at a
+/// time, a character for each column saying which of them are in the current color, so the
+/// image goes out a band of six rows at a time, a pass over the band for each of its colors.
+/// Should there be more colors than registers, they are rounded to the 216 color cube.
+pub fn image(pixels: &[u8], width: usize, scale: usize) -> String {
+    let width = width.max(1);
+    let mut colors: Vec<Rgb> = pixels.chunks_exact(3).map(|pixel| (pixel[0], pixel[1], pixel[2])).collect();
+    let height = colors.len() / width;
+    let mut palette = registers(&colors);
+    if palette.len() > REGISTERS {
+        let level = |value: u8| ((value as u16 * 5 + 127) / 255 * 51) as u8;
+        colors.iter_mut().for_each(|(r, g, b)| (*r, *g, *b) = (level(*r), level(*g), level(*b)));
+        palette = registers(&colors);
+    }
+    let register: Vec<usize> = colors.iter().map(|color| palette[color]).collect();
+
+    let (pixel_width, pixel_height) = (width * scale, height * scale);
+    let mut sixels = format!("\x1bP0;1;0q\"1;1;{};{}", pixel_width, pixel_height);
+    let mut defined: Vec<(&Rgb, &usize)> = palette.iter().collect();
+    defined.sort_unstable_by_key(|(_, index)| **index);
+    let percent = |value: u8| (value as u32 * 100 + 127) / 255;
+    for ((r, g, b), index) in defined {
+        sixels.push_str(&format!("#{};2;{};{};{}", index, percent(*r), percent(*g), percent(*b)));
+    }
+
+    for top in (0..pixel_height).step_by(6) {
+        // The row of cells each of the band's six rows of pixels falls in.
+        let rows: Vec<usize> = (top..(top + 6).min(pixel_height)).map(|y| y / scale).collect();
+        let mut used: Vec<usize> = rows
+            .iter()
+            .flat_map(|row| register[row * width..(row + 1) * width].iter().copied())
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+        for (pass, color) in used.into_iter().enumerate() {
+            if pass > 0 {
+                sixels.push('$');
+            }
+            sixels.push_str(&format!("#{}", color));
+            let mut line = Line::default();
+            for column in 0..width {
+                let bits = rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, row)| register[*row * width + column] == color)
+                    .fold(0, |bits, (bit, _)| bits | 1 << bit);
+                line.push((63 + bits) as u8 as char, scale);
+            }
+            line.finish(&mut sixels);
+        }
+        sixels.push('-');
+    }
+    sixels.push_str("\x1b\\");
+    sixels
+}
+
+/// A register for each distinct color, numbered in the order they first appear.
+fn registers(colors: &[Rgb]) -> HashMap<Rgb, usize> {
+    let mut palette = HashMap::new();
+    for color in colors {
+        let next = palette.len();
+        palette.entry(*color).or_insert(next);
+    }
+    palette
+}
+
+/// The sixels of one pass over a band, run length encoded as they come.
+#[derive(Default)]
+struct Line {
+    text: String,
+    run: Option<(char, usize)>,
+}
+
+impl Line {
+    fn push(&mut self, sixel: char, count: usize) {
+        match &mut self.run {
+            Some((current, length)) if *current == sixel => *length += count,
+            _ => {
+                self.flush();
+                self.run = Some((sixel, count));
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        match self.run.take() {
+            Some((sixel, length)) if length > 3 => self.text.push_str(&format!("!{}{}", length, sixel)),
+            Some((sixel, length)) => self.text.extend(std::iter::repeat_n(sixel, length)),
+            None => {}
+        }
+    }
+
+    /// Adds the pass to `sixels`, leaving off a last run of empty sixels, which draw nothing.
+    fn finish(mut self, sixels: &mut String) {
+        if self.run.is_some_and(|(sixel, _)| sixel != '?') {
+            self.flush();
+        }
+        sixels.push_str(&self.text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image() {
+        // A white cell beside a black one, two pixels a side: a band holding both rows of pixels,
+        // black where the white is not.
+        let sixels = image(&[255, 255, 255, 0, 0, 0], 2, 2);
+        assert_eq!(sixels, "\x1bP0;1;0q\"1;1;4;2#0;2;100;100;100#1;2;0;0;0#0BB$#1??BB-\x1b\\");
+    }
+
+    #[test]
+    fn test_runs() {
+        // Eight pixels of one color across, all six rows of the first band and two of the next.
+        let sixels = image(&[9; 3 * 4 * 4], 4, 2);
+        assert!(sixels.contains("#0!8~-#0!8B-"), "{:?}", sixels);
+    }
+
+    #[test]
+    fn test_too_many_colors() {
+        let pixels: Vec<u8> = (0..300u32).flat_map(|index| [index as u8, (index >> 8) as u8, 0]).collect();
+        let sixels = image(&pixels, 300, 1);
+        assert!(sixels.matches(";2;").count() <= REGISTERS);
+    }
+}
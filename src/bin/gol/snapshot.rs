@@ -0,0 +1,128 @@
+use std::io::{stdout, Result, Write};
+use crossterm::{
+    queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+};
+use game_of_life::simulation::Hook;
+use game_of_life::{BoundingBox, CellState, GameOfLife};
+use crate::config::{Config, Glyphs};
+use crate::render::Glyph;
+
+/// How `gol render` prints the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RenderFormat {
+    /// Plain ASCII characters and no colors, for files and READMEs: the glyphs' own symbols where
+    /// they are ASCII, and otherwise, or where they are blank, `#` for living and immortal cells,
+    /// `%` for walls and `.` for dead ones.
+    Ascii,
+    /// The configured glyphs in their colors, through ANSI escape sequences, for terminals.
+    Ansi,
+}
+
+impl RenderFormat {
+    pub fn parse(name: &str) -> Option<RenderFormat> {
+        match name {
+            "ascii" => Some(RenderFormat::Ascii),
+            "ansi" => Some(RenderFormat::Ansi),
+            _ => None,
+        }
+    }
+}
+
+/// Advances the game `config.generations` times and prints the board once to stdout, as
+/// `config.format` says, without taking the terminal over. `hook` runs after every tick, just
+/// as it would on the simulation thread.
+pub fn run(mut game: GameOfLife, config: &Config, mut hook: Option<Hook>) -> Result<()> {
+    for _ in 0..config.generations {
+        game.tick();
+        if let Some(hook) = &mut hook {
+            hook(&mut game);
+        }
+    }
+    stdout().write_all(&board(&game, config)?)
+}
+
+/// The board, a line per row: all of it when `--size` gave the grid's size, and otherwise only the
+/// smallest rectangle holding what isn't dead, since the grid was sized only to keep the edges out
+/// of the pattern's way.
+fn board(game: &GameOfLife, config: &Config) -> Result<Vec<u8>> {
+    let (rows, columns) = game.shape();
+    let region = match config.size {
+        Some(_) => Some(BoundingBox { top: 0, left: 0, bottom: rows - 1, right: columns - 1 }),
+        None => game.bounding_box(),
+    };
+    let glyphs = match config.format {
+        RenderFormat::Ascii => {
+            let glyphs = config.glyphs.ascii();
+            let plain = |glyph: Glyph, blank| Glyph::new(if glyph.symbol == ' ' { blank } else { glyph.symbol }, Color::Reset);
+            Glyphs {
+                alive: plain(glyphs.alive, '#'),
+                dead: plain(glyphs.dead, '.'),
+                wall: plain(glyphs.wall, '%'),
+                immortal: plain(glyphs.immortal, '#'),
+            }
+        }
+        RenderFormat::Ansi => config.glyphs,
+    };
+    let mut board = vec![];
+    let Some(region) = region else { return Ok(board) };
+    for row in region.top..=region.bottom {
+        for column in region.left..=region.right {
+            let glyph = match game.current().get_state((row, column)) {
+                CellState::Alive => glyphs.alive,
+                CellState::Dead => glyphs.dead,
+                CellState::Wall => glyphs.wall,
+                CellState::Immortal => glyphs.immortal,
+            };
+            match config.format {
+                RenderFormat::Ascii => queue!(board, Print(glyph.symbol))?,
+                RenderFormat::Ansi => queue!(
+                    board,
+                    SetForegroundColor(config.color_depth.fit(glyph.foreground)),
+                    SetBackgroundColor(config.color_depth.fit(glyph.background)),
+                    Print(glyph.symbol)
+                )?,
+            }
+        }
+        if config.format == RenderFormat::Ansi {
+            queue!(board, ResetColor)?;
+        }
+        queue!(board, Print('\n'))?;
+    }
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::Pattern;
+
+    fn load(args: &[&str]) -> Config {
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.extend(["--config".to_string(), "/dev/null".to_string()]);
+        Config::load(args.into_iter()).unwrap()
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut game = GameOfLife::new(10, 10);
+        Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").stamp(game.current_mut(), (3, 4));
+        let text = |game: &GameOfLife, config: &Config| String::from_utf8(board(game, config).unwrap()).unwrap();
+        assert_eq!(text(&game, &load(&["--format", "ascii", "--charset", "unicode"])), ".#.\n..#\n###\n");
+
+        let whole = text(&game, &load(&["--format", "ascii", "--size", "10x10", "--dead-char", "-"]));
+        assert_eq!(whole.lines().count(), 10);
+        assert_eq!(whole.lines().nth(4), Some("------#---"));
+        // Nothing alive and no size given leaves nothing to print.
+        assert_eq!(text(&GameOfLife::new(4, 4), &load(&["--format", "ascii"])), "");
+    }
+
+    #[test]
+    fn test_ansi() {
+        let mut game = GameOfLife::new(4, 4);
+        game.current_mut().set_state((1, 1), CellState::Alive);
+        let text = String::from_utf8(board(&game, &load(&["--format", "ansi", "--charset", "unicode"])).unwrap()).unwrap();
+        assert!(text.starts_with("\x1b["), "{:?}", text);
+        assert!(text.ends_with("\x1b[0m\n"), "{:?}", text);
+    }
+}
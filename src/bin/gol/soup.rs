@@ -0,0 +1,121 @@
+use std::cmp::Reverse;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use game_of_life::stats::lifespan;
+use game_of_life::{Census, GameOfLife};
+use crate::config::Config;
+
+/// What became of one soup.
+struct Outcome {
+    seed: u64,
+    /// Generations until it settled, or the limit if it never did.
+    generations: u64,
+    settled: bool,
+    population: usize,
+    census: Census,
+}
+
+/// The running totals of a search, one per worker thread until they are merged.
+#[derive(Default)]
+struct Summary {
+    soups: u64,
+    unsettled: u64,
+    census: Census,
+    longest_lived: Option<(u64, u64)>,
+    largest: Option<(u64, usize)>,
+}
+
+/// Runs `count` random soups of `size` on every core, each until it settles or `limit`
+/// generations pass, and prints a report: the census totals and the soups worth a closer look.
+/// Soups are seeded one after another from `seed`, so any of them can be replayed with
+/// `gol --seed`.
+pub fn run(config: &Config, size: (usize, usize)) -> Result<()> {
+    let (rows, columns) = size;
+    let first_seed = config.seed.unwrap_or_else(rand::random);
+    let build = |seed: u64| {
+        GameOfLife::builder()
+            .size(rows, columns)
+            .rule(&config.rule)
+            .density(config.density())
+            .symmetry(config.symmetry)
+            .seed(seed)
+            .build()
+    };
+    build(first_seed).map_err(|error| Error::new(ErrorKind::InvalidInput, error.to_string()))?;
+
+    let next = AtomicU64::new(0);
+    let workers = thread::available_parallelism().map_or(1, |workers| workers.get());
+    let summary = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| scope.spawn(|| {
+                let mut summary = Summary::default();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= config.count {
+                        return summary;
+                    }
+                    let seed = first_seed.wrapping_add(index);
+                    if let Ok(game) = build(seed) {
+                        summary.add(settle(game, seed, config.limit));
+                    }
+                }
+            }))
+            .collect();
+        handles.into_iter().fold(Summary::default(), |mut total, handle| {
+            total.merge(handle.join().unwrap_or_default());
+            total
+        })
+    });
+
+    println!(
+        "{} {} soups of {}x{} {} at density {}, seeds {}..",
+        summary.soups, config.symmetry, rows, columns, config.rule, config.density(), first_seed
+    );
+    println!("{} settled, {} still unsettled after {} generations", summary.soups - summary.unsettled, summary.unsettled, config.limit);
+    println!("census: {}", summary.census);
+    if let Some((seed, generations)) = summary.longest_lived {
+        println!("longest lived: seed {} settled after {} generations", seed, generations);
+    }
+    if let Some((seed, population)) = summary.largest {
+        println!("largest final population: seed {} with {} cells", seed, population);
+    }
+    Ok(())
+}
+
+fn settle(mut game: GameOfLife, seed: u64, limit: u64) -> Outcome {
+    let lifespan = lifespan(&mut game, limit);
+    Outcome {
+        seed,
+        generations: lifespan.generations,
+        settled: lifespan.settled,
+        population: lifespan.final_population,
+        census: game.current().census(),
+    }
+}
+
+impl Summary {
+    fn add(&mut self, outcome: Outcome) {
+        self.soups += 1;
+        if !outcome.settled {
+            self.unsettled += 1;
+        }
+        self.census.merge(&outcome.census);
+        if outcome.settled && self.longest_lived.is_none_or(|(_, generations)| outcome.generations > generations) {
+            self.longest_lived = Some((outcome.seed, outcome.generations));
+        }
+        if self.largest.is_none_or(|(_, population)| outcome.population > population) {
+            self.largest = Some((outcome.seed, outcome.population));
+        }
+    }
+
+    fn merge(&mut self, other: Summary) {
+        self.soups += other.soups;
+        self.unsettled += other.unsettled;
+        self.census.merge(&other.census);
+        let longest_lived = self.longest_lived.into_iter().chain(other.longest_lived);
+        self.longest_lived = longest_lived.max_by_key(|(seed, generations)| (*generations, Reverse(*seed)));
+        let largest = self.largest.into_iter().chain(other.largest);
+        self.largest = largest.max_by_key(|(seed, population)| (*population, Reverse(*seed)));
+    }
+}
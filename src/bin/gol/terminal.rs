@@ -0,0 +1,189 @@
+use std::io::{stdout, Write};
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use crossterm::{
+    cursor::{Hide, Show},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    Result,
+};
+use crate::color::{rgb, Background, Rgb};
+
+/// Set when `gol` is asked to stop by a signal, such as SIGINT from `kill -INT` or SIGTERM.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Keeps the terminal in raw mode on the alternate screen, with the cursor hidden, for as long
+/// as it lives, and puts it back however `gol` leaves: when dropped, which covers errors, and from
+/// a panic hook, which runs before the panic message is printed so it lands on the normal screen
+/// rather than being wiped with the alternate one. Dropped, it puts back the panic hook it found,
+/// so a panic afterwards, on any thread, leaves the terminal alone.
+pub struct Terminal {
+    report: Arc<dyn Fn(&PanicHookInfo<'_>) + Sync + Send>,
+}
+
+impl Terminal {
+    pub fn enter() -> Result<Terminal> {
+        let report: Arc<dyn Fn(&PanicHookInfo<'_>) + Sync + Send> = Arc::from(panic::take_hook());
+        let chained = Arc::clone(&report);
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            chained(info);
+        }));
+        // Made first, so the terminal and the hook are restored if entering it fails halfway.
+        let terminal = Terminal { report };
+        // Left to the default, a signal would end `gol` without restoring the terminal.
+        ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst))
+            .map_err(|error| std::io::Error::other(error.to_string()))?;
+        execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All), Hide)?;
+        enable_raw_mode()?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        restore();
+        // The hook can't be changed while panicking, and then it has already run anyway.
+        if !std::thread::panicking() {
+            let report = Arc::clone(&self.report);
+            panic::set_hook(Box::new(move |info| report(info)));
+        }
+    }
+}
+
+/// Whether a signal has asked `gol` to stop since the terminal was entered; the terminal loops
+/// check between frames and quit as if `q` were pressed.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// The size of a character on the terminal in pixels, rows then columns, if it says: worked out
+/// from the size of its window, which not every terminal reports.
+#[cfg(unix)]
+pub fn character_size() -> Option<(usize, usize)> {
+    let mut size = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+    // SAFETY: TIOCGWINSZ only writes a `winsize` through the pointer, which is valid for that.
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 {
+        return None;
+    }
+    let (rows, columns) = (size.ws_row as usize, size.ws_col as usize);
+    let (height, width) = (size.ws_ypixel as usize, size.ws_xpixel as usize);
+    match rows > 0 && columns > 0 && height >= rows && width >= columns {
+        true => Some((height / rows, width / columns)),
+        false => None,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn character_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// Whether the terminal's background is dark or light: asked of the terminal itself, failing that
+/// read from `COLORFGBG`, which some terminals set, and failing that taken to be dark.
+pub fn background() -> Background {
+    query_background()
+        .or_else(|| std::env::var("COLORFGBG").ok().as_deref().and_then(parse_colorfgbg))
+        .map(Background::of)
+        .unwrap_or(Background::Dark)
+}
+
+/// How long to wait for the terminal to answer a query before giving up on it.
+#[cfg(unix)]
+const ANSWER: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Asks the terminal for its background color with OSC 11. Terminals that don't know the query
+/// ignore it, so it is followed by a request for the device attributes, which every terminal
+/// answers: once that answer is in, the color has either come before it or isn't coming, and
+/// nothing is left over to be read as key presses.
+#[cfg(unix)]
+fn query_background() -> Option<Rgb> {
+    // SAFETY: isatty only looks at the descriptors.
+    if unsafe { libc::isatty(libc::STDIN_FILENO) == 0 || libc::isatty(libc::STDOUT_FILENO) == 0 } {
+        return None;
+    }
+    enable_raw_mode().ok()?;
+    let answer = ask("\x1b]11;?\x1b\\\x1b[c");
+    let _ = disable_raw_mode();
+    parse_osc11(&answer?)
+}
+
+#[cfg(not(unix))]
+fn query_background() -> Option<Rgb> {
+    None
+}
+
+/// Writes `query` and reads what comes back up to the end of the device attributes.
+#[cfg(unix)]
+fn ask(query: &str) -> Option<String> {
+    let mut stdout = stdout();
+    stdout.write_all(query.as_bytes()).ok()?;
+    stdout.flush().ok()?;
+    let deadline = std::time::Instant::now() + ANSWER;
+    let mut answer = vec![];
+    // The device attributes end `ESC [ ? ... c`.
+    while !(answer.ends_with(b"c") && answer.windows(3).any(|window| window == b"\x1b[?")) {
+        let left = deadline.saturating_duration_since(std::time::Instant::now()).as_millis() as libc::c_int;
+        let mut poll = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        // SAFETY: poll is handed one valid pollfd.
+        if left == 0 || unsafe { libc::poll(&mut poll, 1, left) } <= 0 {
+            return None;
+        }
+        let mut buffer = [0u8; 64];
+        // SAFETY: read writes at most the buffer's length into it.
+        let read = unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+        if read <= 0 {
+            return None;
+        }
+        answer.extend_from_slice(&buffer[..read as usize]);
+    }
+    Some(String::from_utf8_lossy(&answer).into_owned())
+}
+
+/// The color in an answer to OSC 11, `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` with one to four hex digits
+/// a component.
+fn parse_osc11(answer: &str) -> Option<Rgb> {
+    let (_, color) = answer.split_once("\x1b]11;rgb:")?;
+    let color = color.split(['\x07', '\x1b']).next()?;
+    let mut components = color.split('/').map(|component| {
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1u32 << (4 * component.len().clamp(1, 4))) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    Some((components.next()??, components.next()??, components.next()??))
+}
+
+/// The background in `COLORFGBG`, `foreground;background` as palette numbers, with a field in
+/// the middle in some terminals.
+fn parse_colorfgbg(value: &str) -> Option<Rgb> {
+    let background: u8 = value.rsplit(';').next()?.parse().ok()?;
+    rgb(crossterm::style::Color::AnsiValue(background))
+}
+
+/// Undoes `Terminal::enter`, ignoring failures: there is nothing left to do about them.
+fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), LeaveAlternateScreen, Show);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc11() {
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/ffff/ffff\x1b\\\x1b[?62;c"), Some((255, 255, 255)));
+        assert_eq!(parse_osc11("\x1b]11;rgb:0000/2b2b/3636\x07"), Some((0, 43, 54)));
+        assert_eq!(parse_osc11("\x1b]11;rgb:f/80/000\x07"), Some((255, 128, 0)));
+        // Terminals that don't know the query answer only the device attributes.
+        assert_eq!(parse_osc11("\x1b[?1;2c"), None);
+    }
+
+    #[test]
+    fn test_parse_colorfgbg() {
+        assert_eq!(parse_colorfgbg("15;0").map(Background::of), Some(Background::Dark));
+        assert_eq!(parse_colorfgbg("0;default;15").map(Background::of), Some(Background::Light));
+        assert_eq!(parse_colorfgbg("default"), None);
+    }
+}
@@ -0,0 +1,111 @@
+use crossterm::style::Color;
+use crate::color::Gradient;
+
+/// The colors the grid view marks things with, apart from the glyphs' own. The presets besides
+/// the default keep apart what they must for the color blind: blue and orange in place of
+/// green and red for deuteranopia, blue and yellow for protanopia, which darkens reds, and cyan
+/// and red for tritanopia, which confuses blue with green and yellow with violet. The high
+/// contrast one sticks to the brightest colors of the terminal's palette.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Theme {
+    /// The edit cursor, and the corner a frozen area is being marked from.
+    pub cursor: Color,
+    /// The pattern being placed.
+    pub ghost: Color,
+    /// The background of frozen cells.
+    pub frozen: Color,
+    /// The names of objects.
+    pub label: Color,
+    /// Neighbor counts of cells alive next generation, of cells dying, and of dead cells staying so.
+    pub lives: Color,
+    pub dies: Color,
+    pub stays_dead: Color,
+    /// The heatmap's colors, most visited first, and those of living cells by age with
+    /// `--gradient theme`.
+    pub gradient: Gradient,
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Theme> {
+        let rgb = |r, g, b| Color::Rgb { r, g, b };
+        let gradient = |name| Gradient::preset(name).unwrap();
+        let theme = match name {
+            "default" => Theme::default(),
+            "deuteranopia" => Theme {
+                cursor: rgb(240, 228, 66),
+                ghost: rgb(0, 114, 178),
+                frozen: rgb(0, 45, 90),
+                label: rgb(86, 180, 233),
+                lives: rgb(86, 180, 233),
+                dies: rgb(230, 159, 0),
+                stays_dead: rgb(128, 128, 128),
+                gradient: gradient("cividis"),
+            },
+            "protanopia" => Theme {
+                cursor: rgb(255, 255, 255),
+                ghost: rgb(0, 114, 178),
+                frozen: rgb(0, 45, 90),
+                label: rgb(86, 180, 233),
+                lives: rgb(86, 180, 233),
+                dies: rgb(240, 228, 66),
+                stays_dead: rgb(128, 128, 128),
+                gradient: gradient("cividis"),
+            },
+            "tritanopia" => Theme {
+                cursor: rgb(255, 255, 255),
+                ghost: rgb(0, 140, 140),
+                frozen: rgb(90, 20, 40),
+                label: rgb(255, 150, 170),
+                lives: rgb(0, 210, 210),
+                dies: rgb(230, 40, 40),
+                stays_dead: rgb(128, 128, 128),
+                gradient: gradient("rose"),
+            },
+            "high-contrast" => Theme {
+                cursor: Color::Yellow,
+                ghost: Color::Cyan,
+                frozen: Color::Blue,
+                label: Color::White,
+                lives: Color::White,
+                dies: Color::Red,
+                stays_dead: Color::Grey,
+                gradient: gradient("grey"),
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            cursor: Color::Yellow,
+            ghost: Color::DarkGreen,
+            frozen: Color::DarkBlue,
+            label: Color::Cyan,
+            lives: Color::Green,
+            dies: Color::Red,
+            stays_dead: Color::DarkGrey,
+            gradient: Gradient::preset("fire").unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(Theme::preset("default"), Some(Theme::default()));
+        for name in ["deuteranopia", "protanopia", "tritanopia", "high-contrast"] {
+            let theme = Theme::preset(name).unwrap();
+            // What the neighbor counts tell apart has to stay apart.
+            assert_ne!(theme.lives, theme.dies, "{}", name);
+            assert_ne!(theme.dies, theme.stays_dead, "{}", name);
+            assert_ne!(theme.gradient, Theme::default().gradient, "{}", name);
+        }
+        assert_eq!(Theme::preset("sepia"), None);
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::Write;
+use std::time::Instant;
+use crossterm::{style::Color, Result};
+use game_of_life::{pattern, CellState, GameMatrix, GameOfLife, Pattern};
+use crate::config::Config;
+use crate::render::{Glyph, TerminalRenderer};
+use crate::{Command, Mode};
+
+const TEXT: Color = Color::Cyan;
+
+/// One step of the tutorial: a few sentences and the pattern they are about.
+struct Stage {
+    title: &'static str,
+    text: &'static str,
+    /// Plaintext, or the name of a built-in pattern when it starts with `=`.
+    pattern: &'static str,
+}
+
+const STAGES: &[Stage] = &[
+    Stage {
+        title: "The rules",
+        text: "Every cell is alive or dead and has eight neighbors. A dead cell with exactly three \
+               living neighbors comes to life; a living cell with two or three stays alive; every \
+               other cell dies or stays dead. All cells change at once, one generation at a time. \
+               Step forward and watch the corner of this L fill in.",
+        pattern: "OO\nO.",
+    },
+    Stage {
+        title: "Still lifes",
+        text: "Some patterns never change: every living cell has two or three neighbors and no dead \
+               cell has exactly three. These are the block, the beehive, the loaf and the boat, the \
+               most common still lifes.",
+        pattern: "OO.....OO.....OO.....OO.\n\
+                  OO....O..O...O..O....O.O\n\
+                  .......OO.....O.O.....O.\n\
+                  ...............O........",
+    },
+    Stage {
+        title: "Oscillators",
+        text: "Others cycle through a few shapes and come back to where they started. The blinker, \
+               the toad and the beacon all repeat every two generations. Let them run for a while.",
+        pattern: "........OOO...OO..\n\
+                  OOO....OOO....OO..\n\
+                  ................OO\n\
+                  ................OO",
+    },
+    Stage {
+        title: "Gliders",
+        text: "The glider comes back to its own shape every four generations, one cell further down \
+               and to the right, so it travels across the grid forever. Patterns that move like this \
+               are called spaceships.",
+        pattern: ".O.\n..O\nOOO",
+    },
+    Stage {
+        title: "Spaceships",
+        text: "The lightweight spaceship is the smallest spaceship that flies in a straight line, two \
+               cells every four generations.",
+        pattern: ".O..O\nO....\nO...O\nOOOO.",
+    },
+    Stage {
+        title: "Methuselahs",
+        text: "Small patterns can take a long time to settle. The R-pentomino is just five cells, but \
+               runs for over a thousand generations, throwing off gliders, before it settles down.",
+        pattern: "=R-pentomino",
+    },
+    Stage {
+        title: "Guns",
+        text: "A gun is an oscillator that fires spaceships. Bill Gosper's glider gun, found in 1970, \
+               fires a glider every thirty generations and showed that a pattern can grow forever.",
+        pattern: "=Gosper glider gun",
+    },
+];
+
+/// Walks people new to Life through a sequence of stages, each explained on screen above its
+/// pattern. Every stage starts paused, so the pattern can be stepped through or run at its own
+/// pace before moving on.
+pub fn run<W>(config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = renderer.shape();
+    let interval = config.tick_interval();
+    let mut index = 0;
+    let (mut game, mut text) = start(&STAGES[index], rows, columns);
+    let mut paused = true;
+    let mut generation = 0;
+    let mut next_tick = Instant::now();
+
+    loop {
+        if let Some(command) = config.keys.next_command(&Mode::Tutorial, config.frame_time())? {
+            let mut restart = false;
+            match command {
+                Command::Quit => return Ok(()),
+                Command::Paused => paused = !paused,
+                Command::Step => {
+                    game.tick();
+                    generation += 1;
+                }
+                Command::Reseed => restart = true,
+                Command::NextStage if index + 1 < STAGES.len() => {
+                    index += 1;
+                    restart = true;
+                }
+                Command::PreviousStage if index > 0 => {
+                    index -= 1;
+                    restart = true;
+                }
+                _ => {}
+            }
+            if restart {
+                (game, text) = start(&STAGES[index], rows, columns);
+                generation = 0;
+                paused = true;
+            }
+        }
+
+        if !paused && Instant::now() >= next_tick {
+            game.tick();
+            generation += 1;
+            next_tick = Instant::now() + interval;
+        }
+        draw(&game, &text, index, generation, paused, config, renderer)?;
+    }
+}
+
+/// A fresh game for the stage, with its pattern centered in the space left below its text, and
+/// the text wrapped to the width of the screen.
+fn start(stage: &Stage, rows: usize, columns: usize) -> (GameOfLife, Vec<String>) {
+    let mut text = vec![stage.title.to_uppercase()];
+    text.extend(wrap(stage.text, columns.saturating_sub(2)));
+    let pattern = match stage.pattern.strip_prefix('=') {
+        Some(name) => pattern::builtin().into_iter().find(|pattern| pattern.name() == name),
+        None => Some(Pattern::from_plaintext(stage.title, stage.pattern)),
+    };
+
+    let mut matrix = GameMatrix::new(rows, columns);
+    if let Some(pattern) = pattern {
+        let (height, width) = pattern.shape();
+        let top = text.len() + 1;
+        let origin = (top + rows.saturating_sub(top + height) / 2, columns.saturating_sub(width) / 2);
+        pattern.stamp(&mut matrix, origin);
+    }
+    (GameOfLife::from_matrix(matrix), text)
+}
+
+/// Breaks `text` into lines of at most `width` characters between words, and words longer than
+/// that into pieces.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if !line.is_empty() && line.chars().count() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            lines.push(word.drain(..width).collect());
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn draw<W>(
+    game: &GameOfLife,
+    text: &[String],
+    index: usize,
+    generation: u64,
+    paused: bool,
+    config: &Config,
+    renderer: &mut TerminalRenderer<W>,
+) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = game.shape();
+    for row in 0..rows {
+        // The text sits one column in from the left edge, over the cells.
+        let line: Vec<char> = text.get(row).map(|line| line.chars().collect()).unwrap_or_default();
+        for column in 0..columns {
+            let glyph = match column.checked_sub(1).and_then(|index| line.get(index)) {
+                Some(symbol) => Glyph::new(*symbol, TEXT),
+                _ => match game.current().get_state((row, column)) {
+                    CellState::Alive => config.glyphs.alive,
+                    CellState::Dead => config.glyphs.dead,
+                    CellState::Wall => config.glyphs.wall,
+                    CellState::Immortal => config.glyphs.immortal,
+                },
+            };
+            renderer.put((row, column), glyph);
+        }
+    }
+
+    let keys = |action| config.keys.describe(action);
+    let status = format!(
+        " stage {}/{} | gen {} | {}: {}  {}: step  {}: restart  {}: back  {}: next  {}: quit",
+        index + 1, STAGES.len(), generation,
+        keys("tutorial_pause"), if paused { "play" } else { "pause" }, keys("tutorial_step"), keys("restart"),
+        keys("previous_stage"), keys("next_stage"), keys("quit")
+    );
+    renderer.put_status(format!("{:width$}", status, width = columns))?;
+    renderer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("a glider   moves\nforever", 9), vec!["a glider", "moves", "forever"]);
+        assert_eq!(wrap("methuselah", 4), vec!["meth", "usel", "ah"]);
+        assert!(wrap("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_stages_fit() {
+        for stage in STAGES {
+            let (game, text) = start(stage, 40, 80);
+            assert!(game.current().population() > 0, "{} has no pattern", stage.title);
+            assert!(text.len() < 10, "{} has too much to say", stage.title);
+        }
+    }
+}
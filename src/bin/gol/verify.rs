@@ -0,0 +1,22 @@
+use std::io::Result;
+use game_of_life::simulation::Hook;
+use game_of_life::GameOfLife;
+
+/// Advances the game `generations` times, printing the generation and the grid's fingerprint
+/// every `every` generations, the first included. Two runs with the same seed, rule and pattern
+/// should print the same lines on any machine, so their output can simply be diffed. `hook` runs
+/// after every tick, just as it would on the simulation thread.
+pub fn run(mut game: GameOfLife, generations: u64, every: u64, mut hook: Option<Hook>) -> Result<()> {
+    for generation in 0..=generations {
+        if generation > 0 {
+            game.tick();
+            if let Some(hook) = &mut hook {
+                hook(&mut game);
+            }
+        }
+        if generation % every == 0 {
+            println!("{} {:016x}", generation, game.current().fingerprint());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::io::Write;
+use std::time::Instant;
+use crossterm::{style::Color, Result};
+use game_of_life::{Cell, ColoredCell, Phase, Player, Rule, Versus};
+use crate::config::Config;
+use crate::render::{Glyph, TerminalRenderer};
+use crate::{Command, Mode};
+
+const ONE: Glyph = Glyph::new('█', Color::Red);
+const TWO: Glyph = Glyph::new('█', Color::Blue);
+const ONE_CURSOR: Glyph = Glyph::new('▒', Color::Red);
+const TWO_CURSOR: Glyph = Glyph::new('▒', Color::Blue);
+
+/// Plays a two player game on the whole terminal: the players take turns placing `budget` cells
+/// each with their own keys, then the board runs and the player with more living cells wins.
+pub fn run<W>(config: &Config, rule: Rule, budget: usize, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = renderer.shape();
+    let mut game = Versus::new(rows, columns, rule, budget, config.versus_length);
+    let mut cursors = [(rows / 2, columns / 3), (rows / 2, columns * 2 / 3)];
+    let interval = config.tick_interval();
+    let mut next_tick = Instant::now();
+
+    loop {
+        if let Some(command) = config.keys.next_command(&Mode::Versus, config.frame_time())? {
+            match command {
+                Command::Quit => return Ok(()),
+                Command::Aim(player, d_row, d_column) => {
+                    let (row, column) = cursors[player as usize];
+                    cursors[player as usize] = (
+                        (row as isize + d_row).rem_euclid(rows as isize) as usize,
+                        (column as isize + d_column).rem_euclid(columns as isize) as usize,
+                    );
+                }
+                Command::Claim(player) => {
+                    // Out of turn presses and taken cells are simply ignored.
+                    let _ = game.place(player, cursors[player as usize]);
+                    next_tick = Instant::now() + interval;
+                }
+                _ => {}
+            }
+        }
+
+        if game.phase() == Phase::Running && Instant::now() >= next_tick {
+            game.tick();
+            next_tick = Instant::now() + interval;
+        }
+        draw(&game, &cursors, config, renderer)?;
+    }
+}
+
+fn draw<W>(game: &Versus, cursors: &[Cell; 2], config: &Config, renderer: &mut TerminalRenderer<W>) -> Result<()>
+    where W: Write
+{
+    let (rows, columns) = game.matrix().shape();
+    for row in 0..rows {
+        for column in 0..columns {
+            let cell = (row, column);
+            let glyph = match game.matrix()[cell] {
+                ColoredCell::Alive(Player::One) => ONE,
+                ColoredCell::Alive(Player::Two) => TWO,
+                ColoredCell::Dead => config.glyphs.dead,
+            };
+            renderer.put(cell, glyph);
+        }
+    }
+    if let Phase::Placing(_) = game.phase() {
+        renderer.put(cursors[0], ONE_CURSOR);
+        renderer.put(cursors[1], TWO_CURSOR);
+    }
+
+    let (one, two) = (game.score(Player::One), game.score(Player::Two));
+    let status = match game.phase() {
+        Phase::Placing(player) => format!(
+            " {} to place | red {} left, blue {} left | red: {}  blue: {}  {}: quit",
+            name(player), game.remaining(Player::One), game.remaining(Player::Two),
+            keys(config, "one"), keys(config, "two"), config.keys.describe("quit")
+        ),
+        Phase::Running => format!(
+            " gen {}/{} | red {}  blue {} | {}: quit",
+            game.generation(), game.length(), one, two, config.keys.describe("quit")
+        ),
+        Phase::Finished => match game.winner() {
+            Some(player) => format!(" {} wins {} to {} | {}: quit", name(player), one.max(two), one.min(two), config.keys.describe("quit")),
+            None => format!(" draw at {} each | {}: quit", one, config.keys.describe("quit")),
+        },
+    };
+    renderer.put_status(format!("{:width$}", status, width = columns))?;
+    renderer.flush()
+}
+
+fn name(player: Player) -> &'static str {
+    match player {
+        Player::One => "red",
+        Player::Two => "blue",
+    }
+}
+
+/// The keys a player moves and claims cells with, e.g. `w a s d space`.
+fn keys(config: &Config, player: &str) -> String {
+    ["up", "left", "down", "right", "claim"]
+        .iter()
+        .map(|action| config.keys.describe(&format!("{}_{}", player, action)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
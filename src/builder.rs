@@ -0,0 +1,140 @@
+#[cfg(feature = "rand")]
+use rand::rngs::StdRng;
+#[cfg(feature = "rand")]
+use rand::SeedableRng;
+use crate::engine::{Boundary, GameOfLife};
+use crate::error::{Error, Result};
+use crate::rule::Rule;
+#[cfg(feature = "rand")]
+use crate::symmetry::Symmetry;
+
+/// Collects the options for a new game, e.g.
+/// `GameOfLife::builder().size(40, 80).rule("B36/S23").density(0.15).seed(42).build()`.
+pub struct GameOfLifeBuilder {
+    rows: usize,
+    columns: usize,
+    rule: String,
+    boundary: Boundary,
+    #[cfg(feature = "rand")]
+    seed: Option<u64>,
+    #[cfg(feature = "rand")]
+    density: f64,
+    #[cfg(feature = "rand")]
+    symmetry: Symmetry,
+}
+
+impl Default for GameOfLifeBuilder {
+    fn default() -> GameOfLifeBuilder {
+        GameOfLifeBuilder {
+            rows: 32,
+            columns: 32,
+            rule: Rule::life().to_string(),
+            boundary: Boundary::Wrap,
+            #[cfg(feature = "rand")]
+            seed: None,
+            #[cfg(feature = "rand")]
+            density: 0.0,
+            #[cfg(feature = "rand")]
+            symmetry: Symmetry::C1,
+        }
+    }
+}
+
+impl GameOfLifeBuilder {
+    pub fn size(mut self, rows: usize, columns: usize) -> GameOfLifeBuilder {
+        self.rows = rows;
+        self.columns = columns;
+        self
+    }
+
+    pub fn rule(mut self, rule: &str) -> GameOfLifeBuilder {
+        self.rule = rule.to_string();
+        self
+    }
+
+    pub fn boundary(mut self, boundary: Boundary) -> GameOfLifeBuilder {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Seeds the random number generator so the initial soup is reproducible.
+    #[cfg(feature = "rand")]
+    pub fn seed(mut self, seed: u64) -> GameOfLifeBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The fraction of cells that start alive; zero, the default, starts with an empty grid.
+    #[cfg(feature = "rand")]
+    pub fn density(mut self, density: f64) -> GameOfLifeBuilder {
+        self.density = density;
+        self
+    }
+
+    /// Makes the initial soup symmetric; asymmetric `C1`, the default, leaves it as it comes.
+    #[cfg(feature = "rand")]
+    pub fn symmetry(mut self, symmetry: Symmetry) -> GameOfLifeBuilder {
+        self.symmetry = symmetry;
+        self
+    }
+
+    pub fn build(self) -> Result<GameOfLife> {
+        if self.rows == 0 || self.columns == 0 {
+            return Err(Error::InvalidOption(format!("invalid size {}x{}", self.rows, self.columns)));
+        }
+        #[cfg(feature = "rand")]
+        if !(0.0..=1.0).contains(&self.density) {
+            return Err(Error::InvalidOption(format!("density {} is not between 0 and 1", self.density)));
+        }
+
+        let mut game = GameOfLife::new(self.rows, self.columns);
+        game.set_rule(self.rule.parse()?);
+        game.set_boundary(self.boundary);
+        #[cfg(feature = "rand")]
+        if self.density > 0.0 {
+            let mut rng = match self.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            game.current_mut().randomize_symmetric(self.density, self.symmetry, &mut rng);
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let game = GameOfLife::builder()
+            .size(20, 40)
+            .rule("B36/S23")
+            .boundary(Boundary::Dead)
+            .build()
+            .unwrap();
+        assert_eq!(game.shape(), (20, 40));
+        assert_eq!(game.rule(), Rule::new(&[3, 6], &[2, 3]));
+        assert_eq!(game.boundary(), Boundary::Dead);
+        assert_eq!(game.current().population(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_seeded_soup_is_reproducible() {
+        let build = |seed| GameOfLife::builder().size(20, 20).seed(seed).density(0.5).build().unwrap();
+        let population = build(42).current().population();
+        assert!(population > 100 && population < 300);
+        assert_eq!(build(42).current().get_internal(), build(42).current().get_internal());
+        assert_ne!(build(42).current().get_internal(), build(43).current().get_internal());
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(GameOfLife::builder().rule("nope").build().is_err());
+        #[cfg(feature = "rand")]
+        assert!(GameOfLife::builder().density(1.5).build().is_err());
+        assert!(GameOfLife::builder().size(0, 10).build().is_err());
+    }
+}
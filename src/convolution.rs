@@ -0,0 +1,177 @@
+use std::ops::RangeInclusive;
+use crate::engine::{Boundary, CellState, CellType, GameMatrix};
+use crate::grid::Grid;
+use crate::rule::Rule;
+
+/// A rule over the square of cells within `radius` of each cell, the cell itself left out: Life
+/// and its relatives have a radius of one, Larger than Life rules reach further. Whether a cell is
+/// born or survives is looked up by its count of living neighbors.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BoxRule {
+    radius: usize,
+    birth: Vec<bool>,
+    survival: Vec<bool>,
+}
+
+impl BoxRule {
+    /// A Larger than Life rule: a dead cell is born when the living cells within `radius` of it
+    /// number within `birth`, and a living one survives when they number within `survival`.
+    pub fn larger_than_life(radius: usize, birth: RangeInclusive<usize>, survival: RangeInclusive<usize>) -> BoxRule {
+        let neighbors = (2 * radius + 1).pow(2) - 1;
+        let table = |range: &RangeInclusive<usize>| (0..=neighbors).map(|count| range.contains(&count)).collect();
+        BoxRule { radius, birth: table(&birth), survival: table(&survival) }
+    }
+
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    fn next(&self, state: CellState, neighbors: usize) -> CellState {
+        match state {
+            CellState::Wall => CellState::Wall,
+            CellState::Immortal => CellState::Immortal,
+            CellState::Alive if self.survival.get(neighbors) == Some(&true) => CellState::Alive,
+            CellState::Dead if self.birth.get(neighbors) == Some(&true) => CellState::Alive,
+            _ => CellState::Dead,
+        }
+    }
+}
+
+/// A Life-like rule, looking only at the eight cells around each.
+impl From<Rule> for BoxRule {
+    fn from(rule: Rule) -> BoxRule {
+        BoxRule {
+            radius: 1,
+            birth: (0..=8).map(|count| rule.is_born(count)).collect(),
+            survival: (0..=8).map(|count| rule.survives(count)).collect(),
+        }
+    }
+}
+
+/// How many living cells are within `radius` of every cell, the cell itself included. The square
+/// of weights is the sum of a row of them and a column of them, so the convolution is done as a
+/// running sum along each row and then down the columns of the result, at a constant cost per
+/// cell however far the rule reaches. On a wrapped grid smaller than the square, cells are counted
+/// as often as it wraps onto them, as `GameOfLife::tick` does.
+pub fn box_sums(matrix: &GameMatrix, radius: usize, boundary: Boundary) -> Grid<u32> {
+    let (rows, columns) = matrix.shape();
+    let mut sums = Grid::from_element(rows, columns, 0);
+    if rows == 0 || columns == 0 {
+        return sums;
+    }
+    let wrap = boundary == Boundary::Wrap;
+    let mut across = Grid::from_element(rows, columns, 0);
+    let mut line = Vec::with_capacity(columns);
+    for row in 0..rows {
+        line.clear();
+        line.extend(matrix.get_internal().row(row).iter().map(|state| state.is_alive() as u32));
+        window_sums(&line, radius, wrap, across.row_mut(row));
+    }
+
+    // Down the columns a whole row at a time, so as to walk memory in order: the running totals
+    // of the window of rows gain the row coming into it and lose the one leaving.
+    let reach = radius as isize;
+    let row_at = |index: isize| match wrap {
+        true => Some(across.row(index.rem_euclid(rows as isize) as usize)),
+        false if (0..rows as isize).contains(&index) => Some(across.row(index as usize)),
+        false => None,
+    };
+    let mut totals = vec![0; columns];
+    for entering in (-reach..=reach).filter_map(row_at) {
+        totals.iter_mut().zip(entering).for_each(|(total, sum)| *total += sum);
+    }
+    for row in 0..rows as isize {
+        sums.row_mut(row as usize).copy_from_slice(&totals);
+        if let Some(entering) = row_at(row + reach + 1) {
+            totals.iter_mut().zip(entering).for_each(|(total, sum)| *total += sum);
+        }
+        if let Some(leaving) = row_at(row - reach) {
+            totals.iter_mut().zip(leaving).for_each(|(total, sum)| *total -= sum);
+        }
+    }
+    sums
+}
+
+/// Writes the sums of `line` over every window reaching `radius` either side to `sums`, sliding
+/// the window one place at a time.
+fn window_sums(line: &[u32], radius: usize, wrap: bool, sums: &mut [u32]) {
+    let (length, reach) = (line.len() as isize, radius as isize);
+    let at = |index: isize| match wrap {
+        true => line[index.rem_euclid(length) as usize],
+        false if (0..length).contains(&index) => line[index as usize],
+        false => 0,
+    };
+    let mut sum: u32 = (-reach..=reach).map(at).sum();
+    for (index, slot) in sums.iter_mut().enumerate() {
+        *slot = sum;
+        let index = index as isize;
+        sum = sum + at(index + reach + 1) - at(index - reach);
+    }
+}
+
+/// The generation after `matrix` under `rule`: the neighbor counts of all the cells come from
+/// `box_sums` at once, then every cell looks its fate up in the rule's tables. Walls and immortal
+/// cells stay as they are. For Life the result is the same as `GameOfLife::tick` without regions
+/// or frozen cells.
+pub fn step(matrix: &GameMatrix, rule: &BoxRule, boundary: Boundary) -> GameMatrix {
+    let sums = box_sums(matrix, rule.radius, boundary);
+    let mut next = matrix.clone();
+    let mut counts = sums.iter();
+    next.map_cells(|_, state| {
+        let neighbors = counts.next().map_or(0, |sum| *sum as usize) - state.is_alive() as usize;
+        rule.next(state, neighbors)
+    });
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::GameOfLife;
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn test_matches_tick() {
+        for boundary in [Boundary::Wrap, Boundary::Dead] {
+            // The R-pentomino soon reaches the edges and, when they wrap, comes back round.
+            let mut game = GameOfLife::new(12, 17);
+            game.set_boundary(boundary);
+            Pattern::from_plaintext("R-pentomino", ".OO\nOO.\n.O.").stamp(game.current_mut(), (4, 7));
+            let rule = BoxRule::from(game.rule());
+            for _ in 0..20 {
+                let stepped = step(game.current(), &rule, boundary);
+                game.tick();
+                assert_eq!(&stepped, game.current());
+            }
+        }
+    }
+
+    #[test]
+    fn test_box_sums() {
+        let matrix = GameMatrix::from_ascii("
+            O....
+            .....
+            ....O
+        ").unwrap();
+        let sums = box_sums(&matrix, 1, Boundary::Dead);
+        assert_eq!(sums.row(0), &[1, 1, 0, 0, 0]);
+        assert_eq!(sums.row(1), &[1, 1, 0, 1, 1]);
+        let wrapped = box_sums(&matrix, 1, Boundary::Wrap);
+        // The corners are neighbors across both edges.
+        assert_eq!(wrapped[(0, 0)], 2);
+        assert_eq!(box_sums(&matrix, 2, Boundary::Dead)[(1, 2)], 2);
+    }
+
+    #[test]
+    fn test_larger_than_life() {
+        // Under Bosco's rule, R5 B34-45 S33-57, the crowded middle of a filled 11x11 square dies
+        // while its corners, with 35 living neighbors each, survive.
+        let rule = BoxRule::larger_than_life(5, 34..=45, 33..=57);
+        let mut matrix = GameMatrix::new(31, 31);
+        matrix.fill_rect((10, 10), (20, 20), CellState::Alive);
+        let next = step(&matrix, &rule, Boundary::Dead);
+        assert_eq!(next[(15, 15)], CellState::Dead);
+        assert_eq!(next[(10, 10)], CellState::Alive);
+        assert_eq!(rule.radius(), 5);
+    }
+}
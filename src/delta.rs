@@ -0,0 +1,260 @@
+use crate::engine::{Cell, CellChange, CellState, CellType, GameMatrix};
+use crate::error::{Error, Result};
+
+/// The cells toggled between two generations of a grid, born or died, that is the XOR of the two,
+/// stored compressed: a handful of bytes for a generation where little changed however large the
+/// grid. Cells are numbered in row major order, `row * columns + column`, and every number is an
+/// unsigned LEB128 varint:
+///
+/// - the number of toggled cells or of runs of them, shifted left a bit, the low bit saying which
+/// - as cells, each cell's index as the gap from the index before it, or after the first from 0
+/// - as runs, each run's gap from the end of the run before it, then its length less one
+///
+/// Whichever of the two comes out shorter is used: scattered cells are best listed one by one,
+/// and cells toggling next to each other, as along the edge of a growing pattern, best as runs.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Delta {
+    bytes: Vec<u8>,
+    /// How many cells toggled.
+    len: usize,
+    /// One past the index of the last toggled cell.
+    end: usize,
+}
+
+impl Delta {
+    /// The cells alive in one of `before` and `after` but not the other, which must be the same
+    /// shape.
+    pub fn between(before: &GameMatrix, after: &GameMatrix) -> Delta {
+        let toggled = before.get_internal()
+            .iter()
+            .zip(after.get_internal().iter())
+            .enumerate()
+            .filter(|(_, (before, after))| before.is_alive() != after.is_alive())
+            .map(|(index, _)| index);
+        Delta::from_indices(toggled)
+    }
+
+    /// The cells in `changes`, as `GameOfLife::changes` lists them, on a grid `columns` wide.
+    pub fn from_changes(changes: &[CellChange], columns: usize) -> Delta {
+        Delta::from_indices(changes.iter().map(|change| change.cell.0 * columns + change.cell.1))
+    }
+
+    /// The cells numbered `indices`, which must be in increasing order; repeats are skipped.
+    pub fn from_indices<I>(indices: I) -> Delta
+        where I: IntoIterator<Item=usize>
+    {
+        let mut cells: Vec<usize> = indices.into_iter().collect();
+        cells.dedup();
+        let mut runs: Vec<(usize, usize)> = vec![];
+        for index in &cells {
+            match runs.last_mut() {
+                Some((start, length)) if *start + *length == *index => *length += 1,
+                _ => runs.push((*index, 1)),
+            }
+        }
+
+        let mut listed = vec![];
+        push_number(&mut listed, (cells.len() as u64) << 1);
+        let mut previous = 0;
+        for index in &cells {
+            push_number(&mut listed, (index - previous) as u64);
+            previous = *index;
+        }
+        let mut run_lengths = vec![];
+        push_number(&mut run_lengths, (runs.len() as u64) << 1 | 1);
+        let mut end = 0;
+        for (start, length) in &runs {
+            push_number(&mut run_lengths, (start - end) as u64);
+            push_number(&mut run_lengths, (length - 1) as u64);
+            end = start + length;
+        }
+
+        let mut bytes = if run_lengths.len() < listed.len() { run_lengths } else { listed };
+        bytes.shrink_to_fit();
+        Delta { bytes, len: cells.len(), end }
+    }
+
+    /// Reads a delta from the start of `bytes`, as written by `as_bytes`, returning it and how
+    /// many bytes it took up.
+    pub fn parse(bytes: &[u8]) -> Result<(Delta, usize)> {
+        let mut runs = Runs::new(bytes)?;
+        let (mut len, mut end) = (0, 0);
+        while let Some((start, length)) = runs.next_run()? {
+            len += length;
+            end = start + length;
+        }
+        let used = runs.position;
+        Ok((Delta { bytes: bytes[..used].to_vec(), len, end }, used))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// How many cells toggled.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Roughly how many bytes of memory the delta takes up, itself and its encoding.
+    pub fn footprint(&self) -> usize {
+        std::mem::size_of::<Delta>() + self.bytes.capacity()
+    }
+
+    /// One past the index of the last toggled cell, so a grid needs at least this many cells.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The indices of the toggled cells, in increasing order.
+    pub fn indices(&self) -> impl Iterator<Item=usize> + '_ {
+        // Only well formed bytes make it into a delta, so decoding them again cannot fail.
+        let mut runs = Runs::new(&self.bytes).ok();
+        std::iter::from_fn(move || runs.as_mut()?.next_run().ok().flatten())
+            .flat_map(|(start, length)| start..start + length)
+    }
+
+    /// The toggled cells of a grid `columns` wide.
+    pub fn cells(&self, columns: usize) -> impl Iterator<Item=Cell> + '_ {
+        self.indices().map(move |index| (index / columns.max(1), index % columns.max(1)))
+    }
+
+    /// Toggles the cells in `matrix`, living ones dying and any others coming to life, which
+    /// takes a grid from either generation to the other.
+    pub fn apply(&self, matrix: &mut GameMatrix) {
+        let states: Vec<(Cell, CellState)> = self
+            .cells(matrix.shape().1)
+            .map(|cell| (cell, if matrix.get_state(cell).is_alive() { CellState::Dead } else { CellState::Alive }))
+            .collect();
+        matrix.set_states(&states).expect("deltas of a grid this shape");
+    }
+}
+
+/// Decodes the runs of toggled cells in a delta's bytes, a lone cell being a run of one.
+struct Runs<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    remaining: u64,
+    as_runs: bool,
+    /// One past the end of the run before, or `None` before the first.
+    end: Option<usize>,
+}
+
+impl<'a> Runs<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Runs<'a>> {
+        let mut position = 0;
+        let header = read_number(bytes, &mut position)?;
+        Ok(Runs { bytes, position, remaining: header >> 1, as_runs: header & 1 == 1, end: None })
+    }
+
+    /// The start and length of the next run, if any are left.
+    fn next_run(&mut self) -> Result<Option<(usize, usize)>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let gap = read_number(self.bytes, &mut self.position)?;
+        let (start, length) = match (self.as_runs, self.end) {
+            (true, end) => {
+                let length = read_number(self.bytes, &mut self.position)?.checked_add(1);
+                (end.unwrap_or(0) as u64).checked_add(gap).zip(length)
+            }
+            // Listed cells are gaps from the cell before, which must come strictly before.
+            (false, Some(_)) if gap == 0 => return Err(parse_error("cells out of order")),
+            (false, Some(end)) => (end as u64 - 1).checked_add(gap).map(|start| (start, 1)),
+            (false, None) => Some((gap, 1)),
+        }
+        .ok_or_else(|| parse_error("cell index too large"))?;
+        let end = start
+            .checked_add(length)
+            .and_then(|end| usize::try_from(end).ok())
+            .ok_or_else(|| parse_error("cell index too large"))?;
+        self.end = Some(end);
+        Ok(Some((start as usize, length as usize)))
+    }
+}
+
+pub(crate) fn push_number(bytes: &mut Vec<u8>, mut number: u64) {
+    while number >= 0x80 {
+        bytes.push(number as u8 | 0x80);
+        number >>= 7;
+    }
+    bytes.push(number as u8);
+}
+
+fn read_number(bytes: &[u8], position: &mut usize) -> Result<u64> {
+    let mut number = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*position).ok_or_else(|| parse_error("unexpected end of data"))?;
+        *position += 1;
+        number |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(number);
+        }
+    }
+    Err(parse_error("number too large"))
+}
+
+fn parse_error(message: &str) -> Error {
+    Error::Parse(format!("delta: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::GameOfLife;
+
+    #[test]
+    fn test_between() {
+        let mut game = GameOfLife::new(6, 6);
+        *game.current_mut() = "......\n..O...\n..O...\n..O...\n......\n......".parse().unwrap();
+        game.tick();
+        let delta = Delta::between(game.previous(), game.current());
+        assert_eq!(delta.len(), 4);
+        assert_eq!(delta.cells(6).collect::<Vec<_>>(), vec![(1, 2), (2, 1), (2, 3), (3, 2)]);
+        assert_eq!(delta, Delta::from_changes(&game.changes(), 6));
+        assert_eq!(delta.end(), 21);
+
+        let mut matrix = game.previous().clone();
+        delta.apply(&mut matrix);
+        assert_eq!(&matrix, game.current());
+        delta.apply(&mut matrix);
+        assert_eq!(&matrix, game.previous());
+    }
+
+    #[test]
+    fn test_encodings() {
+        // Scattered cells are listed: a byte for the count and one for each small gap.
+        let scattered = Delta::from_indices([3, 10, 20]);
+        assert_eq!(scattered.as_bytes(), &[6, 3, 7, 10]);
+        // A long run takes a byte for the count, the gap and the length.
+        let run = Delta::from_indices(1000..1500);
+        assert_eq!(run.as_bytes(), &[3, 0xe8, 0x07, 0xf3, 0x03]);
+        assert_eq!((run.len(), run.end()), (500, 1500));
+        assert!(run.indices().eq(1000..1500));
+
+        let empty = Delta::from_indices([]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.indices().count(), 0);
+        assert_eq!(Delta::from_indices([4, 4, 5]).len(), 2);
+    }
+
+    #[test]
+    fn test_parse() {
+        for delta in [Delta::from_indices([0, 7, 8, 9, 300]), Delta::from_indices(5..40), Delta::from_indices([])] {
+            let mut bytes = delta.as_bytes().to_vec();
+            bytes.push(42);
+            assert_eq!(Delta::parse(&bytes).unwrap(), (delta.clone(), bytes.len() - 1));
+            if !delta.is_empty() {
+                assert!(Delta::parse(&bytes[..delta.as_bytes().len() - 1]).is_err());
+            }
+        }
+        // After the first, listed cells must each come after the one before.
+        assert!(Delta::parse(&[4, 3, 0]).is_err());
+        assert!(Delta::parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::delta::Delta;
+use crate::engine::{Boundary, GameOfLife};
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+
+/// Where two evolutions first part ways, as found by `divergence`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Divergence {
+    pub generation: u64,
+    /// The cells alive in one evolution but not the other, in row major order, relative to the
+    /// patterns' top left corner.
+    pub cells: Vec<(isize, isize)>,
+    /// How many cells are alive in each evolution, the first one's first.
+    pub populations: (usize, usize),
+    /// The generation the two are the same again from, through the last one compared, if they
+    /// come back together.
+    pub rejoined: Option<u64>,
+}
+
+/// Runs `first` under the first of `rules` and `second` under the other side by side, their top
+/// left corners in the same place on an unbounded grid, and finds the first generation they
+/// differ in, looking at most `generations` ahead. `None` if they stay the same all the way.
+pub fn divergence(first: &Pattern, second: &Pattern, rules: (Rule, Rule), generations: u64) -> Option<Divergence> {
+    // Cells spread at most one step per generation, so that much room on every side keeps the
+    // grid's edges out of reach.
+    let margin = generations as usize + 1;
+    let rows = first.shape().0.max(second.shape().0) + 2 * margin;
+    let columns = first.shape().1.max(second.shape().1) + 2 * margin;
+    let game = |pattern: &Pattern, rule: Rule| {
+        let mut game = GameOfLife::new(rows, columns);
+        game.set_rule(rule);
+        game.set_boundary(Boundary::Dead);
+        pattern.stamp(game.current_mut(), (margin, margin));
+        game
+    };
+    let (mut first, mut second) = (game(first, rules.0), game(second, rules.1));
+
+    let mut divergence: Option<Divergence> = None;
+    for generation in 0..=generations {
+        let same = first.current() == second.current();
+        match &mut divergence {
+            None if !same => {
+                let cells = Delta::between(first.current(), second.current())
+                    .cells(columns)
+                    .map(|(row, column)| (row as isize - margin as isize, column as isize - margin as isize))
+                    .collect();
+                let populations = (first.current().population(), second.current().population());
+                divergence = Some(Divergence { generation, cells, populations, rejoined: None });
+            }
+            Some(divergence) => match (same, divergence.rejoined) {
+                (true, None) => divergence.rejoined = Some(generation),
+                (false, Some(_)) => divergence.rejoined = None,
+                _ => {}
+            },
+            None => {}
+        }
+        if generation < generations {
+            first.tick();
+            second.tick();
+        }
+    }
+    divergence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same() {
+        let glider = Pattern::from_plaintext("Glider", ".O.\n..O\nOOO");
+        let life = Rule::life();
+        assert_eq!(divergence(&glider, &glider, (life, life), 50), None);
+        // Room to the right and below makes no difference.
+        let padded = Pattern::from_plaintext("Glider", ".O..\n..O.\nOOO.\n....");
+        assert_eq!(divergence(&glider, &padded, (life, life), 50), None);
+    }
+
+    #[test]
+    fn test_differing_patterns() {
+        // Four in a row becomes a beehive while three in a row blinks.
+        let four = Pattern::from_plaintext("Four", "OOOO");
+        let three = Pattern::from_plaintext("Three", "OOO.");
+        let life = Rule::life();
+        let parted = divergence(&four, &three, (life, life), 10).unwrap();
+        assert_eq!((parted.generation, parted.cells, parted.populations), (0, vec![(0, 3)], (4, 3)));
+        assert_eq!(parted.rejoined, None);
+
+        // A block with a corner missing grows it back at once.
+        let block = Pattern::from_plaintext("Block", "OO\nOO");
+        let broken = Pattern::from_plaintext("Broken", "OO\nO.");
+        let healed = divergence(&block, &broken, (life, life), 10).unwrap();
+        assert_eq!((healed.generation, healed.cells, healed.rejoined), (0, vec![(1, 1)], Some(1)));
+    }
+
+    #[test]
+    fn test_differing_rules() {
+        // The R-pentomino runs the same under Life and HighLife until a dead cell first has six
+        // neighbors, in the middle of its second generation.
+        let r = Pattern::from_plaintext("R-pentomino", ".OO\nOO.\n.O.");
+        let divergence = divergence(&r, &r, (Rule::life(), "B36/S23".parse().unwrap()), 20).unwrap();
+        assert_eq!((divergence.generation, divergence.cells, divergence.populations), (2, vec![(1, 1)], (7, 8)));
+    }
+}
@@ -1,34 +1,244 @@
-use nalgebra::DMatrix;
+#[cfg(feature = "rand")]
+use rand::Rng;
+use std::fmt;
 use std::mem::swap;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use crate::builder::GameOfLifeBuilder;
+use crate::error::{Error, Result};
+use crate::grid::Grid;
+use crate::rule::Rule;
 
-pub struct GameMatrix(DMatrix<CellState>);
+/// A grid of cells, `CellState` for Life itself but generic so that other automata can share the
+/// grid and neighborhood code.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameMatrix<T = CellState>(Grid<T>)
+    where T: CellType;
+
+/// A type that can be stored in a `GameMatrix`. The default value is the quiescent state new grids
+/// are filled with.
+pub trait CellType: Copy + PartialEq + fmt::Debug + Default + 'static {
+    /// Whether the cell counts as alive for populations, neighbor counts and `Display`.
+    fn is_alive(&self) -> bool;
+}
 
 pub type Cell = (usize, usize);
 
+#[derive(Clone)]
 pub struct GameOfLife {
     previous: GameMatrix,
     current: GameMatrix,
+    boundary: Boundary,
+    rule: Rule,
+    ages: Grid<u32>,
+    /// How many generations each cell has been alive for in all, counted as the game ticks.
+    visits: Grid<u32>,
+    /// The bounding box of the current generation, kept up to date by ticking; `None` once the
+    /// grid has been handed out for editing, until the next tick.
+    bounds: Option<Option<BoundingBox>>,
+    /// The cells ticking leaves as they are, once any have been frozen.
+    frozen: Option<Grid<bool>>,
+    /// Regions running their own rule instead of `rule`, the last one holding a cell winning.
+    regions: Vec<(BoundingBox, Rule)>,
+    hooks: Hooks,
+}
+
+/// The functions registered with `on_pre_tick` and `on_post_tick`. A clone of a game starts
+/// without them, since the copies made to look ahead or to draw from aren't meant to act on
+/// their generations.
+#[derive(Default)]
+struct Hooks {
+    pre: Vec<PreTick>,
+    post: Vec<PostTick>,
+}
+
+type PreTick = Box<dyn FnMut(&mut GameOfLife) + Send>;
+type PostTick = Box<dyn FnMut(&GameOfLife) + Send>;
+
+impl Clone for Hooks {
+    fn clone(&self) -> Hooks {
+        Hooks::default()
+    }
+}
+
+/// The smallest rectangle holding every living cell, edges included. On a wrapped grid a pattern
+/// straddling an edge has a box reaching across the whole grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BoundingBox {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+impl BoundingBox {
+    fn around((row, column): Cell) -> BoundingBox {
+        BoundingBox { top: row, left: column, bottom: row, right: column }
+    }
+
+    fn include(bounds: &mut Option<BoundingBox>, (row, column): Cell) {
+        match bounds {
+            Some(bounds) => {
+                bounds.top = bounds.top.min(row);
+                bounds.left = bounds.left.min(column);
+                bounds.bottom = bounds.bottom.max(row);
+                bounds.right = bounds.right.max(column);
+            }
+            None => *bounds = Some(BoundingBox::around((row, column))),
+        }
+    }
+
+    pub fn top_left(&self) -> Cell {
+        (self.top, self.left)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.bottom - self.top + 1, self.right - self.left + 1)
+    }
+
+    /// The middle cell, rounding up and to the left.
+    pub fn center(&self) -> Cell {
+        ((self.top + self.bottom) / 2, (self.left + self.right) / 2)
+    }
+
+    pub fn contains(&self, (row, column): Cell) -> bool {
+        (self.top..=self.bottom).contains(&row) && (self.left..=self.right).contains(&column)
+    }
 }
 
+/// A cell that has a different state than it had in the previous generation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CellChange {
+    pub cell: Cell,
+    pub state: CellState,
+}
+
+/// What a single tick did, as returned by `GameOfLife::tick_report`.
 #[derive(Clone, PartialEq, Debug)]
-pub enum CellState {
-    Alive,
+pub struct TickReport {
+    /// How many cells came to life.
+    pub births: usize,
+    /// How many living cells died.
+    pub deaths: usize,
+    /// How many cells are alive after the tick.
+    pub population: usize,
+    /// The cells the tick changed, as `changes()` lists them.
+    pub changes: Vec<CellChange>,
+    /// How long computing the generation took, not counting the report.
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Boundary {
+    /// The grid is a torus: cells on one edge neighbor the cells on the opposite edge.
+    Wrap,
+    /// Everything beyond the edges is permanently dead.
     Dead,
 }
 
+/// One byte per cell, so grids of them are compact and cells are passed around by value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum CellState {
+    Alive = 1,
+    #[default]
+    Dead = 0,
+    /// An obstacle: never born, never dies, and counts as dead to its neighbors, for building
+    /// arenas and channels that patterns can't cross.
+    Wall = 2,
+    /// A living cell that never dies, whatever its neighbors, for building permanent emitters
+    /// and fixed boundary conditions. It counts as alive everywhere else.
+    Immortal = 3,
+}
+
+impl CellType for CellState {
+    fn is_alive(&self) -> bool {
+        matches!(self, CellState::Alive | CellState::Immortal)
+    }
+}
+
 impl GameOfLife {
     pub fn new(rows: usize, columns: usize) -> GameOfLife {
         GameOfLife {
             previous: GameMatrix::new(rows, columns),
             current: GameMatrix::new(rows, columns),
+            boundary: Boundary::Wrap,
+            rule: Rule::life(),
+            ages: Grid::from_element(rows, columns, 0),
+            visits: Grid::from_element(rows, columns, 0),
+            bounds: Some(None),
+            frozen: None,
+            regions: vec![],
+            hooks: Hooks::default(),
         }
     }
 
+    /// A game starting from `matrix`, taking its shape from it and everything else from `new`.
+    pub fn from_matrix(matrix: GameMatrix) -> GameOfLife {
+        let (rows, columns) = matrix.shape();
+        GameOfLife { current: matrix, bounds: None, ..GameOfLife::new(rows, columns) }
+    }
+
+    /// A game of the given size with only `cells` alive, failing on any cell outside of it.
+    pub fn from_cells(rows: usize, columns: usize, cells: &[Cell]) -> Result<GameOfLife> {
+        let mut matrix = GameMatrix::new(rows, columns);
+        matrix.set_alive(cells)?;
+        Ok(GameOfLife::from_matrix(matrix))
+    }
+
+    pub fn builder() -> GameOfLifeBuilder {
+        GameOfLifeBuilder::default()
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// Makes the cells in `region`, edges included, run `rule` instead of the game's own, e.g. to
+    /// watch two rules side by side. A region set later wins where it overlaps an earlier one.
+    /// At the seam every cell follows the rule of the region it is in but counts all its
+    /// neighbors, whichever region they are in, so patterns cross over and change behavior.
+    /// Regions stay where they are when the grid is shifted.
+    pub fn set_region_rule(&mut self, region: BoundingBox, rule: Rule) {
+        self.regions.push((region, rule));
+    }
+
+    /// The regions running their own rule, in the order they were set.
+    pub fn region_rules(&self) -> &[(BoundingBox, Rule)] {
+        &self.regions
+    }
+
+    /// Makes every cell run the game's own rule again.
+    pub fn clear_region_rules(&mut self) {
+        self.regions.clear();
+    }
+
+    /// The rule the cell runs: that of the last region holding it, or the game's own.
+    pub fn rule_at(&self, cell: Cell) -> Rule {
+        self.regions.iter().rev()
+            .find(|(region, _)| region.contains(cell))
+            .map_or(self.rule, |(_, rule)| *rule)
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
     pub fn current(&self) -> &GameMatrix {
         &self.current
     }
 
     pub fn current_mut(&mut self) -> &mut GameMatrix {
+        self.bounds = None;
         &mut self.current
     }
 
@@ -41,129 +251,710 @@ impl GameOfLife {
     }
 
     pub fn tick(&mut self) {
+        // What the generation was before the pre-tick hooks changed it, so that `changes`, and
+        // the replicas and history going by them, take in the hooks' edits along with the tick's.
+        let mut before = None;
+        if !self.hooks.pre.is_empty() {
+            before = Some(self.current.clone());
+            // Taken out while they run, so they can have the game to themselves.
+            let mut hooks = std::mem::take(&mut self.hooks.pre);
+            for hook in &mut hooks {
+                hook(self);
+            }
+            hooks.append(&mut self.hooks.pre);
+            self.hooks.pre = hooks;
+        }
         swap(&mut self.previous, &mut self.current);
 
         let (rows, columns) = self.shape();
+        let counts = self.previous.neighbor_counts(self.boundary);
 
         for row in 0..rows {
             for column in 0..columns {
                 let cell = (row, column);
-                let new_state = self.previous.get_next_state(cell);
-                self.current.set_state(cell, new_state);
+                let state = self.previous.get_state(cell);
+                if self.frozen.as_ref().is_some_and(|frozen| frozen[cell]) {
+                    self.current.set_state(cell, state);
+                    continue;
+                }
+                let alive_neighbors = counts[row * columns + column] as usize;
+                let rule = if self.regions.is_empty() { self.rule } else { self.rule_at(cell) };
+                self.current.set_state(cell, next_state(state, alive_neighbors, &rule));
+            }
+        }
+        if let Some(before) = before {
+            self.previous = before;
+        }
+        self.update_bookkeeping();
+        if !self.hooks.post.is_empty() {
+            let mut hooks = std::mem::take(&mut self.hooks.post);
+            for hook in &mut hooks {
+                hook(self);
             }
+            self.hooks.post = hooks;
+        }
+    }
+
+    /// Runs `hook` at the start of every tick, before the next generation is worked out, free to
+    /// change the game it is about to come from: to drop a glider in every so often, say. The
+    /// previous generation stays as it was before the hook, so `changes` includes its edits.
+    pub fn on_pre_tick(&mut self, hook: impl FnMut(&mut GameOfLife) + Send + 'static) {
+        self.hooks.pre.push(Box::new(hook));
+    }
+
+    /// Runs `hook` at the end of every tick with the generation it made, e.g. to log its stats.
+    pub fn on_post_tick(&mut self, hook: impl FnMut(&GameOfLife) + Send + 'static) {
+        self.hooks.post.push(Box::new(hook));
+    }
+
+    /// Ticks like `tick()` and reports what the tick did, every figure taken from that one
+    /// generation.
+    pub fn tick_report(&mut self) -> TickReport {
+        let start = Instant::now();
+        self.tick();
+        let elapsed = start.elapsed();
+        let changes = self.changes();
+        // Ticking only ever brings cells to life or kills them, walls and immortals staying put.
+        let births = changes.iter().filter(|change| change.state.is_alive()).count();
+        TickReport {
+            births,
+            deaths: changes.len() - births,
+            population: self.current.population(),
+            changes,
+            elapsed,
         }
     }
 
+    /// Freezes every cell in `region`, edges included, so that ticking leaves them as they are
+    /// while the cells around them evolve, or thaws them again when `frozen` is false. Frozen
+    /// cells still count as neighbors and can still be edited by hand.
+    pub fn freeze(&mut self, region: BoundingBox, frozen: bool) {
+        let (rows, columns) = self.shape();
+        let mask = self.frozen.get_or_insert_with(|| Grid::from_element(rows, columns, false));
+        mask.fill_rect(region.top_left(), (region.bottom, region.right), frozen);
+    }
+
+    pub fn is_frozen(&self, cell: Cell) -> bool {
+        self.frozen.as_ref().is_some_and(|frozen| frozen[cell])
+    }
+
+    /// Thaws every frozen cell.
+    pub fn thaw_all(&mut self) {
+        self.frozen = None;
+    }
+
+    /// The bounding box of the living cells, or `None` when there are none. Kept up to date as
+    /// the game ticks, so it only takes a scan of the grid after it has been edited by hand.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounds.unwrap_or_else(|| {
+            let mut bounds = None;
+            let (rows, columns) = self.shape();
+            for row in 0..rows {
+                for column in 0..columns {
+                    if self.current.get_state((row, column)).is_alive() {
+                        BoundingBox::include(&mut bounds, (row, column));
+                    }
+                }
+            }
+            bounds
+        })
+    }
+
+    /// An endless iterator that ticks the game and yields a snapshot of each new generation, e.g.
+    /// `game.generations().take(100).map(|matrix| matrix.population())`.
+    pub fn generations(&mut self) -> Generations<'_> {
+        Generations { game: self }
+    }
+
     pub fn shape(&self) -> (usize, usize) {
         self.current.0.shape()
     }
 
+    /// The cells that changed between the previous and the current generation.
+    pub fn changes(&self) -> Vec<CellChange> {
+        let (rows, columns) = self.shape();
+        let mut changes = vec![];
+        for row in 0..rows {
+            for column in 0..columns {
+                let cell = (row, column);
+                let state = self.current.get_state(cell);
+                if state != self.previous.get_state(cell) {
+                    changes.push(CellChange { cell, state });
+                }
+            }
+        }
+        changes
+    }
+
+    /// Advances to the next generation by applying the changes another game computed with
+    /// `changes()`, keeping ages, visits and the previous generation just as `tick()` would.
+    pub fn apply_changes(&mut self, changes: &[CellChange]) {
+        self.previous = self.current.clone();
+        for change in changes {
+            self.current.set_state(change.cell, change.state);
+        }
+        self.update_bookkeeping();
+    }
+
+    /// Undoes `apply_changes`, taking the game back to the generation before `changes`. Visits
+    /// come back exactly, but a cell brought back to life is given an age of one, as how long it
+    /// had lived isn't known, and the previous generation is left the same as the current one.
+    pub fn revert_changes(&mut self, changes: &[CellChange]) {
+        for (visits, state) in self.visits.iter_mut().zip(self.current.0.iter()) {
+            if state.is_alive() {
+                *visits = visits.saturating_sub(1);
+            }
+        }
+        for change in changes {
+            let state = match change.state {
+                CellState::Alive => CellState::Dead,
+                CellState::Dead => CellState::Alive,
+                state @ (CellState::Wall | CellState::Immortal) => state,
+            };
+            self.current.set_state(change.cell, state);
+        }
+        for (age, state) in self.ages.iter_mut().zip(self.current.0.iter()) {
+            *age = match state {
+                CellState::Alive | CellState::Immortal => age.saturating_sub(1).max(1),
+                CellState::Dead | CellState::Wall => 0,
+            };
+        }
+        self.previous = self.current.clone();
+        self.bounds = None;
+    }
+
+    /// Brings ages, visits and the bounding box up to date with a new generation, in one pass
+    /// over the grid in memory order.
+    fn update_bookkeeping(&mut self) {
+        let columns = self.shape().1;
+        let mut bounds = None;
+        let (mut row, mut column) = (0, 0);
+        for ((age, visits), state) in self.ages.iter_mut().zip(self.visits.iter_mut()).zip(self.current.0.iter()) {
+            *age = match state {
+                CellState::Alive | CellState::Immortal => {
+                    BoundingBox::include(&mut bounds, (row, column));
+                    *visits = visits.saturating_add(1);
+                    *age + 1
+                }
+                CellState::Dead | CellState::Wall => 0,
+            };
+            column += 1;
+            if column == columns {
+                (row, column) = (row + 1, 0);
+            }
+        }
+        self.bounds = Some(bounds);
+    }
+
+    /// The number of consecutive generations the cell has been alive for, zero for dead cells
+    /// and for cells that were set alive by hand since the last tick.
+    pub fn age(&self, cell: Cell) -> u32 {
+        self.ages[cell]
+    }
+
+    /// The number of generations the cell has been alive for in all since the game started or
+    /// was last cleared, however often it died in between: a heatmap of where the action is.
+    pub fn visits(&self, cell: Cell) -> u32 {
+        self.visits[cell]
+    }
+
+    /// The most `visits` of any cell.
+    pub fn max_visits(&self) -> u32 {
+        self.visits.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Moves the whole game `d_row` rows down and `d_column` columns right, wrapping around the
+    /// edges or losing what goes over them as `GameMatrix::shift` does. Ages, visits and frozen
+    /// regions move with their cells.
+    pub fn shift(&mut self, d_row: isize, d_column: isize) {
+        let wrap = self.boundary == Boundary::Wrap;
+        self.current.shift(d_row, d_column, self.boundary);
+        self.previous.shift(d_row, d_column, self.boundary);
+        self.ages = self.ages.shifted(d_row, d_column, wrap, 0);
+        self.visits = self.visits.shifted(d_row, d_column, wrap, 0);
+        self.frozen = self.frozen.as_ref().map(|frozen| frozen.shifted(d_row, d_column, wrap, false));
+        self.bounds = None;
+    }
+
     pub fn kill_em_all(&mut self) {
         self.current.kill_em_all();
         self.previous.kill_em_all();
+        self.ages.fill(0);
+        self.visits.fill(0);
+        self.bounds = Some(None);
     }
 }
 
-impl GameMatrix {
-    pub fn new(rows: usize, columns: usize) -> GameMatrix {
-        GameMatrix(DMatrix::from_element(rows, columns, CellState::Dead))
+pub struct Generations<'a> {
+    game: &'a mut GameOfLife,
+}
+
+impl Iterator for Generations<'_> {
+    type Item = GameMatrix;
+
+    fn next(&mut self) -> Option<GameMatrix> {
+        self.game.tick();
+        Some(self.game.current().clone())
+    }
+}
+
+impl<T> GameMatrix<T>
+    where T: CellType
+{
+    pub fn new(rows: usize, columns: usize) -> GameMatrix<T> {
+        GameMatrix(Grid::from_element(rows, columns, T::default()))
     }
 
-    pub fn set_state(&mut self, cell: Cell, state: CellState) {
+    /// Panics if the cell is outside of the grid; see `try_set_state` for untrusted cells.
+    pub fn set_state(&mut self, cell: Cell, state: T) {
         self.0[cell] = state
     }
 
-    pub fn get_state(&self, cell: Cell) -> &CellState {
-        &self.0[cell]
+    /// Panics if the cell is outside of the grid; see `try_get_state` for untrusted cells.
+    pub fn get_state(&self, cell: Cell) -> T {
+        self.0[cell]
     }
 
-    pub fn get_next_state(&self, cell: Cell) -> CellState {
-        let alive_neighbors = get_neighbor_cells(cell, self.shape())
-            .iter()
-            .map(|cell| self.get_state(*cell))
-            .filter(|state| **state == CellState::Alive)
-            .count();
-
-        match self.get_state(cell) {
-            CellState::Alive => {
-                match alive_neighbors {
-                    2..=3 => CellState::Alive,
-                    _ => CellState::Dead,
-                }
+    pub fn try_set_state(&mut self, cell: Cell, state: T) -> Result<()> {
+        self.check_bounds(cell)?;
+        self.0[cell] = state;
+        Ok(())
+    }
+
+    pub fn try_get_state(&self, cell: Cell) -> Result<T> {
+        self.check_bounds(cell)?;
+        Ok(self.0[cell])
+    }
+
+    /// Writes many states at once, checking all of the cells before writing any of them, so a
+    /// cell outside of the grid fails the whole batch and leaves the matrix untouched.
+    pub fn set_states(&mut self, states: &[(Cell, T)]) -> Result<()> {
+        for (cell, _) in states {
+            self.check_bounds(*cell)?;
+        }
+        for (cell, state) in states {
+            self.0[*cell] = *state;
+        }
+        Ok(())
+    }
+
+    /// Sets every cell from `top_left` to `bottom_right`, edges included, clipping the rectangle
+    /// at the grid's edges.
+    pub fn fill_rect(&mut self, top_left: Cell, bottom_right: Cell, state: T) {
+        self.0.fill_rect(top_left, bottom_right, state);
+    }
+
+    /// Puts every cell from `top_left` to `bottom_right` back to the default state, as with
+    /// `fill_rect`.
+    pub fn clear_rect(&mut self, top_left: Cell, bottom_right: Cell) {
+        self.fill_rect(top_left, bottom_right, T::default());
+    }
+
+    /// Sets the cells of the straight line from `a` to `b`, as laid out by `line`, leaving out
+    /// any that fall off the grid.
+    pub fn draw_line(&mut self, a: Cell, b: Cell, state: T) {
+        for cell in line(a, b) {
+            if self.contains(cell) {
+                self.0[cell] = state;
             }
-            CellState::Dead => {
-                match alive_neighbors {
-                    3 => CellState::Alive,
-                    _ => CellState::Dead,
+        }
+    }
+
+    pub fn contains(&self, (row, column): Cell) -> bool {
+        let (rows, columns) = self.shape();
+        row < rows && column < columns
+    }
+
+    fn check_bounds(&self, cell: Cell) -> Result<()> {
+        if self.contains(cell) {
+            Ok(())
+        } else {
+            Err(Error::OutOfBounds { cell, shape: self.shape() })
+        }
+    }
+
+    /// Computes the next generation of an arbitrary automaton: `next` receives each cell's state
+    /// and the states of its neighbors and returns the cell's new state.
+    pub fn evolve<F>(&self, boundary: Boundary, next: F) -> GameMatrix<T>
+        where F: Fn(T, &[T]) -> T
+    {
+        let (rows, columns) = self.shape();
+        let mut evolved = self.clone();
+        let offsets = NeighborOffsets::new(self.shape(), boundary);
+        let mut neighbors = Vec::with_capacity(8);
+        for row in 0..rows {
+            for column in 0..columns {
+                let cell = (row, column);
+                neighbors.clear();
+                neighbors.extend(offsets.neighbors(cell).map(|cell| self.get_state(cell)));
+                evolved.set_state(cell, next(self.get_state(cell), &neighbors));
+            }
+        }
+        evolved
+    }
+
+    /// Replaces every cell's state with what `f` makes of the cell and its state, row by row.
+    pub fn map_cells<F>(&mut self, mut f: F)
+        where F: FnMut(Cell, T) -> T
+    {
+        let columns = self.shape().1.max(1);
+        for (index, state) in self.0.iter_mut().enumerate() {
+            *state = f((index / columns, index % columns), *state);
+        }
+    }
+
+    /// Calls `f` with every cell and its state, row by row.
+    pub fn for_each_cell<F>(&self, mut f: F)
+        where F: FnMut(Cell, T)
+    {
+        let columns = self.shape().1.max(1);
+        for (index, state) in self.0.iter().enumerate() {
+            f((index / columns, index % columns), *state);
+        }
+    }
+
+    /// How many living neighbors every cell has, row by row, worked out in one pass as a three by
+    /// three box sum: each row's three rows are added up column by column, then each cell takes
+    /// the sums of its three columns, less itself.
+    pub fn neighbor_counts(&self, boundary: Boundary) -> Vec<u8> {
+        let (rows, columns) = self.shape();
+        let offsets = NeighborOffsets::new(self.shape(), boundary);
+        let mut counts = Vec::with_capacity(rows * columns);
+        let mut sums = vec![0u8; columns];
+        for row in 0..rows {
+            sums.fill(0);
+            for neighbor_row in offsets.rows[row].positions() {
+                for (sum, state) in sums.iter_mut().zip(self.0.row(*neighbor_row)) {
+                    *sum += state.is_alive() as u8;
                 }
             }
+            let states = self.0.row(row);
+            counts.extend((0..columns).map(|column| {
+                let around: u8 = offsets.columns[column].positions().iter().map(|column| sums[*column]).sum();
+                around - states[column].is_alive() as u8
+            }));
         }
+        counts
+    }
+
+    pub fn alive_neighbor_count(&self, cell: Cell, boundary: Boundary) -> usize {
+        get_alive_neighbor_count(self, cell, boundary)
     }
 
-    pub fn get_internal(&self) -> &DMatrix<CellState> {
+    pub fn get_internal(&self) -> &Grid<T> {
         &self.0
     }
 
+    pub fn from_grid(grid: Grid<T>) -> GameMatrix<T> {
+        GameMatrix(grid)
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.0
+    }
 
     pub fn shape(&self) -> (usize, usize) {
         self.0.shape()
     }
 
+    pub fn population(&self) -> usize {
+        self.0.iter().filter(|state| state.is_alive()).count()
+    }
+
+    /// Moves every cell `d_row` rows down and `d_column` columns right, e.g. to bring a drifting
+    /// pattern back into view. On a wrapped grid the cells moved over one edge come back on the
+    /// other; with a dead boundary they are lost.
+    pub fn shift(&mut self, d_row: isize, d_column: isize, boundary: Boundary) {
+        self.0 = self.0.shifted(d_row, d_column, boundary == Boundary::Wrap, T::default());
+    }
+
     pub fn kill_em_all(&mut self) {
         for value in self.0.iter_mut() {
-            *value = CellState::Dead
+            *value = T::default()
         }
     }
 }
 
-fn get_alive_neighbor_count(matrix: &GameMatrix, cell: Cell) -> usize {
-    get_neighbor_cells(cell, matrix.shape())
-        .iter()
-        .map(|cell| matrix.get_state(*cell))
-        .filter(|state| **state == CellState::Alive)
+impl GameMatrix {
+    /// Brings `cells` to life, all or none of them as with `set_states`.
+    pub fn set_alive(&mut self, cells: &[Cell]) -> Result<()> {
+        let states: Vec<(Cell, CellState)> = cells.iter().map(|cell| (*cell, CellState::Alive)).collect();
+        self.set_states(&states)
+    }
+
+    /// Parses the picture produced by `Display`: `O` or `*` for live cells and `.` for dead ones.
+    /// Surrounding whitespace and blank lines are ignored and short rows are padded with dead
+    /// cells, so boards can be written as indented multi-line strings.
+    pub fn from_ascii(text: &str) -> Result<GameMatrix> {
+        let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        if columns == 0 {
+            return Err(Error::Parse("empty board".to_string()));
+        }
+
+        let mut matrix = GameMatrix::new(lines.len(), columns);
+        for (row, line) in lines.iter().enumerate() {
+            for (column, symbol) in line.chars().enumerate() {
+                match symbol {
+                    'O' | '*' => matrix.set_state((row, column), CellState::Alive),
+                    '.' => {}
+                    _ => {
+                        let message = format!("unexpected '{}' at row {}, column {}", symbol, row, column);
+                        return Err(Error::Parse(message));
+                    }
+                }
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// A 64-bit FNV-1a hash of the grid's shape and cells. Unlike `std::hash` it is the same on
+    /// every platform and with every compiler, so runs can be checked against each other.
+    pub fn fingerprint(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let (rows, columns) = self.shape();
+        let shape = (rows as u64).to_le_bytes().into_iter().chain((columns as u64).to_le_bytes());
+        shape
+            .chain(self.0.iter().map(|state| *state as u8))
+            .fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+    }
+
+    pub fn get_next_state(&self, cell: Cell, boundary: Boundary, rule: &Rule) -> CellState {
+        next_state(self.get_state(cell), get_alive_neighbor_count(self, cell, boundary), rule)
+    }
+
+    /// Brings each cell to life with probability `density`, leaving the others as they are.
+    #[cfg(feature = "rand")]
+    pub fn randomize<R>(&mut self, density: f64, rng: &mut R)
+        where R: Rng
+    {
+        for value in self.0.iter_mut() {
+            if rng.gen_bool(density.clamp(0.0, 1.0)) {
+                *value = CellState::Alive;
+            }
+        }
+    }
+
+    /// Replaces the cells in `region`, edges included, with a fresh soup in which each is alive
+    /// with probability `density`. Walls and immortal cells stay put, as does everything outside
+    /// of the region, and the region is clipped to the grid.
+    #[cfg(feature = "rand")]
+    pub fn randomize_region<R>(&mut self, region: BoundingBox, density: f64, rng: &mut R)
+        where R: Rng
+    {
+        let (rows, columns) = self.shape();
+        for row in region.top..region.bottom.saturating_add(1).min(rows) {
+            for column in region.left..region.right.saturating_add(1).min(columns) {
+                let state = &mut self.0[(row, column)];
+                if matches!(state, CellState::Alive | CellState::Dead) {
+                    *state = if rng.gen_bool(density.clamp(0.0, 1.0)) { CellState::Alive } else { CellState::Dead };
+                }
+            }
+        }
+    }
+}
+
+/// Draws the grid with `O` for live and `.` for dead cells, one line per row.
+impl<T> fmt::Display for GameMatrix<T>
+    where T: CellType
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rows, columns) = self.shape();
+        for row in 0..rows {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for column in 0..columns {
+                let symbol = if self.get_state((row, column)).is_alive() { 'O' } else { '.' };
+                write!(f, "{}", symbol)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> Index<Cell> for GameMatrix<T>
+    where T: CellType
+{
+    type Output = T;
+
+    fn index(&self, cell: Cell) -> &T {
+        &self.0[cell]
+    }
+}
+
+impl<T> IndexMut<Cell> for GameMatrix<T>
+    where T: CellType
+{
+    fn index_mut(&mut self, cell: Cell) -> &mut T {
+        &mut self.0[cell]
+    }
+}
+
+impl FromStr for GameMatrix {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<GameMatrix> {
+        GameMatrix::from_ascii(text)
+    }
+}
+
+pub(crate) fn next_state(state: CellState, alive_neighbors: usize, rule: &Rule) -> CellState {
+    match state {
+        CellState::Wall => CellState::Wall,
+        CellState::Immortal => CellState::Immortal,
+        CellState::Alive if rule.survives(alive_neighbors) => CellState::Alive,
+        CellState::Dead if rule.is_born(alive_neighbors) => CellState::Alive,
+        _ => CellState::Dead,
+    }
+}
+
+fn get_alive_neighbor_count<T>(matrix: &GameMatrix<T>, cell: Cell, boundary: Boundary) -> usize
+    where T: CellType
+{
+    get_neighbor_cells(cell, matrix.shape(), boundary)
+        .filter(|cell| matrix.get_state(*cell).is_alive())
         .count()
 }
 
-fn get_neighbor_cells(cell: Cell, shape: (usize, usize)) -> Vec<Cell> {
-    let mut offsets = vec![];
+/// The row and column offsets of the eight neighbors, in row major order.
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// The neighbors of `cell`, wrapping around the edges or leaving out the ones beyond them
+/// depending on `boundary`. Computed on the fly so ticking doesn't allocate for every cell.
+pub(crate) fn get_neighbor_cells(cell: Cell, shape: (usize, usize), boundary: Boundary) -> impl Iterator<Item=Cell> {
     let (row_count, column_count) = shape;
     let (row, column) = cell;
-    for row_offset in -1..=1 {
-        for column_offset in -1..=1 {
-            if !(row_offset == 0 && column_offset == 0) {
-                offsets.push((
-                    get_offset(row, row_offset, row_count),
-                    get_offset(column, column_offset, column_count),
-                ));
+    NEIGHBOR_OFFSETS.into_iter().filter_map(move |(row_offset, column_offset)| match boundary {
+        Boundary::Wrap => Some((
+            get_offset(row, row_offset, row_count),
+            get_offset(column, column_offset, column_count),
+        )),
+        Boundary::Dead => {
+            let neighbor_row = row as isize + row_offset;
+            let neighbor_column = column as isize + column_offset;
+            if (0..row_count as isize).contains(&neighbor_row)
+                && (0..column_count as isize).contains(&neighbor_column) {
+                Some((neighbor_row as usize, neighbor_column as usize))
+            } else {
+                None
             }
         }
-    }
-
-    offsets
+    })
 }
 
 fn get_offset(position: usize, offset: isize, cells: usize) -> usize {
     (((position as isize + offset) + cells as isize) % cells as isize) as usize
 }
 
+/// The cells of the straight line from `from` to `to`, both ends included, in order from one to
+/// the other. Bresenham's algorithm picks one cell per step along the longer axis, so diagonals
+/// come out as single steps and no cell is visited twice.
+pub fn line(from: Cell, to: Cell) -> Vec<Cell> {
+    let (mut row, mut column) = (from.0 as isize, from.1 as isize);
+    let (to_row, to_column) = (to.0 as isize, to.1 as isize);
+    let (d_row, d_column) = ((to_row - row).abs(), -(to_column - column).abs());
+    let (step_row, step_column) = ((to_row - row).signum(), (to_column - column).signum());
+    let mut error = d_row + d_column;
+    let mut cells = Vec::with_capacity(d_row.max(-d_column) as usize + 1);
+    loop {
+        cells.push((row as usize, column as usize));
+        if (row, column) == (to_row, to_column) {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= d_column {
+            error += d_column;
+            row += step_row;
+        }
+        if doubled <= d_row {
+            error += d_row;
+            column += step_column;
+        }
+    }
+}
+
+/// The rows above, at and below every row and the columns left of, at and right of every column,
+/// worked out once per generation so that finding the neighbors of each cell is just lookups
+/// rather than `get_offset`'s arithmetic. Positions beyond the edges of a dead boundary are left
+/// out.
+struct NeighborOffsets {
+    rows: Vec<Span>,
+    columns: Vec<Span>,
+}
+
+/// Up to three positions next to each other, without allocating.
+#[derive(Clone, Copy)]
+struct Span {
+    positions: [usize; 3],
+    len: usize,
+}
+
+impl Span {
+    fn positions(&self) -> &[usize] {
+        &self.positions[..self.len]
+    }
+}
+
+impl NeighborOffsets {
+    fn new((rows, columns): (usize, usize), boundary: Boundary) -> NeighborOffsets {
+        let table = |cells: usize| -> Vec<Span> {
+            (0..cells)
+                .map(|position| {
+                    let mut span = Span { positions: [0; 3], len: 0 };
+                    for offset in -1..=1 {
+                        let neighbor = match boundary {
+                            Boundary::Wrap => Some(get_offset(position, offset, cells)),
+                            Boundary::Dead => position.checked_add_signed(offset).filter(|neighbor| *neighbor < cells),
+                        };
+                        if let Some(neighbor) = neighbor {
+                            span.positions[span.len] = neighbor;
+                            span.len += 1;
+                        }
+                    }
+                    span
+                })
+                .collect()
+        };
+        NeighborOffsets { rows: table(rows), columns: table(columns) }
+    }
+
+    /// The same cells as `get_neighbor_cells`, in the same order.
+    fn neighbors(&self, cell: Cell) -> impl Iterator<Item=Cell> + '_ {
+        let (row, column) = cell;
+        let columns = &self.columns[column];
+        self.rows[row]
+            .positions()
+            .iter()
+            .flat_map(move |row| columns.positions().iter().map(move |column| (*row, *column)))
+            .filter(move |neighbor| *neighbor != cell)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "rand")]
+    use rand::rngs::StdRng;
+    #[cfg(feature = "rand")]
+    use rand::SeedableRng;
+    use crate::pattern::Pattern;
 
     #[test]
     fn test_internal_top_left_neighbor_alive() {
         let game = &mut GameOfLife::new(6, 6);
         game.current_mut().set_state((0, 0), CellState::Alive);
-        assert_eq!(get_alive_neighbor_count(game.current_mut(), (1, 1)), 1);
+        assert_eq!(get_alive_neighbor_count(game.current_mut(), (1, 1), Boundary::Wrap), 1);
 
         game.kill_em_all();
         game.current_mut().set_state((0, 1), CellState::Alive);
-        assert_eq!(get_alive_neighbor_count(game.current_mut(), (1, 1)), 1);
+        assert_eq!(get_alive_neighbor_count(game.current_mut(), (1, 1), Boundary::Wrap), 1);
     }
 
     #[test]
     fn test_neighbor_cells() {
-        let offsets = get_neighbor_cells((0, 0), (10, 10));
+        let offsets: Vec<Cell> = get_neighbor_cells((0, 0), (10, 10), Boundary::Wrap).collect();
         assert_eq!(offsets[0], (9, 9));
         assert_eq!(offsets[1], (9, 0));
         assert_eq!(offsets[2], (9, 1));
@@ -173,7 +964,7 @@ mod tests {
         assert_eq!(offsets[6], (1, 0));
         assert_eq!(offsets[7], (1, 1));
 
-        let offsets = get_neighbor_cells((5, 5), (10, 10));
+        let offsets: Vec<Cell> = get_neighbor_cells((5, 5), (10, 10), Boundary::Wrap).collect();
         assert_eq!(offsets[0], (4, 4));
         assert_eq!(offsets[1], (4, 5));
         assert_eq!(offsets[2], (4, 6));
@@ -185,6 +976,482 @@ mod tests {
         assert_eq!(offsets[7], (6, 6));
     }
 
+    #[test]
+    fn test_neighbor_cells_dead_boundary() {
+        let offsets: Vec<Cell> = get_neighbor_cells((0, 0), (10, 10), Boundary::Dead).collect();
+        assert_eq!(offsets, vec![(0, 1), (1, 0), (1, 1)]);
+
+        assert_eq!(get_neighbor_cells((5, 5), (10, 10), Boundary::Dead).count(), 8);
+    }
+
+    #[test]
+    fn test_neighbor_offsets_match_neighbor_cells() {
+        for boundary in [Boundary::Wrap, Boundary::Dead] {
+            let offsets = NeighborOffsets::new((4, 7), boundary);
+            for cell in [(0, 0), (0, 6), (3, 0), (3, 6), (2, 3)] {
+                let expected: Vec<Cell> = get_neighbor_cells(cell, (4, 7), boundary).collect();
+                assert_eq!(offsets.neighbors(cell).collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dead_boundary_does_not_wrap() {
+        let game = &mut GameOfLife::new(6, 6);
+        game.current_mut().set_state((0, 5), CellState::Alive);
+        assert_eq!(get_alive_neighbor_count(game.current(), (0, 0), Boundary::Wrap), 1);
+        assert_eq!(get_alive_neighbor_count(game.current(), (0, 0), Boundary::Dead), 0);
+    }
+
+    #[test]
+    fn test_ages() {
+        let game = &mut GameOfLife::new(6, 6);
+        for cell in [(1, 1), (1, 2), (2, 1), (2, 2), (4, 0)] {
+            game.current_mut().set_state(cell, CellState::Alive);
+        }
+        game.tick();
+        game.tick();
+        assert_eq!(game.age((1, 1)), 2);
+        assert_eq!(game.age((4, 0)), 0);
+    }
+
+    #[test]
+    fn test_visits() {
+        let mut game = GameOfLife::new(5, 5);
+        Pattern::from_plaintext("", "OOO").stamp(game.current_mut(), (2, 1));
+        let mut replica = game.clone();
+        for _ in 0..4 {
+            game.tick();
+            replica.apply_changes(&game.changes());
+        }
+        // The middle of a blinker is always alive, its ends every other generation.
+        assert_eq!((game.visits((2, 2)), game.visits((1, 2)), game.visits((2, 1)), game.visits((0, 0))), (4, 2, 2, 0));
+        assert_eq!(game.max_visits(), 4);
+        assert_eq!(replica.visits((1, 2)), 2);
+        game.kill_em_all();
+        assert_eq!(game.max_visits(), 0);
+    }
+
+    #[test]
+    fn test_rule() {
+        let game = &mut GameOfLife::new(6, 6);
+        game.set_rule("B2/S".parse().unwrap());
+        game.current_mut().set_state((2, 2), CellState::Alive);
+        game.current_mut().set_state((2, 3), CellState::Alive);
+        game.tick();
+        assert_eq!(game.current().get_state((2, 2)), CellState::Dead);
+        assert_eq!(game.current().get_state((1, 2)), CellState::Alive);
+        assert_eq!(game.current().population(), 4);
+    }
+
+    #[test]
+    fn test_generations() {
+        let game = &mut GameOfLife::new(5, 5);
+        for cell in [(2, 1), (2, 2), (2, 3)] {
+            game.current_mut().set_state(cell, CellState::Alive);
+        }
+        let start = game.current().clone();
+
+        let generations: Vec<GameMatrix> = game.generations().take(4).collect();
+        assert_eq!(generations.len(), 4);
+        assert_ne!(generations[0], start);
+        assert_eq!(generations[1], start);
+        assert_eq!(generations[3], start);
+        assert!(generations.iter().all(|matrix| matrix.population() == 3));
+    }
+
+    #[test]
+    fn test_display() {
+        let game = &mut GameOfLife::new(3, 4);
+        for cell in [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)] {
+            game.current_mut().set_state(cell, CellState::Alive);
+        }
+        assert_eq!(game.current().to_string(), ".O..\n..O.\nOOO.");
+    }
+
+    #[test]
+    fn test_from_ascii() {
+        let matrix: GameMatrix = "
+            .O..
+            ..O
+            OOO.
+        ".parse().unwrap();
+        assert_eq!(matrix.shape(), (3, 4));
+        assert_eq!(matrix.population(), 5);
+        assert_eq!(matrix.to_string(), ".O..\n..O.\nOOO.");
+        assert_eq!(GameMatrix::from_ascii(&matrix.to_string()).unwrap(), matrix);
+
+        assert!(GameMatrix::from_ascii("").is_err());
+        assert!(GameMatrix::from_ascii(".O.\n.x.").is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut matrix = GameMatrix::new(4, 5);
+        matrix[(3, 4)] = CellState::Alive;
+        assert_eq!(matrix[(3, 4)], CellState::Alive);
+        assert_eq!(matrix.get_state((3, 4)), CellState::Alive);
+        assert_eq!(matrix[(0, 0)], CellState::Dead);
+    }
+
+    #[test]
+    fn test_try_accessors() {
+        let mut matrix = GameMatrix::new(4, 5);
+        assert!(matrix.try_set_state((3, 4), CellState::Alive).is_ok());
+        assert_eq!(matrix.try_get_state((3, 4)).unwrap(), CellState::Alive);
+        assert!(matches!(
+            matrix.try_set_state((4, 3), CellState::Alive),
+            Err(Error::OutOfBounds { cell: (4, 3), shape: (4, 5) })
+        ));
+        assert!(matrix.try_get_state((0, 5)).is_err());
+    }
+
+    #[test]
+    fn test_set_states() {
+        let mut matrix = GameMatrix::new(4, 5);
+        matrix.set_states(&[((0, 0), CellState::Alive), ((3, 4), CellState::Wall)]).unwrap();
+        assert_eq!((matrix[(0, 0)], matrix[(3, 4)]), (CellState::Alive, CellState::Wall));
+
+        // One cell off the grid and none are written.
+        assert!(matches!(
+            matrix.set_alive(&[(1, 1), (4, 0), (2, 2)]),
+            Err(Error::OutOfBounds { cell: (4, 0), shape: (4, 5) })
+        ));
+        assert_eq!(matrix.population(), 1);
+        matrix.set_alive(&[(1, 1), (2, 2)]).unwrap();
+        assert_eq!(matrix.population(), 3);
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut matrix = GameMatrix::new(4, 5);
+        matrix.fill_rect((1, 1), (2, 3), CellState::Alive);
+        assert_eq!(matrix.to_string(), ".....\n.OOO.\n.OOO.\n.....");
+        matrix.clear_rect((0, 2), (3, 2));
+        assert_eq!(matrix.population(), 4);
+
+        // Clipped at the edges, and nothing at all when wholly off the grid.
+        matrix.fill_rect((2, 3), (10, 10), CellState::Alive);
+        assert_eq!(matrix.population(), 7);
+        matrix.fill_rect((4, 0), (9, 9), CellState::Alive);
+        matrix.fill_rect((0, 5), (9, 9), CellState::Alive);
+        assert_eq!(matrix.population(), 7);
+    }
+
+    #[test]
+    fn test_line() {
+        assert_eq!(line((1, 1), (1, 1)), vec![(1, 1)]);
+        assert_eq!(line((0, 0), (2, 2)), vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(line((2, 0), (0, 5)), vec![(2, 0), (2, 1), (1, 2), (1, 3), (0, 4), (0, 5)]);
+        // The same cells either way round.
+        let mut back = line((0, 5), (2, 0));
+        back.reverse();
+        assert_eq!(back, line((2, 0), (0, 5)));
+
+        let mut matrix = GameMatrix::new(3, 4);
+        matrix.draw_line((0, 3), (2, 3), CellState::Wall);
+        matrix.draw_line((0, 0), (4, 4), CellState::Alive);
+        assert_eq!(matrix.to_string(), "O...\n.O..\n..O.");
+        assert!((0..3).all(|row| matrix[(row, 3)] == CellState::Wall));
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_randomize_region() {
+        let mut matrix = GameMatrix::from_ascii("
+            OOOO
+            O..O
+            O..O
+            OOOO
+        ").unwrap();
+        matrix.set_state((1, 2), CellState::Wall);
+        let region = BoundingBox { top: 1, left: 1, bottom: 2, right: 9 };
+        matrix.randomize_region(region, 1.0, &mut StdRng::seed_from_u64(1));
+        assert_eq!(matrix.to_string(), "OOOO\nOO.O\nOOOO\nOOOO");
+        assert_eq!(matrix[(1, 2)], CellState::Wall);
+        matrix.randomize_region(region, 0.0, &mut StdRng::seed_from_u64(1));
+        assert_eq!(matrix.to_string(), "OOOO\nO...\nO...\nOOOO");
+    }
+
+    #[test]
+    fn test_map_cells() {
+        let mut matrix = GameMatrix::from_ascii("
+            O..
+            .O.
+        ").unwrap();
+        // Inverting the board.
+        matrix.map_cells(|_, state| if state.is_alive() { CellState::Dead } else { CellState::Alive });
+        assert_eq!(matrix.to_string(), ".OO\nO.O");
+        // Masking off the last column.
+        matrix.map_cells(|(_, column), state| if column == 2 { CellState::Dead } else { state });
+        assert_eq!(matrix.to_string(), ".O.\nO..");
+
+        let mut alive = vec![];
+        matrix.for_each_cell(|cell, state| if state.is_alive() { alive.push(cell) });
+        assert_eq!(alive, vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_neighbor_counts() {
+        let mut game = GameOfLife::builder().size(7, 9).density(0.4).seed(3).build().unwrap();
+        for boundary in [Boundary::Wrap, Boundary::Dead] {
+            game.set_boundary(boundary);
+            let counts = game.current().neighbor_counts(boundary);
+            for row in 0..7 {
+                for column in 0..9 {
+                    assert_eq!(counts[row * 9 + column] as usize, game.current().alive_neighbor_count((row, column), boundary));
+                }
+            }
+        }
+        // Tiny wrapped grids count a cell's neighbors as often as they wrap onto it.
+        let tiny = GameMatrix::from_ascii("O").unwrap();
+        assert_eq!(tiny.neighbor_counts(Boundary::Wrap), vec![8]);
+        assert_eq!(tiny.neighbor_counts(Boundary::Dead), vec![0]);
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut game = GameOfLife::from_cells(5, 5, &[(1, 1), (1, 2), (2, 1), (2, 2)]).unwrap();
+        game.tick();
+        // The block is split across the wrapped corners.
+        game.shift(-2, 3);
+        assert_eq!(game.current().to_string(), "O...O\n.....\n.....\n.....\nO...O");
+        assert_eq!(game.bounding_box().map(|bounds| bounds.shape()), Some((5, 5)));
+        assert_eq!(game.age((0, 0)), 1);
+
+        game.set_boundary(Boundary::Dead);
+        game.shift(0, 1);
+        assert_eq!(game.current().to_string(), ".O...\n.....\n.....\n.....\n.O...");
+        assert_eq!(game.age((4, 1)), 1);
+    }
+
+    #[test]
+    fn test_walls() {
+        // The corner of this L would be born, making a block, but it is walled off.
+        let mut game = GameOfLife::from_cells(5, 5, &[(1, 1), (1, 2), (2, 1)]).unwrap();
+        game.current_mut().set_state((2, 2), CellState::Wall);
+        assert_eq!(game.current().alive_neighbor_count((1, 1), Boundary::Wrap), 2);
+        for _ in 0..3 {
+            game.tick();
+            assert_eq!(game.current().get_state((2, 2)), CellState::Wall);
+            assert_eq!(game.current().population(), 3);
+        }
+    }
+
+    #[test]
+    fn test_immortal() {
+        // A lone cell would die at once; an immortal one stays, and with a live neighbor on
+        // each side of it makes a blinker whose middle never flickers.
+        let mut game = GameOfLife::from_cells(5, 5, &[(2, 1), (2, 3)]).unwrap();
+        game.current_mut().set_state((2, 2), CellState::Immortal);
+        assert_eq!(game.current().alive_neighbor_count((2, 1), Boundary::Wrap), 1);
+        game.tick();
+        assert_eq!(game.current().to_string(), ".....\n..O..\n..O..\n..O..\n.....");
+        assert_eq!(game.current().get_state((2, 2)), CellState::Immortal);
+        assert_eq!(game.age((2, 2)), 1);
+        game.kill_em_all();
+        game.current_mut().set_state((0, 0), CellState::Immortal);
+        game.tick();
+        assert_eq!(game.current().population(), 1);
+    }
+
+    #[test]
+    fn test_region_rules() {
+        // The same domino on both sides of the seam: it dies out under Life on the left and
+        // grows under Seeds on the right.
+        let mut game = GameOfLife::from_cells(10, 20, &[(4, 4), (5, 4), (4, 14), (5, 14)]).unwrap();
+        game.set_boundary(Boundary::Dead);
+        let right = BoundingBox { top: 0, left: 10, bottom: 9, right: 19 };
+        game.set_region_rule(right, "B2/S".parse().unwrap());
+        assert_eq!(game.rule_at((4, 4)), Rule::life());
+        assert_eq!(game.rule_at((4, 14)).to_string(), "B2/S");
+        game.tick();
+        assert!((0..10).all(|row| (0..10).all(|column| !game.current().get_state((row, column)).is_alive())));
+        assert_eq!(game.current().population(), 4);
+        assert!(right.contains((4, 13)) && right.contains((5, 15)));
+
+        // At the seam each cell runs its own region's rule: the dead cells either side of it
+        // have the same two neighbors, one in each region, but only the right one is born.
+        let mut game = GameOfLife::from_cells(10, 20, &[(3, 9), (5, 10)]).unwrap();
+        game.set_region_rule(right, "B2/S".parse().unwrap());
+        game.tick();
+        assert_eq!(game.current().get_state((4, 9)), CellState::Dead);
+        assert_eq!(game.current().get_state((4, 10)), CellState::Alive);
+        game.clear_region_rules();
+        assert!(game.region_rules().is_empty());
+    }
+
+    #[test]
+    fn test_freeze() {
+        // A blinker with its middle column frozen stays upright, while one left alone turns.
+        let mut game = GameOfLife::from_cells(5, 8, &[(1, 1), (2, 1), (3, 1), (1, 5), (2, 5), (3, 5)]).unwrap();
+        game.freeze(BoundingBox { top: 0, left: 1, bottom: 9, right: 1 }, true);
+        assert!(game.is_frozen((4, 1)) && !game.is_frozen((2, 2)));
+        game.tick();
+        assert_eq!(game.current().to_string(), "........\n.O......\nOOO.OOO.\n.O......\n........");
+
+        game.freeze(BoundingBox { top: 2, left: 1, bottom: 2, right: 1 }, false);
+        assert!(!game.is_frozen((2, 1)) && game.is_frozen((1, 1)));
+        game.thaw_all();
+        assert!(!game.is_frozen((1, 1)));
+    }
+
+    #[test]
+    fn test_from_cells() {
+        let glider = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        let mut game = GameOfLife::from_cells(6, 6, &glider).unwrap();
+        assert_eq!(game.shape(), (6, 6));
+        assert_eq!(game.current().to_string(), ".O....\n..O...\nOOO...\n......\n......\n......");
+
+        let mut replica = GameOfLife::from_matrix(game.current().clone());
+        game.tick();
+        replica.tick();
+        assert_eq!(replica.current(), game.current());
+
+        assert!(matches!(
+            GameOfLife::from_cells(6, 6, &[(6, 0)]),
+            Err(Error::OutOfBounds { cell: (6, 0), shape: (6, 6) })
+        ));
+    }
+
+    #[derive(Clone, Copy, PartialEq, Debug, Default)]
+    enum BriansBrain {
+        #[default]
+        Off,
+        On,
+        Dying,
+    }
+
+    impl CellType for BriansBrain {
+        fn is_alive(&self) -> bool {
+            *self == BriansBrain::On
+        }
+    }
+
+    #[test]
+    fn test_evolve_other_automaton() {
+        let mut matrix: GameMatrix<BriansBrain> = GameMatrix::new(5, 5);
+        matrix[(2, 1)] = BriansBrain::On;
+        matrix[(2, 2)] = BriansBrain::On;
+
+        let next = |state: BriansBrain, neighbors: &[BriansBrain]| match state {
+            BriansBrain::On => BriansBrain::Dying,
+            BriansBrain::Dying => BriansBrain::Off,
+            BriansBrain::Off if neighbors.iter().filter(|state| state.is_alive()).count() == 2 => BriansBrain::On,
+            BriansBrain::Off => BriansBrain::Off,
+        };
+        let matrix = matrix.evolve(Boundary::Wrap, next);
+        assert_eq!(matrix[(2, 1)], BriansBrain::Dying);
+        assert_eq!(matrix.to_string(), ".....\n.OO..\n.....\n.OO..\n.....");
+        assert_eq!(matrix.alive_neighbor_count((2, 1), Boundary::Wrap), 4);
+    }
+
+    #[test]
+    fn test_cell_state_is_one_byte() {
+        assert_eq!(std::mem::size_of::<CellState>(), 1);
+        assert_eq!(CellState::Alive as u8, 1);
+        assert_eq!(CellState::default() as u8, 0);
+    }
+
+    #[test]
+    fn test_glider_moves() {
+        let mut game = GameOfLife::new(6, 6);
+        *game.current_mut() = ".O....\n..O...\nOOO...\n......\n......\n......".parse().unwrap();
+        game.generations().nth(3);
+        assert_eq!(game.current().to_string(), "......\n..O...\n...O..\n.OOO..\n......\n......");
+    }
+
+    #[test]
+    fn test_changes() {
+        let mut game = GameOfLife::new(5, 5);
+        *game.current_mut() = ".....\n.....\n.OOO.\n.....\n.....".parse().unwrap();
+        let mut replica = game.clone();
+        game.tick();
+
+        let changes = game.changes();
+        assert_eq!(changes.len(), 4);
+        assert!(changes.contains(&CellChange { cell: (1, 2), state: CellState::Alive }));
+        assert!(changes.contains(&CellChange { cell: (2, 1), state: CellState::Dead }));
+
+        replica.apply_changes(&changes);
+        assert_eq!(replica.current(), game.current());
+        assert_eq!(replica.previous(), game.previous());
+        assert_eq!(replica.age((2, 2)), game.age((2, 2)));
+    }
+
+    #[test]
+    fn test_tick_report() {
+        // A glider's tick births two cells and kills two, against an unchanged population.
+        let mut game = GameOfLife::new(6, 6);
+        *game.current_mut() = "......\n..O...\n...O..\n.OOO..\n......\n......".parse().unwrap();
+        let mut replica = game.clone();
+        let report = game.tick_report();
+        assert_eq!((report.births, report.deaths, report.population), (2, 2, 5));
+        assert_eq!(report.changes, game.changes());
+        replica.tick();
+        assert_eq!(replica.current(), game.current());
+    }
+
+    #[test]
+    fn test_tick_hooks() {
+        use std::sync::{Arc, Mutex};
+
+        // A blinker gets a block added beside it every other generation, and the populations
+        // after each tick are logged.
+        let mut game = GameOfLife::new(6, 6);
+        game.set_boundary(Boundary::Dead);
+        *game.current_mut() = "......\n.OOO..\n......\n......\n......\n......".parse().unwrap();
+        let mut ticks = 0;
+        game.on_pre_tick(move |game| {
+            ticks += 1;
+            if ticks % 2 == 0 {
+                for cell in [(4, 4), (4, 5), (5, 4), (5, 5)] {
+                    game.current_mut().set_state(cell, CellState::Alive);
+                }
+            }
+        });
+        let populations = Arc::new(Mutex::new(vec![]));
+        let log = Arc::clone(&populations);
+        game.on_post_tick(move |game| log.lock().unwrap().push(game.current().population()));
+        let mut clone = game.clone();
+        game.generations().nth(2);
+        assert_eq!(*populations.lock().unwrap(), [3, 7, 7]);
+
+        // Clones leave the hooks behind.
+        clone.tick();
+        assert_eq!(populations.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let mut game = GameOfLife::new(6, 6);
+        assert_eq!(game.bounding_box(), None);
+        *game.current_mut() = "......\n......\n.OOO..\n......\n......\n......".parse().unwrap();
+        let bounds = game.bounding_box().unwrap();
+        assert_eq!((bounds.top_left(), bounds.shape(), bounds.center()), ((2, 1), (1, 3), (2, 2)));
+
+        let mut replica = game.clone();
+        game.tick();
+        assert_eq!(game.bounding_box(), Some(BoundingBox { top: 1, left: 2, bottom: 3, right: 2 }));
+        replica.apply_changes(&game.changes());
+        assert_eq!(replica.bounding_box(), game.bounding_box());
+
+        game.current_mut().set_state((5, 5), CellState::Alive);
+        assert_eq!(game.bounding_box(), Some(BoundingBox { top: 1, left: 2, bottom: 5, right: 5 }));
+        game.kill_em_all();
+        assert_eq!(game.bounding_box(), None);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let matrix: GameMatrix = "OO.\n.O.".parse().unwrap();
+        assert_eq!(matrix.fingerprint(), 0x841b_149f_307e_fa63);
+        assert_eq!(matrix.clone().fingerprint(), matrix.fingerprint());
+        assert_ne!("OO.\n..O".parse::<GameMatrix>().unwrap().fingerprint(), matrix.fingerprint());
+        assert_ne!(GameMatrix::new(2, 3).fingerprint(), GameMatrix::new(3, 2).fingerprint());
+    }
+
     #[test]
     fn test_get_offset() {
         assert_eq!(get_offset(0, -1, 10), 9);
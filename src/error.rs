@@ -0,0 +1,47 @@
+use std::fmt;
+use std::io;
+use crate::engine::Cell;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A cell outside of a grid of the given shape was accessed.
+    OutOfBounds { cell: Cell, shape: (usize, usize) },
+    /// A pattern, board or rule could not be parsed.
+    Parse(String),
+    /// An option passed to a builder or command is out of range.
+    InvalidOption(String),
+    /// A move in a two player game broke its rules.
+    IllegalMove(String),
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfBounds { cell, shape } => {
+                write!(f, "cell {:?} is outside of the {}x{} grid", cell, shape.0, shape.1)
+            }
+            Error::Parse(message) => write!(f, "parse error: {}", message),
+            Error::InvalidOption(message) => write!(f, "invalid option: {}", message),
+            Error::IllegalMove(message) => write!(f, "illegal move: {}", message),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
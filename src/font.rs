@@ -0,0 +1,200 @@
+use crate::engine::Cell;
+use crate::pattern::Pattern;
+
+/// A built-in bitmap font for turning text into living cells with `Font::render`. Each covers
+/// the letters, which are drawn in capitals, the digits and a little punctuation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Font {
+    /// Five columns by seven rows.
+    Standard,
+    /// Three columns by five rows, for fitting more text on a small grid.
+    Small,
+}
+
+/// Each glyph's rows, top to bottom, with the leftmost column in the highest of the font's
+/// `width` bits.
+const STANDARD: &[(char, [u8; 7])] = &[
+    ('A', [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('B', [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e]),
+    ('C', [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e]),
+    ('D', [0x1e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1e]),
+    ('E', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f]),
+    ('F', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10]),
+    ('G', [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f]),
+    ('H', [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('I', [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f]),
+    ('M', [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('P', [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10]),
+    ('Q', [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d]),
+    ('R', [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11]),
+    ('S', [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e]),
+    ('T', [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a]),
+    ('X', [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x0a, 0x04, 0x04, 0x04, 0x04]),
+    ('Z', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f]),
+    ('0', [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e]),
+    ('1', [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('2', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f]),
+    ('3', [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e]),
+    ('4', [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02]),
+    ('5', [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e]),
+    ('6', [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e]),
+    ('7', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e]),
+    ('9', [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c]),
+    (' ', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('!', [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04]),
+    ('?', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c]),
+    (',', [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08]),
+    ('-', [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00]),
+    (':', [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00]),
+    ('\'', [0x0c, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00]),
+];
+
+const SMALL: &[(char, [u8; 5])] = &[
+    ('A', [2, 5, 7, 5, 5]),
+    ('B', [6, 5, 6, 5, 6]),
+    ('C', [3, 4, 4, 4, 3]),
+    ('D', [6, 5, 5, 5, 6]),
+    ('E', [7, 4, 6, 4, 7]),
+    ('F', [7, 4, 6, 4, 4]),
+    ('G', [3, 4, 5, 5, 3]),
+    ('H', [5, 5, 7, 5, 5]),
+    ('I', [7, 2, 2, 2, 7]),
+    ('J', [1, 1, 1, 5, 2]),
+    ('K', [5, 5, 6, 5, 5]),
+    ('L', [4, 4, 4, 4, 7]),
+    ('M', [5, 7, 7, 5, 5]),
+    ('N', [6, 5, 5, 5, 5]),
+    ('O', [2, 5, 5, 5, 2]),
+    ('P', [6, 5, 6, 4, 4]),
+    ('Q', [2, 5, 5, 6, 3]),
+    ('R', [6, 5, 6, 5, 5]),
+    ('S', [3, 4, 2, 1, 6]),
+    ('T', [7, 2, 2, 2, 2]),
+    ('U', [5, 5, 5, 5, 7]),
+    ('V', [5, 5, 5, 5, 2]),
+    ('W', [5, 5, 7, 7, 5]),
+    ('X', [5, 5, 2, 5, 5]),
+    ('Y', [5, 5, 2, 2, 2]),
+    ('Z', [7, 1, 2, 4, 7]),
+    ('0', [7, 5, 5, 5, 7]),
+    ('1', [2, 6, 2, 2, 7]),
+    ('2', [6, 1, 2, 4, 7]),
+    ('3', [6, 1, 2, 1, 6]),
+    ('4', [5, 5, 7, 1, 1]),
+    ('5', [7, 4, 6, 1, 6]),
+    ('6', [3, 4, 7, 5, 7]),
+    ('7', [7, 1, 2, 2, 2]),
+    ('8', [7, 5, 7, 5, 7]),
+    ('9', [7, 5, 7, 1, 6]),
+    (' ', [0, 0, 0, 0, 0]),
+    ('!', [2, 2, 2, 0, 2]),
+    ('?', [6, 1, 2, 0, 2]),
+    ('.', [0, 0, 0, 0, 2]),
+    (',', [0, 0, 0, 2, 4]),
+    ('-', [0, 0, 7, 0, 0]),
+    (':', [0, 2, 0, 2, 0]),
+    ('\'', [2, 2, 0, 0, 0]),
+];
+
+impl Font {
+    /// Accepts the font's size, `5x7` or `3x5`.
+    pub fn from_name(name: &str) -> Option<Font> {
+        match name {
+            "5x7" => Some(Font::Standard),
+            "3x5" => Some(Font::Small),
+            _ => None,
+        }
+    }
+
+    /// The rows and columns of every glyph.
+    pub fn shape(self) -> (usize, usize) {
+        match self {
+            Font::Standard => (7, 5),
+            Font::Small => (5, 3),
+        }
+    }
+
+    /// The rows of the glyph for `symbol`, in capitals, with `?` standing in for the symbols the
+    /// font lacks.
+    fn glyph(self, symbol: char) -> &'static [u8] {
+        let symbol = symbol.to_ascii_uppercase();
+        let find = |symbol| match self {
+            Font::Standard => STANDARD.iter().find(|(c, _)| *c == symbol).map(|(_, rows)| &rows[..]),
+            Font::Small => SMALL.iter().find(|(c, _)| *c == symbol).map(|(_, rows)| &rows[..]),
+        };
+        find(symbol).or_else(|| find('?')).unwrap_or_default()
+    }
+
+    /// Draws `text` as living cells, a column between characters and a row between lines, with
+    /// every line centered under the widest.
+    pub fn render(self, text: &str) -> Pattern {
+        let (height, width) = self.shape();
+        let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        let line_width = |line: &[char]| (line.len() * (width + 1)).saturating_sub(1);
+        let widest = lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+
+        let mut cells: Vec<Cell> = vec![];
+        for (index, line) in lines.iter().enumerate() {
+            let top = index * (height + 1);
+            let indent = (widest - line_width(line)) / 2;
+            for (position, symbol) in line.iter().enumerate() {
+                let left = indent + position * (width + 1);
+                for (row, bits) in self.glyph(*symbol).iter().enumerate() {
+                    let columns = (0..width).filter(|column| bits & (1 << (width - 1 - column)) != 0);
+                    cells.extend(columns.map(|column| (top + row, left + column)));
+                }
+            }
+        }
+        // Pattern files keep names to one line.
+        Pattern::new(&text.lines().collect::<Vec<_>>().join(" "), cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let pattern = Font::Small.render("Hi");
+        assert_eq!(pattern.name(), "Hi");
+        assert_eq!(pattern, Pattern::from_plaintext("Hi", "\
+            O.O.OOO\n\
+            O.O..O.\n\
+            OOO..O.\n\
+            O.O..O.\n\
+            O.O.OOO"));
+    }
+
+    #[test]
+    fn test_lines_are_centered() {
+        // The second line is two glyphs narrower, so it starts six columns in.
+        let pattern = Font::Standard.render("LIFE\nGO");
+        assert_eq!(pattern.name(), "LIFE GO");
+        assert_eq!(pattern.shape(), (15, 23));
+        let second = pattern.cells().iter().filter(|(row, _)| *row >= 8).map(|(_, column)| *column);
+        assert_eq!(second.min(), Some(6));
+    }
+
+    #[test]
+    fn test_every_glyph_fits() {
+        for font in [Font::Standard, Font::Small] {
+            let (_, width) = font.shape();
+            for symbol in "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 !?.,-:'".chars() {
+                assert!(font.glyph(symbol).iter().all(|bits| *bits < 1 << width), "{:?} {}", font, symbol);
+            }
+            assert_eq!(font.glyph('~'), font.glyph('?'));
+        }
+    }
+}
@@ -0,0 +1,592 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use crate::engine::{Boundary, CellType, GameMatrix};
+use crate::error::{Error, Result};
+
+/// A state of a multi-state automaton, `0` being the quiescent background new grids are filled
+/// with and every other state counting as alive.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct GollyState(pub u8);
+
+impl CellType for GollyState {
+    fn is_alive(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Neighborhood {
+    /// The eight surrounding cells.
+    Moore,
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+}
+
+/// The row and column offsets of the neighbors clockwise from north, the order `next_state`
+/// takes them in and Golly's tables list them in.
+const CLOCKWISE: [(isize, isize); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+
+/// Where the von Neumann neighbors, north, east, south and west, are in `CLOCKWISE`.
+const VON_NEUMANN: [usize; 4] = [0, 2, 4, 6];
+
+/// A user-defined automaton read from one of Golly's `.rule` files, from its `@TABLE` or, failing
+/// that, its `@TREE` section; other sections, such as colors and icons, are skipped. Tables take
+/// `n_states`, `neighborhood` (`Moore` or `vonNeumann`), `symmetries` (`none`, `rotate4`,
+/// `rotate8`, `reflect_horizontal`, `rotate4reflect`, `rotate8reflect` or `permute`), `var`
+/// declarations and transitions, the first that matches deciding a cell's next state; as in
+/// Golly, a variable used twice in a transition stands for the same state both times.
+pub struct GollyRule {
+    name: String,
+    states: usize,
+    neighborhood: Neighborhood,
+    kind: Kind,
+}
+
+enum Kind {
+    Table { transitions: Vec<Transition>, symmetries: Symmetries },
+    /// Nodes as Golly numbers them, each with its level: level 1 nodes hold next states, higher
+    /// ones the indices of the nodes a level down. The last node is the root.
+    Tree(Vec<(usize, Vec<usize>)>),
+}
+
+enum Symmetries {
+    /// The rearrangements of the neighbors a transition also applies to: position `j` of each
+    /// is the neighbor a transition's `j`th neighbor is compared with.
+    Fixed(Vec<Vec<usize>>),
+    /// Any order of the neighbors.
+    Permute,
+}
+
+/// A cell or neighbor in a transition: one of a set of states, bound to the same state wherever
+/// the variable it was written as appears in the transition.
+#[derive(Clone)]
+enum Term {
+    Any(Vec<u8>),
+    Bound(usize, Vec<u8>),
+}
+
+impl Term {
+    fn bind(&self, state: u8, bindings: &mut [Option<u8>]) -> bool {
+        match self {
+            Term::Any(states) => states.contains(&state),
+            Term::Bound(slot, states) => match bindings[*slot] {
+                Some(bound) => bound == state,
+                None if states.contains(&state) => {
+                    bindings[*slot] = Some(state);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// The state a transition's output stands for, which parsing made sure is a single state or
+    /// bound by the inputs.
+    fn value(&self, bindings: &[Option<u8>]) -> u8 {
+        match self {
+            Term::Any(states) => states[0],
+            Term::Bound(slot, _) => bindings[*slot].unwrap_or_default(),
+        }
+    }
+}
+
+struct Transition {
+    center: Term,
+    neighbors: Vec<Term>,
+    next: Term,
+    /// How many variables the transition binds.
+    slots: usize,
+}
+
+impl Transition {
+    fn apply(&self, center: u8, neighbors: &[u8], symmetries: &Symmetries) -> Option<u8> {
+        let mut bindings = vec![None; self.slots];
+        if !self.center.bind(center, &mut bindings) {
+            return None;
+        }
+        match symmetries {
+            Symmetries::Fixed(orders) => orders.iter().find_map(|order| {
+                let mut bindings = bindings.clone();
+                self.neighbors.iter().zip(order)
+                    .all(|(term, position)| term.bind(neighbors[*position], &mut bindings))
+                    .then(|| self.next.value(&bindings))
+            }),
+            Symmetries::Permute => self.permute(0, neighbors, &mut [false; 8], &bindings),
+        }
+    }
+
+    /// Matches the neighbors from the `term`th on with the neighbors not `used` yet, in any order.
+    fn permute(&self, term: usize, neighbors: &[u8], used: &mut [bool; 8], bindings: &[Option<u8>]) -> Option<u8> {
+        if term == self.neighbors.len() {
+            return Some(self.next.value(bindings));
+        }
+        let mut tried = vec![];
+        for position in 0..neighbors.len() {
+            // Neighbors in the same state are interchangeable, so each state is tried once.
+            if used[position] || tried.contains(&neighbors[position]) {
+                continue;
+            }
+            tried.push(neighbors[position]);
+            let mut bound = bindings.to_vec();
+            if self.neighbors[term].bind(neighbors[position], &mut bound) {
+                used[position] = true;
+                let next = self.permute(term + 1, neighbors, used, &bound);
+                used[position] = false;
+                if next.is_some() {
+                    return next;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl GollyRule {
+    pub fn load<P>(path: P) -> Result<GollyRule>
+        where P: AsRef<Path>
+    {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// The name given after `@RULE`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many states cells can be in, the background `0` included.
+    pub fn states(&self) -> usize {
+        self.states
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// The next state of a cell in state `center` with the given neighbors, clockwise from north;
+    /// only the north, east, south and west ones matter in a von Neumann neighborhood. Cells in or
+    /// next to a state beyond the rule's stay as they are.
+    pub fn next_state(&self, center: u8, neighbors: &[u8; 8]) -> u8 {
+        if usize::from(center) >= self.states || neighbors.iter().any(|state| usize::from(*state) >= self.states) {
+            return center;
+        }
+        match &self.kind {
+            Kind::Table { transitions, symmetries } => {
+                let neighbors = match self.neighborhood {
+                    Neighborhood::Moore => neighbors.to_vec(),
+                    Neighborhood::VonNeumann => VON_NEUMANN.iter().map(|position| neighbors[*position]).collect(),
+                };
+                transitions.iter()
+                    .find_map(|transition| transition.apply(center, &neighbors, symmetries))
+                    .unwrap_or(center)
+            }
+            Kind::Tree(nodes) => {
+                // Golly's trees branch on the neighbors in this order, then on the cell itself.
+                let order: &[usize] = match self.neighborhood {
+                    Neighborhood::Moore => &[7, 1, 5, 3, 0, 6, 2, 4],
+                    Neighborhood::VonNeumann => &[0, 6, 2, 4],
+                };
+                let node = order.iter().fold(nodes.len() - 1, |node, position| nodes[node].1[usize::from(neighbors[*position])]);
+                nodes[node].1[usize::from(center)] as u8
+            }
+        }
+    }
+
+    /// Computes the next generation of `matrix`. With a dead boundary everything beyond the edges
+    /// is in state `0`.
+    pub fn step(&self, matrix: &GameMatrix<GollyState>, boundary: Boundary) -> GameMatrix<GollyState> {
+        let (rows, columns) = matrix.shape();
+        let mut next = matrix.clone();
+        // Most cells share their surroundings with many others, so each is only worked out once.
+        let mut known = HashMap::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let mut neighbors = [0; 8];
+                for (neighbor, (d_row, d_column)) in neighbors.iter_mut().zip(CLOCKWISE) {
+                    let (neighbor_row, neighbor_column) = (row as isize + d_row, column as isize + d_column);
+                    *neighbor = match boundary {
+                        Boundary::Wrap => matrix.get_state((
+                            neighbor_row.rem_euclid(rows as isize) as usize,
+                            neighbor_column.rem_euclid(columns as isize) as usize,
+                        )).0,
+                        Boundary::Dead if (0..rows as isize).contains(&neighbor_row)
+                            && (0..columns as isize).contains(&neighbor_column) => {
+                            matrix.get_state((neighbor_row as usize, neighbor_column as usize)).0
+                        }
+                        Boundary::Dead => 0,
+                    };
+                }
+                let center = matrix.get_state((row, column)).0;
+                let state = *known.entry((center, neighbors)).or_insert_with(|| self.next_state(center, &neighbors));
+                next.set_state((row, column), GollyState(state));
+            }
+        }
+        next
+    }
+}
+
+impl FromStr for GollyRule {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<GollyRule> {
+        let mut name = None;
+        let mut section = "";
+        let (mut table, mut tree) = (vec![], vec![]);
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some(header) = line.strip_prefix('@') {
+                let (header, rest) = header.split_once(char::is_whitespace).unwrap_or((header, ""));
+                section = header;
+                if section == "RULE" {
+                    name = Some(rest.trim().to_string());
+                }
+            } else if !line.is_empty() {
+                match section {
+                    "TABLE" => table.push((index + 1, line)),
+                    "TREE" => tree.push((index + 1, line)),
+                    _ => {}
+                }
+            }
+        }
+        let name = name.filter(|name| !name.is_empty()).ok_or_else(|| Error::Parse("missing @RULE name".to_string()))?;
+        let (states, neighborhood, kind) = if !table.is_empty() {
+            parse_table(&table)?
+        } else if !tree.is_empty() {
+            parse_tree(&tree)?
+        } else {
+            return Err(Error::Parse(format!("rule {} has no @TABLE or @TREE section", name)));
+        };
+        Ok(GollyRule { name, states, neighborhood, kind })
+    }
+}
+
+fn invalid(line: usize, message: String) -> Error {
+    Error::Parse(format!("line {}: {}", line, message))
+}
+
+/// The value of a `key:value` setting, or `key=value` in a tree.
+fn setting<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(key)?.trim_start();
+    rest.strip_prefix(':').or_else(|| rest.strip_prefix('=')).map(str::trim)
+}
+
+fn parse_count(line: usize, value: &str) -> Result<usize> {
+    value.parse().map_err(|_| invalid(line, format!("invalid number '{}'", value)))
+}
+
+fn parse_table(lines: &[(usize, &str)]) -> Result<(usize, Neighborhood, Kind)> {
+    let mut states = None;
+    let mut neighborhood = Neighborhood::Moore;
+    let mut symmetries = "none";
+    let mut variables: HashMap<&str, Vec<u8>> = HashMap::new();
+    let mut transitions = vec![];
+    for &(line, text) in lines {
+        if let Some(value) = setting(text, "n_states") {
+            let count = parse_count(line, value)?;
+            if !(2..=256).contains(&count) {
+                return Err(invalid(line, format!("{} states is not between 2 and 256", count)));
+            }
+            states = Some(count);
+        } else if let Some(value) = setting(text, "neighborhood") {
+            neighborhood = match value.to_lowercase().as_str() {
+                "moore" => Neighborhood::Moore,
+                "vonneumann" => Neighborhood::VonNeumann,
+                _ => return Err(invalid(line, format!("unsupported neighborhood '{}'", value))),
+            };
+        } else if let Some(value) = setting(text, "symmetries") {
+            symmetries = value;
+        } else {
+            let states = states.ok_or_else(|| invalid(line, "n_states must come first".to_string()))?;
+            if let Some(declaration) = text.strip_prefix("var ") {
+                let (name, set) = declaration.split_once('=')
+                    .ok_or_else(|| invalid(line, format!("invalid variable '{}'", declaration)))?;
+                let set = parse_set(line, set.trim(), &variables, states)?;
+                variables.insert(name.trim(), set);
+            } else {
+                transitions.push(parse_transition(line, text, &variables, states, neighborhood)?);
+            }
+        }
+    }
+    let states = states.ok_or_else(|| Error::Parse("missing n_states".to_string()))?;
+    let line = lines.last().map_or(0, |(line, _)| *line);
+    let symmetries = parse_symmetries(symmetries, neighborhood).ok_or_else(|| {
+        invalid(line, format!("unsupported symmetries '{}' for this neighborhood", symmetries))
+    })?;
+    Ok((states, neighborhood, Kind::Table { transitions, symmetries }))
+}
+
+/// A state, a variable or a set of them in braces, e.g. `{0,2,a}`.
+fn parse_set(line: usize, text: &str, variables: &HashMap<&str, Vec<u8>>, states: usize) -> Result<Vec<u8>> {
+    let items = match text.strip_prefix('{').and_then(|text| text.strip_suffix('}')) {
+        Some(items) => items.split(',').collect(),
+        None => vec![text],
+    };
+    let mut set = vec![];
+    for item in items.into_iter().map(str::trim) {
+        match (item.parse::<usize>(), variables.get(item)) {
+            (Ok(state), _) if state < states => set.push(state as u8),
+            (Ok(state), _) => return Err(invalid(line, format!("state {} is not below n_states {}", state, states))),
+            (_, Some(values)) => set.extend(values),
+            _ => return Err(invalid(line, format!("unknown variable '{}'", item))),
+        }
+    }
+    Ok(set)
+}
+
+/// A transition as comma separated terms, or as one digit per state when all are below ten.
+fn parse_transition(
+    line: usize,
+    text: &str,
+    variables: &HashMap<&str, Vec<u8>>,
+    states: usize,
+    neighborhood: Neighborhood,
+) -> Result<Transition> {
+    let terms: Vec<String> = if text.contains(',') {
+        // Commas inside braces separate the states of a set, not terms.
+        let mut terms = vec![String::new()];
+        let mut depth = 0;
+        for c in text.chars() {
+            match c {
+                ',' if depth == 0 => terms.push(String::new()),
+                _ => {
+                    depth += (c == '{') as i32 - (c == '}') as i32;
+                    terms.last_mut().unwrap().push(c);
+                }
+            }
+        }
+        terms.into_iter().map(|term| term.trim().to_string()).collect()
+    } else {
+        text.chars().filter(|c| !c.is_whitespace()).map(String::from).collect()
+    };
+    let expected = match neighborhood {
+        Neighborhood::Moore => 10,
+        Neighborhood::VonNeumann => 6,
+    };
+    if terms.len() != expected {
+        return Err(invalid(line, format!("expected {} states in a transition, found {}", expected, terms.len())));
+    }
+
+    let mut slots: HashMap<&str, usize> = HashMap::new();
+    let mut inputs = vec![];
+    for term in &terms[..expected - 1] {
+        let set = parse_set(line, term, variables, states)?;
+        inputs.push(match variables.contains_key(term.as_str()) {
+            true => {
+                let count = slots.len();
+                Term::Bound(*slots.entry(term).or_insert(count), set)
+            }
+            false => Term::Any(set),
+        });
+    }
+    let output = &terms[expected - 1];
+    let next = match (slots.get(output.as_str()), parse_set(line, output, variables, states)?) {
+        (Some(slot), set) => Term::Bound(*slot, set),
+        (None, set) if set.len() == 1 && !variables.contains_key(output.as_str()) => Term::Any(set),
+        _ => return Err(invalid(line, format!("the new state '{}' must be a state or a variable used before it", output))),
+    };
+    let center = inputs.remove(0);
+    Ok(Transition { center, neighbors: inputs, next, slots: slots.len() })
+}
+
+fn parse_symmetries(name: &str, neighborhood: Neighborhood) -> Option<Symmetries> {
+    let (count, quarter) = match neighborhood {
+        Neighborhood::Moore => (8, 2),
+        Neighborhood::VonNeumann => (4, 1),
+    };
+    let (turns, reflect): (Vec<usize>, bool) = match name {
+        "none" => (vec![0], false),
+        "rotate4" => ((0..4).map(|turn| turn * quarter).collect(), false),
+        "rotate8" if neighborhood == Neighborhood::Moore => ((0..8).collect(), false),
+        "reflect_horizontal" => (vec![0], true),
+        "rotate4reflect" => ((0..4).map(|turn| turn * quarter).collect(), true),
+        "rotate8reflect" if neighborhood == Neighborhood::Moore => ((0..8).collect(), true),
+        "permute" => return Some(Symmetries::Permute),
+        _ => return None,
+    };
+    let mut orders = vec![];
+    for turn in turns {
+        orders.push((0..count).map(|position| (position + turn) % count).collect());
+        if reflect {
+            // Mirroring left to right keeps north and south and swaps the sides.
+            orders.push((0..count).map(|position| ((count - position) % count + turn) % count).collect());
+        }
+    }
+    Some(Symmetries::Fixed(orders))
+}
+
+fn parse_tree(lines: &[(usize, &str)]) -> Result<(usize, Neighborhood, Kind)> {
+    let (mut states, mut neighbors) = (None, None);
+    let mut nodes: Vec<(usize, Vec<usize>)> = vec![];
+    for &(line, text) in lines {
+        if let Some(value) = setting(text, "num_states") {
+            states = Some(parse_count(line, value)?).filter(|states| (2..=256).contains(states));
+            if states.is_none() {
+                return Err(invalid(line, format!("{} states is not between 2 and 256", value)));
+            }
+        } else if let Some(value) = setting(text, "num_neighbors") {
+            neighbors = Some(match value {
+                "8" => Neighborhood::Moore,
+                "4" => Neighborhood::VonNeumann,
+                _ => return Err(invalid(line, format!("unsupported number of neighbors {}", value))),
+            });
+        } else if setting(text, "num_nodes").is_some() {
+            // The nodes are counted as they come.
+        } else {
+            let states = states.ok_or_else(|| invalid(line, "num_states must come first".to_string()))?;
+            let numbers = text.split_whitespace().map(|number| parse_count(line, number)).collect::<Result<Vec<_>>>()?;
+            let (level, children) = numbers.split_first().ok_or_else(|| invalid(line, "empty node".to_string()))?;
+            if children.len() != states {
+                return Err(invalid(line, format!("expected {} branches, found {}", states, children.len())));
+            }
+            let valid = |child: &usize| match level {
+                0 => false,
+                1 => *child < states,
+                _ => nodes.get(*child).is_some_and(|(child_level, _)| child_level + 1 == *level),
+            };
+            if let Some(child) = children.iter().find(|child| !valid(child)) {
+                return Err(invalid(line, format!("invalid branch {} of a level {} node", child, level)));
+            }
+            nodes.push((*level, children.to_vec()));
+        }
+    }
+    let states = states.ok_or_else(|| Error::Parse("missing num_states".to_string()))?;
+    let neighborhood = neighbors.ok_or_else(|| Error::Parse("missing num_neighbors".to_string()))?;
+    let depth = match neighborhood {
+        Neighborhood::Moore => 9,
+        Neighborhood::VonNeumann => 5,
+    };
+    if nodes.last().map(|(level, _)| *level) != Some(depth) {
+        return Err(Error::Parse(format!("the root of the tree must be the last node, at level {}", depth)));
+    }
+    Ok((states, neighborhood, Kind::Tree(nodes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "rand")]
+    use crate::engine::GameOfLife;
+
+    /// Life as a table: births on three neighbors, survival on two or three, death otherwise.
+    const LIFE: &str = "@RULE Life
+# Conway's Game of Life
+@TABLE
+n_states:2
+neighborhood:Moore
+symmetries:permute
+var a={0,1}
+var b={0,1}
+var c={0,1}
+var d={0,1}
+var e={0,1}
+var f={0,1}
+var g={0,1}
+var h={0,1}
+0,1,1,1,0,0,0,0,0,1
+1,1,1,0,0,0,0,0,0,1
+1,1,1,1,0,0,0,0,0,1
+1,a,b,c,d,e,f,g,h,0
+
+@COLORS
+1 255 255 255
+";
+
+    /// Every cell takes the state of its northern neighbor, so everything drifts south.
+    const DRIFT: &str = "@RULE Drift
+@TREE
+num_states=2
+num_neighbors=4
+num_nodes=9
+1 0 0
+1 1 1
+2 0 0
+2 1 1
+3 2 2
+3 3 3
+4 4 4
+4 5 5
+5 6 7
+";
+
+    #[cfg(feature = "rand")]
+    fn golly(matrix: &GameMatrix) -> GameMatrix<GollyState> {
+        let (rows, columns) = matrix.shape();
+        let mut converted = GameMatrix::new(rows, columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                converted.set_state((row, column), GollyState(matrix.get_state((row, column)).is_alive() as u8));
+            }
+        }
+        converted
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_table_runs_life() {
+        let rule: GollyRule = LIFE.parse().unwrap();
+        assert_eq!((rule.name(), rule.states(), rule.neighborhood()), ("Life", 2, Neighborhood::Moore));
+        for boundary in [Boundary::Wrap, Boundary::Dead] {
+            let mut game = GameOfLife::builder().size(16, 20).seed(3).density(0.4).boundary(boundary).build().unwrap();
+            let mut matrix = golly(game.current());
+            for _ in 0..10 {
+                game.tick();
+                matrix = rule.step(&matrix, boundary);
+                assert_eq!(matrix, golly(game.current()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree() {
+        let rule: GollyRule = DRIFT.parse().unwrap();
+        assert_eq!(rule.neighborhood(), Neighborhood::VonNeumann);
+        let mut matrix = GameMatrix::new(4, 3);
+        matrix.set_state((3, 1), GollyState(1));
+        let matrix = rule.step(&matrix, Boundary::Wrap);
+        assert_eq!(matrix.to_string(), ".O.\n...\n...\n...");
+        assert_eq!(rule.step(&matrix, Boundary::Dead).to_string(), "...\n.O.\n...\n...");
+    }
+
+    #[test]
+    fn test_bound_variables_and_symmetries() {
+        // A dead cell between two cells in the same state across it takes that state.
+        let rule = |symmetries| -> GollyRule {
+            format!("@RULE Between\n@TABLE\nn_states:3\nneighborhood:vonNeumann\nsymmetries:{}\nvar a={{1,2}}\n0,a,0,a,0,a\n", symmetries)
+                .parse()
+                .unwrap()
+        };
+        let (north_south, east_west) = ([2, 0, 0, 0, 2, 0, 0, 0], [0, 0, 1, 0, 0, 0, 1, 0]);
+        assert_eq!(rule("none").next_state(0, &north_south), 2);
+        assert_eq!(rule("none").next_state(0, &east_west), 0);
+        assert_eq!(rule("rotate4").next_state(0, &east_west), 1);
+        assert_eq!(rule("rotate4").next_state(0, &[1, 0, 0, 0, 2, 0, 0, 0]), 0);
+
+        // The same in the compact form, with one digit per state.
+        let compact: GollyRule = "@RULE Compact\n@TABLE\nn_states:3\nneighborhood:vonNeumann\nsymmetries:reflect_horizontal\n002002\n"
+            .parse()
+            .unwrap();
+        assert_eq!(compact.next_state(0, &[0, 0, 2, 0, 0, 0, 0, 0]), 2);
+        assert_eq!(compact.next_state(0, &[0, 0, 0, 0, 0, 0, 2, 0]), 2);
+        assert_eq!(compact.next_state(0, &[2, 0, 0, 0, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let table = |body: &str| format!("@RULE Broken\n@TABLE\nn_states:2\nneighborhood:Moore\n{}\n", body).parse::<GollyRule>();
+        assert!(table("0,1,1,1,0,0,0,0,0,1").is_ok());
+        assert!(table("0,1,1,1,0,0,0,0,1").is_err());
+        assert!(table("0,1,1,1,0,0,0,0,0,2").is_err());
+        assert!(table("0,x,1,1,0,0,0,0,0,1").is_err());
+        assert!(table("var a={0,1}\n0,1,1,1,0,0,0,0,0,a").is_err());
+        assert!(table("symmetries:rotate5").is_err());
+        assert!("@TABLE\nn_states:2".parse::<GollyRule>().is_err());
+        assert!("@RULE Empty\n@COLORS\n".parse::<GollyRule>().is_err());
+        assert!(DRIFT.replace("5 6 7", "5 6 8").parse::<GollyRule>().is_err());
+        assert!(DRIFT.replace("5 6 7", "4 6 7").parse::<GollyRule>().is_err());
+
+        // States the rule doesn't know about are left alone.
+        let rule: GollyRule = LIFE.parse().unwrap();
+        assert_eq!(rule.next_state(7, &[1, 1, 1, 0, 0, 0, 0, 0]), 7);
+    }
+}
@@ -0,0 +1,147 @@
+use std::ops::{Index, IndexMut};
+use crate::engine::Cell;
+
+/// A rectangle of values stored row by row, so walking a row, as ticking does, walks memory in
+/// order.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Grid<T> {
+    rows: usize,
+    columns: usize,
+    values: Vec<T>,
+}
+
+impl<T> Grid<T>
+    where T: Clone
+{
+    pub fn from_element(rows: usize, columns: usize, value: T) -> Grid<T> {
+        Grid { rows, columns, values: vec![value; rows * columns] }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.values.fill(value);
+    }
+
+    /// Sets every value from `top_left` to `bottom_right`, edges included, to `value`, clipping
+    /// the rectangle to the grid. Nothing changes if it lies wholly outside of it.
+    pub fn fill_rect(&mut self, (top, left): Cell, (bottom, right): Cell, value: T) {
+        let (end_row, end_column) = (bottom.saturating_add(1).min(self.rows), right.saturating_add(1).min(self.columns));
+        for row in top..end_row {
+            if left < end_column {
+                self.values[row * self.columns + left..row * self.columns + end_column].fill(value.clone());
+            }
+        }
+    }
+
+    /// A copy with every value moved `d_row` rows down and `d_column` columns right. Values moved
+    /// past an edge come back on the other side when `wrap` is set and are dropped otherwise,
+    /// with `fill` taking the places nothing moved into.
+    pub fn shifted(&self, d_row: isize, d_column: isize, wrap: bool, fill: T) -> Grid<T> {
+        let mut shifted = Grid::from_element(self.rows, self.columns, fill);
+        let moved = |index: usize, by: isize, size: usize| {
+            let index = index as isize + by;
+            match wrap {
+                true => Some(index.rem_euclid(size as isize) as usize),
+                false => usize::try_from(index).ok().filter(|index| *index < size),
+            }
+        };
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                if let (Some(to_row), Some(to_column)) = (moved(row, d_row, self.rows), moved(column, d_column, self.columns)) {
+                    shifted[(to_row, to_column)] = self[(row, column)].clone();
+                }
+            }
+        }
+        shifted
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid of `values` given row by row, if there are `rows` times `columns` of them.
+    pub fn from_vec(rows: usize, columns: usize, values: Vec<T>) -> Option<Grid<T>> {
+        (values.len() == rows * columns).then_some(Grid { rows, columns, values })
+    }
+
+    /// The values, row by row, without copying them.
+    pub fn into_vec(self) -> Vec<T> {
+        self.values
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    /// The values of one row, left to right.
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.values[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.values[row * self.columns..(row + 1) * self.columns]
+    }
+
+    /// Every value, row by row.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.values.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.values.iter_mut()
+    }
+
+    fn offset(&self, (row, column): Cell) -> usize {
+        assert!(
+            row < self.rows && column < self.columns,
+            "cell {:?} is outside of the {}x{} grid", (row, column), self.rows, self.columns
+        );
+        row * self.columns + column
+    }
+}
+
+impl<T> Index<Cell> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, cell: Cell) -> &T {
+        &self.values[self.offset(cell)]
+    }
+}
+
+impl<T> IndexMut<Cell> for Grid<T> {
+    fn index_mut(&mut self, cell: Cell) -> &mut T {
+        let offset = self.offset(cell);
+        &mut self.values[offset]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_major() {
+        let mut grid = Grid::from_element(2, 3, 0);
+        grid[(0, 2)] = 1;
+        grid[(1, 0)] = 2;
+        assert_eq!(grid.shape(), (2, 3));
+        assert_eq!(grid.iter().copied().collect::<Vec<_>>(), vec![0, 0, 1, 2, 0, 0]);
+        assert_eq!(grid.row(1), &[2, 0, 0]);
+    }
+
+    #[test]
+    fn test_shifted() {
+        let grid = Grid { rows: 2, columns: 3, values: vec![1, 2, 3, 4, 5, 6] };
+        assert_eq!(grid.shifted(1, 1, true, 0).values, vec![6, 4, 5, 3, 1, 2]);
+        assert_eq!(grid.shifted(1, -1, false, 0).values, vec![0, 0, 0, 2, 3, 0]);
+        assert_eq!(grid.shifted(0, 7, false, 0).values, vec![0; 6]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_columns_do_not_spill_into_the_next_row() {
+        let grid = Grid::from_element(2, 3, 0);
+        let _ = grid[(0, 3)];
+    }
+}
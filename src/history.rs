@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use crate::delta::Delta;
+use crate::engine::{CellChange, GameOfLife};
+
+/// The most recent generations of a game, kept as the cells that changed from one to the next so
+/// the game can be played backward. Only generations advanced one at a time can be recorded;
+/// anything else breaks the chain and calls for `clear`. Each generation is held as a `Delta`,
+/// usually a few bytes, so even a deep history of a large grid takes little memory.
+pub struct History {
+    capacity: Capacity,
+    generations: VecDeque<Delta>,
+    /// The sum of the generations' footprints.
+    size: usize,
+}
+
+/// How much a `History` keeps before it forgets its oldest generations.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Capacity {
+    /// At most this many generations.
+    Generations(usize),
+    /// As many generations as fit in this many bytes of memory.
+    Bytes(usize),
+}
+
+impl History {
+    /// A history holding at most `capacity`; a capacity of zero records nothing.
+    pub fn new(capacity: Capacity) -> History {
+        History { capacity, generations: VecDeque::new(), size: 0 }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self.capacity, Capacity::Generations(0) | Capacity::Bytes(0))
+    }
+
+    /// Records the generation the game just advanced to, by how it differs from the one before,
+    /// forgetting the oldest generations once full.
+    pub fn record(&mut self, game: &GameOfLife) {
+        if !self.is_enabled() {
+            return;
+        }
+        let delta = Delta::between(game.previous(), game.current());
+        self.size += delta.footprint();
+        self.generations.push_back(delta);
+        while self.is_over_capacity() {
+            if let Some(oldest) = self.generations.pop_front() {
+                self.size -= oldest.footprint();
+            }
+        }
+    }
+
+    fn is_over_capacity(&self) -> bool {
+        match self.capacity {
+            Capacity::Generations(generations) => self.generations.len() > generations,
+            Capacity::Bytes(bytes) => self.size > bytes,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.generations.clear();
+        self.size = 0;
+    }
+
+    /// How many generations back the game can be taken.
+    pub fn len(&self) -> usize {
+        self.generations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generations.is_empty()
+    }
+
+    /// Roughly how many bytes of memory the recorded generations take up.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Takes the game back one generation with `GameOfLife::revert_changes`, or returns `false`
+    /// if there is nothing left to go back to.
+    pub fn step_back(&mut self, game: &mut GameOfLife) -> bool {
+        match self.generations.pop_back() {
+            Some(delta) => {
+                self.size -= delta.footprint();
+                let columns = game.shape().1;
+                let changes: Vec<CellChange> = delta
+                    .cells(columns)
+                    .map(|cell| CellChange { cell, state: game.current().get_state(cell) })
+                    .collect();
+                game.revert_changes(&changes);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn test_step_back() {
+        let mut game = GameOfLife::new(8, 8);
+        Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").stamp(game.current_mut(), (1, 1));
+        let mut history = History::new(Capacity::Generations(3));
+        let mut generations = vec![game.current().clone()];
+        for _ in 0..5 {
+            game.tick();
+            history.record(&game);
+            generations.push(game.current().clone());
+        }
+        assert_eq!(history.len(), 3);
+
+        for generation in [4, 3, 2] {
+            assert!(history.step_back(&mut game));
+            assert_eq!(game.current(), &generations[generation]);
+        }
+        assert!(!history.step_back(&mut game));
+        assert!(history.is_empty());
+        assert_eq!(history.size(), 0);
+
+        // Playing forward again from where it was taken back to lands on the same generations.
+        game.tick();
+        assert_eq!(game.current(), &generations[3]);
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let mut game = GameOfLife::new(32, 32);
+        Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").stamp(game.current_mut(), (1, 1));
+        game.tick();
+        let footprint = Delta::between(game.previous(), game.current()).footprint();
+        let mut history = History::new(Capacity::Bytes(footprint * 10));
+        for _ in 0..50 {
+            game.tick();
+            history.record(&game);
+            assert!(history.size() <= footprint * 10);
+        }
+        // The oldest generations were forgotten to stay within the budget, around ten of them
+        // kept since each generation of a glider toggles as many cells.
+        assert!((8..=10).contains(&history.len()));
+        let size = history.size();
+        assert!(history.step_back(&mut game));
+        assert!(history.size() < size);
+    }
+
+    #[test]
+    fn test_revert_keeps_visits() {
+        let mut game = GameOfLife::new(5, 5);
+        Pattern::from_plaintext("Blinker", "OOO").stamp(game.current_mut(), (2, 1));
+        game.tick();
+        let before = game.clone();
+        game.tick();
+        let changes = game.changes();
+        game.revert_changes(&changes);
+        assert_eq!(game.current(), before.current());
+        assert_eq!(game.visits((2, 2)), before.visits((2, 2)));
+        assert_eq!(game.visits((1, 2)), before.visits((1, 2)));
+        assert_eq!(game.age((2, 2)), before.age((2, 2)));
+        assert_eq!(game.bounding_box(), before.bounding_box());
+    }
+
+    #[test]
+    fn test_disabled() {
+        for capacity in [Capacity::Generations(0), Capacity::Bytes(0)] {
+            let mut history = History::new(capacity);
+            history.record(&GameOfLife::new(4, 4));
+            assert!(!history.is_enabled());
+            assert!(history.is_empty());
+        }
+    }
+}
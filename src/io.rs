@@ -0,0 +1,372 @@
+use std::fs;
+use std::path::Path;
+use crate::engine::Cell;
+use crate::error::{Error, Result};
+use crate::pattern::{Metadata, Pattern};
+use crate::plane::Point;
+use crate::rule::Rule;
+
+/// A pattern file format.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// The run length encoded format used by Golly and the LifeWiki.
+    Rle,
+    /// The plaintext `.cells` format.
+    Plaintext,
+}
+
+impl Format {
+    /// Accepts `rle`, and `cells` or `plaintext`.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" | "plaintext" => Some(Format::Plaintext),
+            _ => None,
+        }
+    }
+
+    /// `.rle` files are run length encoded and anything else is taken to be plaintext.
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("rle") => Format::Rle,
+            _ => Format::Plaintext,
+        }
+    }
+
+    pub fn parse(self, name: &str, text: &str) -> Result<Pattern> {
+        match self {
+            Format::Rle => parse_rle(name, text),
+            Format::Plaintext => Ok(parse_plaintext(name, text)),
+        }
+    }
+
+    pub fn write(self, pattern: &Pattern) -> String {
+        match self {
+            Format::Rle => to_rle(pattern),
+            Format::Plaintext => to_plaintext(pattern),
+        }
+    }
+}
+
+/// Reads a pattern file, picking the format from the extension with `Format::from_path`.
+pub fn load<P>(path: P) -> Result<Pattern>
+    where P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    Format::from_path(path).parse(&name, &text)
+}
+
+/// Parses the plaintext (`.cells`) format, taking the name and metadata from the `!Name:`,
+/// `!Author:`, `!Discovered:` and `!Rule:` comments if present and keeping the other comments.
+pub fn parse_plaintext(name: &str, text: &str) -> Pattern {
+    let mut name = name;
+    let mut metadata = Metadata::default();
+    for comment in text.lines().filter_map(|line| line.strip_prefix('!')) {
+        if let Some(value) = comment.strip_prefix("Name:") {
+            name = value.trim();
+        } else if let Some(value) = comment.strip_prefix("Author:") {
+            metadata.author = Some(value.trim().to_string());
+        } else if let Some(value) = comment.strip_prefix("Discovered:") {
+            metadata.discovered = Some(value.trim().to_string());
+        } else if let Some(rule) = comment.strip_prefix("Rule:").and_then(|value| value.parse().ok()) {
+            metadata.rule = Some(rule);
+        } else {
+            metadata.comments.push(comment.trim().to_string());
+        }
+    }
+    Pattern::from_plaintext(name, text).with_metadata(metadata)
+}
+
+/// The largest width or height accepted from a pattern file, so a corrupt run length cannot move
+/// the pattern's cells absurdly far apart.
+const MAX_EXTENT: usize = 1 << 20;
+
+/// The most living cells accepted from a pattern file, so that runs which each fit within
+/// `MAX_EXTENT` cannot add up to more cells than there is memory for.
+const MAX_CELLS: usize = 1 << 22;
+
+/// Parses the run length encoded format used by Golly and the LifeWiki, taking the name from the
+/// `#N` line, the author from `#O`, the rule from the header and the rest of the metadata from
+/// the `#C` comments, as `to_rle` writes them.
+pub fn parse_rle(name: &str, text: &str) -> Result<Pattern> {
+    parse_rle_within(name, text, MAX_CELLS)
+}
+
+/// Parses RLE like `parse_rle`, failing as soon as more than `max_cells` cells are alive, before
+/// any memory is set aside for them: a pattern bound for a grid can have no more than it has.
+pub fn parse_rle_within(name: &str, text: &str, max_cells: usize) -> Result<Pattern> {
+    let mut name = name.to_string();
+    let mut metadata = Metadata::default();
+    let mut cells: Vec<Cell> = vec![];
+    let (mut row, mut column): (usize, usize) = (0, 0);
+    let mut count = String::new();
+    let mut header_seen = false;
+    let mut origin: Point = (0, 0);
+
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(position) = line.strip_prefix("#CXRLE").and_then(|extension| extension.split_once("Pos=")) {
+            origin = parse_position(position.1.split_whitespace().next().unwrap_or_default(), ',')?;
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let value = comment.get(1..).unwrap_or_default().trim();
+            match comment.chars().next() {
+                Some('P' | 'R') => origin = parse_position(value, ' ')?,
+                Some('N') => name = value.to_string(),
+                Some('O') => metadata.author = Some(value.to_string()),
+                Some('C' | 'c') => match value.strip_prefix("Discovered:") {
+                    Some(discovered) => metadata.discovered = Some(discovered.trim().to_string()),
+                    None => metadata.comments.push(value.to_string()),
+                },
+                _ => {}
+            }
+            continue;
+        }
+        if !header_seen && line.starts_with('x') {
+            header_seen = true;
+            // Rules this crate can't run, such as Golly's bounded grids, are left out.
+            metadata.rule = line
+                .split(',')
+                .filter_map(|field| field.split_once('='))
+                .find(|(key, _)| key.trim() == "rule")
+                .and_then(|(_, rule)| rule.parse().ok());
+            continue;
+        }
+
+        for c in line.chars() {
+            match c {
+                '0'..='9' => count.push(c),
+                'b' | '.' | 'o' | 'A'..='Z' | '$' => {
+                    let run: usize = if count.is_empty() {
+                        1
+                    } else {
+                        count.parse().map_err(|_| Error::Parse(format!("invalid run length '{}'", count)))?
+                    };
+                    count.clear();
+                    let too_large = || Error::Parse("pattern is too large".to_string());
+                    match c {
+                        '$' => {
+                            row = row.checked_add(run).filter(|row| *row <= MAX_EXTENT).ok_or_else(too_large)?;
+                            column = 0;
+                        }
+                        _ => {
+                            let end = column.checked_add(run).filter(|end| *end <= MAX_EXTENT).ok_or_else(too_large)?;
+                            if c != 'b' && c != '.' {
+                                cells.len().checked_add(run).filter(|total| *total <= max_cells.min(MAX_CELLS)).ok_or_else(too_large)?;
+                                cells.extend((column..end).map(|column| (row, column)));
+                            }
+                            column = end;
+                        }
+                    }
+                }
+                '!' => return Ok(Pattern::new(&name, cells).with_metadata(metadata).with_origin(origin)),
+                c if c.is_whitespace() => {}
+                _ => return Err(Error::Parse(format!("unexpected '{}' in RLE data", c))),
+            }
+        }
+    }
+
+    Ok(Pattern::new(&name, cells).with_metadata(metadata).with_origin(origin))
+}
+
+/// The `x` and `y` of a pattern's top left corner, as Golly's `#CXRLE Pos=x,y` and the older
+/// `#P x y` give it, as a row and column.
+fn parse_position(text: &str, separator: char) -> Result<Point> {
+    let invalid = || Error::Parse(format!("invalid position '{}'", text));
+    let (x, y) = text.split_once(separator).ok_or_else(invalid)?;
+    Ok((y.trim().parse().map_err(|_| invalid())?, x.trim().parse().map_err(|_| invalid())?))
+}
+
+/// Writes the pattern in the plaintext (`.cells`) format, with its metadata in the comments
+/// `parse_plaintext` reads.
+pub fn to_plaintext(pattern: &Pattern) -> String {
+    let (rows, columns) = pattern.shape();
+    let mut lines = vec![vec!['.'; columns]; rows];
+    for (row, column) in pattern.cells() {
+        lines[*row][*column] = 'O';
+    }
+
+    let metadata = pattern.metadata();
+    let mut text = format!("!Name: {}\n", pattern.name());
+    if let Some(author) = &metadata.author {
+        text.push_str(&format!("!Author: {}\n", author));
+    }
+    if let Some(discovered) = &metadata.discovered {
+        text.push_str(&format!("!Discovered: {}\n", discovered));
+    }
+    if let Some(rule) = metadata.rule {
+        text.push_str(&format!("!Rule: {}\n", rule));
+    }
+    for comment in &metadata.comments {
+        text.push_str(&format!("!{}\n", comment));
+    }
+    for line in lines {
+        text.extend(line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Writes the pattern in the run length encoded format, wrapping lines at 70 characters. The rule
+/// is Life's unless the metadata says otherwise.
+pub fn to_rle(pattern: &Pattern) -> String {
+    let (rows, columns) = pattern.shape();
+    let mut tokens: Vec<String> = vec![];
+    let mut push = |run: usize, tag: char| {
+        if run == 1 {
+            tokens.push(tag.to_string());
+        } else if run > 1 {
+            tokens.push(format!("{}{}", run, tag));
+        }
+    };
+
+    let mut cells = pattern.cells().iter().peekable();
+    let mut previous_row = 0;
+    for row in 0..rows {
+        let mut alive_columns = vec![];
+        while let Some((_, column)) = cells.next_if(|(cell_row, _)| *cell_row == row) {
+            alive_columns.push(*column);
+        }
+        if alive_columns.is_empty() {
+            continue;
+        }
+        push(row - previous_row, '$');
+        previous_row = row;
+
+        let mut column = 0;
+        let mut index = 0;
+        while index < alive_columns.len() {
+            let start = alive_columns[index];
+            let mut end = start;
+            while index + 1 < alive_columns.len() && alive_columns[index + 1] == end + 1 {
+                index += 1;
+                end += 1;
+            }
+            push(start - column, 'b');
+            push(end - start + 1, 'o');
+            column = end + 1;
+            index += 1;
+        }
+    }
+    tokens.push("!".to_string());
+
+    let metadata = pattern.metadata();
+    let mut text = format!("#N {}\n", pattern.name());
+    if let Some(author) = &metadata.author {
+        text.push_str(&format!("#O {}\n", author));
+    }
+    if let Some(discovered) = &metadata.discovered {
+        text.push_str(&format!("#C Discovered: {}\n", discovered));
+    }
+    for comment in &metadata.comments {
+        text.push_str(&format!("#C {}\n", comment));
+    }
+    if pattern.origin() != (0, 0) {
+        let (row, column) = pattern.origin();
+        text.push_str(&format!("#CXRLE Pos={},{}\n", column, row));
+    }
+    let rule = metadata.rule.unwrap_or_else(Rule::life);
+    text.push_str(&format!("x = {}, y = {}, rule = {}\n", columns, rows, rule));
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > 70 {
+            text.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        text.push_str(&token);
+    }
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER_RLE: &str = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!";
+
+    #[test]
+    fn test_parse_rle() {
+        let glider = parse_rle("glider", GLIDER_RLE).unwrap();
+        assert_eq!(glider.name(), "Glider");
+        assert_eq!(glider.cells(), Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").cells());
+        assert_eq!(glider.metadata().rule, Some(Rule::life()));
+        assert_eq!(glider.metadata().comments, vec!["A comment"]);
+
+        let gap = parse_rle("gap", "x = 3, y = 3\no2$2bo!").unwrap();
+        assert_eq!(gap.cells(), &[(0, 0), (2, 2)]);
+
+        assert!(parse_rle("bad", "x = 3, y = 3\nbo$?!").is_err());
+        assert!(parse_rle("huge", "x = 3, y = 3\n99999999999999999999999o!").is_err());
+        assert!(parse_rle("huge", "x = 3, y = 3\n9999999o!").is_err());
+        // Every run is within bounds, but together they would fill a million square grid.
+        let filled = format!("x = 1048576, y = 1048576\n{}!", "1048576o$".repeat(1024));
+        assert!(matches!(parse_rle("huge", &filled), Err(Error::Parse(_))));
+        assert!(parse_rle_within("glider", GLIDER_RLE, 5).is_ok());
+        assert!(parse_rle_within("glider", GLIDER_RLE, 4).is_err());
+    }
+
+    #[test]
+    fn test_rle_round_trip() {
+        for pattern in crate::pattern::builtin() {
+            // Everything but the catalog's category is written out.
+            let metadata = Metadata { category: None, ..pattern.metadata().clone() };
+            assert_eq!(parse_rle("", &to_rle(&pattern)).unwrap(), pattern.with_metadata(metadata));
+        }
+        assert_eq!(to_rle(&parse_rle("", GLIDER_RLE).unwrap()), format!("{}\n", GLIDER_RLE));
+    }
+
+    #[test]
+    fn test_position() {
+        // Golly's far-off patterns keep where they are through a round trip.
+        let far = parse_rle("", "#CXRLE Pos=-12,3000000000 Gen=7\nx = 3, y = 1\n3o!").unwrap();
+        assert_eq!(far.origin(), (3_000_000_000, -12));
+        assert_eq!(far.points().collect::<Vec<_>>(), [(3_000_000_000, -12), (3_000_000_000, -11), (3_000_000_000, -10)]);
+        assert!(to_rle(&far).contains("#CXRLE Pos=-12,3000000000\n"));
+        assert!(parse_rle("", &to_rle(&far)).unwrap().points().eq(far.points()));
+
+        assert_eq!(parse_rle("", "#P -4 -2\nx = 1, y = 1\no!").unwrap().origin(), (-2, -4));
+        assert!(parse_rle("", "#CXRLE Pos=left,top\nx = 1, y = 1\no!").is_err());
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(Format::from_name("RLE"), Some(Format::Rle));
+        assert_eq!(Format::from_name("cells"), Some(Format::Plaintext));
+        assert_eq!(Format::from_name("png"), None);
+        assert_eq!(Format::from_path(Path::new("gun.rle")), Format::Rle);
+        assert_eq!(Format::from_path(Path::new("gun.cells")), Format::Plaintext);
+
+        let glider = Format::Rle.parse("", GLIDER_RLE).unwrap();
+        assert_eq!(Format::Plaintext.parse("", &Format::Plaintext.write(&glider)).unwrap(), glider);
+    }
+
+    #[test]
+    fn test_plaintext_round_trip() {
+        let glider = parse_rle("", GLIDER_RLE).unwrap();
+        let text = to_plaintext(&glider);
+        assert_eq!(text, "!Name: Glider\n!Rule: B3/S23\n!A comment\n.O.\n..O\nOOO\n");
+        assert_eq!(parse_plaintext("", &text), glider);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let gun = parse_rle("", "#N Gun\n#O Bill Gosper\n#C Discovered: 1970\n#C The first gun.\nx = 1, y = 1, rule = B36/S23\no!").unwrap();
+        assert_eq!(gun.metadata(), &Metadata {
+            author: Some("Bill Gosper".to_string()),
+            discovered: Some("1970".to_string()),
+            rule: Some(Rule::new(&[3, 6], &[2, 3])),
+            comments: vec!["The first gun.".to_string()],
+            category: None,
+        });
+        assert_eq!(parse_plaintext("", &to_plaintext(&gun)), gun);
+        assert_eq!(gun.rotate_clockwise().metadata(), gun.metadata());
+
+        // A rule this crate can't run isn't recorded.
+        assert_eq!(parse_rle("", "x = 1, y = 1, rule = B3/S23:T20,20\no!").unwrap().metadata().rule, None);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::engine::{Boundary, Cell, CellType, GameMatrix, GameOfLife};
+use crate::rule::Rule;
+
+/// Several games run side by side from the same start, each under its own rule, to be drawn one
+/// over the other and compared generation by generation.
+#[derive(Clone)]
+pub struct Layers {
+    games: Vec<GameOfLife>,
+    generation: u64,
+}
+
+impl Layers {
+    /// A layer for each rule, in order, all starting from `start`.
+    pub fn new(start: &GameMatrix, boundary: Boundary, rules: &[Rule]) -> Layers {
+        let games = rules
+            .iter()
+            .map(|rule| {
+                let mut game = GameOfLife::from_matrix(start.clone());
+                game.set_rule(*rule);
+                game.set_boundary(boundary);
+                game
+            })
+            .collect();
+        Layers { games, generation: 0 }
+    }
+
+    /// Starts every layer over from `start`, keeping their rules.
+    pub fn reset(&mut self, start: &GameMatrix) {
+        for game in self.games.iter_mut() {
+            game.kill_em_all();
+            *game.current_mut() = start.clone();
+        }
+        self.generation = 0;
+    }
+
+    pub fn tick(&mut self) {
+        for game in self.games.iter_mut() {
+            game.tick();
+        }
+        self.generation += 1;
+    }
+
+    pub fn games(&self) -> &[GameOfLife] {
+        &self.games
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Whether `cell` is alive in each layer, in order.
+    pub fn alive(&self, cell: Cell) -> impl Iterator<Item=bool> + '_ {
+        self.games.iter().map(move |game| game.current().get_state(cell).is_alive())
+    }
+
+    /// How many cells are alive in some layers but not all of them.
+    pub fn differences(&self) -> usize {
+        let (rows, columns) = match self.games.first() {
+            Some(game) => game.shape(),
+            None => return 0,
+        };
+        (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .filter(|cell| {
+                let mut alive = self.alive(*cell);
+                let first = alive.next().unwrap_or_default();
+                alive.any(|alive| alive != first)
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        // A blinker and a lone cell: Life keeps the blinker, while under B1/S every living cell
+        // dies and the cells touching just one are born.
+        let start: GameMatrix = ".....\n.OOO.\n.....\n.....\n....O".parse().unwrap();
+        let life = Rule::life();
+        let mut layers = Layers::new(&start, Boundary::Dead, &[life, "B1/S".parse().unwrap()]);
+        assert_eq!(layers.games().len(), 2);
+        assert_eq!(layers.games()[0].rule(), life);
+        assert_eq!(layers.differences(), 0);
+
+        layers.tick();
+        assert_eq!(layers.generation(), 1);
+        assert_eq!(layers.games()[0].current().to_string(), "..O..\n..O..\n..O..\n.....\n.....");
+        assert_eq!(layers.alive((0, 0)).collect::<Vec<_>>(), vec![false, true]);
+        assert_eq!(layers.alive((1, 2)).collect::<Vec<_>>(), vec![true, false]);
+        assert!(layers.differences() > 0);
+
+        layers.reset(&start);
+        assert_eq!(layers.generation(), 0);
+        assert!(layers.games().iter().all(|game| game.current() == &start));
+        assert_eq!(layers.differences(), 0);
+    }
+}
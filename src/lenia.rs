@@ -0,0 +1,201 @@
+use std::fmt;
+use crate::engine::{Boundary, Cell, CellType, GameMatrix};
+
+/// A continuous state between `0.0` and `1.0`, as in Lenia and SmoothLife. Cells at least half
+/// full count as alive for populations and `Display`.
+impl CellType for f32 {
+    fn is_alive(&self) -> bool {
+        *self >= 0.5
+    }
+}
+
+/// An automaton whose cells take continuous states, such as `Lenia` or `SmoothLife`. `Display`
+/// names it and its parameters.
+pub trait Continuous: fmt::Display {
+    /// Computes the next generation of `matrix`.
+    fn step(&self, matrix: &GameMatrix<f32>, boundary: Boundary) -> GameMatrix<f32>;
+
+    /// How far, in cells, a cell's surroundings reach.
+    fn radius(&self) -> usize;
+}
+
+/// The row and column offset and the weight of every cell a kernel reaches.
+pub(crate) type Kernel = Vec<(isize, isize, f32)>;
+
+/// Scales the weights of `kernel` to sum to one.
+pub(crate) fn normalize(mut kernel: Kernel) -> Kernel {
+    let total: f32 = kernel.iter().map(|(_, _, weight)| weight).sum();
+    for (_, _, weight) in kernel.iter_mut() {
+        *weight /= total;
+    }
+    kernel
+}
+
+/// The states around `cell` weighted by `kernel`. With a dead boundary everything beyond the edges
+/// is empty.
+pub(crate) fn convolve(matrix: &GameMatrix<f32>, kernel: &[(isize, isize, f32)], (row, column): Cell, boundary: Boundary) -> f32 {
+    let (rows, columns) = matrix.shape();
+    kernel.iter()
+        .map(|(d_row, d_column, weight)| {
+            let (neighbor_row, neighbor_column) = (row as isize + d_row, column as isize + d_column);
+            let state = match boundary {
+                Boundary::Wrap => matrix.get_state((
+                    neighbor_row.rem_euclid(rows as isize) as usize,
+                    neighbor_column.rem_euclid(columns as isize) as usize,
+                )),
+                Boundary::Dead if (0..rows as isize).contains(&neighbor_row)
+                    && (0..columns as isize).contains(&neighbor_column) => {
+                    matrix.get_state((neighbor_row as usize, neighbor_column as usize))
+                }
+                Boundary::Dead => 0.0,
+            };
+            state * weight
+        })
+        .sum()
+}
+
+/// A Lenia automaton: each generation every cell takes the weighted average of its surroundings
+/// within `radius` through a smooth ring-shaped kernel, and grows by `dt` times the growth
+/// function of that average, a bell around `mu` of width `sigma` scaled to run from -1 to 1, its
+/// state clipped to stay between 0 and 1.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Lenia {
+    radius: usize,
+    mu: f32,
+    sigma: f32,
+    dt: f32,
+    /// The weights summing to one.
+    kernel: Kernel,
+}
+
+impl Lenia {
+    pub fn new(radius: usize, mu: f32, sigma: f32, dt: f32) -> Lenia {
+        let reach = radius as isize;
+        let mut kernel = vec![];
+        for d_row in -reach..=reach {
+            for d_column in -reach..=reach {
+                let distance = ((d_row * d_row + d_column * d_column) as f32).sqrt() / radius.max(1) as f32;
+                if distance > 0.0 && distance < 1.0 {
+                    let weight = (4.0 - 1.0 / (distance * (1.0 - distance))).exp();
+                    kernel.push((d_row, d_column, weight));
+                }
+            }
+        }
+        Lenia { radius, mu, sigma, dt, kernel: normalize(kernel) }
+    }
+
+    pub fn mu(&self) -> f32 {
+        self.mu
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// How much a cell with the weighted average `potential` around it grows, from -1 far from
+    /// `mu` to 1 at it.
+    pub fn growth(&self, potential: f32) -> f32 {
+        2.0 * (-(potential - self.mu).powi(2) / (2.0 * self.sigma * self.sigma)).exp() - 1.0
+    }
+
+    /// The weighted average of the states around `cell`. With a dead boundary everything beyond
+    /// the edges is empty.
+    pub fn potential(&self, matrix: &GameMatrix<f32>, cell: Cell, boundary: Boundary) -> f32 {
+        convolve(matrix, &self.kernel, cell, boundary)
+    }
+}
+
+impl Continuous for Lenia {
+    fn step(&self, matrix: &GameMatrix<f32>, boundary: Boundary) -> GameMatrix<f32> {
+        let (rows, columns) = matrix.shape();
+        let mut next = matrix.clone();
+        for row in 0..rows {
+            for column in 0..columns {
+                let cell = (row, column);
+                let growth = self.growth(self.potential(matrix, cell, boundary));
+                next.set_state(cell, (matrix.get_state(cell) + self.dt * growth).clamp(0.0, 1.0));
+            }
+        }
+        next
+    }
+
+    fn radius(&self) -> usize {
+        self.radius
+    }
+}
+
+impl fmt::Display for Lenia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Lenia R {} mu {} sigma {} dt {}", self.radius, self.mu, self.sigma, self.dt)
+    }
+}
+
+/// Orbium's parameters, the best known of Lenia's gliders.
+impl Default for Lenia {
+    fn default() -> Lenia {
+        Lenia::new(13, 0.15, 0.015, 0.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel() {
+        let lenia = Lenia::new(5, 0.15, 0.015, 0.1);
+        let total: f32 = lenia.kernel.iter().map(|(_, _, weight)| weight).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+        assert!(lenia.kernel.iter().all(|(d_row, d_column, _)| d_row.abs() <= 5 && d_column.abs() <= 5));
+        assert!(!lenia.kernel.iter().any(|(d_row, d_column, _)| (*d_row, *d_column) == (0, 0)));
+
+        // The kernel is a ring, heaviest half way out.
+        let weight = |offset| lenia.kernel.iter().find(|(d_row, d_column, _)| (*d_row, *d_column) == offset).unwrap().2;
+        assert!(weight((0, 2)) > weight((0, 1)));
+        assert!(weight((2, 0)) > weight((4, 0)));
+        assert_eq!(weight((0, 3)), weight((-3, 0)));
+    }
+
+    #[test]
+    fn test_growth() {
+        let lenia = Lenia::default();
+        assert_eq!(lenia.growth(0.15), 1.0);
+        assert!((lenia.growth(0.0) + 1.0).abs() < 1e-5);
+        assert!((lenia.growth(0.15 + 0.015) - lenia.growth(0.15 - 0.015)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_step() {
+        let lenia = Lenia::new(3, 0.15, 0.015, 0.1);
+
+        // An empty grid stays empty, and a full one has nothing left to grow on.
+        let empty = GameMatrix::<f32>::new(10, 10);
+        assert_eq!(lenia.step(&empty, Boundary::Wrap), empty);
+        let mut full = GameMatrix::<f32>::new(10, 10);
+        for row in 0..10 {
+            for column in 0..10 {
+                full.set_state((row, column), 1.0);
+            }
+        }
+        assert!((lenia.step(&full, Boundary::Wrap).get_state((4, 4)) - 0.9).abs() < 1e-5);
+
+        // A lone cell fades, feeling nothing of itself.
+        let mut matrix = GameMatrix::<f32>::new(10, 10);
+        matrix.set_state((5, 5), 1.0);
+        assert_eq!(lenia.potential(&matrix, (5, 5), Boundary::Wrap), 0.0);
+        assert!(lenia.potential(&matrix, (5, 7), Boundary::Wrap) > 0.0);
+        let next = lenia.step(&matrix, Boundary::Wrap);
+        assert!((next.get_state((5, 5)) - 0.9).abs() < 1e-5);
+        assert_eq!(next.population(), 1);
+
+        // Across the edge the cell is only felt when the grid wraps.
+        matrix.set_state((5, 5), 0.0);
+        matrix.set_state((0, 0), 1.0);
+        assert!(lenia.potential(&matrix, (9, 9), Boundary::Wrap) > 0.0);
+        assert_eq!(lenia.potential(&matrix, (9, 9), Boundary::Dead), 0.0);
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(feature = "ndarray")]
+pub mod array;
+pub mod builder;
+pub mod convolution;
+pub mod delta;
+pub mod diff;
+pub mod engine;
+pub mod error;
+pub mod font;
+pub mod golly;
+pub mod grid;
+pub mod history;
+pub mod io;
+pub mod layers;
+pub mod lenia;
+pub mod life3d;
+#[cfg(all(feature = "mmap", unix))]
+pub mod mapped;
+pub mod objects;
+pub mod pattern;
+pub mod period;
+pub mod plane;
+pub mod protocol;
+pub mod render;
+pub mod replay;
+pub mod rule;
+pub mod simulation;
+pub mod smoothlife;
+pub mod stats;
+pub mod storage;
+#[cfg(feature = "rand")]
+pub mod symmetry;
+pub mod versus;
+#[cfg(feature = "web")]
+pub mod web;
+
+pub use builder::GameOfLifeBuilder;
+pub use convolution::BoxRule;
+pub use delta::Delta;
+pub use diff::{divergence, Divergence};
+pub use error::{Error, Result};
+pub use font::Font;
+pub use golly::{GollyRule, GollyState, Neighborhood};
+pub use engine::{Boundary, BoundingBox, Cell, CellChange, CellState, CellType, GameMatrix, GameOfLife, Generations, TickReport, line};
+pub use grid::Grid;
+pub use history::{Capacity, History};
+pub use layers::Layers;
+pub use lenia::{Continuous, Lenia};
+pub use life3d::{Cell3d, Life3d, Rule3d};
+#[cfg(all(feature = "mmap", unix))]
+pub use mapped::MappedGrid;
+pub use objects::{Census, ObjectKind};
+pub use pattern::{Category, Metadata, Pattern, Query};
+pub use period::{Behavior, Periodicity};
+pub use plane::{Plane, Point, Viewport};
+pub use render::Renderer;
+pub use replay::{Frame, Playback, Recorder, Replay};
+pub use rule::{Rule, RulePreset};
+pub use simulation::{Simulation, Update};
+pub use smoothlife::SmoothLife;
+pub use stats::{Lifespan, Metrics, Stats};
+pub use storage::{step, GridStorage, PackedGrid, SparseGrid};
+#[cfg(feature = "rand")]
+pub use symmetry::Symmetry;
+pub use versus::{ColoredCell, Phase, Player, Versus};
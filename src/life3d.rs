@@ -0,0 +1,350 @@
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "rand")]
+use rand::Rng;
+use crate::engine::{Boundary, CellState, GameMatrix};
+use crate::error::{Error, Result};
+use crate::grid::Grid;
+
+/// A cell of a three dimensional grid: its layer, row and column.
+pub type Cell3d = (usize, usize, usize);
+
+/// A totalistic rule for three dimensions, where a cell has 26 neighbors: which live neighbor
+/// counts cause a dead cell to be born and which let a live cell survive.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rule3d {
+    birth: [bool; 27],
+    survival: [bool; 27],
+}
+
+impl Rule3d {
+    /// Counts over 26 are ignored.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Rule3d {
+        let mut rule = Rule3d { birth: [false; 27], survival: [false; 27] };
+        for count in birth.iter().filter(|count| **count <= 26) {
+            rule.birth[*count] = true;
+        }
+        for count in survival.iter().filter(|count| **count <= 26) {
+            rule.survival[*count] = true;
+        }
+        rule
+    }
+
+    /// Bays' Life 5766: survival on 5 to 7 neighbors and birth on 6, which keeps gliders and
+    /// oscillators like the two dimensional rule does.
+    pub fn life() -> Rule3d {
+        Rule3d::new(&[6], &[5, 6, 7])
+    }
+
+    pub fn is_born(&self, alive_neighbors: usize) -> bool {
+        self.birth[alive_neighbors]
+    }
+
+    pub fn survives(&self, alive_neighbors: usize) -> bool {
+        self.survival[alive_neighbors]
+    }
+
+    /// The counts as `(low, high)` when they run unbroken between two single digits, for Bays'
+    /// notation.
+    fn range(counts: &[bool; 27]) -> Option<(usize, usize)> {
+        let low = counts.iter().position(|on| *on)?;
+        let high = counts.iter().rposition(|on| *on)?;
+        Some((low, high)).filter(|_| high <= 9 && counts[low..=high].iter().all(|on| *on))
+    }
+}
+
+impl Default for Rule3d {
+    fn default() -> Rule3d {
+        Rule3d::life()
+    }
+}
+
+/// Parses Bays' notation, four digits giving the lowest and highest counts a live cell survives
+/// on, then the lowest and highest a dead one is born on, like `5766` or `4555`; or a `B`/`S`
+/// rule with the counts separated by commas, where a range can be written `5-7`, like
+/// `B6/S5-7` or `B5,7/S4,5,6`.
+impl FromStr for Rule3d {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Rule3d> {
+        let text = text.trim();
+        if text.len() == 4 && text.chars().all(|c| c.is_ascii_digit()) {
+            let digits: Vec<usize> = text.chars().filter_map(|c| c.to_digit(10)).map(|digit| digit as usize).collect();
+            if digits[0] > digits[1] || digits[2] > digits[3] {
+                return Err(Error::Parse(format!("rule '{}' has a range running backwards", text)));
+            }
+            let birth: Vec<usize> = (digits[2]..=digits[3]).collect();
+            let survival: Vec<usize> = (digits[0]..=digits[1]).collect();
+            return Ok(Rule3d::new(&birth, &survival));
+        }
+
+        let counts = |part: &str, prefix: char| -> Result<Vec<usize>> {
+            let part = part
+                .strip_prefix(prefix)
+                .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+                .ok_or_else(|| Error::Parse(format!("rule '{}' must be like 5766 or B6/S5-7", text)))?;
+            let mut counts = vec![];
+            for item in part.split(',').filter(|item| !item.is_empty()) {
+                let number = |number: &str| number
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|number| *number <= 26)
+                    .ok_or_else(|| Error::Parse(format!("'{}' in rule '{}' is not a count from 0 to 26", number, text)));
+                match item.split_once('-') {
+                    Some((low, high)) => counts.extend(number(low)?..=number(high)?),
+                    None => counts.push(number(item)?),
+                }
+            }
+            Ok(counts)
+        };
+        let (birth, survival) = text
+            .split_once('/')
+            .ok_or_else(|| Error::Parse(format!("rule '{}' must be like 5766 or B6/S5-7", text)))?;
+        Ok(Rule3d::new(&counts(birth, 'B')?, &counts(survival, 'S')?))
+    }
+}
+
+/// Writes Bays' notation where it fits, and a `B`/`S` rule otherwise.
+impl fmt::Display for Rule3d {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (Some(survival), Some(birth)) = (Rule3d::range(&self.survival), Rule3d::range(&self.birth)) {
+            return write!(f, "{}{}{}{}", survival.0, survival.1, birth.0, birth.1);
+        }
+        let counts = |counts: &[bool; 27]| {
+            counts.iter()
+                .enumerate()
+                .filter(|(_, on)| **on)
+                .map(|(count, _)| count.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+/// Life on a three dimensional grid of layers, each of rows and columns, where each cell has the
+/// 26 cells of the cube around it as neighbors.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Life3d {
+    shape: Cell3d,
+    cells: Vec<bool>,
+    rule: Rule3d,
+    boundary: Boundary,
+}
+
+impl Life3d {
+    pub fn new(layers: usize, rows: usize, columns: usize, rule: Rule3d) -> Life3d {
+        Life3d {
+            shape: (layers, rows, columns),
+            cells: vec![false; layers * rows * columns],
+            rule,
+            boundary: Boundary::Wrap,
+        }
+    }
+
+    pub fn shape(&self) -> Cell3d {
+        self.shape
+    }
+
+    pub fn rule(&self) -> Rule3d {
+        self.rule
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    fn index(&self, (layer, row, column): Cell3d) -> usize {
+        let (layers, rows, columns) = self.shape;
+        assert!(layer < layers && row < rows && column < columns, "cell {:?} is outside of the {:?} grid", (layer, row, column), self.shape);
+        (layer * rows + row) * columns + column
+    }
+
+    /// Panics if the cell is outside of the grid.
+    pub fn is_alive(&self, cell: Cell3d) -> bool {
+        self.cells[self.index(cell)]
+    }
+
+    /// Panics if the cell is outside of the grid.
+    pub fn set_alive(&mut self, cell: Cell3d, alive: bool) {
+        let index = self.index(cell);
+        self.cells[index] = alive;
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|alive| **alive).count()
+    }
+
+    /// Brings each cell to life with probability `density`, killing the rest.
+    #[cfg(feature = "rand")]
+    pub fn randomize<R>(&mut self, density: f64, rng: &mut R)
+        where R: Rng
+    {
+        for alive in self.cells.iter_mut() {
+            *alive = rng.gen_bool(density.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn alive_neighbor_count(&self, (layer, row, column): Cell3d) -> usize {
+        let (layers, rows, columns) = self.shape;
+        let wrap = |index: usize, offset: isize, size: usize| -> Option<usize> {
+            let moved = index as isize + offset;
+            match self.boundary {
+                Boundary::Wrap => Some(moved.rem_euclid(size as isize) as usize),
+                Boundary::Dead => Some(moved as usize).filter(|_| (0..size as isize).contains(&moved)),
+            }
+        };
+        let mut count = 0;
+        for d_layer in -1..=1 {
+            for d_row in -1..=1 {
+                for d_column in -1..=1 {
+                    if (d_layer, d_row, d_column) == (0, 0, 0) {
+                        continue;
+                    }
+                    if let (Some(layer), Some(row), Some(column)) =
+                        (wrap(layer, d_layer, layers), wrap(row, d_row, rows), wrap(column, d_column, columns))
+                    {
+                        // On a wrapped grid fewer than three cells across, a neighbor can be met
+                        // from both sides, and is counted each time, as in two dimensions.
+                        count += usize::from(self.is_alive((layer, row, column)));
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    pub fn tick(&mut self) {
+        let (layers, rows, columns) = self.shape;
+        let mut next = Vec::with_capacity(self.cells.len());
+        for layer in 0..layers {
+            for row in 0..rows {
+                for column in 0..columns {
+                    let cell = (layer, row, column);
+                    let count = self.alive_neighbor_count(cell);
+                    next.push(if self.is_alive(cell) { self.rule.survives(count) } else { self.rule.is_born(count) });
+                }
+            }
+        }
+        self.cells = next;
+    }
+
+    /// The cells of one layer, as a two dimensional grid.
+    pub fn slice(&self, layer: usize) -> GameMatrix {
+        let (_, rows, columns) = self.shape;
+        let mut matrix = GameMatrix::new(rows, columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                if self.is_alive((layer, row, column)) {
+                    matrix.set_state((row, column), CellState::Alive);
+                }
+            }
+        }
+        matrix
+    }
+
+    /// How many layers each row and column is alive in, looking down through the grid.
+    pub fn projection(&self) -> Grid<usize> {
+        let (layers, rows, columns) = self.shape;
+        let mut projection = Grid::from_element(rows, columns, 0);
+        for layer in 0..layers {
+            for row in 0..rows {
+                for column in 0..columns {
+                    projection[(row, column)] += usize::from(self.is_alive((layer, row, column)));
+                }
+            }
+        }
+        projection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "rand")]
+    use rand::rngs::StdRng;
+    #[cfg(feature = "rand")]
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_rule_notation() {
+        let rule: Rule3d = "5766".parse().unwrap();
+        assert_eq!(rule, Rule3d::life());
+        assert!(rule.is_born(6) && !rule.is_born(5) && !rule.is_born(7));
+        assert!(rule.survives(5) && rule.survives(7) && !rule.survives(8));
+        assert_eq!(rule.to_string(), "5766");
+        assert_eq!("B6/S5-7".parse::<Rule3d>().unwrap(), rule);
+        assert_eq!("4555".parse::<Rule3d>().unwrap().to_string(), "4555");
+
+        let rule: Rule3d = "b5,7/s4,5,13-14".parse().unwrap();
+        assert!(rule.is_born(5) && rule.is_born(7) && !rule.is_born(6));
+        assert!(rule.survives(13) && rule.survives(14) && !rule.survives(12));
+        assert_eq!(rule.to_string(), "B5,7/S4,5,13,14");
+        assert_eq!(rule.to_string().parse::<Rule3d>().unwrap(), rule);
+
+        assert!("7566".parse::<Rule3d>().is_err());
+        assert!("B27/S5".parse::<Rule3d>().is_err());
+        assert!("B3S23".parse::<Rule3d>().is_err());
+        assert!("576".parse::<Rule3d>().is_err());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let mut game = Life3d::new(4, 4, 4, Rule3d::life());
+        game.set_alive((0, 0, 0), true);
+        game.set_alive((3, 3, 3), true);
+        game.set_alive((1, 1, 1), true);
+        assert_eq!(game.alive_neighbor_count((0, 0, 0)), 2);
+        assert_eq!(game.alive_neighbor_count((1, 1, 1)), 1);
+        game.set_boundary(Boundary::Dead);
+        assert_eq!(game.alive_neighbor_count((0, 0, 0)), 1);
+        assert_eq!(game.alive_neighbor_count((2, 2, 2)), 2);
+    }
+
+    #[test]
+    fn test_tick() {
+        // Under 4555 a dead cell touching exactly five live ones is born, and a 2x2x2 cube,
+        // each of whose cells has seven neighbors, dies out.
+        let mut game = Life3d::new(6, 6, 6, "4555".parse().unwrap());
+        game.set_boundary(Boundary::Dead);
+        for cell in [(2, 2, 2), (2, 2, 3), (2, 3, 2), (2, 3, 3), (3, 2, 2), (3, 2, 3), (3, 3, 2), (3, 3, 3)] {
+            game.set_alive(cell, true);
+        }
+        assert_eq!(game.alive_neighbor_count((2, 2, 2)), 7);
+        game.tick();
+        assert_eq!(game.population(), 0);
+
+        // Under 5766 the same cube is stable: its cells keep seven neighbors, and the cells
+        // around it touch at most four.
+        let mut game = Life3d::new(6, 6, 6, Rule3d::life());
+        for cell in [(2, 2, 2), (2, 2, 3), (2, 3, 2), (2, 3, 3), (3, 2, 2), (3, 2, 3), (3, 3, 2), (3, 3, 3)] {
+            game.set_alive(cell, true);
+        }
+        let before = game.clone();
+        game.tick();
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_views() {
+        let mut game = Life3d::new(3, 2, 3, Rule3d::life());
+        game.set_alive((0, 0, 1), true);
+        game.set_alive((2, 0, 1), true);
+        game.set_alive((2, 1, 2), true);
+        assert_eq!(game.slice(0).to_string(), ".O.\n...");
+        assert_eq!(game.slice(1).to_string(), "...\n...");
+        assert_eq!(game.slice(2).to_string(), ".O.\n..O");
+        let projection = game.projection();
+        assert_eq!(projection.row(0), &[0, 2, 0]);
+        assert_eq!(projection.row(1), &[0, 0, 1]);
+
+        game.randomize(1.0, &mut StdRng::seed_from_u64(1));
+        assert_eq!(game.population(), 18);
+    }
+}
@@ -0,0 +1,287 @@
+use std::fs::{self, File, OpenOptions};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::engine::{next_state, Boundary, Cell, CellState, CellType};
+use crate::error::{Error, Result};
+use crate::rule::Rule;
+use crate::storage::{packed_index, packed_live, packed_words, read_packed, write_packed, GridStorage};
+
+/// How many rows `MappedGrid::step_into` works out at a time.
+const CHUNK_ROWS: usize = 64;
+
+/// A grid laid out as `PackedGrid` lays it out, two bits a cell, but kept in a file mapped into
+/// memory rather than in memory itself, so that a grid of billions of cells, more than there is
+/// memory for, can still be stepped, if slowly, with the system paging the file in and out as
+/// `step_into` works its way down it. The file holds the words and nothing else, in the
+/// machine's byte order; the shape is up to whoever opens it.
+pub struct MappedGrid {
+    rows: usize,
+    columns: usize,
+    map: Mapping,
+}
+
+/// A file mapped into memory for reading and writing as words, unmapped when dropped.
+struct Mapping {
+    words: NonNull<u64>,
+    len: usize,
+    /// Held open for as long as it is mapped.
+    _file: File,
+}
+
+// SAFETY: the mapped words belong to the one `Mapping` and are reached only through it, as a
+// `Vec`'s buffer is, so it can move between threads and be shared for reading as one can.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    /// Maps the first `len` words of `file`, which must be at least that long.
+    fn new(file: File, len: usize) -> Result<Mapping> {
+        if len == 0 {
+            // There is nothing to map, and mmap refuses an empty range.
+            return Ok(Mapping { words: NonNull::dangling(), len, _file: file });
+        }
+        // SAFETY: a new mapping of a file held open, no longer than it, which nothing else in the
+        // process refers to; the result is checked before use.
+        let pointer = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len * size_of::<u64>(),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if pointer == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let words = NonNull::new(pointer.cast()).ok_or_else(|| Error::Io(std::io::Error::other("mapped at null")))?;
+        Ok(Mapping { words, len, _file: file })
+    }
+
+    fn words(&self) -> &[u64] {
+        // SAFETY: `words` points at `len` mapped words, page aligned, for as long as `self` lives.
+        unsafe { slice::from_raw_parts(self.words.as_ptr(), self.len) }
+    }
+
+    fn words_mut(&mut self) -> &mut [u64] {
+        // SAFETY: as in `words`, and `&mut self` makes this the only reference to them.
+        unsafe { slice::from_raw_parts_mut(self.words.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: unmaps exactly the range `new` mapped, which nothing refers to any more.
+            unsafe { libc::munmap(self.words.as_ptr().cast(), self.len * size_of::<u64>()) };
+        }
+    }
+}
+
+impl MappedGrid {
+    /// A grid of `rows` by `columns` dead cells in a new file at `path`, replacing any file there.
+    /// The file is sparse where the system allows, taking up disk only where cells have lived.
+    pub fn create<P>(path: P, rows: usize, columns: usize) -> Result<MappedGrid>
+        where P: AsRef<Path>
+    {
+        let len = words(rows, columns)?;
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        file.set_len((len * size_of::<u64>()) as u64)?;
+        Ok(MappedGrid { rows, columns, map: Mapping::new(file, len)? })
+    }
+
+    /// The grid of `rows` by `columns` cells in the file at `path`, as `create` made it and
+    /// editing and `flush` left it.
+    pub fn open<P>(path: P, rows: usize, columns: usize) -> Result<MappedGrid>
+        where P: AsRef<Path>
+    {
+        let len = words(rows, columns)?;
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let size = file.metadata()?.len();
+        if size != (len * size_of::<u64>()) as u64 {
+            return Err(Error::InvalidOption(format!("a {}x{} grid doesn't take {} bytes", rows, columns, size)));
+        }
+        Ok(MappedGrid { rows, columns, map: Mapping::new(file, len)? })
+    }
+
+    /// A grid of `rows` by `columns` dead cells in a file of its own in the temporary directory,
+    /// removed again as soon as it is mapped, so that nothing is left behind.
+    pub fn temporary(rows: usize, columns: usize) -> Result<MappedGrid> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("game-of-life-{}-{}.grid", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
+        let grid = MappedGrid::create(&path, rows, columns)?;
+        fs::remove_file(&path)?;
+        Ok(grid)
+    }
+
+    /// Writes changes through to the file, returning once they are on disk.
+    pub fn flush(&self) -> Result<()> {
+        if self.map.len == 0 {
+            return Ok(());
+        }
+        // SAFETY: syncs exactly the mapped range.
+        let result = unsafe {
+            libc::msync(self.map.words.as_ptr().cast(), self.map.len * size_of::<u64>(), libc::MS_SYNC)
+        };
+        match result {
+            0 => Ok(()),
+            _ => Err(std::io::Error::last_os_error().into()),
+        }
+    }
+
+    /// Writes the generation after this one under `rule` into `next`, with the same edges as
+    /// `GameOfLife` has for `boundary`. `CHUNK_ROWS` rows are worked out at a time from those rows
+    /// and the ones either side of them, unpacked, so only those and not the grid need be in
+    /// memory at once, and both files are read and written from top to bottom. Two grids, one
+    /// holding the generation and the other the next, can be swapped after every step.
+    ///
+    /// # Panics
+    ///
+    /// If `next` isn't the same shape as this grid.
+    pub fn step_into(&self, next: &mut MappedGrid, rule: &Rule, boundary: Boundary) {
+        let (rows, columns) = self.shape();
+        assert_eq!(next.shape(), (rows, columns), "stepping into a grid of another shape");
+        let words = self.map.words();
+        let mut window: Vec<CellState> = Vec::with_capacity((CHUNK_ROWS + 2) * columns);
+        for start in (0..rows).step_by(CHUNK_ROWS) {
+            let end = (start + CHUNK_ROWS).min(rows);
+            // The chunk and a row either side, those beyond the edges wrapped round or dead.
+            window.clear();
+            for row in start as isize - 1..=end as isize {
+                let row = match boundary {
+                    Boundary::Wrap => Some(row.rem_euclid(rows as isize) as usize),
+                    Boundary::Dead => usize::try_from(row).ok().filter(|row| *row < rows),
+                };
+                match row {
+                    Some(row) => window.extend((0..columns).map(|column| read_packed(words, row * columns + column))),
+                    None => window.extend((0..columns).map(|_| CellState::Dead)),
+                }
+            }
+            for row in start..end {
+                let middle = row - start + 1;
+                for column in 0..columns {
+                    let mut alive_neighbors = 0;
+                    for d_row in 0..3 {
+                        for d_column in -1..=1isize {
+                            if (d_row, d_column) == (1, 0) {
+                                continue;
+                            }
+                            let neighbor = column as isize + d_column;
+                            let neighbor = match boundary {
+                                Boundary::Wrap => neighbor.rem_euclid(columns as isize) as usize,
+                                Boundary::Dead if (0..columns as isize).contains(&neighbor) => neighbor as usize,
+                                Boundary::Dead => continue,
+                            };
+                            alive_neighbors += window[(middle + d_row - 1) * columns + neighbor].is_alive() as usize;
+                        }
+                    }
+                    let state = next_state(window[middle * columns + column], alive_neighbors, rule);
+                    write_packed(next.map.words_mut(), row * columns + column, state);
+                }
+            }
+        }
+    }
+}
+
+/// How many words a grid of `rows` by `columns` takes, failing where that is more than a file
+/// can be mapped as.
+fn words(rows: usize, columns: usize) -> Result<usize> {
+    packed_words(rows, columns)
+        .filter(|words| words.checked_mul(size_of::<u64>()).is_some_and(|bytes| bytes <= isize::MAX as usize))
+        .ok_or_else(|| Error::InvalidOption(format!("a {}x{} grid is too large to map", rows, columns)))
+}
+
+/// In a temporary file, panicking if there is no room for one in the temporary directory:
+/// `temporary` reports that as an error instead.
+impl GridStorage for MappedGrid {
+    fn empty(rows: usize, columns: usize) -> MappedGrid {
+        MappedGrid::temporary(rows, columns).expect("a temporary file to map the grid to")
+    }
+
+    fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    fn get(&self, cell: Cell) -> CellState {
+        read_packed(self.map.words(), packed_index(cell, self.shape()))
+    }
+
+    fn set(&mut self, cell: Cell, state: CellState) {
+        let index = packed_index(cell, self.shape());
+        write_packed(self.map.words_mut(), index, state);
+    }
+
+    fn iter_live(&self) -> impl Iterator<Item=(Cell, CellState)> + '_ {
+        packed_live(self.map.words(), self.shape())
+    }
+}
+
+/// A copy in a temporary file of its own, panicking as `GridStorage::empty` does.
+impl Clone for MappedGrid {
+    fn clone(&self) -> MappedGrid {
+        let mut grid = MappedGrid::empty(self.rows, self.columns);
+        grid.map.words_mut().copy_from_slice(self.map.words());
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{GameMatrix, GameOfLife};
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn test_cells() {
+        let mut grid = MappedGrid::temporary(3, 40).unwrap();
+        grid.set((0, 1), CellState::Alive);
+        grid.set((1, 33), CellState::Wall);
+        grid.set((2, 39), CellState::Immortal);
+        assert_eq!(grid.get((1, 33)), CellState::Wall);
+        assert_eq!(grid.iter_live().count(), 3);
+        let copy = grid.clone();
+        grid.set((0, 1), CellState::Dead);
+        assert_eq!((grid.get((0, 1)), copy.get((0, 1))), (CellState::Dead, CellState::Alive));
+        assert_eq!(MappedGrid::temporary(0, 7).unwrap().iter_live().count(), 0);
+    }
+
+    #[test]
+    fn test_step_into() {
+        // The chunks meet mid-pattern, and rows don't end on word boundaries.
+        for (shape, origin) in [((150, 37), (60, 15)), ((6, 6), (1, 1)), ((2, 5), (0, 0))] {
+            for boundary in [Boundary::Wrap, Boundary::Dead] {
+                let mut game = GameOfLife::new(shape.0, shape.1);
+                game.set_boundary(boundary);
+                Pattern::from_plaintext("R-pentomino", ".OO\nOO.\n.O.").stamp(game.current_mut(), origin);
+                game.current_mut().set_state((shape.0 - 1, 0), CellState::Wall);
+                let mut grid = MappedGrid::from_storage(game.current());
+                let mut next = MappedGrid::temporary(shape.0, shape.1).unwrap();
+                for _ in 0..40 {
+                    grid.step_into(&mut next, &Rule::life(), boundary);
+                    std::mem::swap(&mut grid, &mut next);
+                    game.tick();
+                    assert_eq!(&GameMatrix::from_storage(&grid), game.current(), "{:?} {:?}", shape, boundary);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_file() {
+        let path = std::env::temp_dir().join(format!("game-of-life-test-{}.grid", std::process::id()));
+        let mut grid = MappedGrid::create(&path, 100, 100).unwrap();
+        grid.set((99, 99), CellState::Alive);
+        grid.flush().unwrap();
+        drop(grid);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 313 * 8);
+        assert_eq!(MappedGrid::open(&path, 100, 100).unwrap().get((99, 99)), CellState::Alive);
+        assert!(MappedGrid::open(&path, 100, 101).is_err());
+        fs::remove_file(&path).unwrap();
+        assert!(MappedGrid::create(&path, usize::MAX, 2).is_err());
+    }
+}
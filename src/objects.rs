@@ -0,0 +1,351 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use crate::engine::{Boundary, Cell, CellType, GameMatrix, GameOfLife};
+use crate::pattern::Pattern;
+
+/// A common still life, oscillator or spaceship that `GameMatrix::classify_objects` recognizes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ObjectKind {
+    Block,
+    Beehive,
+    Loaf,
+    Boat,
+    Tub,
+    Pond,
+    Blinker,
+    Toad,
+    Beacon,
+    Glider,
+    LightweightSpaceship,
+}
+
+impl ObjectKind {
+    pub fn plural(&self) -> &'static str {
+        match self {
+            ObjectKind::Block => "blocks",
+            ObjectKind::Beehive => "beehives",
+            ObjectKind::Loaf => "loaves",
+            ObjectKind::Boat => "boats",
+            ObjectKind::Tub => "tubs",
+            ObjectKind::Pond => "ponds",
+            ObjectKind::Blinker => "blinkers",
+            ObjectKind::Toad => "toads",
+            ObjectKind::Beacon => "beacons",
+            ObjectKind::Glider => "gliders",
+            ObjectKind::LightweightSpaceship => "LWSSes",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObjectKind::Block => "block",
+            ObjectKind::Beehive => "beehive",
+            ObjectKind::Loaf => "loaf",
+            ObjectKind::Boat => "boat",
+            ObjectKind::Tub => "tub",
+            ObjectKind::Pond => "pond",
+            ObjectKind::Blinker => "blinker",
+            ObjectKind::Toad => "toad",
+            ObjectKind::Beacon => "beacon",
+            ObjectKind::Glider => "glider",
+            ObjectKind::LightweightSpaceship => "LWSS",
+        }
+    }
+
+    /// One phase of the object and how many generations it takes to come back to it.
+    fn template(&self) -> (&'static str, usize) {
+        match self {
+            ObjectKind::Block => ("OO\nOO", 1),
+            ObjectKind::Beehive => (".OO.\nO..O\n.OO.", 1),
+            ObjectKind::Loaf => (".OO.\nO..O\n.O.O\n..O.", 1),
+            ObjectKind::Boat => ("OO.\nO.O\n.O.", 1),
+            ObjectKind::Tub => (".O.\nO.O\n.O.", 1),
+            ObjectKind::Pond => (".OO.\nO..O\nO..O\n.OO.", 1),
+            ObjectKind::Blinker => ("OOO", 2),
+            ObjectKind::Toad => (".OOO\nOOO.", 2),
+            ObjectKind::Beacon => ("OO..\nOO..\n..OO\n..OO", 2),
+            ObjectKind::Glider => (".O.\n..O\nOOO", 4),
+            ObjectKind::LightweightSpaceship => (".O..O\nO....\nO...O\nOOOO.", 4),
+        }
+    }
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How many objects of each kind a grid holds, as counted by `GameMatrix::census`. Displays as
+/// e.g. `blocks: 14, blinkers: 9, gliders: 3`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Census {
+    counts: Vec<(ObjectKind, usize)>,
+}
+
+impl Census {
+    /// The kinds present, most common first.
+    pub fn counts(&self) -> &[(ObjectKind, usize)] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Adds another census's counts to this one, e.g. to total up many soups.
+    pub fn merge(&mut self, other: &Census) {
+        for (kind, count) in &other.counts {
+            match self.counts.iter_mut().find(|(other, _)| other == kind) {
+                Some((_, total)) => *total += count,
+                None => self.counts.push((*kind, *count)),
+            }
+        }
+        self.sort();
+    }
+
+    /// Most common first, and kinds that are as common as each other in `KINDS` order.
+    fn sort(&mut self) {
+        self.counts.sort_by_key(|(kind, count)| (Reverse(*count), KINDS.iter().position(|other| other == kind)));
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.counts.is_empty() {
+            return f.write_str("nothing recognized");
+        }
+        for (index, (kind, count)) in self.counts.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", kind.plural(), count)?;
+        }
+        Ok(())
+    }
+}
+
+/// How far apart, in rows and in columns, living cells of the same object can be.
+const OBJECT_GAP: usize = 2;
+
+const KINDS: [ObjectKind; 11] = [
+    ObjectKind::Block,
+    ObjectKind::Beehive,
+    ObjectKind::Loaf,
+    ObjectKind::Boat,
+    ObjectKind::Tub,
+    ObjectKind::Pond,
+    ObjectKind::Blinker,
+    ObjectKind::Toad,
+    ObjectKind::Beacon,
+    ObjectKind::Glider,
+    ObjectKind::LightweightSpaceship,
+];
+
+impl GameMatrix {
+    /// Splits the living cells into objects and returns the ones it recognizes under Life's rule
+    /// in any phase, rotation or reflection, each with the top-left corner of its bounding box.
+    ///
+    /// Living cells at most two rows and columns apart belong to the same object; touching isn't
+    /// enough, as some phases of the beacon and the lightweight spaceship have gaps. So objects
+    /// that close to something else are part of a bigger, unknown one. Objects aren't followed
+    /// across a wrapped edge.
+    pub fn classify_objects(&self) -> Vec<(ObjectKind, Cell)> {
+        let known = known_objects();
+        let largest = known.keys().map(Vec::len).max().unwrap_or(0);
+        groups(self, OBJECT_GAP)
+            .into_iter()
+            .filter(|cells| cells.len() <= largest)
+            .filter_map(|cells| {
+                let top = cells.iter().map(|(row, _)| *row).min()?;
+                let left = cells.iter().map(|(_, column)| *column).min()?;
+                let pattern = Pattern::new("", cells.iter().map(|(row, column)| (row - top, column - left)).collect());
+                known.get(&canonical(&pattern)).map(|kind| (*kind, (top, left)))
+            })
+            .collect()
+    }
+
+    /// The groups of living cells that touch, orthogonally or diagonally, each listed row by row.
+    /// Groups aren't followed across a wrapped edge.
+    pub fn components(&self) -> Vec<Vec<Cell>> {
+        groups(self, 1)
+            .into_iter()
+            .map(|mut cells| {
+                cells.sort_unstable();
+                cells
+            })
+            .collect()
+    }
+
+    /// Counts the objects `classify_objects` recognizes by kind, like apgsearch does once a soup
+    /// has settled.
+    pub fn census(&self) -> Census {
+        let mut counts: Vec<(ObjectKind, usize)> = KINDS.iter().map(|kind| (*kind, 0)).collect();
+        for (kind, _) in self.classify_objects() {
+            if let Some((_, count)) = counts.iter_mut().find(|(other, _)| *other == kind) {
+                *count += 1;
+            }
+        }
+        counts.retain(|(_, count)| *count > 0);
+        let mut census = Census { counts };
+        census.sort();
+        census
+    }
+}
+
+/// Every phase of every kind of object, in canonical form.
+fn known_objects() -> &'static HashMap<Vec<Cell>, ObjectKind> {
+    static KNOWN: OnceLock<HashMap<Vec<Cell>, ObjectKind>> = OnceLock::new();
+    KNOWN.get_or_init(|| {
+        let mut known = HashMap::new();
+        for kind in KINDS {
+            let (text, period) = kind.template();
+            let pattern = Pattern::from_plaintext(kind.name(), text);
+            let (rows, columns) = pattern.shape();
+            let mut game = GameOfLife::new(rows + 2 * period, columns + 2 * period);
+            game.set_boundary(Boundary::Dead);
+            pattern.stamp(game.current_mut(), (period, period));
+            for _ in 0..period {
+                known.insert(canonical(&Pattern::from_game(kind.name(), &game)), kind);
+                game.tick();
+            }
+        }
+        known
+    })
+}
+
+/// The smallest of the cells of the pattern's eight rotations and reflections, which is the same
+/// for every orientation of it.
+fn canonical(pattern: &Pattern) -> Vec<Cell> {
+    let mut orientations = vec![];
+    let mut rotated = pattern.clone();
+    for _ in 0..4 {
+        orientations.push(rotated.flip_horizontal().cells().to_vec());
+        orientations.push(rotated.cells().to_vec());
+        rotated = rotated.rotate_clockwise();
+    }
+    orientations.into_iter().min().unwrap_or_default()
+}
+
+/// The groups of living cells at most `gap` rows and columns apart, found with a flood fill.
+fn groups(matrix: &GameMatrix, gap: usize) -> Vec<Vec<Cell>> {
+    let (rows, columns) = matrix.shape();
+    let mut seen = vec![false; rows * columns];
+    let mut objects = vec![];
+    for row in 0..rows {
+        for column in 0..columns {
+            if seen[row * columns + column] || !matrix.get_state((row, column)).is_alive() {
+                continue;
+            }
+            seen[row * columns + column] = true;
+            let mut object = vec![];
+            let mut pending = vec![(row, column)];
+            while let Some(cell) = pending.pop() {
+                object.push(cell);
+                let (row, column) = cell;
+                for neighbor_row in row.saturating_sub(gap)..=(row + gap).min(rows - 1) {
+                    for neighbor_column in column.saturating_sub(gap)..=(column + gap).min(columns - 1) {
+                        let index = neighbor_row * columns + neighbor_column;
+                        if !seen[index] && matrix.get_state((neighbor_row, neighbor_column)).is_alive() {
+                            seen[index] = true;
+                            pending.push((neighbor_row, neighbor_column));
+                        }
+                    }
+                }
+            }
+            objects.push(object);
+        }
+    }
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_objects() {
+        let matrix: GameMatrix = "
+            OO.......O
+            OO.......O
+            .........O
+            ..........
+            ....OOO...
+            ....O.....
+            .....O....
+            ..........
+            ..........
+            ..........
+            OOOOO.....
+            ..........
+            ..........
+            ..........
+            ......OO..
+            ......O...
+            .........O
+            ........OO
+        ".parse().unwrap();
+        let mut objects = matrix.classify_objects();
+        objects.sort_by_key(|(_, cell)| *cell);
+        assert_eq!(objects, vec![
+            (ObjectKind::Block, (0, 0)),
+            (ObjectKind::Blinker, (0, 9)),
+            (ObjectKind::Glider, (4, 4)),
+            (ObjectKind::Beacon, (14, 6)),
+        ]);
+    }
+
+    #[test]
+    fn test_census() {
+        let matrix: GameMatrix = "
+            OO...OO...OOO
+            OO...OO......
+            .............
+            .............
+            OO...........
+            OO...........
+        ".parse().unwrap();
+        let census = matrix.census();
+        assert_eq!(census.counts(), &[(ObjectKind::Block, 3), (ObjectKind::Blinker, 1)]);
+        assert_eq!(census.total(), 4);
+        assert_eq!(census.to_string(), "blocks: 3, blinkers: 1");
+        assert_eq!(GameMatrix::new(3, 3).census().to_string(), "nothing recognized");
+
+        let mut total = Census::default();
+        total.merge(&census);
+        total.merge(&"OOO....OOO".parse::<GameMatrix>().unwrap().census());
+        total.merge(&"OOO".parse::<GameMatrix>().unwrap().census());
+        assert_eq!(total.to_string(), "blinkers: 4, blocks: 3");
+    }
+
+    #[test]
+    fn test_components() {
+        let matrix: GameMatrix = "
+            OO..O
+            .O...
+            ..O..
+            .....
+            O...O
+        ".parse().unwrap();
+        assert_eq!(matrix.components(), vec![
+            vec![(0, 0), (0, 1), (1, 1), (2, 2)],
+            vec![(0, 4)],
+            vec![(4, 0)],
+            vec![(4, 4)],
+        ]);
+    }
+
+    #[test]
+    fn test_every_phase_is_recognized() {
+        let mut game = GameOfLife::new(12, 12);
+        Pattern::from_plaintext("", ".O..O\nO....\nO...O\nOOOO.").stamp(game.current_mut(), (4, 4));
+        for _ in 0..4 {
+            let objects = game.current().classify_objects();
+            assert_eq!(objects.len(), 1);
+            assert_eq!(objects[0].0, ObjectKind::LightweightSpaceship);
+            game.tick();
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use std::fmt;
+use std::sync::OnceLock;
+use crate::engine::{Cell, CellType, GameMatrix, GameOfLife};
+use crate::error::{Error, Result};
+use crate::plane::Point;
+use crate::rule::Rule;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pattern {
+    name: String,
+    metadata: Metadata,
+    rows: usize,
+    columns: usize,
+    cells: Vec<Cell>,
+    /// Where the top left corner sits on the unbounded plane, which `cells` are relative to.
+    origin: Point,
+}
+
+/// Where a pattern came from, as recorded in the headers of pattern files and written back out
+/// with it, so a pattern keeps its provenance through the browser, stamping and exports.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Metadata {
+    pub author: Option<String>,
+    /// When it was found, in whatever form the file gave, usually a year.
+    pub discovered: Option<String>,
+    /// The rule it was found under, when known.
+    pub rule: Option<Rule>,
+    /// Comment lines, in order.
+    pub comments: Vec<String>,
+    /// What kind of pattern it is, for the built-in catalog; pattern files don't say.
+    pub category: Option<Category>,
+}
+
+/// The kinds of pattern the built-in catalog is sorted into.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Category {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    /// A small pattern that takes a long time to settle.
+    Methuselah,
+    Gun,
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::StillLife => "still-life",
+            Category::Oscillator => "oscillator",
+            Category::Spaceship => "spaceship",
+            Category::Methuselah => "methuselah",
+            Category::Gun => "gun",
+        }
+    }
+
+    /// Accepts the names from `name`, in any case and with or without a plural `s`.
+    pub fn from_name(name: &str) -> Option<Category> {
+        let name = name.to_lowercase();
+        let name = name.strip_suffix('s').unwrap_or(&name);
+        [Category::StillLife, Category::Oscillator, Category::Spaceship, Category::Methuselah, Category::Gun]
+            .into_iter()
+            .find(|category| category.name() == name)
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What to look for in the catalog with `search`: every word of `text` somewhere in the name or
+/// author, of the given category, and no larger than `max_size` rows and columns.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Query {
+    pub text: String,
+    pub category: Option<Category>,
+    pub max_size: Option<(usize, usize)>,
+}
+
+impl Query {
+    /// Reads a query as typed into a search box, e.g. `glider spaceships 5x5`: a category name
+    /// sets the category, `RxC` the largest size, and every other word goes into the text.
+    pub fn parse(text: &str) -> Query {
+        let mut query = Query::default();
+        let mut words = vec![];
+        for word in text.split_whitespace() {
+            let size = word.split_once('x').and_then(|(rows, columns)| Some((rows.parse().ok()?, columns.parse().ok()?)));
+            match (Category::from_name(word), size) {
+                (Some(category), _) => query.category = Some(category),
+                (None, Some(size)) => query.max_size = Some(size),
+                (None, None) => words.push(word),
+            }
+        }
+        query.text = words.join(" ");
+        query
+    }
+
+    pub fn matches(&self, pattern: &Pattern) -> bool {
+        let name = pattern.name.to_lowercase();
+        let author = pattern.metadata.author.as_deref().unwrap_or_default().to_lowercase();
+        let (rows, columns) = pattern.shape();
+        self.text.to_lowercase().split_whitespace().all(|word| name.contains(word) || author.contains(word))
+            && self.category.is_none_or(|category| pattern.metadata.category == Some(category))
+            && self.max_size.is_none_or(|(max_rows, max_columns)| rows <= max_rows && columns <= max_columns)
+    }
+}
+
+impl Pattern {
+    pub fn new(name: &str, mut cells: Vec<Cell>) -> Pattern {
+        cells.sort_unstable();
+        let rows = cells.iter().map(|(row, _)| row + 1).max().unwrap_or(0);
+        let columns = cells.iter().map(|(_, column)| column + 1).max().unwrap_or(0);
+        Pattern {
+            name: name.to_string(),
+            metadata: Metadata::default(),
+            rows,
+            columns,
+            cells,
+            origin: (0, 0),
+        }
+    }
+
+    /// A pattern of the living `points` anywhere on the plane, its top left corner at the
+    /// smallest row and column among them.
+    pub fn from_points(name: &str, points: &[Point]) -> Pattern {
+        let top = points.iter().map(|(row, _)| *row).min().unwrap_or(0);
+        let left = points.iter().map(|(_, column)| *column).min().unwrap_or(0);
+        let cells = points.iter().map(|(row, column)| ((row - top) as usize, (column - left) as usize)).collect();
+        Pattern::new(name, cells).with_origin((top, left))
+    }
+
+    /// Parses the plaintext (`.cells`) format: `O` or `*` is alive, anything else is dead and
+    /// lines starting with `!` are comments.
+    pub fn from_plaintext(name: &str, text: &str) -> Pattern {
+        let cells = text
+            .lines()
+            .filter(|line| !line.starts_with('!'))
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == 'O' || *c == '*')
+                    .map(move |(column, _)| (row, column))
+            })
+            .collect();
+        Pattern::new(name, cells)
+    }
+
+    /// The living cells of `matrix`, cropped to their bounding box.
+    pub fn from_matrix(name: &str, matrix: &GameMatrix) -> Pattern {
+        let (rows, columns) = matrix.shape();
+        let cells: Vec<Cell> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .filter(|cell| matrix.get_state(*cell).is_alive())
+            .collect();
+        let top = cells.iter().map(|(row, _)| *row).min().unwrap_or(0);
+        let left = cells.iter().map(|(_, column)| *column).min().unwrap_or(0);
+        Pattern::new(name, cells.into_iter().map(|(row, column)| (row - top, column - left)).collect())
+    }
+
+    /// The living cells of the game's current generation, cropped to their bounding box like
+    /// `from_matrix`, but scanning only the box the game already keeps track of.
+    pub fn from_game(name: &str, game: &GameOfLife) -> Pattern {
+        let Some(bounds) = game.bounding_box() else {
+            return Pattern::new(name, vec![]);
+        };
+        let (rows, columns) = bounds.shape();
+        let cells = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .filter(|(row, column)| game.current().get_state((bounds.top + row, bounds.left + column)).is_alive())
+            .collect();
+        Pattern::new(name, cells)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn with_metadata(mut self, metadata: Metadata) -> Pattern {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn with_origin(mut self, origin: Point) -> Pattern {
+        self.origin = origin;
+        self
+    }
+
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    /// The living cells, relative to the top left corner.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// The living cells where they are on the plane, `origin` added to `cells`.
+    pub fn points(&self) -> impl Iterator<Item=Point> + '_ {
+        let (top, left) = self.origin;
+        self.cells.iter().map(move |(row, column)| (top + *row as i64, left + *column as i64))
+    }
+
+    pub fn rotate_clockwise(&self) -> Pattern {
+        self.transform(|(row, column)| (column, self.rows - 1 - row))
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Pattern {
+        self.transform(|(row, column)| (self.columns - 1 - column, row))
+    }
+
+    pub fn flip_horizontal(&self) -> Pattern {
+        self.transform(|(row, column)| (row, self.columns - 1 - column))
+    }
+
+    fn transform<F>(&self, f: F) -> Pattern
+        where F: Fn(Cell) -> Cell
+    {
+        Pattern::new(&self.name, self.cells.iter().map(|cell| f(*cell)).collect())
+            .with_metadata(self.metadata.clone())
+            .with_origin(self.origin)
+    }
+
+    /// Yields the cells the pattern covers when its top-left corner sits at `origin`, wrapping
+    /// around the edges of a grid of the given shape.
+    pub fn placed_cells(&self, origin: Cell, shape: (usize, usize)) -> impl Iterator<Item=Cell> + '_ {
+        let (rows, columns) = shape;
+        self.cells
+            .iter()
+            .map(move |(row, column)| ((origin.0 + row) % rows, (origin.1 + column) % columns))
+    }
+
+    pub fn stamp(&self, matrix: &mut GameMatrix, origin: Cell) {
+        let cells: Vec<Cell> = self.placed_cells(origin, matrix.shape()).collect();
+        matrix.set_alive(&cells).expect("placed cells wrap onto the grid");
+    }
+
+    /// Stamps the pattern without wrapping, failing and leaving the matrix untouched if any of its
+    /// cells would fall outside of the grid.
+    pub fn try_stamp(&self, matrix: &mut GameMatrix, origin: Cell) -> Result<()> {
+        let (rows, columns) = self.shape();
+        let far_corner = (origin.0 + rows.max(1) - 1, origin.1 + columns.max(1) - 1);
+        if !matrix.contains(far_corner) {
+            return Err(Error::OutOfBounds { cell: far_corner, shape: matrix.shape() });
+        }
+        let cells: Vec<Cell> = self.cells.iter().map(|(row, column)| (origin.0 + row, origin.1 + column)).collect();
+        matrix.set_alive(&cells)
+    }
+}
+
+pub fn builtin() -> Vec<Pattern> {
+    use Category::*;
+
+    vec![
+        catalogued(Spaceship, "Glider", ".O.\n..O\nOOO", Some(("Richard K. Guy", "1969"))),
+        catalogued(Spaceship, "Lightweight spaceship", ".O..O\nO....\nO...O\nOOOO.", Some(("John Conway", "1970"))),
+        catalogued(Oscillator, "Blinker", "OOO", Some(("John Conway", "1969"))),
+        catalogued(Oscillator, "Toad", ".OOO\nOOO.", Some(("Simon Norton", "1970"))),
+        catalogued(Oscillator, "Beacon", "OO..\nOO..\n..OO\n..OO", Some(("John Conway", "1970"))),
+        catalogued(Methuselah, "R-pentomino", ".OO\nOO.\n.O.", Some(("John Conway", "1969"))),
+        catalogued(Methuselah, "Acorn", ".O.....\n...O...\nOO..OOO", Some(("Charles Corderman", "1971"))),
+        catalogued(
+            Gun,
+            "Gosper glider gun",
+            "........................O...........\n\
+             ......................O.O...........\n\
+             ............OO......OO............OO\n\
+             ...........O...O....OO............OO\n\
+             OO........O.....O...OO..............\n\
+             OO........O...O.OO....O.O...........\n\
+             ..........O.....O.......O...........\n\
+             ...........O...O....................\n\
+             ............OO......................",
+            Some(("Bill Gosper", "1970")),
+        ),
+        catalogued(StillLife, "Block", "OO\nOO", None),
+        catalogued(StillLife, "Beehive", ".OO.\nO..O\n.OO.", None),
+        catalogued(StillLife, "Loaf", ".OO.\nO..O\n.O.O\n..O.", None),
+        catalogued(StillLife, "Boat", "OO.\nO.O\n.O.", None),
+    ]
+}
+
+/// A built-in Life pattern in plaintext, and who found it and when, if anyone is credited.
+fn catalogued(category: Category, name: &str, text: &str, found: Option<(&str, &str)>) -> Pattern {
+    Pattern::from_plaintext(name, text).with_metadata(Metadata {
+        author: found.map(|(author, _)| author.to_string()),
+        discovered: found.map(|(_, year)| year.to_string()),
+        rule: Some(Rule::life()),
+        comments: vec![],
+        category: Some(category),
+    })
+}
+
+/// The built-in patterns, built once.
+pub fn catalog() -> &'static [Pattern] {
+    static CATALOG: OnceLock<Vec<Pattern>> = OnceLock::new();
+    CATALOG.get_or_init(builtin)
+}
+
+/// The patterns in the catalog that match `query`, in catalog order.
+pub fn search(query: &Query) -> Vec<&'static Pattern> {
+    catalog().iter().filter(|pattern| query.matches(pattern)).collect()
+}
+
+/// The patterns in the catalog whose name, category or author fuzzily match every word of `text`,
+/// the best matches first and the rest in catalog order. A word matches where it is found whole,
+/// best at the start of a word, or else where its letters are found in order, all but one in four
+/// of them, so that a typo dropping, doubling or swapping a letter still finds what was meant.
+pub fn fuzzy_search(text: &str) -> Vec<&'static Pattern> {
+    let words: Vec<String> = text.to_lowercase().split_whitespace().map(str::to_string).collect();
+    let mut found: Vec<(u32, &Pattern)> = catalog()
+        .iter()
+        .filter_map(|pattern| {
+            let fields = [
+                pattern.name.to_lowercase(),
+                pattern.metadata.category.map(|category| category.name().to_string()).unwrap_or_default(),
+                pattern.metadata.author.as_deref().unwrap_or_default().to_lowercase(),
+            ];
+            let score = words
+                .iter()
+                .map(|word| fields.iter().filter_map(|field| fuzzy_score(word, field)).max())
+                .sum::<Option<u32>>()?;
+            Some((score, pattern))
+        })
+        .collect();
+    found.sort_by(|(a, _), (b, _)| b.cmp(a));
+    found.into_iter().map(|(_, pattern)| pattern).collect()
+}
+
+/// How well `word` matches `text`, or `None` if too few of its letters are in it in order. Finding
+/// it whole scores highest; otherwise every letter matched right after the one before it counts,
+/// and one letter in four may be missing from `text` altogether, costing a point each.
+fn fuzzy_score(word: &str, text: &str) -> Option<u32> {
+    let length = word.chars().count() as u32;
+    if let Some(index) = text.find(word) {
+        let at_word_start = text[..index].chars().next_back().is_none_or(|c| c == ' ' || c == '-');
+        return Some(3 * length + if at_word_start { 2 } else { 0 });
+    }
+    let mut letters = text.chars();
+    let (mut adjacent, mut missing) = (0, 0);
+    for c in word.chars() {
+        let mut ahead = letters.clone();
+        match ahead.position(|letter| letter == c) {
+            Some(skipped) => {
+                letters = ahead;
+                adjacent += (skipped == 0) as u32;
+            }
+            None => missing += 1,
+        }
+    }
+    (missing <= length / 4).then(|| adjacent.saturating_sub(missing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::CellState;
+
+    #[test]
+    fn test_from_plaintext() {
+        let glider = Pattern::from_plaintext("Glider", "!Name: Glider\n.O.\n..O\nOOO");
+        assert_eq!(glider.shape(), (3, 3));
+        assert_eq!(glider.cells(), &[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        let matrix: GameMatrix = "....\n..O.\n...O\n.OOO".parse().unwrap();
+        assert_eq!(Pattern::from_matrix("Glider", &matrix), Pattern::from_plaintext("Glider", ".O.\n..O\nOOO"));
+        assert_eq!(Pattern::from_matrix("", &GameMatrix::new(3, 3)).shape(), (0, 0));
+    }
+
+    #[test]
+    fn test_from_game() {
+        let mut game = GameOfLife::new(6, 6);
+        assert_eq!(Pattern::from_game("", &game).shape(), (0, 0));
+        Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").stamp(game.current_mut(), (1, 2));
+        for _ in 0..3 {
+            game.tick();
+            assert_eq!(Pattern::from_game("Glider", &game), Pattern::from_matrix("Glider", game.current()));
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let pattern = Pattern::from_plaintext("L", "O.\nO.\nOO");
+        let rotated = pattern.rotate_clockwise();
+        assert_eq!(rotated.shape(), (2, 3));
+        assert_eq!(rotated, Pattern::from_plaintext("L", "OOO\nO.."));
+        assert_eq!(rotated.rotate_counter_clockwise(), pattern);
+        assert_eq!(pattern.rotate_clockwise().rotate_clockwise().rotate_clockwise().rotate_clockwise(), pattern);
+    }
+
+    #[test]
+    fn test_flip() {
+        let pattern = Pattern::from_plaintext("L", "O.\nO.\nOO");
+        assert_eq!(pattern.flip_horizontal(), Pattern::from_plaintext("L", ".O\n.O\nOO"));
+    }
+
+    #[test]
+    fn test_stamp_wraps() {
+        let mut matrix = GameMatrix::new(4, 4);
+        Pattern::from_plaintext("Blinker", "OOO").stamp(&mut matrix, (3, 2));
+        assert_eq!(matrix.get_state((3, 2)), CellState::Alive);
+        assert_eq!(matrix.get_state((3, 3)), CellState::Alive);
+        assert_eq!(matrix.get_state((3, 0)), CellState::Alive);
+        assert_eq!(matrix.get_state((3, 1)), CellState::Dead);
+    }
+
+    #[test]
+    fn test_try_stamp() {
+        let mut matrix = GameMatrix::new(4, 4);
+        let blinker = Pattern::from_plaintext("Blinker", "OOO");
+        assert!(blinker.try_stamp(&mut matrix, (3, 2)).is_err());
+        assert_eq!(matrix.population(), 0);
+        assert!(blinker.try_stamp(&mut matrix, (3, 1)).is_ok());
+        assert_eq!(matrix.population(), 3);
+    }
+
+    #[test]
+    fn test_search() {
+        let names = |query: &str| search(&Query::parse(query)).iter().map(|pattern| pattern.name()).collect::<Vec<_>>();
+        assert_eq!(names("spaceships"), vec!["Glider", "Lightweight spaceship"]);
+        assert_eq!(names("GLIDER"), vec!["Glider", "Gosper glider gun"]);
+        assert_eq!(names("conway oscillator"), vec!["Blinker", "Beacon"]);
+        assert_eq!(names("still-life 3x3"), vec!["Block", "Boat"]);
+        assert!(names("glider still-life").is_empty());
+        assert_eq!(names("").len(), catalog().len());
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let names = |text: &str| fuzzy_search(text).iter().map(|pattern| pattern.name()).collect::<Vec<_>>();
+        // Equal matches keep catalog order, and a typo still finds both, the closer one first.
+        assert_eq!(names("glider"), vec!["Glider", "Gosper glider gun"]);
+        assert_eq!(names("glidr"), vec!["Glider", "Gosper glider gun"]);
+        assert_eq!(names("gliider"), vec!["Glider", "Gosper glider gun"]);
+        assert_eq!(names("blinkre"), vec!["Blinker"]);
+        assert_eq!(names("gun"), vec!["Gosper glider gun"]);
+        assert_eq!(names("conway oscilator"), vec!["Blinker", "Beacon"]);
+        assert_eq!(names("still life")[0], "Block");
+        assert!(names("puffer").is_empty());
+        assert_eq!(names("").len(), catalog().len());
+    }
+
+    #[test]
+    fn test_query() {
+        assert_eq!(Query::parse(" gun  Gosper 40x40 "), Query {
+            text: "Gosper".to_string(),
+            category: Some(Category::Gun),
+            max_size: Some((40, 40)),
+        });
+        assert_eq!(Category::from_name("Still-Lifes"), Some(Category::StillLife));
+        assert_eq!(Category::from_name("puffer"), None);
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::engine::{Boundary, Cell, CellType, GameOfLife};
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+
+/// What a pattern does in the long run, as found by `Pattern::periodicity`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Behavior {
+    DiesOut,
+    StillLife,
+    Oscillator { period: u64 },
+    /// Comes back to the same shape every `period` generations, moved `displacement` rows and
+    /// columns.
+    Spaceship { period: u64, displacement: (isize, isize) },
+}
+
+/// A pattern's `Behavior` and the generation it starts behaving so from: zero when the pattern
+/// itself is a still life, oscillator or spaceship rather than turning into one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Periodicity {
+    pub behavior: Behavior,
+    pub start: u64,
+}
+
+impl fmt::Display for Behavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Behavior::DiesOut => f.write_str("dies out"),
+            Behavior::StillLife => f.write_str("still life"),
+            Behavior::Oscillator { period } => write!(f, "oscillator with period {}", period),
+            Behavior::Spaceship { period, displacement: (rows, columns) } => {
+                write!(f, "spaceship with period {} and displacement ({}, {})", period, rows, columns)
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// Runs the pattern on an unbounded grid under `rule` for at most `max` generations, looking
+    /// for a generation it has been in before, anywhere on the grid: every generation is hashed
+    /// with its cells relative to their bounding box, so a shape that comes back moved is found
+    /// as readily as one that comes back in place. `None` if nothing repeats in time.
+    pub fn periodicity(&self, rule: Rule, max: u64) -> Option<Periodicity> {
+        let mut seen: HashMap<Vec<Cell>, (u64, (isize, isize))> = HashMap::new();
+        let (mut cells, mut position) = normalize(self.cells().to_vec());
+        for generation in 0..=max {
+            if cells.is_empty() {
+                return Some(Periodicity { behavior: Behavior::DiesOut, start: generation });
+            }
+            if let Some(&(start, earlier)) = seen.get(&cells) {
+                let period = generation - start;
+                let displacement = (position.0 - earlier.0, position.1 - earlier.1);
+                let behavior = match (period, displacement) {
+                    (1, (0, 0)) => Behavior::StillLife,
+                    (_, (0, 0)) => Behavior::Oscillator { period },
+                    _ => Behavior::Spaceship { period, displacement },
+                };
+                return Some(Periodicity { behavior, start });
+            }
+            let (next, shift) = step(&cells, rule);
+            seen.insert(cells, (generation, position));
+            cells = next;
+            position = (position.0 + shift.0, position.1 + shift.1);
+        }
+        None
+    }
+}
+
+/// The cells moved so their bounding box starts at the origin, and where it started.
+fn normalize(cells: Vec<Cell>) -> (Vec<Cell>, (isize, isize)) {
+    let top = cells.iter().map(|(row, _)| *row).min().unwrap_or(0);
+    let left = cells.iter().map(|(_, column)| *column).min().unwrap_or(0);
+    let mut normalized: Vec<Cell> = cells.into_iter().map(|(row, column)| (row - top, column - left)).collect();
+    normalized.sort_unstable();
+    (normalized, (top as isize, left as isize))
+}
+
+/// The next generation of normalized cells, normalized again, and how far its bounding box moved.
+/// Cells spread at most one step per generation, so a one cell margin is all the room it needs.
+fn step(cells: &[Cell], rule: Rule) -> (Vec<Cell>, (isize, isize)) {
+    let rows = cells.iter().map(|(row, _)| row + 1).max().unwrap_or(0);
+    let columns = cells.iter().map(|(_, column)| column + 1).max().unwrap_or(0);
+    let shifted: Vec<Cell> = cells.iter().map(|(row, column)| (row + 1, column + 1)).collect();
+    let mut game = GameOfLife::from_cells(rows + 2, columns + 2, &shifted).expect("cells fit the margin");
+    game.set_boundary(Boundary::Dead);
+    game.set_rule(rule);
+    game.tick();
+    let alive = (0..rows + 2)
+        .flat_map(|row| (0..columns + 2).map(move |column| (row, column)))
+        .filter(|cell| game.current().get_state(*cell).is_alive())
+        .collect();
+    let (next, (top, left)) = normalize(alive);
+    (next, (top - 1, left - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn periodicity(text: &str) -> Option<Periodicity> {
+        Pattern::from_plaintext("", text).periodicity(Rule::life(), 100)
+    }
+
+    #[test]
+    fn test_periodicity() {
+        let behavior = |text| periodicity(text).map(|periodicity| periodicity.behavior);
+        assert_eq!(behavior("OO\nOO"), Some(Behavior::StillLife));
+        assert_eq!(behavior("OOO"), Some(Behavior::Oscillator { period: 2 }));
+        assert_eq!(behavior("OO..\nOO..\n..OO\n..OO"), Some(Behavior::Oscillator { period: 2 }));
+        assert_eq!(behavior(".O.\n..O\nOOO"), Some(Behavior::Spaceship { period: 4, displacement: (1, 1) }));
+        assert_eq!(behavior(".O..O\nO....\nO...O\nOOOO."), Some(Behavior::Spaceship { period: 4, displacement: (0, -2) }));
+        assert_eq!(behavior("O"), Some(Behavior::DiesOut));
+    }
+
+    #[test]
+    fn test_periodicity_after_a_while() {
+        // Four in a row turns into a beehive by way of one other shape.
+        assert_eq!(periodicity("OOOO"), Some(Periodicity { behavior: Behavior::StillLife, start: 2 }));
+        // The R-pentomino takes over a thousand generations to settle.
+        assert_eq!(periodicity(".OO\nOO.\n.O."), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Behavior::Oscillator { period: 15 }.to_string(), "oscillator with period 15");
+        assert_eq!(
+            Behavior::Spaceship { period: 4, displacement: (1, 1) }.to_string(),
+            "spaceship with period 4 and displacement (1, 1)"
+        );
+    }
+}
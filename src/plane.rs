@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+use crate::engine::{Cell, GameMatrix};
+use crate::error::{Error, Result};
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+
+/// A row and column on the unbounded plane, either of which may be negative.
+pub type Point = (i64, i64);
+
+/// The window a grid of `shape` cells gives onto the plane, its top left cell at `origin`:
+/// translates between the grid's cells and the plane's points, so a pattern far from the origin
+/// can be shown, and one edited on the grid put back where it was.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Viewport {
+    pub origin: Point,
+    pub shape: (usize, usize),
+}
+
+impl Viewport {
+    /// The viewport of `shape` with `pattern` in the middle of it.
+    pub fn centered(pattern: &Pattern, shape: (usize, usize)) -> Viewport {
+        let (top, left) = pattern.origin();
+        let (rows, columns) = pattern.shape();
+        let margin = |outer: usize, inner: usize| (outer as i64 - inner as i64) / 2;
+        Viewport { origin: (top - margin(shape.0, rows), left - margin(shape.1, columns)), shape }
+    }
+
+    /// The grid cell showing `point`, if it is in view.
+    pub fn cell(&self, (row, column): Point) -> Option<Cell> {
+        let inside = |position: i64, start: i64, size: usize| {
+            usize::try_from(position.checked_sub(start)?).ok().filter(|offset| *offset < size)
+        };
+        Some((inside(row, self.origin.0, self.shape.0)?, inside(column, self.origin.1, self.shape.1)?))
+    }
+
+    /// The point the grid `cell` shows.
+    pub fn point(&self, (row, column): Cell) -> Point {
+        (self.origin.0 + row as i64, self.origin.1 + column as i64)
+    }
+
+    /// Brings the living points of `pattern` that are in view to life on `matrix`, which has the
+    /// viewport's shape.
+    pub fn stamp(&self, pattern: &Pattern, matrix: &mut GameMatrix) {
+        let cells: Vec<Cell> = pattern.points().filter_map(|point| self.cell(point)).collect();
+        matrix.set_alive(&cells).expect("the matrix has the viewport's shape");
+    }
+}
+
+/// Life on the whole plane, keeping only its living points, so however far a pattern spreads or
+/// flies it never meets an edge, and the work each tick goes with the population.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Plane {
+    alive: HashSet<Point>,
+}
+
+impl Plane {
+    pub fn from_pattern(pattern: &Pattern) -> Plane {
+        Plane { alive: pattern.points().collect() }
+    }
+
+    pub fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    pub fn is_alive(&self, point: Point) -> bool {
+        self.alive.contains(&point)
+    }
+
+    pub fn set_alive(&mut self, point: Point, alive: bool) {
+        match alive {
+            true => self.alive.insert(point),
+            false => self.alive.remove(&point),
+        };
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle holding every living
+    /// point, `None` when there are none.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let rows = self.alive.iter().map(|(row, _)| *row);
+        let columns = self.alive.iter().map(|(_, column)| *column);
+        Some(((rows.clone().min()?, columns.clone().min()?), (rows.max()?, columns.max()?)))
+    }
+
+    /// Moves the plane on a generation under `rule`. Rules with B0 are refused: under them every
+    /// dead point on the plane, of which there are endlessly many, would be born at once.
+    pub fn tick(&mut self, rule: &Rule) -> Result<()> {
+        if rule.is_born(0) {
+            return Err(Error::InvalidOption(format!("{} can't run on an unbounded plane", rule)));
+        }
+        let mut neighbors: HashMap<Point, usize> = HashMap::new();
+        for (row, column) in &self.alive {
+            // Every living point is counted, even with no neighbors, for rules that survive on 0.
+            neighbors.entry((*row, *column)).or_insert(0);
+            for d_row in -1..=1 {
+                for d_column in -1..=1 {
+                    if (d_row, d_column) != (0, 0) {
+                        *neighbors.entry((row.wrapping_add(d_row), column.wrapping_add(d_column))).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        let next = neighbors
+            .into_iter()
+            .filter(|(point, count)| match self.alive.contains(point) {
+                true => rule.survives(*count),
+                false => rule.is_born(*count),
+            })
+            .map(|(point, _)| point)
+            .collect();
+        self.alive = next;
+        Ok(())
+    }
+
+    /// The living points as a pattern, with its origin where they are.
+    pub fn to_pattern(&self, name: &str) -> Pattern {
+        Pattern::from_points(name, &self.alive.iter().copied().collect::<Vec<_>>())
+    }
+
+    /// What the plane looks like through `viewport`.
+    pub fn view(&self, viewport: &Viewport) -> GameMatrix {
+        let mut matrix = GameMatrix::new(viewport.shape.0, viewport.shape.1);
+        let cells: Vec<Cell> = self.alive.iter().filter_map(|point| viewport.cell(*point)).collect();
+        matrix.set_alive(&cells).expect("viewport cells are on its grid");
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport() {
+        let viewport = Viewport { origin: (-5, 1_000_000_000_000), shape: (10, 20) };
+        assert_eq!(viewport.cell((-5, 1_000_000_000_000)), Some((0, 0)));
+        assert_eq!(viewport.cell((4, 1_000_000_000_019)), Some((9, 19)));
+        assert_eq!(viewport.cell((5, 1_000_000_000_000)), None);
+        assert_eq!(viewport.cell((-6, 1_000_000_000_000)), None);
+        assert_eq!(viewport.cell((i64::MIN, i64::MAX)), None);
+        assert_eq!(viewport.point((9, 19)), (4, 1_000_000_000_019));
+    }
+
+    #[test]
+    fn test_plane() {
+        // A glider flies south east a cell every four generations, past where any grid would end.
+        let glider = Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").with_origin((-100, -100));
+        let mut plane = Plane::from_pattern(&glider);
+        for _ in 0..400 {
+            plane.tick(&Rule::life()).unwrap();
+        }
+        let moved = plane.to_pattern("Glider");
+        assert_eq!((moved.origin(), moved.cells()), ((0, 0), glider.cells()));
+        assert_eq!(plane.bounds(), Some(((0, 0), (2, 2))));
+
+        let viewport = Viewport::centered(&moved, (7, 7));
+        assert_eq!(viewport.origin, (-2, -2));
+        let mut matrix = GameMatrix::new(7, 7);
+        viewport.stamp(&moved, &mut matrix);
+        assert_eq!(matrix, plane.view(&viewport));
+        assert_eq!(Pattern::from_matrix("Glider", &matrix).cells(), glider.cells());
+    }
+
+    #[test]
+    fn test_survives_alone() {
+        // Under Life without Death a cell with no neighbors lives on; under Life it dies.
+        let mut plane = Plane::from_pattern(&Pattern::from_plaintext("Dot", "O").with_origin((7, -3)));
+        plane.tick(&"B3/S012345678".parse().unwrap()).unwrap();
+        assert!(plane.is_alive((7, -3)));
+        plane.tick(&Rule::life()).unwrap();
+        assert_eq!(plane.population(), 0);
+    }
+
+    #[test]
+    fn test_born_from_nothing() {
+        // Under B0 the empty plane around a dot would fill at once, so it isn't run at all.
+        let mut plane = Plane::from_pattern(&Pattern::from_plaintext("Dot", "O"));
+        assert!(plane.tick(&"B0/S8".parse().unwrap()).is_err());
+        assert_eq!(plane.population(), 1);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::engine::{Cell, CellChange, CellState, CellType, GameOfLife};
+
+/// Encodes the complete state of a game as one line of JSON, for clients that have just
+/// connected or need to resynchronize after an edit:
+///
+/// `{"type":"snapshot","generation":0,"rows":3,"columns":3,"rule":"B3/S23","alive":[[1,1]]}`
+pub fn snapshot(game: &GameOfLife, generation: u64) -> String {
+    let (rows, columns) = game.shape();
+    let matrix = game.current();
+    let alive: Vec<Cell> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .filter(|cell| matrix.get_state(*cell).is_alive())
+        .collect();
+    format!(
+        "{{\"type\":\"snapshot\",\"generation\":{},\"rows\":{},\"columns\":{},\"rule\":\"{}\",\"alive\":{}}}",
+        generation, rows, columns, game.rule(), cells(&alive)
+    )
+}
+
+/// Encodes one generation's changes as one line of JSON, split into cells that were born and
+/// cells that died:
+///
+/// `{"type":"generation","generation":1,"born":[[0,1]],"died":[[1,1]]}`
+pub fn generation(changes: &[CellChange], generation: u64) -> String {
+    let with_state = |state: CellState| -> Vec<Cell> {
+        changes.iter().filter(|change| change.state == state).map(|change| change.cell).collect()
+    };
+    format!(
+        "{{\"type\":\"generation\",\"generation\":{},\"born\":{},\"died\":{}}}",
+        generation, cells(&with_state(CellState::Alive)), cells(&with_state(CellState::Dead))
+    )
+}
+
+fn cells(cells: &[Cell]) -> String {
+    let pairs: Vec<String> = cells.iter().map(|(row, column)| format!("[{},{}]", row, column)).collect();
+    format!("[{}]", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_generation() {
+        let mut game = GameOfLife::new(3, 3);
+        *game.current_mut() = "...\nOOO\n...".parse().unwrap();
+        assert_eq!(
+            snapshot(&game, 7),
+            "{\"type\":\"snapshot\",\"generation\":7,\"rows\":3,\"columns\":3,\"rule\":\"B3/S23\",\"alive\":[[1,0],[1,1],[1,2]]}"
+        );
+
+        game.set_boundary(crate::Boundary::Dead);
+        game.tick();
+        assert_eq!(
+            generation(&game.changes(), 8),
+            "{\"type\":\"generation\",\"generation\":8,\"born\":[[0,1],[2,1]],\"died\":[[1,0],[1,2]]}"
+        );
+    }
+}
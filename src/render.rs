@@ -0,0 +1,59 @@
+use crate::engine::{Cell, CellChange, CellState, GameMatrix};
+use crate::error::Result;
+
+/// A frontend that displays a game, driven by the cells that change from one generation to the
+/// next so that backends only redraw what they have to.
+pub trait Renderer {
+    /// Draws the cells that changed since the previous call. Pass `full_frame` first so that every
+    /// cell has been drawn once.
+    fn draw(&mut self, changes: &[CellChange]) -> Result<()>;
+}
+
+/// Changes that draw every cell of the matrix from scratch.
+pub fn full_frame(matrix: &GameMatrix) -> Vec<CellChange> {
+    let (rows, columns) = matrix.shape();
+    let mut changes = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        for column in 0..columns {
+            let cell = (row, column);
+            changes.push(CellChange { cell, state: matrix.get_state(cell) });
+        }
+    }
+    changes
+}
+
+/// A matrix can render into itself, keeping an in-memory copy of what a frontend shows. Useful
+/// for backends that redraw the whole image every frame and for tests.
+impl Renderer for GameMatrix<CellState> {
+    fn draw(&mut self, changes: &[CellChange]) -> Result<()> {
+        let states: Vec<(Cell, CellState)> = changes.iter().map(|change| (change.cell, change.state)).collect();
+        self.set_states(&states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::GameOfLife;
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn test_diffs_reproduce_the_game() {
+        let mut game = GameOfLife::new(8, 8);
+        Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").stamp(game.current_mut(), (0, 0));
+
+        let mut screen = GameMatrix::new(8, 8);
+        screen.draw(&full_frame(game.current())).unwrap();
+        for _ in 0..20 {
+            game.tick();
+            screen.draw(&game.changes()).unwrap();
+            assert_eq!(&screen, game.current());
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_changes_are_errors() {
+        let mut screen = GameMatrix::new(2, 2);
+        assert!(screen.draw(&[CellChange { cell: (2, 0), state: CellState::Alive }]).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use crate::delta::{push_number, Delta};
+use crate::engine::{Cell, GameMatrix, GameOfLife};
+use crate::error::{Error, Result};
+use crate::rule::Rule;
+
+const MAGIC: &[u8] = b"GOLREC";
+const VERSION: u8 = 2;
+
+/// The most cells accepted in a replay's grid, so a corrupt header cannot exhaust memory.
+const MAX_CELLS: usize = 1 << 28;
+
+/// A recorded run: the grid it started from and, for every update after that, the generation it
+/// reached and the cells that changed on the way. Only living cells are recorded, so walls play
+/// back as dead cells and immortal ones as ordinary live ones. Written by `Recorder` in a compact
+/// binary format, every number an unsigned LEB128 varint:
+///
+/// - the magic `GOLREC` and a version byte, 2
+/// - rows and columns, then the rule as a length and that many bytes of `B3/S23` notation
+/// - the living cells of the first generation, as a `Delta` from an empty grid
+/// - frames until the end of the file, each a generation and the `Delta` from the frame before
+///
+/// Version 1 files, which listed cells as a count and the gaps between their indices rather than
+/// as deltas, are still read.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    rule: Rule,
+    initial: GameMatrix,
+    frames: Vec<Frame>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+    pub generation: u64,
+    /// The cells that were born or died since the frame before.
+    pub delta: Delta,
+}
+
+impl Replay {
+    pub fn load<P>(path: P) -> Result<Replay>
+        where P: AsRef<Path>
+    {
+        Replay::parse(&fs::read(path)?)
+    }
+
+    /// Reads a replay written by `Recorder`. A frame cut short at the end, as a run killed while
+    /// recording leaves behind, is dropped rather than failing the whole replay.
+    pub fn parse(bytes: &[u8]) -> Result<Replay> {
+        let bytes = bytes.strip_prefix(MAGIC).ok_or_else(|| parse_error("not a replay"))?;
+        let mut reader = Reader { bytes, position: 0 };
+        let version = reader.byte()?;
+        if version != 1 && version != VERSION {
+            return Err(parse_error("unsupported replay version"));
+        }
+        let (rows, columns) = (reader.number()? as usize, reader.number()? as usize);
+        if rows.checked_mul(columns).is_none_or(|cells| cells > MAX_CELLS) {
+            return Err(parse_error(&format!("a {}x{} grid is too large", rows, columns)));
+        }
+        let length = reader.number()? as usize;
+        let rule = String::from_utf8_lossy(reader.take(length)?)
+            .parse()
+            .map_err(|_| parse_error("invalid rule"))?;
+        let mut initial = GameMatrix::new(rows, columns);
+        let alive = reader.delta(version)?;
+        check_bounds(&alive, (rows, columns))?;
+        alive.apply(&mut initial);
+
+        let mut frames = vec![];
+        while !reader.is_empty() {
+            let frame = match (reader.number(), reader.delta(version)) {
+                (Ok(generation), Ok(delta)) => Frame { generation, delta },
+                _ => break,
+            };
+            check_bounds(&frame.delta, (rows, columns))?;
+            frames.push(frame);
+        }
+        Ok(Replay { rule, initial, frames })
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        self.initial.shape()
+    }
+
+    pub fn initial(&self) -> &GameMatrix {
+        &self.initial
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+}
+
+/// Writes a replay of a game as it runs, starting from the generation it is created with.
+pub struct Recorder<W>
+    where W: Write
+{
+    writer: W,
+    last: GameMatrix,
+    generation: u64,
+}
+
+impl<W> Recorder<W>
+    where W: Write
+{
+    pub fn new(mut writer: W, game: &GameOfLife) -> Result<Recorder<W>> {
+        let (rows, columns) = game.shape();
+        let rule = game.rule().to_string();
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        for number in [rows as u64, columns as u64, rule.len() as u64] {
+            push_number(&mut header, number);
+        }
+        header.extend(rule.bytes());
+        header.extend(Delta::between(&GameMatrix::new(rows, columns), game.current()).as_bytes());
+        writer.write_all(&header)?;
+        Ok(Recorder { writer, last: game.current().clone(), generation: 0 })
+    }
+
+    /// Adds a frame for `matrix` at `generation`, unless neither has changed since the last one.
+    pub fn record(&mut self, generation: u64, matrix: &GameMatrix) -> Result<()> {
+        let delta = Delta::between(&self.last, matrix);
+        if delta.is_empty() && generation == self.generation {
+            return Ok(());
+        }
+        let mut frame = vec![];
+        push_number(&mut frame, generation);
+        frame.extend(delta.as_bytes());
+        self.writer.write_all(&frame)?;
+        self.last = matrix.clone();
+        self.generation = generation;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Plays a replay forward and backward, a frame at a time or by jumping straight to one. Frames
+/// only say which cells changed, so going back over one is the same as going forward over it.
+pub struct Playback {
+    replay: Replay,
+    matrix: GameMatrix,
+    position: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        let matrix = replay.initial.clone();
+        Playback { replay, matrix, position: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn matrix(&self) -> &GameMatrix {
+        &self.matrix
+    }
+
+    /// How many frames have been played: zero at the first generation, up to `len`.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.replay.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replay.frames.is_empty()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.position.checked_sub(1).map_or(0, |index| self.replay.frames[index].generation)
+    }
+
+    /// Moves to the given number of played frames, clamped to the end of the replay.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.len());
+        let (from, to) = (self.position.min(position), self.position.max(position));
+        for frame in &self.replay.frames[from..to] {
+            frame.delta.apply(&mut self.matrix);
+        }
+        self.position = position;
+    }
+}
+
+/// Fails if `delta` toggles any cell outside a grid of the given shape.
+fn check_bounds(delta: &Delta, shape: (usize, usize)) -> Result<()> {
+    let (rows, columns) = shape;
+    if delta.end() <= rows * columns {
+        return Ok(());
+    }
+    let index = delta.end() - 1;
+    let cell: Cell = (index / columns.max(1), index % columns.max(1));
+    Err(Error::OutOfBounds { cell, shape })
+}
+
+fn parse_error(message: &str) -> Error {
+    Error::Parse(format!("replay: {}", message))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self.position.checked_add(length).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| parse_error("unexpected end of file"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn number(&mut self) -> Result<u64> {
+        let mut number = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            number |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(number);
+            }
+        }
+        Err(parse_error("number too large"))
+    }
+
+    /// Reads the cells toggled in a frame, in the encoding of the given replay version.
+    fn delta(&mut self, version: u8) -> Result<Delta> {
+        if version != 1 {
+            let (delta, used) = Delta::parse(&self.bytes[self.position..])?;
+            self.position += used;
+            return Ok(delta);
+        }
+        let count = self.number()? as usize;
+        let mut indices = Vec::with_capacity(count.min(self.bytes.len()));
+        let mut index: u64 = 0;
+        for _ in 0..count {
+            index = index.checked_add(self.number()?).ok_or_else(|| parse_error("cell index too large"))?;
+            indices.push(usize::try_from(index).map_err(|_| parse_error("cell index too large"))?);
+        }
+        Ok(Delta::from_indices(indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::CellState;
+
+    fn record(game: &mut GameOfLife, generations: u64) -> Vec<u8> {
+        let mut recorder = Recorder::new(vec![], game).unwrap();
+        for generation in 1..=generations {
+            game.tick();
+            recorder.record(generation, game.current()).unwrap();
+        }
+        recorder.writer
+    }
+
+    #[test]
+    fn test_record_and_play() {
+        let mut game = GameOfLife::new(8, 8);
+        *game.current_mut() = ".O......\n..O.....\nOOO.....\n........\n........\n........\n........\n........".parse().unwrap();
+        let initial = game.current().clone();
+        let bytes = record(&mut game, 4);
+
+        let replay = Replay::parse(&bytes).unwrap();
+        assert_eq!(replay.shape(), (8, 8));
+        assert_eq!(replay.rule(), Rule::life());
+        assert_eq!(replay.frames().len(), 4);
+
+        let mut playback = Playback::new(replay);
+        playback.seek(10);
+        assert_eq!((playback.position(), playback.generation()), (4, 4));
+        assert_eq!(playback.matrix(), game.current());
+        playback.seek(0);
+        assert_eq!((playback.position(), playback.generation()), (0, 0));
+        assert_eq!(playback.matrix(), &initial);
+    }
+
+    #[test]
+    fn test_compact() {
+        // Sixteen bytes of header and four for the first generation's cells, a run of three, then
+        // a byte for each generation and six for the four cells that change in it.
+        let mut game = GameOfLife::new(100, 100);
+        for column in 10..13 {
+            game.current_mut().set_state((10, column), CellState::Alive);
+        }
+        let bytes = record(&mut game, 100);
+        assert_eq!(bytes.len(), 16 + 4 + 100 * 7);
+    }
+
+    #[test]
+    fn test_version_1() {
+        // A blinker on a 5x5 grid and a frame turning it upright, as the first version wrote it:
+        // each list of cells a count and the gaps between their indices.
+        let mut bytes = b"GOLREC\x01\x05\x05\x06B3/S23".to_vec();
+        bytes.extend([3, 11, 1, 1]);
+        bytes.extend([1, 4, 7, 4, 2, 4]);
+        let replay = Replay::parse(&bytes).unwrap();
+        assert_eq!(replay.initial().to_string(), ".....\n.....\n.OOO.\n.....\n.....");
+        assert_eq!(replay.frames()[0].generation, 1);
+
+        let mut playback = Playback::new(replay);
+        playback.seek(1);
+        assert_eq!(playback.matrix().to_string(), ".....\n..O..\n..O..\n..O..\n.....");
+
+        // A cell past the end of the grid.
+        let mut bytes = b"GOLREC\x01\x05\x05\x06B3/S23".to_vec();
+        bytes.extend([1, 25]);
+        assert!(matches!(Replay::parse(&bytes), Err(Error::OutOfBounds { cell: (5, 0), .. })));
+    }
+
+    #[test]
+    fn test_damaged() {
+        let mut game = GameOfLife::new(8, 8);
+        for column in 0..3 {
+            game.current_mut().set_state((4, column), CellState::Alive);
+        }
+        let bytes = record(&mut game, 3);
+        assert!(matches!(Replay::parse(b"GOLREX\x01"), Err(Error::Parse(_))));
+        assert!(matches!(Replay::parse(&bytes[..10]), Err(Error::Parse(_))));
+        // The last frame was cut short and is left out.
+        assert_eq!(Replay::parse(&bytes[..bytes.len() - 1]).unwrap().frames().len(), 2);
+    }
+}
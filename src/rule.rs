@@ -0,0 +1,214 @@
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "rand")]
+use rand::Rng;
+use crate::error::Error;
+
+/// An outer totalistic rule: which live neighbor counts cause a dead cell to be born and which
+/// let a live cell survive.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Rule {
+        let mut rule = Rule {
+            birth: [false; 9],
+            survival: [false; 9],
+        };
+        for count in birth.iter().filter(|count| **count <= 8) {
+            rule.birth[*count] = true;
+        }
+        for count in survival.iter().filter(|count| **count <= 8) {
+            rule.survival[*count] = true;
+        }
+        rule
+    }
+
+    pub fn life() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    /// A rule with its birth and survival counts picked at random, for discovering automata by
+    /// chance. There is always at least one birth count, and never births on no neighbors at
+    /// all, which would make empty space flash on and off.
+    #[cfg(feature = "rand")]
+    pub fn random<R>(rng: &mut R) -> Rule
+        where R: Rng
+    {
+        let mut birth: Vec<usize> = (1..=8).filter(|_| rng.gen_bool(0.3)).collect();
+        if birth.is_empty() {
+            birth.push(rng.gen_range(1..=8));
+        }
+        let survival: Vec<usize> = (0..=8).filter(|_| rng.gen_bool(0.4)).collect();
+        Rule::new(&birth, &survival)
+    }
+
+    pub fn is_born(&self, alive_neighbors: usize) -> bool {
+        self.birth[alive_neighbors]
+    }
+
+    pub fn survives(&self, alive_neighbors: usize) -> bool {
+        self.survival[alive_neighbors]
+    }
+
+    /// Makes dead cells with `alive_neighbors` living neighbors be born, or not. Counts over 8
+    /// are ignored, as in `new`.
+    pub fn set_birth(&mut self, alive_neighbors: usize, born: bool) {
+        if let Some(flag) = self.birth.get_mut(alive_neighbors) {
+            *flag = born;
+        }
+    }
+
+    /// Makes living cells with `alive_neighbors` living neighbors survive, or not.
+    pub fn set_survival(&mut self, alive_neighbors: usize, survives: bool) {
+        if let Some(flag) = self.survival.get_mut(alive_neighbors) {
+            *flag = survives;
+        }
+    }
+}
+
+/// A well known rule, with what makes it worth watching and the density of random soup that
+/// shows it off best.
+#[derive(Debug)]
+pub struct RulePreset {
+    pub name: &'static str,
+    /// The rule in `B3/S23` notation.
+    pub notation: &'static str,
+    pub description: &'static str,
+    pub density: f64,
+}
+
+/// The famous rules, selectable by name wherever a rule is asked for.
+pub const PRESETS: &[RulePreset] = &[
+    RulePreset { name: "Life", notation: "B3/S23", description: "Conway's original, full of gliders and oscillators", density: 1.0 / 7.0 },
+    RulePreset { name: "HighLife", notation: "B36/S23", description: "Life with a self-replicating pattern", density: 0.15 },
+    RulePreset { name: "Seeds", notation: "B2/S", description: "every cell dies at once, yet almost anything explodes", density: 0.02 },
+    RulePreset { name: "Day & Night", notation: "B3678/S34678", description: "live and dead cells behave alike, growing blobs", density: 0.5 },
+    RulePreset { name: "Anneal", notation: "B4678/S35678", description: "the majority wins and boundaries smooth out", density: 0.5 },
+    RulePreset { name: "Diamoeba", notation: "B35678/S5678", description: "large diamonds with chaotic edges", density: 0.5 },
+    RulePreset { name: "Maze", notation: "B3/S12345", description: "grows corridors from a small seed", density: 0.05 },
+    RulePreset { name: "Life without Death", notation: "B3/S012345678", description: "cells never die, growing ladders and inkblots", density: 0.08 },
+    RulePreset { name: "2x2", notation: "B36/S125", description: "patterns built out of 2x2 blocks", density: 0.3 },
+    RulePreset { name: "Morley", notation: "B368/S245", description: "rich in spaceships, also called Move", density: 0.15 },
+    RulePreset { name: "Coral", notation: "B3/S45678", description: "slow coral-like growth", density: 0.1 },
+    RulePreset { name: "Replicator", notation: "B1357/S1357", description: "every pattern copies itself", density: 0.01 },
+];
+
+impl RulePreset {
+    pub fn rule(&self) -> Rule {
+        self.notation.parse().expect("presets are valid rules")
+    }
+
+    /// Looks a preset up by name, ignoring case and anything but letters and digits, so
+    /// `daynight` finds Day & Night.
+    pub fn find(name: &str) -> Option<&'static RulePreset> {
+        let key = |name: &str| -> String { name.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_lowercase()).collect() };
+        let name = key(name);
+        PRESETS.iter().find(|preset| key(preset.name) == name)
+    }
+
+    /// The preset running `rule`, if there is one.
+    pub fn of(rule: Rule) -> Option<&'static RulePreset> {
+        PRESETS.iter().find(|preset| preset.rule() == rule)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::life()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |flags: &[bool; 9]| -> String {
+            (0..9).filter(|count| flags[*count]).map(|count| count.to_string()).collect()
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    /// Accepts both `B3/S23` notation and the older `23/3` survival/birth notation.
+    fn from_str(text: &str) -> Result<Rule, Error> {
+        let invalid = || Error::Parse(format!("invalid rule '{}'", text));
+        let digits = |part: &str| -> Result<Vec<usize>, Error> {
+            part.chars()
+                .map(|c| c.to_digit(10).filter(|digit| *digit <= 8).map(|digit| digit as usize).ok_or_else(invalid))
+                .collect()
+        };
+
+        let (first, second) = text.trim().split_once('/').ok_or_else(invalid)?;
+        let upper = |part: &str| part.to_ascii_uppercase();
+        let (first, second) = (upper(first), upper(second));
+        match (first.strip_prefix('B'), second.strip_prefix('S')) {
+            (Some(birth), Some(survival)) => Ok(Rule::new(&digits(birth)?, &digits(survival)?)),
+            _ => match (first.strip_prefix('S'), second.strip_prefix('B')) {
+                (Some(survival), Some(birth)) => Ok(Rule::new(&digits(birth)?, &digits(survival)?)),
+                _ => Ok(Rule::new(&digits(&second)?, &digits(&first)?)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "rand")]
+    use rand::rngs::StdRng;
+    #[cfg(feature = "rand")]
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("B3/S23".parse::<Rule>().unwrap(), Rule::life());
+        assert_eq!("b3/s23".parse::<Rule>().unwrap(), Rule::life());
+        assert_eq!("S23/B3".parse::<Rule>().unwrap(), Rule::life());
+        assert_eq!("23/3".parse::<Rule>().unwrap(), Rule::life());
+        assert_eq!("B2/S".parse::<Rule>().unwrap(), Rule::new(&[2], &[]));
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("life".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(RulePreset::find("day-night").unwrap().rule(), Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8]));
+        assert_eq!(RulePreset::find("LIFE WITHOUT DEATH").unwrap().notation, "B3/S012345678");
+        assert!(RulePreset::find("nope").is_none());
+        assert_eq!(RulePreset::of(Rule::life()).unwrap().name, "Life");
+        for preset in PRESETS {
+            assert_eq!(preset.rule().to_string(), preset.notation);
+            assert!((0.0..=1.0).contains(&preset.density));
+        }
+    }
+
+    #[test]
+    fn test_set() {
+        let mut rule = Rule::life();
+        rule.set_birth(6, true);
+        rule.set_survival(2, false);
+        rule.set_birth(9, true);
+        assert_eq!(rule.to_string(), "B36/S3");
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let rules: Vec<Rule> = (0..50).map(|_| Rule::random(&mut rng)).collect();
+        assert!(rules.iter().all(|rule| !rule.is_born(0) && (1..=8).any(|count| rule.is_born(count))));
+        assert!(rules.iter().any(|rule| *rule != rules[0]));
+        assert_eq!(Rule::random(&mut StdRng::seed_from_u64(5)), rules[0]);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Rule::life().to_string(), "B3/S23");
+        assert_eq!(Rule::new(&[3, 6], &[2, 3]).to_string(), "B36/S23");
+    }
+}
@@ -0,0 +1,328 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::debug;
+use crate::engine::{CellChange, GameOfLife};
+
+/// How many generations may be queued up for a slow consumer before the simulation thread
+/// waits for it to catch up.
+const BACKLOG: usize = 64;
+
+type Edit = Box<dyn FnOnce(&mut GameOfLife) + Send>;
+
+/// Runs on the simulation thread after every tick; see `Simulation::set_hook`.
+pub type Hook = Box<dyn FnMut(&mut GameOfLife) + Send>;
+
+enum Control {
+    Running(bool),
+    Step,
+    Interval(Duration),
+    Stride(u64),
+    Edit(Edit),
+    Hook(Hook),
+}
+
+pub enum Update {
+    /// The simulation advanced one generation and these cells changed.
+    Generation(Vec<CellChange>),
+    /// The game was edited; this is the complete new state.
+    Snapshot(Box<GameOfLife>),
+    /// The simulation advanced this many generations at once, as set with `set_stride`; this is
+    /// the complete new state.
+    Leap(u64, Box<GameOfLife>),
+}
+
+/// Runs a game on its own thread, ticking it every `interval` while running and publishing each
+/// generation as a list of changed cells. Keep a replica in sync by passing every update to
+/// `Update::apply`.
+pub struct Simulation {
+    controls: Option<Sender<Control>>,
+    updates: Receiver<Update>,
+    handle: Option<JoinHandle<()>>,
+    /// Nanoseconds the most recent tick took.
+    tick_nanos: Arc<AtomicU64>,
+}
+
+impl Simulation {
+    pub fn spawn(game: GameOfLife, interval: Duration) -> Simulation {
+        let (controls, control_receiver) = mpsc::channel();
+        let (update_sender, updates) = mpsc::sync_channel(BACKLOG);
+        let tick_nanos = Arc::new(AtomicU64::new(0));
+        let thread_tick_nanos = tick_nanos.clone();
+        let handle = thread::spawn(move || run(game, interval, control_receiver, update_sender, thread_tick_nanos));
+        Simulation {
+            controls: Some(controls),
+            updates,
+            handle: Some(handle),
+            tick_nanos,
+        }
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.send(Control::Running(running));
+    }
+
+    /// Advances exactly one generation, whether or not the simulation is running.
+    pub fn step(&self) {
+        self.send(Control::Step);
+    }
+
+    pub fn set_interval(&self, interval: Duration) {
+        self.send(Control::Interval(interval));
+    }
+
+    /// Advances `stride` generations every interval instead of one, publishing only the last of
+    /// them, so long-term evolution can be watched faster than it could be drawn. Steps still
+    /// advance a single generation.
+    pub fn set_stride(&self, stride: u64) {
+        self.send(Control::Stride(stride.max(1)));
+    }
+
+    /// Applies `edit` to the game between generations and publishes a snapshot afterwards.
+    pub fn edit<F>(&self, edit: F)
+        where F: FnOnce(&mut GameOfLife) + Send + 'static
+    {
+        self.send(Control::Edit(Box::new(edit)));
+    }
+
+    /// Calls `hook` after every tick, replacing any earlier one. Whatever it does to the game is
+    /// published as part of that generation.
+    pub fn set_hook(&self, hook: Hook) {
+        self.send(Control::Hook(hook));
+    }
+
+    /// How long computing the most recent generation took.
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_nanos(self.tick_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Returns the next pending update without blocking.
+    pub fn try_recv(&self) -> Option<Update> {
+        self.updates.try_recv().ok()
+    }
+
+    /// Blocks until the next update arrives, or `None` once the simulation has stopped.
+    pub fn recv(&self) -> Option<Update> {
+        self.updates.recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next update.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Update> {
+        self.updates.recv_timeout(timeout).ok()
+    }
+
+    fn send(&self, control: Control) {
+        if let Some(controls) = &self.controls {
+            let _ = controls.send(control);
+        }
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        // Hanging up the control channel stops the thread; drain the updates so it isn't left
+        // blocked on a full channel.
+        self.controls.take();
+        while self.updates.recv().is_ok() {}
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Update {
+    pub fn apply(self, replica: &mut GameOfLife) {
+        match self {
+            Update::Generation(changes) => replica.apply_changes(&changes),
+            Update::Snapshot(game) | Update::Leap(_, game) => *replica = *game,
+        }
+    }
+
+    /// How many generations the update advances the game by.
+    pub fn generations(&self) -> u64 {
+        match self {
+            Update::Generation(_) => 1,
+            Update::Snapshot(_) => 0,
+            Update::Leap(generations, _) => *generations,
+        }
+    }
+}
+
+fn run(
+    mut game: GameOfLife,
+    mut interval: Duration,
+    controls: Receiver<Control>,
+    updates: SyncSender<Update>,
+    tick_nanos: Arc<AtomicU64>,
+) {
+    let mut running = false;
+    let mut stride = 1;
+    let mut hook: Option<Hook> = None;
+    let tick = |game: &mut GameOfLife, hook: &mut Option<Hook>, generations: u64| {
+        for _ in 0..generations {
+            let start = Instant::now();
+            game.tick();
+            let took = start.elapsed();
+            tick_nanos.store(took.as_nanos() as u64, Ordering::Relaxed);
+            debug!(micros = took.as_micros() as u64, "tick");
+            if let Some(hook) = hook {
+                hook(game);
+            }
+        }
+        match generations {
+            1 => Update::Generation(game.changes()),
+            _ => Update::Leap(generations, Box::new(game.clone())),
+        }
+    };
+    let mut next_tick = Instant::now() + interval;
+    loop {
+        let timeout = if running {
+            next_tick.saturating_duration_since(Instant::now())
+        } else {
+            Duration::from_secs(3600)
+        };
+
+        let update = match controls.recv_timeout(timeout) {
+            Ok(Control::Running(value)) => {
+                if value && !running {
+                    next_tick = Instant::now() + interval;
+                }
+                running = value;
+                None
+            }
+            Ok(Control::Interval(value)) => {
+                interval = value;
+                next_tick = Instant::now() + interval;
+                None
+            }
+            Ok(Control::Stride(value)) => {
+                stride = value;
+                None
+            }
+            Ok(Control::Step) => Some(tick(&mut game, &mut hook, 1)),
+            Ok(Control::Hook(value)) => {
+                hook = Some(value);
+                None
+            }
+            Ok(Control::Edit(edit)) => {
+                edit(&mut game);
+                Some(Update::Snapshot(Box::new(game.clone())))
+            }
+            Err(RecvTimeoutError::Timeout) if running => {
+                next_tick = (next_tick + interval).max(Instant::now());
+                Some(tick(&mut game, &mut hook, stride))
+            }
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+
+        if let Some(update) = update {
+            if updates.send(update).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::CellState;
+    use crate::pattern::Pattern;
+
+    fn blinker() -> GameOfLife {
+        let mut game = GameOfLife::new(5, 5);
+        *game.current_mut() = ".....\n.....\n.OOO.\n.....\n.....".parse().unwrap();
+        game
+    }
+
+    #[test]
+    fn test_replica_follows_steps_and_edits() {
+        let mut replica = blinker();
+        let simulation = Simulation::spawn(replica.clone(), Duration::from_secs(60));
+
+        simulation.step();
+        simulation.recv().unwrap().apply(&mut replica);
+        assert_eq!(replica.current().to_string(), ".....\n..O..\n..O..\n..O..\n.....");
+        assert!(simulation.tick_duration() > Duration::ZERO);
+
+        simulation.edit(|game| game.current_mut().set_state((0, 0), CellState::Alive));
+        simulation.recv().unwrap().apply(&mut replica);
+        assert_eq!(replica.current().get_state((0, 0)), CellState::Alive);
+
+        let mut reference = blinker();
+        reference.tick();
+        reference.current_mut().set_state((0, 0), CellState::Alive);
+        reference.tick();
+        simulation.step();
+        simulation.recv().unwrap().apply(&mut replica);
+        assert_eq!(replica.current(), reference.current());
+        assert_eq!(replica.age((2, 2)), reference.age((2, 2)));
+    }
+
+    #[test]
+    fn test_hook_edits_are_published() {
+        let mut replica = blinker();
+        let simulation = Simulation::spawn(replica.clone(), Duration::from_secs(60));
+        simulation.set_hook(Box::new(|game| game.current_mut().set_state((0, 0), CellState::Alive)));
+
+        simulation.step();
+        simulation.recv().unwrap().apply(&mut replica);
+        assert_eq!(replica.current().to_string(), "O....\n..O..\n..O..\n..O..\n.....");
+    }
+
+    #[test]
+    fn test_pre_tick_edits_are_published() {
+        let mut game = GameOfLife::new(6, 6);
+        let mut replica = game.clone();
+        let mut stamped = false;
+        game.on_pre_tick(move |game| {
+            if !std::mem::replace(&mut stamped, true) {
+                Pattern::from_plaintext("Block", "OO\nOO").stamp(game.current_mut(), (2, 2));
+            }
+        });
+        let simulation = Simulation::spawn(game, Duration::from_secs(60));
+
+        simulation.step();
+        simulation.recv().unwrap().apply(&mut replica);
+        assert_eq!(replica.current().population(), 4);
+        simulation.step();
+        simulation.recv().unwrap().apply(&mut replica);
+        assert_eq!(replica.current().population(), 4);
+        assert_eq!(replica.age((2, 2)), 2);
+    }
+
+    #[test]
+    fn test_stride() {
+        let mut replica = GameOfLife::new(8, 8);
+        Pattern::from_plaintext("Glider", ".O.\n..O\nOOO").stamp(replica.current_mut(), (0, 0));
+        let mut reference = replica.clone();
+        let simulation = Simulation::spawn(replica.clone(), Duration::from_millis(1));
+        simulation.set_stride(4);
+        simulation.set_running(true);
+
+        let update = simulation.recv().unwrap();
+        assert_eq!(update.generations(), 4);
+        update.apply(&mut replica);
+        simulation.set_running(false);
+        for _ in 0..4 {
+            reference.tick();
+        }
+        assert_eq!(replica.current(), reference.current());
+        assert_eq!(replica.age((3, 2)), reference.age((3, 2)));
+    }
+
+    #[test]
+    fn test_runs_on_its_own() {
+        let mut replica = blinker();
+        let simulation = Simulation::spawn(replica.clone(), Duration::from_millis(1));
+        simulation.set_running(true);
+        for _ in 0..10 {
+            simulation.recv().unwrap().apply(&mut replica);
+        }
+        assert_eq!(replica.current(), blinker().current());
+        simulation.set_running(false);
+    }
+}
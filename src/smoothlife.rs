@@ -0,0 +1,169 @@
+use std::fmt;
+use crate::engine::{Boundary, Cell, GameMatrix};
+use crate::lenia::{convolve, normalize, Continuous, Kernel};
+
+/// How sharply the birth and survival intervals are cut off at their ends.
+const ALPHA_N: f32 = 0.028;
+/// How sharply a cell counts as alive rather than dead by its inner filling.
+const ALPHA_M: f32 = 0.147;
+
+/// Rafler's SmoothLife, Life on a continuum: a cell's inner filling is the average state of the disk
+/// of `inner_radius` around it and its outer filling that of the ring out to `outer_radius`. A cell
+/// whose inner filling is under a half is born when its outer filling falls within `birth`, and
+/// one above a half survives when it falls within `survival`, the two blending smoothly between.
+/// Each generation a cell moves `dt` of the way towards that, all the way being Rafler's discrete
+/// rule and less his smooth time.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SmoothLife {
+    inner_radius: usize,
+    outer_radius: usize,
+    birth: (f32, f32),
+    survival: (f32, f32),
+    dt: f32,
+    /// Both kernels' weights summing to one, the edges of the disks anti-aliased.
+    inner: Kernel,
+    outer: Kernel,
+}
+
+impl SmoothLife {
+    pub fn new(inner_radius: usize, outer_radius: usize, birth: (f32, f32), survival: (f32, f32), dt: f32) -> SmoothLife {
+        // How much of the cell at `distance` a disk of `radius` covers.
+        let coverage = |radius: usize, distance: f32| (radius as f32 + 0.5 - distance).clamp(0.0, 1.0);
+        let reach = outer_radius as isize + 1;
+        let (mut inner, mut outer) = (vec![], vec![]);
+        for d_row in -reach..=reach {
+            for d_column in -reach..=reach {
+                let distance = ((d_row * d_row + d_column * d_column) as f32).sqrt();
+                let disk = coverage(inner_radius, distance);
+                let ring = coverage(outer_radius, distance) - disk;
+                if disk > 0.0 {
+                    inner.push((d_row, d_column, disk));
+                }
+                if ring > 0.0 {
+                    outer.push((d_row, d_column, ring));
+                }
+            }
+        }
+        SmoothLife { inner_radius, outer_radius, birth, survival, dt, inner: normalize(inner), outer: normalize(outer) }
+    }
+
+    pub fn inner_radius(&self) -> usize {
+        self.inner_radius
+    }
+
+    pub fn outer_radius(&self) -> usize {
+        self.outer_radius
+    }
+
+    pub fn birth(&self) -> (f32, f32) {
+        self.birth
+    }
+
+    pub fn survival(&self) -> (f32, f32) {
+        self.survival
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// The inner and outer fillings around `cell`.
+    pub fn fillings(&self, matrix: &GameMatrix<f32>, cell: Cell, boundary: Boundary) -> (f32, f32) {
+        (convolve(matrix, &self.inner, cell, boundary), convolve(matrix, &self.outer, cell, boundary))
+    }
+
+    /// How alive a cell with these fillings should be, from 0 to 1.
+    pub fn transition(&self, inner: f32, outer: f32) -> f32 {
+        let sigmoid = |x: f32, a: f32, alpha: f32| 1.0 / (1.0 + (-(x - a) * 4.0 / alpha).exp());
+        let aliveness = sigmoid(inner, 0.5, ALPHA_M);
+        let mix = |dead: f32, alive: f32| dead * (1.0 - aliveness) + alive * aliveness;
+        let (low, high) = (mix(self.birth.0, self.survival.0), mix(self.birth.1, self.survival.1));
+        sigmoid(outer, low, ALPHA_N) * (1.0 - sigmoid(outer, high, ALPHA_N))
+    }
+}
+
+impl Continuous for SmoothLife {
+    fn step(&self, matrix: &GameMatrix<f32>, boundary: Boundary) -> GameMatrix<f32> {
+        let (rows, columns) = matrix.shape();
+        let mut next = matrix.clone();
+        for row in 0..rows {
+            for column in 0..columns {
+                let cell = (row, column);
+                let (inner, outer) = self.fillings(matrix, cell, boundary);
+                let state = matrix.get_state(cell);
+                next.set_state(cell, state + self.dt * (self.transition(inner, outer) - state));
+            }
+        }
+        next
+    }
+
+    fn radius(&self) -> usize {
+        self.outer_radius
+    }
+}
+
+impl fmt::Display for SmoothLife {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "SmoothLife ri {} ra {} B {}-{} S {}-{} dt {}",
+            self.inner_radius, self.outer_radius, self.birth.0, self.birth.1, self.survival.0, self.survival.1, self.dt
+        )
+    }
+}
+
+/// Rafler's intervals for gliders in smooth time, with the disks scaled down to fit a terminal; a
+/// soup under them keeps moving.
+impl Default for SmoothLife {
+    fn default() -> SmoothLife {
+        SmoothLife::new(4, 12, (0.257, 0.336), (0.365, 0.549), 0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels() {
+        let rule = SmoothLife::default();
+        for kernel in [&rule.inner, &rule.outer] {
+            let total: f32 = kernel.iter().map(|(_, _, weight)| weight).sum();
+            assert!((total - 1.0).abs() < 1e-5);
+        }
+        let weight = |kernel: &Kernel, offset| kernel.iter().find(|(d_row, d_column, _)| (*d_row, *d_column) == offset).map(|weight| weight.2);
+        assert!(weight(&rule.inner, (0, 0)).is_some());
+        assert_eq!(weight(&rule.outer, (0, 0)), None);
+        assert_eq!(weight(&rule.inner, (0, 8)), None);
+        assert!(weight(&rule.outer, (0, 8)).is_some());
+        assert_eq!(weight(&rule.outer, (0, 13)), None);
+        assert_eq!(rule.radius(), 12);
+    }
+
+    #[test]
+    fn test_transition() {
+        let rule = SmoothLife::default();
+        // Dead cells are born inside the birth interval and alive ones survive inside theirs.
+        assert!(rule.transition(0.0, 0.3) > 0.9);
+        assert!(rule.transition(0.0, 0.45) < 0.1);
+        assert!(rule.transition(1.0, 0.45) > 0.9);
+        assert!(rule.transition(1.0, 0.3) < 0.1);
+        assert!(rule.transition(1.0, 0.0) < 0.01);
+        assert!(rule.transition(0.0, 1.0) < 0.01);
+    }
+
+    #[test]
+    fn test_step() {
+        let rule = SmoothLife::new(1, 3, (0.257, 0.336), (0.365, 0.549), 0.5);
+        // The sigmoids never quite reach zero, but an empty grid stays as good as empty.
+        let empty = GameMatrix::<f32>::new(10, 10);
+        assert!(rule.step(&empty, Boundary::Wrap).get_internal().iter().all(|state| *state < 1e-6));
+
+        // A cell with too little around it moves half way towards dead.
+        let mut matrix = GameMatrix::<f32>::new(10, 10);
+        matrix.set_state((5, 5), 1.0);
+        assert!((rule.step(&matrix, Boundary::Wrap).get_state((5, 5)) - 0.5).abs() < 0.01);
+        assert_eq!(rule.fillings(&matrix, (5, 5), Boundary::Wrap).1, 0.0);
+        assert!(rule.fillings(&matrix, (5, 7), Boundary::Wrap).1 > 0.0);
+        assert_eq!(rule.to_string(), "SmoothLife ri 1 ra 3 B 0.257-0.336 S 0.365-0.549 dt 0.5");
+    }
+}
@@ -0,0 +1,352 @@
+use crate::engine::{CellType, GameOfLife};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// How many generations `is_settled` looks back over, and so the least capacity it works with.
+pub const SETTLE_WINDOW: usize = 60;
+/// The longest population cycle `is_settled` recognizes, the pentadecathlon's.
+const SETTLE_PERIOD: usize = 15;
+
+pub struct Stats {
+    generation: u64,
+    capacity: usize,
+    populations: VecDeque<usize>,
+    metrics: VecDeque<Metrics>,
+}
+
+/// Measures of a generation beyond its population, for studying how a rule behaves, e.g. where
+/// it turns from dying out to filling the grid.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Metrics {
+    /// The fraction of cells alive.
+    pub density: f64,
+    /// The Shannon entropy of the grid's 2x2 blocks, scaled to run from 0 when every block looks
+    /// the same to 1 when all 16 arrangements are equally common.
+    pub entropy: f64,
+    /// The fraction of cells that changed since the previous generation.
+    pub activity: f64,
+}
+
+impl Metrics {
+    pub fn measure(game: &GameOfLife) -> Metrics {
+        let (rows, columns) = game.shape();
+        let cells = (rows * columns).max(1) as f64;
+        let mut changed = 0;
+        game.current().for_each_cell(|cell, state| changed += usize::from(state != game.previous().get_state(cell)));
+        Metrics {
+            density: game.current().population() as f64 / cells,
+            entropy: block_entropy(game),
+            activity: changed as f64 / cells,
+        }
+    }
+}
+
+/// The entropy of the overlapping 2x2 blocks of the current generation, in units of the 4 bits
+/// it takes to tell the 16 arrangements apart. Blocks aren't taken across a wrapped edge.
+fn block_entropy(game: &GameOfLife) -> f64 {
+    let matrix = game.current();
+    let (rows, columns) = matrix.shape();
+    if rows < 2 || columns < 2 {
+        return 0.0;
+    }
+    let alive = |row, column| matrix.get_state((row, column)).is_alive() as usize;
+    let mut counts = [0usize; 16];
+    for row in 0..rows - 1 {
+        for column in 0..columns - 1 {
+            let block = alive(row, column)
+                | alive(row, column + 1) << 1
+                | alive(row + 1, column) << 2
+                | alive(row + 1, column + 1) << 3;
+            counts[block] += 1;
+        }
+    }
+    let blocks = ((rows - 1) * (columns - 1)) as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|&count| {
+            let p = count as f64 / blocks;
+            -p * p.log2()
+        })
+        .sum();
+    // Without this a uniform grid comes out as -0.
+    entropy.abs() / 4.0
+}
+
+impl Stats {
+    pub fn new(capacity: usize) -> Stats {
+        Stats {
+            generation: 0,
+            capacity,
+            populations: VecDeque::with_capacity(capacity),
+            metrics: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records the population and the `Metrics` of the game's current generation.
+    pub fn record(&mut self, game: &GameOfLife) {
+        if self.populations.len() == self.capacity {
+            self.populations.pop_front();
+            self.metrics.pop_front();
+        }
+        self.populations.push_back(game.current().population());
+        self.metrics.push_back(Metrics::measure(game));
+        self.generation += 1;
+    }
+
+    /// Counts generations that went by without being recorded, as when only every so many are
+    /// drawn.
+    pub fn skip(&mut self, generations: u64) {
+        self.generation += generations;
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> usize {
+        self.populations.back().copied().unwrap_or(0)
+    }
+
+    pub fn populations(&self) -> impl Iterator<Item=usize> + '_ {
+        self.populations.iter().copied()
+    }
+
+    /// The metrics of the most recent generation.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.back().copied().unwrap_or_default()
+    }
+
+    /// The metrics of every generation still held, oldest first, alongside `populations`.
+    pub fn metrics_history(&self) -> impl Iterator<Item=Metrics> + '_ {
+        self.metrics.iter().copied()
+    }
+
+    /// The generations still held as CSV with a header row, one line per generation, oldest
+    /// first: `generation,population,density,entropy,activity`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("generation,population,density,entropy,activity\n");
+        let first = self.generation - self.populations.len() as u64;
+        for (index, (population, metrics)) in self.populations.iter().zip(&self.metrics).enumerate() {
+            let _ = writeln!(
+                csv, "{},{},{:.6},{:.6},{:.6}",
+                first + index as u64, population, metrics.density, metrics.entropy, metrics.activity
+            );
+        }
+        csv
+    }
+
+    /// Whether the population has gone round the same short cycle for the last `SETTLE_WINDOW`
+    /// generations, the usual sign that a soup has burnt out into still lifes, oscillators and
+    /// spaceships.
+    pub fn is_settled(&self) -> bool {
+        let skip = self.populations.len().saturating_sub(SETTLE_WINDOW);
+        let recent: Vec<usize> = self.populations.iter().skip(skip).copied().collect();
+        settled_period(&recent).is_some()
+    }
+
+    /// Renders the most recent population history as a sparkline at most `width` characters wide,
+    /// averaging neighboring generations together when there are more samples than characters.
+    pub fn sparkline(&self, width: usize) -> String {
+        if width == 0 || self.populations.is_empty() {
+            return String::new();
+        }
+
+        let bucket_size = self.populations.len().div_ceil(width);
+        let buckets: Vec<usize> = self.populations
+            .iter()
+            .copied()
+            .collect::<Vec<_>>()
+            .chunks(bucket_size)
+            .map(|chunk| chunk.iter().sum::<usize>() / chunk.len())
+            .collect();
+
+        let min = *buckets.iter().min().unwrap();
+        let max = *buckets.iter().max().unwrap();
+        buckets
+            .iter()
+            .map(|value| {
+                if max == min {
+                    SPARKS[0]
+                } else {
+                    SPARKS[(value - min) * (SPARKS.len() - 1) / (max - min)]
+                }
+            })
+            .collect()
+    }
+}
+
+/// How a pattern played out, as measured by `lifespan`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Lifespan {
+    /// The generation from which the population has gone round its final cycle, or how many
+    /// generations were run if it never settled.
+    pub generations: u64,
+    pub settled: bool,
+    pub final_population: usize,
+    pub peak_population: usize,
+    /// The first generation the population was at its peak.
+    pub peak_generation: u64,
+}
+
+/// Runs the game until it settles, or for `limit` generations if it doesn't, and measures how
+/// long it took: a methuselah evaluator. Settling is judged by population, as `Stats::is_settled`
+/// does. The game is left where it stopped, so what it settled into can be looked at.
+pub fn lifespan(game: &mut GameOfLife, limit: u64) -> Lifespan {
+    let mut populations = vec![game.current().population()];
+    let mut period = None;
+    while period.is_none() && (populations.len() as u64) <= limit {
+        game.tick();
+        populations.push(game.current().population());
+        period = settled_period(&populations[populations.len().saturating_sub(SETTLE_WINDOW)..]);
+    }
+
+    // The window only shows the cycle was going on by its start; it may have begun earlier.
+    let generations = match period {
+        Some(period) => {
+            let mut start = populations.len() - SETTLE_WINDOW;
+            while start > 0 && populations[start - 1] == populations[start - 1 + period] {
+                start -= 1;
+            }
+            start
+        }
+        None => populations.len() - 1,
+    };
+    let (peak_generation, peak_population) = populations
+        .iter()
+        .enumerate()
+        .fold((0, 0), |peak, (generation, &population)| if population > peak.1 { (generation, population) } else { peak });
+    Lifespan {
+        generations: generations as u64,
+        settled: period.is_some(),
+        final_population: populations.last().copied().unwrap_or(0),
+        peak_population,
+        peak_generation: peak_generation as u64,
+    }
+}
+
+/// The shortest period, up to `SETTLE_PERIOD`, that `SETTLE_WINDOW` populations go round, if
+/// there are that many.
+fn settled_period(recent: &[usize]) -> Option<usize> {
+    if recent.len() < SETTLE_WINDOW {
+        return None;
+    }
+    (1..=SETTLE_PERIOD).find(|&period| recent.iter().zip(&recent[period..]).all(|(a, b)| a == b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Boundary, CellState};
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn test_record_is_capped() {
+        let mut game = GameOfLife::new(4, 4);
+        let mut stats = Stats::new(3);
+        for column in 0..4 {
+            game.current_mut().set_state((0, column), CellState::Alive);
+            stats.record(&game);
+        }
+        assert_eq!(stats.generation(), 4);
+        assert_eq!(stats.population(), 4);
+        assert_eq!(stats.populations().collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_is_settled() {
+        let mut game = GameOfLife::new(6, 8);
+        *game.current_mut() = "........\n.OOO....\n........\n........\n.....OO.\n.....OO.".parse().unwrap();
+        let mut stats = Stats::new(SETTLE_WINDOW);
+        for _ in 0..SETTLE_WINDOW - 1 {
+            stats.record(&game);
+            game.tick();
+        }
+        assert!(!stats.is_settled());
+        stats.record(&game);
+        assert!(stats.is_settled());
+
+        game.current_mut().set_state((5, 0), CellState::Alive);
+        stats.record(&game);
+        assert!(!stats.is_settled());
+    }
+
+    #[test]
+    fn test_lifespan() {
+        // Four in a row becomes a beehive by way of a six-cell block of two columns.
+        let mut game = GameOfLife::new(8, 8);
+        game.set_boundary(Boundary::Dead);
+        Pattern::from_plaintext("", "OOOO").stamp(game.current_mut(), (3, 2));
+        let lifespan = lifespan(&mut game, 1000);
+        assert_eq!(lifespan, Lifespan {
+            generations: 1,
+            settled: true,
+            final_population: 6,
+            peak_population: 6,
+            peak_generation: 1,
+        });
+        assert_eq!(game.current().census().to_string(), "beehives: 1");
+    }
+
+    #[test]
+    fn test_lifespan_limit() {
+        let mut game = GameOfLife::new(8, 8);
+        Pattern::from_plaintext("", "OOO").stamp(game.current_mut(), (1, 2));
+        let blinker = lifespan(&mut game, 1000);
+        assert_eq!((blinker.generations, blinker.settled, blinker.final_population), (0, true, 3));
+
+        let unsettled = lifespan(&mut game, 10);
+        assert_eq!((unsettled.generations, unsettled.settled, unsettled.final_population), (10, false, 3));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut game = GameOfLife::new(3, 4);
+        assert_eq!(Metrics::measure(&game), Metrics::default());
+
+        // Two kinds of block, as common as each other: one bit out of four.
+        *game.current_mut() = "OOOO\n....\nOOOO".parse().unwrap();
+        let metrics = Metrics::measure(&game);
+        assert_eq!(metrics.density, 8.0 / 12.0);
+        assert_eq!(metrics.entropy, 0.25);
+        assert_eq!(metrics.activity, 8.0 / 12.0);
+
+        // Every cell is overcrowded across the wrapped edge.
+        game.tick();
+        assert_eq!(Metrics::measure(&game).density, 0.0);
+        assert_eq!(Metrics::measure(&game).activity, 8.0 / 12.0);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut game = GameOfLife::new(4, 4);
+        let mut stats = Stats::new(2);
+        game.current_mut().set_state((1, 1), CellState::Alive);
+        for _ in 0..3 {
+            stats.record(&game);
+            game.tick();
+        }
+        assert_eq!(stats.metrics(), Metrics::default());
+        assert_eq!(stats.metrics_history().count(), 2);
+        assert_eq!(stats.to_csv(), concat!(
+            "generation,population,density,entropy,activity\n",
+            "1,0,0.000000,0.000000,0.062500\n",
+            "2,0,0.000000,0.000000,0.000000\n",
+        ));
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let mut game = GameOfLife::new(4, 4);
+        let mut stats = Stats::new(100);
+        assert_eq!(stats.sparkline(10), "");
+
+        for cell in [(0, 0), (0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (1, 2), (1, 3)] {
+            game.current_mut().set_state(cell, CellState::Alive);
+            stats.record(&game);
+        }
+        assert_eq!(stats.sparkline(8), "▁▂▃▄▅▆▇█");
+        assert_eq!(stats.sparkline(4).chars().count(), 4);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use crate::engine::{get_neighbor_cells, next_state, Boundary, Cell, CellState, CellType, GameMatrix};
+use crate::rule::Rule;
+
+/// Where the cells of a Life grid live. `GameMatrix`, which `GameOfLife` ticks, keeps a whole
+/// `CellState` for every cell; `PackedGrid` keeps two bits a cell, for big grids that need to
+/// stay small; `SparseGrid` keeps only the cells that aren't dead, for big grids with little
+/// on them; with the `mmap` feature, `MappedGrid` keeps `PackedGrid`'s words in a file, for grids
+/// bigger than memory. `step` runs Life on any of them, and `from_storage` moves a grid between
+/// them.
+pub trait GridStorage: Clone {
+    /// A grid of `rows` by `columns` dead cells.
+    fn empty(rows: usize, columns: usize) -> Self;
+
+    fn shape(&self) -> (usize, usize);
+
+    fn get(&self, cell: Cell) -> CellState;
+
+    fn set(&mut self, cell: Cell, state: CellState);
+
+    /// Every cell that isn't dead, walls included, with its state. Dense grids give them row by
+    /// row; the order is otherwise unspecified.
+    fn iter_live(&self) -> impl Iterator<Item=(Cell, CellState)> + '_;
+
+    /// A copy of `other` in this kind of storage.
+    fn from_storage<S>(other: &S) -> Self
+        where S: GridStorage
+    {
+        let (rows, columns) = other.shape();
+        let mut grid = Self::empty(rows, columns);
+        for (cell, state) in other.iter_live() {
+            grid.set(cell, state);
+        }
+        grid
+    }
+}
+
+impl GridStorage for GameMatrix {
+    fn empty(rows: usize, columns: usize) -> GameMatrix {
+        GameMatrix::new(rows, columns)
+    }
+
+    fn shape(&self) -> (usize, usize) {
+        GameMatrix::shape(self)
+    }
+
+    fn get(&self, cell: Cell) -> CellState {
+        self.get_state(cell)
+    }
+
+    fn set(&mut self, cell: Cell, state: CellState) {
+        self.set_state(cell, state);
+    }
+
+    fn iter_live(&self) -> impl Iterator<Item=(Cell, CellState)> + '_ {
+        let columns = self.shape().1;
+        self.get_internal()
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| **state != CellState::Dead)
+            .map(move |(index, state)| ((index / columns, index % columns), *state))
+    }
+}
+
+/// Two bits a cell, 32 cells to a word, row by row.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PackedGrid {
+    rows: usize,
+    columns: usize,
+    words: Vec<u64>,
+}
+
+/// How many cells `PackedGrid`, and grids laid out like it, keep in a word.
+const CELLS_PER_WORD: usize = 32;
+
+/// How many words a packed grid of `rows` by `columns` cells takes, `None` if more than there
+/// can be.
+pub(crate) fn packed_words(rows: usize, columns: usize) -> Option<usize> {
+    Some(rows.checked_mul(columns)?.div_ceil(CELLS_PER_WORD))
+}
+
+/// The index of the cell in a packed grid `columns` wide, panicking if it is off the grid.
+pub(crate) fn packed_index((row, column): Cell, (rows, columns): (usize, usize)) -> usize {
+    assert!(row < rows && column < columns, "cell {:?} is outside of the {}x{} grid", (row, column), rows, columns);
+    row * columns + column
+}
+
+/// The state of the cell at `index`, counted row by row, in packed `words`.
+pub(crate) fn read_packed(words: &[u64], index: usize) -> CellState {
+    match words[index / CELLS_PER_WORD] >> (2 * (index % CELLS_PER_WORD)) & 0b11 {
+        0 => CellState::Dead,
+        1 => CellState::Alive,
+        2 => CellState::Wall,
+        _ => CellState::Immortal,
+    }
+}
+
+/// Sets the state of the cell at `index`, counted row by row, in packed `words`.
+pub(crate) fn write_packed(words: &mut [u64], index: usize, state: CellState) {
+    let bits = match state {
+        CellState::Dead => 0,
+        CellState::Alive => 1,
+        CellState::Wall => 2,
+        CellState::Immortal => 3,
+    };
+    let (word, shift) = (index / CELLS_PER_WORD, 2 * (index % CELLS_PER_WORD));
+    words[word] = words[word] & !(0b11 << shift) | bits << shift;
+}
+
+/// The cells of packed `words` that aren't dead, row by row. Whole words of dead cells are
+/// skipped without looking at their cells.
+pub(crate) fn packed_live(words: &[u64], (rows, columns): (usize, usize)) -> impl Iterator<Item=(Cell, CellState)> + '_ {
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| **word != 0)
+        .flat_map(move |(word, _)| {
+            let start = word * CELLS_PER_WORD;
+            start..(start + CELLS_PER_WORD).min(rows * columns)
+        })
+        .map(move |index| ((index / columns, index % columns), read_packed(words, index)))
+        .filter(|(_, state)| *state != CellState::Dead)
+}
+
+impl GridStorage for PackedGrid {
+    fn empty(rows: usize, columns: usize) -> PackedGrid {
+        let words = packed_words(rows, columns).expect("grid is too large");
+        PackedGrid { rows, columns, words: vec![0; words] }
+    }
+
+    fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    fn get(&self, cell: Cell) -> CellState {
+        read_packed(&self.words, packed_index(cell, self.shape()))
+    }
+
+    fn set(&mut self, cell: Cell, state: CellState) {
+        let index = packed_index(cell, self.shape());
+        write_packed(&mut self.words, index, state);
+    }
+
+    fn iter_live(&self) -> impl Iterator<Item=(Cell, CellState)> + '_ {
+        packed_live(&self.words, self.shape())
+    }
+}
+
+/// Only the cells that aren't dead, by position.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SparseGrid {
+    rows: usize,
+    columns: usize,
+    cells: HashMap<Cell, CellState>,
+}
+
+impl GridStorage for SparseGrid {
+    fn empty(rows: usize, columns: usize) -> SparseGrid {
+        SparseGrid { rows, columns, cells: HashMap::new() }
+    }
+
+    fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    fn get(&self, cell: Cell) -> CellState {
+        self.cells.get(&cell).copied().unwrap_or(CellState::Dead)
+    }
+
+    fn set(&mut self, (row, column): Cell, state: CellState) {
+        assert!(
+            row < self.rows && column < self.columns,
+            "cell {:?} is outside of the {}x{} grid", (row, column), self.rows, self.columns
+        );
+        match state {
+            CellState::Dead => self.cells.remove(&(row, column)),
+            state => self.cells.insert((row, column), state),
+        };
+    }
+
+    fn iter_live(&self) -> impl Iterator<Item=(Cell, CellState)> + '_ {
+        self.cells.iter().map(|(cell, state)| (*cell, *state))
+    }
+}
+
+/// The generation after `grid` under `rule`, with the same edges as `GameOfLife` has for
+/// `boundary`. Only the cells that aren't dead and their neighbors are looked at, so the work
+/// goes with the population rather than the size of the grid, except under rules with B0, where
+/// dead cells with no living neighbors are born too and every cell has to be looked at.
+pub fn step<S>(grid: &S, rule: &Rule, boundary: Boundary) -> S
+    where S: GridStorage
+{
+    let shape = grid.shape();
+    let mut neighbors: HashMap<Cell, usize> = HashMap::new();
+    if rule.is_born(0) {
+        for row in 0..shape.0 {
+            neighbors.extend((0..shape.1).map(|column| ((row, column), 0)));
+        }
+    }
+    for (cell, state) in grid.iter_live() {
+        neighbors.entry(cell).or_insert(0);
+        if state.is_alive() {
+            for neighbor in get_neighbor_cells(cell, shape, boundary) {
+                *neighbors.entry(neighbor).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut next = S::empty(shape.0, shape.1);
+    for (cell, count) in neighbors {
+        let state = next_state(grid.get(cell), count, rule);
+        if state != CellState::Dead {
+            next.set(cell, state);
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::GameOfLife;
+
+    fn sorted<S: GridStorage>(grid: &S) -> Vec<(Cell, CellState)> {
+        let mut cells: Vec<_> = grid.iter_live().collect();
+        cells.sort_by_key(|(cell, _)| *cell);
+        cells
+    }
+
+    fn round_trip<S: GridStorage>() {
+        let mut grid = S::empty(3, 40);
+        grid.set((0, 1), CellState::Alive);
+        grid.set((1, 33), CellState::Wall);
+        grid.set((2, 39), CellState::Immortal);
+        grid.set((0, 1), CellState::Dead);
+        grid.set((0, 2), CellState::Alive);
+        assert_eq!(grid.shape(), (3, 40));
+        assert_eq!((grid.get((0, 1)), grid.get((1, 33)), grid.get((2, 38))), (CellState::Dead, CellState::Wall, CellState::Dead));
+        assert_eq!(sorted(&grid), [((0, 2), CellState::Alive), ((1, 33), CellState::Wall), ((2, 39), CellState::Immortal)]);
+    }
+
+    #[test]
+    fn test_storage() {
+        round_trip::<GameMatrix>();
+        round_trip::<PackedGrid>();
+        round_trip::<SparseGrid>();
+    }
+
+    #[test]
+    fn test_step() {
+        // Every backend runs a glider next to a wall just as `GameOfLife` does, including under a
+        // rule where empty space comes alive.
+        for rule in ["B3/S23", "B0/S8"] {
+            let rule: Rule = rule.parse().unwrap();
+            let mut matrix: GameMatrix = "......\n..O...\n...O..\n.OOO..\n......\n......".parse().unwrap();
+            matrix.set_state((5, 0), CellState::Wall);
+            let (mut packed, mut sparse) = (PackedGrid::from_storage(&matrix), SparseGrid::from_storage(&matrix));
+            let mut game = GameOfLife::from_matrix(matrix.clone());
+            game.set_rule(rule);
+            for _ in 0..12 {
+                game.tick();
+                matrix = step(&matrix, &rule, Boundary::Wrap);
+                packed = step(&packed, &rule, Boundary::Wrap);
+                sparse = step(&sparse, &rule, Boundary::Wrap);
+                assert_eq!(&matrix, game.current(), "{}", rule);
+                assert_eq!(sorted(&packed), sorted(&matrix));
+                assert_eq!(sorted(&sparse), sorted(&matrix));
+            }
+        }
+    }
+}
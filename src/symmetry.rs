@@ -0,0 +1,139 @@
+use std::fmt;
+use rand::Rng;
+use crate::engine::{Cell, CellState, GameMatrix};
+
+/// The symmetry of a random soup, named as soup searches name them: `C2` and `C4` are unchanged
+/// by turning a half and a quarter, `D2` by mirroring left to right, `D4` by mirroring either way
+/// and `D8` by any turn or mirror of the square. Soups with any of them evolve very differently
+/// from asymmetric `C1` ones, the default.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Symmetry {
+    #[default]
+    C1,
+    C2,
+    C4,
+    D2,
+    D4,
+    D8,
+}
+
+impl Symmetry {
+    /// Accepts the names above in either case.
+    pub fn from_name(name: &str) -> Option<Symmetry> {
+        match name.to_uppercase().as_str() {
+            "C1" => Some(Symmetry::C1),
+            "C2" => Some(Symmetry::C2),
+            "C4" => Some(Symmetry::C4),
+            "D2" => Some(Symmetry::D2),
+            "D4" => Some(Symmetry::D4),
+            "D8" => Some(Symmetry::D8),
+            _ => None,
+        }
+    }
+
+    /// The part of a grid of `shape` the symmetry covers, as its top left cell and shape: all of
+    /// it, except that turning by a quarter needs a square, so `C4` and `D8` take the largest one
+    /// in the middle.
+    fn region(self, (rows, columns): (usize, usize)) -> (Cell, (usize, usize)) {
+        match self {
+            Symmetry::C4 | Symmetry::D8 => {
+                let side = rows.min(columns);
+                (((rows - side) / 2, (columns - side) / 2), (side, side))
+            }
+            _ => ((0, 0), (rows, columns)),
+        }
+    }
+
+    /// Every cell `cell` is carried to in a region of `shape`, itself included.
+    fn images(self, (row, column): Cell, (rows, columns): (usize, usize)) -> Vec<Cell> {
+        let (flipped_row, flipped_column) = (rows - 1 - row, columns - 1 - column);
+        match self {
+            Symmetry::C1 => vec![(row, column)],
+            Symmetry::C2 => vec![(row, column), (flipped_row, flipped_column)],
+            Symmetry::C4 => vec![(row, column), (column, flipped_row), (flipped_row, flipped_column), (flipped_column, row)],
+            Symmetry::D2 => vec![(row, column), (row, flipped_column)],
+            Symmetry::D4 => vec![(row, column), (row, flipped_column), (flipped_row, column), (flipped_row, flipped_column)],
+            Symmetry::D8 => vec![
+                (row, column), (column, flipped_row), (flipped_row, flipped_column), (flipped_column, row),
+                (row, flipped_column), (flipped_row, column), (column, row), (flipped_column, flipped_row),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl GameMatrix {
+    /// Like `randomize`, but deciding once for each set of cells the symmetry carries into each
+    /// other, so the living cells come out with that symmetry. `C4` and `D8` leave the cells
+    /// outside the square they cover as they are.
+    pub fn randomize_symmetric<R>(&mut self, density: f64, symmetry: Symmetry, rng: &mut R)
+        where R: Rng
+    {
+        let ((top, left), shape) = symmetry.region(self.shape());
+        for row in 0..shape.0 {
+            for column in 0..shape.1 {
+                // Each set is decided from its first cell, row by row, so `C1` draws exactly as
+                // `randomize` does.
+                let images = symmetry.images((row, column), shape);
+                if images.iter().min() != Some(&(row, column)) || !rng.gen_bool(density.clamp(0.0, 1.0)) {
+                    continue;
+                }
+                for (image_row, image_column) in images {
+                    self.set_state((top + image_row, left + image_column), CellState::Alive);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn soup(shape: (usize, usize), symmetry: Symmetry) -> GameMatrix {
+        let mut matrix = GameMatrix::new(shape.0, shape.1);
+        matrix.randomize_symmetric(0.5, symmetry, &mut StdRng::seed_from_u64(7));
+        matrix
+    }
+
+    #[test]
+    fn test_symmetric() {
+        for symmetry in [Symmetry::C2, Symmetry::C4, Symmetry::D2, Symmetry::D4, Symmetry::D8] {
+            for shape in [(9, 9), (8, 8)] {
+                let matrix = soup(shape, symmetry);
+                assert!(matrix.population() > 0);
+                for row in 0..shape.0 {
+                    for column in 0..shape.1 {
+                        let state = matrix.get_state((row, column));
+                        for image in symmetry.images((row, column), shape) {
+                            assert_eq!(matrix.get_state(image), state, "{} at {:?}", symmetry, (row, column));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_c1_matches_randomize() {
+        let mut matrix = GameMatrix::new(6, 7);
+        matrix.randomize(0.5, &mut StdRng::seed_from_u64(7));
+        assert_eq!(soup((6, 7), Symmetry::C1), matrix);
+    }
+
+    #[test]
+    fn test_quarter_turns_need_a_square() {
+        // Only the middle 4x4 of a 4x8 grid is seeded.
+        let matrix = soup((4, 8), Symmetry::C4);
+        assert!((0..4).all(|row| [0, 1, 6, 7].iter().all(|column| matrix.get_state((row, *column)) == CellState::Dead)));
+        assert_eq!(Symmetry::from_name("d8"), Some(Symmetry::D8));
+        assert_eq!(Symmetry::from_name("D3"), None);
+    }
+}
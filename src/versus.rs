@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use crate::engine::{Boundary, Cell, CellType, GameMatrix};
+use crate::error::{Error, Result};
+use crate::rule::Rule;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Player {
+    One,
+    Two,
+}
+
+/// A cell of a two player game: dead, or alive and owned by one of the players.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColoredCell {
+    #[default]
+    Dead,
+    Alive(Player),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// The players are placing their cells and it is this player's turn.
+    Placing(Player),
+    Running,
+    Finished,
+}
+
+/// A two player game: the players take turns placing one cell at a time until both have used
+/// their budget, then the board runs for a fixed number of generations and whoever owns more
+/// living cells wins.
+///
+/// The board follows the rule as usual, except that a newborn cell belongs to whichever player
+/// owns the majority of its living neighbors. A birth the players tie on doesn't happen.
+pub struct Versus {
+    matrix: GameMatrix<ColoredCell>,
+    boundary: Boundary,
+    rule: Rule,
+    remaining: [usize; 2],
+    turn: Player,
+    generation: u64,
+    length: u64,
+}
+
+impl CellType for ColoredCell {
+    fn is_alive(&self) -> bool {
+        *self != ColoredCell::Dead
+    }
+}
+
+impl Player {
+    pub fn other(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Versus {
+    /// A game on an empty board where each player may place `budget` cells, then `length`
+    /// generations are run.
+    pub fn new(rows: usize, columns: usize, rule: Rule, budget: usize, length: u64) -> Versus {
+        Versus {
+            matrix: GameMatrix::new(rows, columns),
+            boundary: Boundary::Wrap,
+            rule,
+            remaining: [budget; 2],
+            turn: Player::One,
+            generation: 0,
+            length,
+        }
+    }
+
+    pub fn matrix(&self) -> &GameMatrix<ColoredCell> {
+        &self.matrix
+    }
+
+    pub fn phase(&self) -> Phase {
+        if self.remaining.iter().any(|remaining| *remaining > 0) {
+            Phase::Placing(self.turn)
+        } else if self.generation < self.length {
+            Phase::Running
+        } else {
+            Phase::Finished
+        }
+    }
+
+    /// How many more cells the player may place.
+    pub fn remaining(&self, player: Player) -> usize {
+        self.remaining[player.index()]
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Places one of the player's cells. Fails if it isn't their turn or the cell is taken.
+    pub fn place(&mut self, player: Player, cell: Cell) -> Result<()> {
+        if self.phase() != Phase::Placing(player) {
+            return Err(Error::IllegalMove(format!("it is not player {:?}'s turn", player)));
+        }
+        if self.matrix.try_get_state(cell)?.is_alive() {
+            return Err(Error::IllegalMove(format!("cell {:?} is already taken", cell)));
+        }
+
+        self.matrix.set_state(cell, ColoredCell::Alive(player));
+        self.remaining[player.index()] -= 1;
+        if self.remaining(player.other()) > 0 {
+            self.turn = player.other();
+        }
+        Ok(())
+    }
+
+    /// Advances one generation while the game is running; does nothing in the other phases.
+    pub fn tick(&mut self) {
+        if self.phase() != Phase::Running {
+            return;
+        }
+        let rule = self.rule;
+        self.matrix = self.matrix.evolve(self.boundary, |cell, neighbors| {
+            let alive = neighbors.iter().filter(|neighbor| neighbor.is_alive()).count();
+            match cell {
+                ColoredCell::Alive(_) if rule.survives(alive) => cell,
+                ColoredCell::Dead if rule.is_born(alive) => {
+                    let ones = neighbors.iter().filter(|neighbor| **neighbor == ColoredCell::Alive(Player::One)).count();
+                    match (ones * 2).cmp(&alive) {
+                        Ordering::Greater => ColoredCell::Alive(Player::One),
+                        Ordering::Less => ColoredCell::Alive(Player::Two),
+                        Ordering::Equal => ColoredCell::Dead,
+                    }
+                }
+                _ => ColoredCell::Dead,
+            }
+        });
+        self.generation += 1;
+    }
+
+    /// The number of living cells the player owns.
+    pub fn score(&self, player: Player) -> usize {
+        let mut score = 0;
+        self.matrix.for_each_cell(|_, state| score += usize::from(state == ColoredCell::Alive(player)));
+        score
+    }
+
+    /// The player with the higher score once the game is finished, or `None` for a draw or a game
+    /// that is still going.
+    pub fn winner(&self) -> Option<Player> {
+        if self.phase() != Phase::Finished {
+            return None;
+        }
+        match self.score(Player::One).cmp(&self.score(Player::Two)) {
+            Ordering::Greater => Some(Player::One),
+            Ordering::Less => Some(Player::Two),
+            Ordering::Equal => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        let mut game = Versus::new(5, 5, Rule::life(), 2, 1);
+        assert_eq!(game.phase(), Phase::Placing(Player::One));
+        assert!(game.place(Player::Two, (0, 0)).is_err());
+
+        game.place(Player::One, (0, 0)).unwrap();
+        assert_eq!(game.phase(), Phase::Placing(Player::Two));
+        assert!(game.place(Player::Two, (0, 0)).is_err());
+        assert!(game.place(Player::Two, (9, 9)).is_err());
+        game.place(Player::Two, (4, 4)).unwrap();
+        game.place(Player::One, (0, 1)).unwrap();
+        game.place(Player::Two, (4, 3)).unwrap();
+        assert_eq!(game.phase(), Phase::Running);
+        assert_eq!(game.remaining(Player::One), 0);
+    }
+
+    #[test]
+    fn test_births_take_the_majority_color() {
+        let mut game = Versus::new(7, 7, Rule::life(), 3, 1);
+        // A blinker with two cells of player one and one of player two, plus lone cells that die.
+        game.place(Player::One, (3, 2)).unwrap();
+        game.place(Player::Two, (3, 4)).unwrap();
+        game.place(Player::One, (3, 3)).unwrap();
+        game.place(Player::Two, (0, 3)).unwrap();
+        game.place(Player::One, (0, 0)).unwrap();
+        game.place(Player::Two, (5, 6)).unwrap();
+
+        game.tick();
+        assert_eq!(game.phase(), Phase::Finished);
+        assert_eq!(game.matrix()[(2, 3)], ColoredCell::Alive(Player::One));
+        assert_eq!(game.matrix()[(3, 3)], ColoredCell::Alive(Player::One));
+        assert_eq!(game.matrix()[(4, 3)], ColoredCell::Alive(Player::One));
+        assert_eq!((game.score(Player::One), game.score(Player::Two)), (3, 0));
+        assert_eq!(game.winner(), Some(Player::One));
+
+        game.tick();
+        assert_eq!(game.generation(), 1);
+    }
+}
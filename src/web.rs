@@ -0,0 +1,163 @@
+use crate::engine::{CellState, CellType, GameOfLife};
+
+/// A game plus a byte per cell, one for alive and zero for dead in row major order, refreshed
+/// after every change so JavaScript can view it as a `Uint8Array`.
+///
+/// The `gol_*` exports below wrap it in a C ABI that takes and returns plain numbers, so the crate
+/// can be loaded with `WebAssembly.instantiate` without wasm-bindgen. `examples/web` is a canvas
+/// page built on them:
+///
+/// ```text
+/// cargo rustc --lib --release --features web --target wasm32-unknown-unknown --crate-type cdylib
+/// ```
+pub struct Canvas {
+    game: GameOfLife,
+    cells: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(rows: usize, columns: usize, density: f64, seed: u64) -> Canvas {
+        let game = GameOfLife::builder()
+            .size(rows.max(1), columns.max(1))
+            .density(density.clamp(0.0, 1.0))
+            .seed(seed)
+            .build()
+            .expect("the size and density are valid");
+        let mut canvas = Canvas { game, cells: vec![] };
+        canvas.refresh();
+        canvas
+    }
+
+    pub fn game(&self) -> &GameOfLife {
+        &self.game
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    pub fn tick(&mut self) {
+        self.game.tick();
+        self.refresh();
+    }
+
+    /// Flips a cell, ignoring walls and coordinates outside the grid.
+    pub fn toggle(&mut self, cell: (usize, usize)) {
+        let matrix = self.game.current_mut();
+        let state = match matrix.try_get_state(cell) {
+            Ok(CellState::Alive) => CellState::Dead,
+            Ok(CellState::Dead) => CellState::Alive,
+            Ok(CellState::Wall | CellState::Immortal) | Err(_) => return,
+        };
+        let _ = matrix.try_set_state(cell, state);
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        let (rows, columns) = self.game.shape();
+        let matrix = self.game.current();
+        self.cells.clear();
+        for row in 0..rows {
+            for column in 0..columns {
+                self.cells.push(matrix.get_state((row, column)).is_alive() as u8);
+            }
+        }
+    }
+}
+
+/// Creates a game; the returned handle is passed to every other export and released with
+/// `gol_free`.
+#[no_mangle]
+pub extern "C" fn gol_new(rows: u32, columns: u32, density: f64, seed: u32) -> *mut Canvas {
+    Box::into_raw(Box::new(Canvas::new(rows as usize, columns as usize, density, seed as u64)))
+}
+
+/// # Safety
+/// `canvas` must come from `gol_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gol_free(canvas: *mut Canvas) {
+    drop(Box::from_raw(canvas));
+}
+
+/// # Safety
+/// `canvas` must come from `gol_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gol_rows(canvas: *const Canvas) -> u32 {
+    (*canvas).game.shape().0 as u32
+}
+
+/// # Safety
+/// `canvas` must come from `gol_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gol_columns(canvas: *const Canvas) -> u32 {
+    (*canvas).game.shape().1 as u32
+}
+
+/// # Safety
+/// `canvas` must come from `gol_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gol_tick(canvas: *mut Canvas) {
+    (*canvas).tick();
+}
+
+/// # Safety
+/// `canvas` must come from `gol_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gol_toggle(canvas: *mut Canvas, row: u32, column: u32) {
+    (*canvas).toggle((row as usize, column as usize));
+}
+
+/// The address of the cell bytes. It moves only when the canvas is freed, but views over it must
+/// be recreated whenever the WebAssembly memory grows.
+///
+/// # Safety
+/// `canvas` must come from `gol_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gol_cells(canvas: *const Canvas) -> *const u8 {
+    (*canvas).cells.as_ptr()
+}
+
+/// There is no OS random source in the browser; the exports are always seeded by the page, so
+/// anything asking for entropy is refused.
+#[cfg(target_arch = "wasm32")]
+fn no_entropy(_: &mut [u8]) -> std::result::Result<(), getrandom::Error> {
+    Err(getrandom::Error::UNSUPPORTED)
+}
+
+#[cfg(target_arch = "wasm32")]
+getrandom::register_custom_getrandom!(no_entropy);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_follow_the_game() {
+        let mut canvas = Canvas::new(5, 5, 0.0, 1);
+        assert_eq!(canvas.cells(), &[0; 25]);
+
+        for column in 1..4 {
+            canvas.toggle((2, column));
+        }
+        canvas.toggle((9, 9));
+        assert_eq!(canvas.cells().iter().filter(|cell| **cell == 1).count(), 3);
+
+        canvas.tick();
+        let column: Vec<u8> = (0..5).map(|row| canvas.cells()[row * 5 + 2]).collect();
+        assert_eq!(column, vec![0, 1, 1, 1, 0]);
+        assert_eq!(canvas.game().current().population(), 3);
+    }
+
+    #[test]
+    fn test_exports() {
+        unsafe {
+            let canvas = gol_new(4, 6, 0.0, 7);
+            assert_eq!((gol_rows(canvas), gol_columns(canvas)), (4, 6));
+            gol_toggle(canvas, 1, 1);
+            assert_eq!(*gol_cells(canvas).add(7), 1);
+            gol_tick(canvas);
+            assert_eq!(*gol_cells(canvas).add(7), 0);
+            gol_free(canvas);
+        }
+    }
+}